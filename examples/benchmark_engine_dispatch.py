@@ -0,0 +1,148 @@
+"""
+JSEngine 任务分发基准测试
+
+多个 Python 线程以最快速度调用 engine.call()，统计吞吐量和调用延迟分位数（p50/p99/p99.9）。
+调用越短、并发线程越多，分发开销（入队、唤醒 Worker、取任务）占比越高。
+
+对比两个版本：
+    pip install never_jscore==<旧版本>
+    python benchmark_engine_dispatch.py --save old.json
+    pip install never_jscore==<新版本>
+    python benchmark_engine_dispatch.py --save new.json
+    python benchmark_engine_dispatch.py --compare old.json new.json
+
+加 --markdown 输出 Markdown 表格，可直接填入 docs/ENGINE_DISPATCH_BENCHMARK.md 的结果部分。
+"""
+
+import argparse
+import json
+import os
+import statistics
+import threading
+import time
+
+JS_CODE = """
+function add(a, b) { return a + b; }
+function work(n) {
+    let s = 0;
+    for (let i = 0; i < n; i++) s = (s + i * 31) % 1000003;
+    return s;
+}
+"""
+
+# (名称, 函数, 参数)：极短调用测分发开销，较长调用测 Worker 利用率
+WORKLOADS = [
+    ("add", "add", [1, 2]),
+    ("work_10k", "work", [10000]),
+]
+
+
+def percentile(sorted_values, q):
+    if not sorted_values:
+        return 0.0
+    index = min(len(sorted_values) - 1, int(q * len(sorted_values)))
+    return sorted_values[index]
+
+
+def run_case(engine, func_name, args, threads, calls_per_thread):
+    """threads 个线程各调用 calls_per_thread 次，返回吞吐量和延迟（毫秒）"""
+    latencies = [[] for _ in range(threads)]
+    barrier = threading.Barrier(threads + 1)
+
+    def client(i):
+        local = latencies[i]
+        barrier.wait()
+        for _ in range(calls_per_thread):
+            start = time.perf_counter()
+            engine.call(func_name, args)
+            local.append((time.perf_counter() - start) * 1000)
+
+    workers = [threading.Thread(target=client, args=(i,)) for i in range(threads)]
+    for t in workers:
+        t.start()
+    barrier.wait()
+    start = time.perf_counter()
+    for t in workers:
+        t.join()
+    elapsed = time.perf_counter() - start
+
+    values = sorted(v for local in latencies for v in local)
+    return {
+        "calls_per_sec": len(values) / elapsed,
+        "p50_ms": percentile(values, 0.50),
+        "p99_ms": percentile(values, 0.99),
+        "p999_ms": percentile(values, 0.999),
+        "mean_ms": statistics.fmean(values),
+    }
+
+
+def run(workers, thread_counts, calls_per_thread):
+    import never_jscore
+
+    results = {"version": getattr(never_jscore, "__version__", "unknown"), "workers": workers, "cases": {}}
+    with never_jscore.JSEngine(JS_CODE, workers=workers) as engine:
+        # 预热
+        engine.call_many("add", [[1, 2]] * 1000)
+        for name, func_name, args in WORKLOADS:
+            for threads in thread_counts:
+                key = f"{name}/threads={threads}"
+                case = run_case(engine, func_name, args, threads, calls_per_thread)
+                results["cases"][key] = case
+                print(
+                    f"{key:<24} {case['calls_per_sec']:>10.0f} calls/s  "
+                    f"p50={case['p50_ms']:.3f}ms  p99={case['p99_ms']:.3f}ms  p99.9={case['p999_ms']:.3f}ms"
+                )
+    return results
+
+
+def compare(old_path, new_path, markdown=False):
+    with open(old_path, encoding="utf-8") as f:
+        old = json.load(f)
+    with open(new_path, encoding="utf-8") as f:
+        new = json.load(f)
+
+    if markdown:
+        print(f"旧版本 {old['version']}（workers={old['workers']}） -> 新版本 {new['version']}（workers={new['workers']}）")
+        print()
+        print("| case | calls/s | p99 (ms) | p99.9 (ms) |")
+        print("|---|---|---|---|")
+    else:
+        print(f"{'case':<24} {'calls/s':>22} {'p99 (ms)':>22} {'p99.9 (ms)':>22}")
+    for key, before in old["cases"].items():
+        after = new["cases"].get(key)
+        if after is None:
+            continue
+        cells = []
+        for metric in ["calls_per_sec", "p99_ms", "p999_ms"]:
+            change = (after[metric] - before[metric]) / before[metric] * 100 if before[metric] else 0.0
+            cells.append(f"{before[metric]:.1f} -> {after[metric]:.1f} ({change:+.0f}%)")
+        if markdown:
+            print(f"| {key} | " + " | ".join(cells) + " |")
+        else:
+            print(f"{key:<24} " + " ".join(f"{c:>22}" for c in cells))
+
+
+def main():
+    parser = argparse.ArgumentParser(description="JSEngine 任务分发基准测试")
+    parser.add_argument("--workers", type=int, default=os.cpu_count() or 4)
+    parser.add_argument("--threads", type=int, nargs="+", default=[1, 4, 16, 64])
+    parser.add_argument("--calls", type=int, default=2000, help="每个线程的调用次数")
+    parser.add_argument("--save", help="把结果保存为 JSON 文件")
+    parser.add_argument("--compare", nargs=2, metavar=("OLD", "NEW"), help="对比两次保存的结果")
+    parser.add_argument("--markdown", action="store_true", help="--compare 输出 Markdown 表格")
+    args = parser.parse_args()
+
+    if args.compare:
+        compare(*args.compare, markdown=args.markdown)
+        return
+
+    print(f"JSEngine dispatch benchmark: workers={args.workers}, calls/thread={args.calls}")
+    results = run(args.workers, args.threads, args.calls)
+    if args.save:
+        with open(args.save, "w", encoding="utf-8") as f:
+            json.dump(results, f, indent=2)
+        print(f"saved to {args.save}")
+
+
+if __name__ == "__main__":
+    main()
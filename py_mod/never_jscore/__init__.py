@@ -14,9 +14,13 @@ Features:
 v3.0 Architecture:
 - JSEngine (NEW): Recommended for multi-threaded scenarios, JS code loaded only once
 - Context (LEGACY): Backward compatible API
+- ThreadedContext: Context usable from any Python thread (calls serialized on a dedicated thread)
+- Realm: lightweight V8 context sharing a Context's isolate (ctx.create_realm())
+- JSFuture: cancellable JSEngine task handle (engine.submit()), awaitable from asyncio
+- Trace: function call trace recorded by ctx.trace(), exportable as Chrome trace-event JSON
 """
 
-from .never_jscore import Context, JSEngine
+from .never_jscore import Context, JSEngine, ThreadedContext, Realm, JSFuture, Trace, PoolOverloadedError, HookTerminated
 
 __version__ = "2.5.2"
-__all__ = ["Context", "JSEngine"]
+__all__ = ["Context", "JSEngine", "ThreadedContext", "Realm", "JSFuture", "Trace", "PoolOverloadedError", "HookTerminated"]
@@ -0,0 +1,45 @@
+"""
+JSFuture 的 asyncio 适配
+
+``await engine.submit(...)`` 时由 JSFuture.__await__ 调用：把 JSFuture 包装为当前事件循环的
+asyncio.Future。JSFuture 在完成线程上结束，结果通过 call_soon_threadsafe 回到事件循环；
+asyncio 协程被取消（例如 asyncio.wait_for 超时、HTTP 客户端断开）时取消 JSFuture，
+排队中的任务被移除，执行中的任务被终止。
+"""
+
+import asyncio
+
+
+def wrap_future(js_future):
+    """将 JSFuture 包装为当前事件循环的 asyncio.Future"""
+    loop = asyncio.get_running_loop()
+    future = loop.create_future()
+
+    def on_cancelled(fut):
+        if fut.cancelled():
+            js_future.cancel()
+
+    def on_done(_):
+        try:
+            loop.call_soon_threadsafe(_copy_state, js_future, future)
+        except RuntimeError:
+            # 事件循环已关闭
+            pass
+
+    future.add_done_callback(on_cancelled)
+    js_future.add_done_callback(on_done)
+    return future
+
+
+def _copy_state(js_future, future):
+    """在事件循环线程中把 JSFuture 的结果复制到 asyncio.Future"""
+    if future.done():
+        return
+    if js_future.cancelled():
+        future.cancel()
+        return
+    exception = js_future.exception()
+    if exception is not None:
+        future.set_exception(exception)
+    else:
+        future.set_result(js_future.result())
@@ -5,7 +5,97 @@ never_jscore - 基于 Deno Core 的 JavaScript 运行时
 py_mini_racer 风格的实例化 API。
 """
 
-from typing import Any, List, Union, Optional
+from typing import Any, Callable, Dict, Generator, Iterable, List, Literal, TypedDict, Union, Optional
+
+WaitTimers = Literal["none", "all", "until_result"]
+Priority = Literal["high", "normal", "low"]
+HookAction = Literal["log", "terminate", "replace"]
+
+class JSErrorReport(TypedDict):
+    """未处理的 Promise rejection / 定时器回调异常"""
+    type: Literal["unhandled_rejection", "uncaught_exception"]
+    source: Literal["promise", "timer", "interval"]
+    name: str
+    message: str
+    stack: Optional[str]
+
+class JSEvent(TypedDict):
+    """JS 通过 $emit(channel, data) 发送的事件"""
+    channel: str
+    data: Any
+
+class _HookOptionsRequired(TypedDict):
+    path: str
+
+class HookOptions(_HookOptionsRequired, total=False):
+    """JSEngine(hooks=[...]) 中的一项，含义同 Context.hook() 的参数"""
+    action: HookAction
+    replacement: Union[str, Callable[..., Any]]
+    capture_args: bool
+    capture_return: bool
+
+# Trace.calls() 中的一次调用（时间单位为毫秒，从调用 trace() 时开始计时）
+# "return" 是关键字，只能用函数式语法定义
+TraceCall = TypedDict("TraceCall", {
+    "name": str,
+    "args": Optional[List[Any]],
+    "return": Any,
+    "error": Optional[str],
+    "caller": Optional[str],
+    "start": float,
+    "end": Optional[float],
+    "duration": Optional[float],
+    "children": List["TraceCall"],
+})
+
+class LatencyStats(TypedDict):
+    """任务执行耗时分布（毫秒，分位数按直方图分桶估算；没有样本时为 None）"""
+    count: int
+    mean: Optional[float]
+    p50: Optional[float]
+    p95: Optional[float]
+    p99: Optional[float]
+    max: Optional[float]
+
+class WorkerStats(TypedDict):
+    """单个Worker的运行指标"""
+    worker_id: int
+    healthy: bool
+    busy: bool
+    tasks: int
+    errors: int
+    heap_used_bytes: int
+    heap_total_bytes: int
+    latency_ms: LatencyStats
+
+class QueueSizeByPriority(TypedDict):
+    """各优先级排队中的任务数"""
+    high: int
+    normal: int
+    low: int
+
+class EngineStats(TypedDict):
+    """JSEngine 运行指标（计数从引擎创建开始累计）"""
+    workers: int
+    min_workers: int
+    max_workers: int
+    scaled_up_workers: int
+    scaled_down_workers: int
+    healthy_workers: int
+    queue_size: int
+    queue_size_by_priority: QueueSizeByPriority
+    max_queue_size: Optional[int]
+    in_flight: int
+    completed: int
+    errors: int
+    rejected: int
+    submit_timeouts: int
+    recycled_workers: int
+    respawned_workers: int
+    heap_used_bytes: int
+    uptime_seconds: float
+    latency_ms: LatencyStats
+    per_worker: List[WorkerStats]
 
 class Context:
     """
@@ -19,6 +109,14 @@ class Context:
     - 多个 Context 必须按 LIFO 顺序删除（后创建先删除）
     - 推荐使用单 Context 模式，将所有函数定义在一个 Context 中
 
+    Ctrl+C 中断:
+    - 主线程上创建的 Context 在执行 JS 时（包括等待定时器）按下 Ctrl+C 会终止 JS
+      并抛出 KeyboardInterrupt（或 signal.signal 自定义处理函数抛出的异常；
+      处理函数没有抛出异常时抛出 RuntimeError），Context 之后仍可继续使用
+    - 被中断的调用发送的事件、上报的错误和 Hook 数据会被丢弃
+    - 在创建 Context 之后用 signal.signal() 替换 SIGINT 处理函数时，
+      JS 执行期间的 Ctrl+C 要等调用结束才生效（直到再创建一个 Context）
+
     🆕 扩展功能 (enable_extensions=True 时自动加载):
     - Web APIs: fetch, URL, TextEncoder/Decoder, crypto, Blob, FormData
     - 定时器: setTimeout, setInterval, clearTimeout, clearInterval
@@ -66,7 +164,11 @@ class Context:
         enable_logging: bool = False,
         random_seed: Optional[int] = None,
         enable_node_compat: bool = False,  # Default False - only enable when you need require()
-        fast_return: bool = False  # 快速返回模式，函数return后立即返回不等待定时器
+        fast_return: bool = False,  # 快速返回模式，函数return后立即返回不等待定时器
+        deterministic: bool = False,  # 确定性模式，相同脚本+输入得到逐字节相同的输出
+        on_error: Optional[Callable[[JSErrorReport], Any]] = None,
+        strict_errors: bool = False,
+        on_event: Optional[Callable[[JSEvent], Any]] = None
     ) -> None:
         """
         创建一个新的 JavaScript 执行上下文
@@ -91,6 +193,26 @@ class Context:
                         - True: 函数 return 后立即返回，不等待 setTimeout/setInterval
                           适用于有定时器但只需要函数返回值的场景
                         - False: 正常等待事件循环完成
+            deterministic: 确定性模式，默认 False
+                          - True: 在 random_seed 的基础上进一步固定:
+                            Date / Date.now()（虚拟时钟，起点 2023-11-14T22:13:20Z）、
+                            performance.now()、crypto.randomUUID()、
+                            crypto.subtle.generateKey()（AES/HMAC）、定时器执行顺序
+                            （按虚拟到期时间排序，不真实等待）、navigator 中与宿主相关的值
+                            未指定 random_seed 时使用默认种子 0。
+                            V8 对象哈希种子对整个进程生效，只能在第一次创建
+                            Context / ThreadedContext / JSEngine 时固定：第一个实例使用
+                            deterministic=True 时自动固定；之后才使用时发出 RuntimeWarning。
+                            也可以用环境变量 NEVER_JSCORE_HASH_SEED 指定（无效值抛出 ValueError）
+                          - False: 使用真实时间
+            on_error: 错误回调（可选），Promise rejection 未被处理或定时器回调抛出异常时
+                     调用，参数为 JSErrorReport；所有错误同时记录在 ctx.errors 中
+            strict_errors: 严格模式，默认 False
+                          - True: 未处理错误使当前调用失败（抛出异常）
+                          - False: 只上报，不影响当前调用（类似浏览器控制台）
+            on_event: 事件回调（可选），JS 每调用一次 $emit(channel, data) 调用一次，
+                     参数为 JSEvent；在调用返回前按发送顺序调用。
+                     设置后事件只交给回调，不再记录到 ctx.events() 中
 
         Example:
             >>> # 使用固定随机数种子
@@ -112,15 +234,21 @@ class Context:
             >>> ctx.compile("setInterval(() => {}, 1000); function getData() { return 42; }")
             >>> ctx.call("getData", [])  # 立即返回，不等待定时器
             42
+
+            >>> # 确定性模式（回归测试）
+            >>> ctx = Context(deterministic=True)
+            >>> ctx.evaluate("[Date.now(), performance.now(), crypto.randomUUID()]")  # 每次运行结果相同
         """
         ...
 
-    def compile(self, code: str) -> None:
+    def compile(self, code: str, name: Optional[str] = None) -> None:
         """
         编译 JavaScript 代码并加入全局作用域
 
         Args:
             code: JavaScript 代码字符串
+            name: 脚本名称（可选）。错误堆栈中显示为该名称，
+                  并可用 trace(["script:名称"]) 跟踪该脚本定义的全局函数
 
         Raises:
             Exception: 当代码编译失败时
@@ -140,7 +268,8 @@ class Context:
         self,
         code: str,
         return_value: bool = True,
-        auto_await: Optional[bool] = None
+        auto_await: Optional[bool] = None,
+        wait_timers: Optional[WaitTimers] = None
     ) -> Any:
         """
         执行代码并将其加入全局作用域
@@ -149,6 +278,7 @@ class Context:
             code: JavaScript 代码字符串
             return_value: 是否返回最后一个表达式的值（默认 False）
             auto_await: 是否自动等待 Promise（默认 True）
+            wait_timers: 定时器处理策略，仅 return_value=True 时生效（见 call()）
 
         Returns:
             如果 return_value=True，返回最后表达式的值；否则返回 None
@@ -165,13 +295,19 @@ class Context:
         """
         ...
 
-    def evaluate(self, code: str, auto_await: Optional[bool] = None) -> Any:
+    def evaluate(
+        self,
+        code: str,
+        auto_await: Optional[bool] = None,
+        wait_timers: Optional[WaitTimers] = None
+    ) -> Any:
         """
         执行代码并返回结果（不影响全局作用域）
 
         Args:
             code: JavaScript 代码字符串
             auto_await: 是否自动等待 Promise（默认 True）
+            wait_timers: 定时器处理策略（见 call()，默认 "until_result"）
 
         Returns:
             表达式的值，自动转换为 Python 对象
@@ -196,7 +332,8 @@ class Context:
         self,
         name: str,
         args: List[Any] = [],
-        auto_await: Optional[bool] = None
+        auto_await: Optional[bool] = None,
+        wait_timers: Optional[WaitTimers] = None
     ) -> Any:
         """
         调用 JavaScript 函数（支持 Promise）
@@ -205,11 +342,17 @@ class Context:
             name: 函数名称
             args: 参数列表
             auto_await: 是否自动等待 Promise（默认 True）
+            wait_timers: 本次调用创建的定时器如何处理（默认 "until_result"）
+                - "until_result": 拿到结果立即返回，并取消本次调用创建的定时器
+                  （auto_await=False 时取消所有定时器，包括 compile() 创建的）
+                - "none": 拿到结果立即返回，定时器保留，可稍后 run_until_idle()
+                - "all": 等待所有定时器/异步操作完成后再返回
 
         Returns:
             函数返回值，自动转换为 Python 对象
 
         Raises:
+            HookTerminated: JS 调用了 $terminate(data)，e.data 为保存的数据
             Exception: 当函数调用失败时
 
         Example:
@@ -221,6 +364,196 @@ class Context:
         """
         ...
 
+    def pending_tasks(self) -> dict[str, Any]:
+        """
+        列出事件循环中待处理的任务
+
+        Returns:
+            - timers: [{"id", "type": "timeout" | "interval", "delay"}]
+            - promises: 尚未 settle 的 Promise 数量（自首次调用 pending_tasks() 起统计，之前为 None）
+            - ops: 进行中的异步操作数量
+            - resources: 打开的资源数量
+            - native_timers: deno_core 层面的定时器数量
+
+        Example:
+            >>> ctx.evaluate("setInterval(() => {}, 1000); 1", wait_timers="none")
+            >>> ctx.pending_tasks()["timers"]
+            [{'id': 1, 'type': 'interval', 'delay': 1000}]
+        """
+        ...
+
+    def run_until_idle(self, timeout_ms: Optional[int] = None) -> bool:
+        """
+        运行事件循环直到空闲（执行剩余的定时器、Promise、异步操作）
+
+        Args:
+            timeout_ms: 最长等待时间（毫秒），None 表示一直等待
+
+        Returns:
+            True 表示已空闲；False 表示超时（例如仍有 setInterval）
+        """
+        ...
+
+    def run_microtasks(self) -> None:
+        """
+        执行一次微任务检查点，只运行已排队的 Promise 回调，不触发定时器
+        """
+        ...
+
+    @property
+    def errors(self) -> List[JSErrorReport]:
+        """
+        已收集的未处理错误（未处理的 Promise rejection、定时器回调异常）
+
+        Example:
+            >>> ctx.evaluate("setTimeout(() => { throw new Error('x') }, 0); 1", wait_timers="all")
+            >>> ctx.errors[0]["message"]
+            'x'
+        """
+        ...
+
+    def clear_errors(self) -> None:
+        """清空 ctx.errors"""
+        ...
+
+    def events(self, channel: Optional[str] = None) -> List[JSEvent]:
+        """
+        已收集的 $emit() 事件（按发送顺序）
+
+        JS 调用 $emit(channel, data) 不会中断执行，data 经 JSON 序列化后传给 Python；
+        被 $terminate() 终止的调用在终止前发送的事件同样会记录。
+        设置了 on_event 时事件只交给回调，不在这里记录。
+
+        Args:
+            channel: 只返回该频道的事件（默认返回全部）
+
+        Example:
+            >>> ctx.evaluate("[1, 2, 3].forEach(k => $emit('round_key', k)); 'done'")
+            'done'
+            >>> [e["data"] for e in ctx.events("round_key")]
+            [1, 2, 3]
+        """
+        ...
+
+    def clear_events(self) -> None:
+        """清空已收集的 $emit() 事件"""
+        ...
+
+    def hook(
+        self,
+        path: str,
+        action: HookAction = "log",
+        replacement: Optional[Union[str, Callable[..., Any]]] = None,
+        capture_args: bool = True,
+        capture_return: bool = True
+    ) -> None:
+        """
+        为全局函数安装 Hook，不需要手写 monkey-patch 代码
+
+        目标按路径从 globalThis 查找，必须已经定义。包装后的函数保留原函数的
+        name / length，toString() 显示为 [native code]。
+        每次调用作为 "hook" 频道的事件上报（ctx.events("hook") / on_event）：
+        {"path", "args"?, "return"? | "error"?}，返回 Promise 时上报 resolve 的值。
+
+        Args:
+            path: 目标函数路径，如 "CryptoJS.AES.encrypt"
+            action: 调用时的行为，默认 "log"
+                   - "log": 调用原函数并上报
+                   - "terminate": 调用 $terminate({"path", "args"})，本次调用抛出 HookTerminated
+                   - "replace": 以 replacement 代替原函数并上报
+            replacement: action="replace" 时必填
+                        - str: JS 函数源码，以原来的 this 和参数调用
+                        - Python 可调用对象: 以 JS 参数按位置调用，返回值传回 JS，
+                          抛出的异常在 JS 中抛出
+            capture_args: 上报参数，默认 True
+            capture_return: 上报返回值，默认 True
+
+        Raises:
+            ValueError: action 无效或 replacement 与 action 不匹配
+            Exception: 目标不存在或不是函数
+
+        Example:
+            >>> ctx.hook("CryptoJS.AES.encrypt")
+            >>> ctx.call("login", ["user", "pass"])
+            >>> ctx.events("hook")[0]["data"]["args"]
+            >>> ctx.hook("getTimestamp", action="replace", replacement=lambda: 1700000000000)
+            >>> ctx.hook("sign", action="terminate")  # 拿到参数后立即停止
+        """
+        ...
+
+    def trace(
+        self,
+        targets: List[str],
+        max_events: int = 100000,
+        capture_args: bool = True,
+        capture_return: bool = True
+    ) -> "Trace":
+        """
+        跟踪函数调用，记录参数、返回值、调用者和时间
+
+        被跟踪的函数包装后仍显示为 [native code]。调用记录在 Rust 侧缓冲区中，
+        组成调用树：调用者为调用开始时仍在执行的最内层被跟踪调用。
+
+        Args:
+            targets: 跟踪目标列表，每项为
+                    - 函数路径，如 "CryptoJS.AES.encrypt"
+                    - 对象路径，跟踪该对象自身的所有方法，如 "CryptoJS.AES"
+                    - "script:名称"，跟踪 compile(code, name=名称) 定义的所有全局函数
+                      （只包括 var / function 声明；顶层 const / let / class 不是全局对象的属性，
+                      无法从脚本外部替换，需要跟踪时改用对象路径或改为 var 声明）
+            max_events: 最多记录的调用数，默认 100000，超出的调用计入 trace.dropped
+            capture_args: 记录参数，默认 True
+            capture_return: 记录返回值，默认 True；返回 Promise 时记录 resolve 的值
+
+        Returns:
+            Trace: calls() 返回调用树，save() 导出 Chrome trace-event JSON；
+                   trace.stop()（或退出 with 语句）时还原被包装的函数
+
+        Raises:
+            Exception: 目标不存在，或没有以该名称编译的脚本
+
+        Example:
+            >>> ctx.compile(code, name="app.js")
+            >>> with ctx.trace(["script:app.js"]) as trace:
+            ...     ctx.call("login", ["user", "pass"])
+            >>> for call in trace.calls():
+            ...     print(call["name"], call["args"], call["return"])
+            >>> trace.save("trace.json")  # 在 https://ui.perfetto.dev 打开
+        """
+        ...
+
+    def create_realm(self, expose_parent: bool = False) -> "Realm":
+        """
+        创建轻量级 realm（同一 Isolate 中的新 V8 Context）
+
+        realm 共享本 Context 的 Isolate 和事件循环，但拥有独立的全局对象和
+        ECMAScript 内置对象（Object、Array、Date 等，类似 iframe / ShadowRealm），
+        并安装了相同的 Web API 全局对象（URL、atob、crypto、setTimeout 等）。
+        比创建新的 Context 快得多。
+
+        注意：Web API 对象与本 Context 是同一批对象，在 realm 中修改它们对双方可见，
+        realm 不是安全沙箱。Math.random 和确定性模式下的 Date 是 realm 自己的包装函数，
+        委托给本 Context 的实现（共享随机数流和虚拟时钟）。
+        Deno 等运行时内部对象不会安装到 realm 中；realm 自己的
+        Function.prototype.toString、Object.keys 等同样应用 native 显示和全局属性隐藏。
+
+        Args:
+            expose_parent: realm 中的 parent / top 是否指向本 Context 的全局对象
+                           （默认 False，指向 realm 自身，类似顶层窗口）
+
+        Returns:
+            Realm: 使用 realm.eval() / realm.call() 执行代码
+
+        Example:
+            >>> ctx = never_jscore.Context()
+            >>> realm = ctx.create_realm()
+            >>> realm.eval("Array.prototype.evil = 1; var token = 'realm'")
+            >>> ctx.evaluate("[].evil")           # None，内置对象互不影响
+            >>> ctx.evaluate("typeof token")      # 'undefined'
+            >>> realm.call("btoa", ["hi"])        # 'aGk='
+        """
+        ...
+
     def gc(self) -> None:
         """
         请求 V8 垃圾回收
@@ -257,6 +590,119 @@ class Context:
         """
         ...
 
+    def set_random_seed(self, seed: Optional[int]) -> None:
+        """
+        运行时重设随机数种子，无需重建 Context
+
+        同时作用于 Math.random、crypto.getRandomValues 和 crypto.randomUUID。
+
+        Args:
+            seed: 新种子；None 恢复为系统随机数
+
+        Example:
+            >>> ctx = Context()
+            >>> ctx.set_random_seed(42)
+            >>> a = ctx.evaluate("Math.random()")
+            >>> ctx.set_random_seed(42)
+            >>> b = ctx.evaluate("Math.random()")
+            >>> a == b
+            True
+        """
+        ...
+
+    def get_rng_state(self) -> dict[str, Any]:
+        """
+        获取当前随机数生成器状态
+
+        Returns:
+            {"seed": int | None, "math_pos": int, "crypto_pos": int,
+             "v8": (s0, s1, index) | None}
+            可传给 set_rng_state() 恢复到完全相同的位置
+
+        Example:
+            >>> ctx = Context(random_seed=1)
+            >>> state = ctx.get_rng_state()
+            >>> a = ctx.evaluate("[Math.random(), crypto.randomUUID()]")
+            >>> ctx.set_rng_state(state)
+            >>> b = ctx.evaluate("[Math.random(), crypto.randomUUID()]")
+            >>> a == b
+            True
+        """
+        ...
+
+    def set_rng_state(self, state: dict[str, Any]) -> None:
+        """
+        恢复 get_rng_state() 保存的随机数生成器状态
+
+        Args:
+            state: get_rng_state() 返回的字典
+        """
+        ...
+
+    def set_v8_random_state(self, s0: int, s1: int, index: int = 0) -> None:
+        """
+        设置 V8 Math.random 内部状态（xorshift128+），切换到 V8 模拟模式
+
+        Math.random() 将与 Chrome/Node 在相同内部状态下的输出逐位一致，
+        包括 64 个值的缓存及其倒序消费顺序。调用 set_random_seed() 退出此模式。
+
+        Args:
+            s0: V8 内部 state0（最近一次缓存填充后的状态）
+            s1: V8 内部 state1
+            index: 缓存中剩余未消费的数量（0-64），0 表示下次调用时重新填充
+        """
+        ...
+
+    def set_v8_random_seed(self, seed: int) -> None:
+        """
+        按 V8 --random-seed 规则设置 Math.random 状态
+
+        Example:
+            >>> ctx = Context()
+            >>> ctx.set_v8_random_seed(12345)
+            >>> ctx.evaluate("Math.random()")  # 与 node --random-seed=12345 相同
+            0.9044192244068718
+        """
+        ...
+
+    def get_v8_random_state(self) -> Optional[tuple[int, int, int]]:
+        """
+        获取 V8 Math.random 模拟状态
+
+        Returns:
+            (s0, s1, index)，未启用 V8 模式时返回 None
+        """
+        ...
+
+    def recover_v8_random_state(
+        self,
+        outputs: List[float],
+        cache_offset: int = 0,
+        apply: bool = True
+    ) -> tuple[int, int, int]:
+        """
+        从观察到的连续 Math.random() 输出恢复 V8 内部状态
+
+        Args:
+            outputs: 按调用顺序排列的连续输出（通常 4-5 个即可）
+            cache_offset: outputs[0] 之前当前 64 值缓存块已被消费的数量（新页面为 0）
+                         所有输出必须位于同一个缓存块内
+            apply: 是否立即应用到当前 Context（默认 True）
+
+        Returns:
+            (s0, s1, index)，可传给 set_v8_random_state()
+
+        Raises:
+            Exception: 输出不足或不是同一缓存块内的连续 V8 输出
+
+        Example:
+            >>> ctx = Context()
+            >>> ctx.recover_v8_random_state(observed_in_browser[:5])
+            >>> ctx.evaluate("Math.random()") == observed_in_browser[5]
+            True
+        """
+        ...
+
     def get_heap_statistics(self) -> dict[str, int]:
         """
         获取 V8 堆内存统计信息
@@ -314,73 +760,73 @@ class Context:
 
     def get_hook_data(self) -> Optional[str]:
         """
-        获取 Hook 拦截的数据
+        获取最近一次 Hook 拦截的数据（兼容旧版本）
 
-        当 JavaScript 调用 __saveAndTerminate__() 或 $terminate() 时，
-        数据会保存到全局存储中。使用此方法可以在 JS 被终止后获取保存的数据。
-
-        ⚠️ 重要特性：
-        - Hook 数据会一直保留，直到被手动清空或被新的 $terminate() 覆盖
-        - 可以多次调用 get_hook_data() 读取同一个数据
-        - 如果需要执行新的 hook，建议先调用 clear_hook_data() 清空旧数据
+        JS 调用 $terminate() / __saveAndTerminate__() 时，当前调用抛出 HookTerminated，
+        数据在异常的 data 属性中（推荐）。此方法返回本 Context 最近一次保存的数据，
+        各 Context 相互独立，不会互相覆盖。
 
         Returns:
             Optional[str]: 如果有保存的数据则返回 JSON 字符串，否则返回 None
 
         Example:
-            >>> import never_jscore
-            >>> import json
-            >>>
             >>> ctx = never_jscore.Context()
-            >>>
-            >>> # 第一次拦截
             >>> try:
             ...     ctx.evaluate('$terminate({ n: 1 });')
-            ... except:
-            ...     pass
-            >>> data1 = ctx.get_hook_data()  # {"n": 1}
-            >>>
-            >>> # 第二次拦截 - 自动清空了第一次的数据
-            >>> try:
-            ...     ctx.evaluate('$terminate({ n: 2 });')
-            ... except:
-            ...     pass
-            >>> data2 = ctx.get_hook_data()  # {"n": 2} ✅ 正确
-            >>>
-            >>> # 如果需要保留第一次的数据，必须在第二次执行前保存：
-            >>> # saved = ctx.get_hook_data()  # 在下一次 evaluate() 前保存
+            ... except never_jscore.HookTerminated as e:
+            ...     print(e.data)  # {'n': 1}（推荐）
+            >>> ctx.get_hook_data()  # '{"n":1}'（兼容）
         """
         ...
 
     def clear_hook_data(self) -> None:
         """
-        清空保存的 Hook 数据
-
-        建议在执行新的 hook 之前调用此方法，以确保不会读到旧的 hook 数据。
+        清空 get_hook_data() 保存的数据
 
         Example:
-            >>> ctx = never_jscore.Context()
-            >>>
-            >>> # 第一次 hook
-            >>> try:
-            ...     ctx.evaluate('$terminate({ n: 1 });')
-            ... except:
-            ...     pass
-            >>> data1 = ctx.get_hook_data()  # {"n": 1}
-            >>>
-            >>> # 清空旧数据，准备下一次 hook
             >>> ctx.clear_hook_data()
-            >>>
-            >>> # 第二次 hook
-            >>> try:
-            ...     ctx.evaluate('$terminate({ n: 2 });')
-            ... except:
-            ...     pass
-            >>> data2 = ctx.get_hook_data()  # {"n": 2}
+            >>> assert ctx.get_hook_data() is None
         """
         ...
 
 
+class PoolOverloadedError(RuntimeError):
+    """
+    JSEngine 任务队列已满，任务被拒绝
+
+    submit_policy 为 "reject" 或 "timeout" 时抛出，用于在高负载时主动降级（例如返回 503）。
+
+    Example:
+        >>> engine = JSEngine(code, max_queue_size=1000, submit_policy="reject")
+        >>> try:
+        ...     engine.call("sign", [data])
+        ... except never_jscore.PoolOverloadedError:
+        ...     return Response(status=503)
+    """
+    ...
+
+
+class HookTerminated(Exception):
+    """
+    JS 调用 $terminate(data) / __saveAndTerminate__(data) 终止了执行
+
+    由 Context / ThreadedContext / JSEngine 的调用抛出，Hook 数据属于本次调用，
+    多个 Context、多个 JSEngine 同时拦截也不会互相覆盖。
+
+    Attributes:
+        data: $terminate() 保存的数据（已解析为 Python 对象）
+        worker_id: 执行的 Worker ID（JSEngine），Context 为 None
+
+    Example:
+        >>> try:
+        ...     engine.call("encrypt", ["hello"])
+        ... except never_jscore.HookTerminated as e:
+        ...     key = e.data["key"]
+    """
+    data: Any
+    worker_id: Optional[int]
+
+
 class JSEngine:
     """
     JavaScript引擎 (v3.0新增 - 推荐使用)
@@ -431,11 +877,24 @@ class JSEngine:
         enable_node_compat: bool = False,
         enable_logging: bool = False,
         random_seed: Optional[int] = None,
-        fast_return: bool = False  # 快速返回模式，函数return后立即返回不等待定时器
+        fast_return: bool = False,  # 快速返回模式，函数return后立即返回不等待定时器
+        deterministic: bool = False,  # 确定性模式，所有Worker输出一致
+        per_worker_seed: bool = False,  # 每个Worker使用 random_seed + worker_id
+        max_queue_size: Optional[int] = None,  # 任务队列最大长度
+        submit_policy: Literal["block", "reject", "timeout"] = "block",
+        submit_timeout: Optional[float] = None,  # "timeout" 策略的等待秒数
+        max_tasks_per_worker: Optional[int] = None,  # 处理多少任务后重建Worker
+        max_worker_heap_mb: Optional[int] = None,  # Worker堆内存上限（MB）
+        min_workers: Optional[int] = None,  # 弹性伸缩：最少Worker数
+        max_workers: Optional[int] = None,  # 弹性伸缩：最多Worker数
+        idle_timeout: float = 60.0,  # 弹性伸缩：空闲Worker回收时间（秒）
+        hooks: Optional[List[HookOptions]] = None,  # 每个Worker安装的 Hook
     ) -> None:
         """
         创建JavaScript引擎
 
+        等待所有Worker加载初始化代码后返回；初始化代码出错时抛出 RuntimeError。
+
         Args:
             code: JavaScript代码（只在Worker初始化时加载一次）
                  可以是大型JS库、函数定义等
@@ -449,10 +908,38 @@ class JSEngine:
                           开启后会输出Worker状态信息
             random_seed: 随机数种子（默认None）
                         用于确定性随机数生成
+            per_worker_seed: 每个Worker使用不同的种子（默认False）
+                           - True: Worker N 使用 random_seed + N，避免所有Worker输出相同序列
+                           - False: 所有Worker使用相同种子
             fast_return: 快速返回模式，默认 False
                         - True: 函数 return 后立即返回，不等待 setTimeout/setInterval
                           适用于有定时器但只需要函数返回值的场景
                         - False: 正常等待事件循环完成
+            deterministic: 确定性模式，默认 False
+                          与 Context(deterministic=True) 相同，
+                          每个任务开始时虚拟时钟和随机数都回到初始状态，
+                          输出与任务由哪个Worker执行、之前执行过多少任务无关
+            max_queue_size: 任务队列最大长度（默认None，不限制）
+                          高负载时限制排队任务数，避免内存无限增长、延迟失控
+            submit_policy: 队列已满时的提交策略（默认"block"）
+                         - "block": 等待队列有空位
+                         - "reject": 立即抛出 PoolOverloadedError
+                         - "timeout": 最多等待 submit_timeout 秒，超时抛出 PoolOverloadedError
+            submit_timeout: "timeout" 策略的等待时间（秒）
+            max_tasks_per_worker: 每个Worker处理多少个任务后重建（默认None，不限制）
+                                在当前任务完成后销毁 JsRuntime，用相同的 code 重新初始化，
+                                排队中的任务不会丢失。适合全局缓存持续增长的混淆脚本
+            max_worker_heap_mb: Worker堆内存上限（MB，默认None）
+                              任务完成后已用堆超过该值时重建 Worker
+            min_workers: 弹性伸缩时最少保持的Worker数（默认None，固定 workers 个Worker；
+                       只指定 max_workers 时为1）
+            max_workers: 弹性伸缩时的Worker数上限（默认等于 workers，两者只需指定一个）
+                       启动 min_workers 个Worker，排队任务多于空闲Worker时按需增加，
+                       新Worker使用相同的配置和初始化代码（以及 persist=True 的广播）
+            idle_timeout: 空闲超过该秒数的Worker退出，不少于 min_workers 个（默认60）
+            hooks: 每个Worker加载 code 后安装的 Hook 列表（默认None），含义同 Context.hook()
+                  重建 JsRuntime 后重新安装；上报的 "hook" 事件随本次调用返回
+                  （call(..., with_events=True)）。Python 回调在 Worker 线程上获取 GIL 调用
 
         Example:
             >>> # 基本用法
@@ -464,6 +951,13 @@ class JSEngine:
             >>> # 指定Worker数量
             >>> engine = JSEngine(code, workers=8)
             >>>
+            >>> # 弹性伸缩：空闲时1个Worker，突发流量时最多16个
+            >>> engine = JSEngine(code, min_workers=1, max_workers=16, idle_timeout=30)
+            >>>
+            >>> # 记录每次加密调用的参数
+            >>> engine = JSEngine(code, hooks=[{"path": "CryptoJS.AES.encrypt"}])
+            >>> result, events = engine.call("login", ["user", "pass"], with_events=True)
+            >>>
             >>> # 使用Node.js库
             >>> engine = JSEngine('''
             ...     const _ = require('lodash');
@@ -482,18 +976,38 @@ class JSEngine:
         """
         ...
 
-    def call(self, func_name: str, args: List[Any]) -> Any:
+    def call(
+        self,
+        func_name: str,
+        args: List[Any],
+        seed: Optional[int] = None,
+        affinity_key: Optional[str] = None,
+        priority: Priority = "normal",
+        with_events: bool = False,
+    ) -> Any:
         """
         调用已定义的JavaScript函数
 
         Args:
             func_name: 函数名（必须在初始化代码中定义）
             args: 参数列表
+            seed: 本次调用使用的随机数种子（可选）
+                 无论分配到哪个Worker结果都相同，调用结束后恢复Worker原有的随机数状态
+            affinity_key: 亲和 key（可选），相同 key 的调用始终交给同一个Worker，
+                 用于有状态的脚本（会话计数器、缓存的token等）；
+                 弹性伸缩时 key 的归属不变（对应Worker按需启动且不做空闲回收）；
+                 该Worker意外退出时迁移到其他Worker，重建完成后迁回
+            priority: 任务优先级 "high" / "normal" / "low"（默认"normal"）
+                 排队时高优先级任务先执行，低优先级任务按较小的份额（high:normal:low 约 4:2:1）
+                 继续执行，不会被饿死；指定了 affinity_key 的任务在对应Worker上按同样的规则排队
+            with_events: True 时返回 (result, events)，events 为本次调用 $emit() 发送的
+                 JSEvent 列表；调用失败时事件列表设置在异常的 events 属性上（默认False）
 
         Returns:
-            函数返回值，自动转换为Python对象
+            函数返回值，自动转换为Python对象（with_events=True 时为 (返回值, 事件列表)）
 
         Raises:
+            HookTerminated: JS 调用了 $terminate(data)，e.data 为保存的数据，e.worker_id 为执行的Worker
             Exception: 函数不存在或执行失败时
 
         Example:
@@ -501,10 +1015,19 @@ class JSEngine:
             >>> result = engine.call("add", [1, 2])
             >>> print(result)
             3
+            >>> engine.call("sign", [payload], priority="high")  # 交互请求优先于批量任务
+            >>> result, events = engine.call("encrypt", ["hello"], with_events=True)
         """
         ...
 
-    def execute(self, code: str) -> Any:
+    def execute(
+        self,
+        code: str,
+        seed: Optional[int] = None,
+        affinity_key: Optional[str] = None,
+        priority: Priority = "normal",
+        with_events: bool = False,
+    ) -> Any:
         """
         执行JavaScript代码
 
@@ -512,6 +1035,10 @@ class JSEngine:
 
         Args:
             code: JavaScript代码
+            seed: 本次执行使用的随机数种子（可选）
+            affinity_key: 亲和 key（可选，同 call）
+            priority: 任务优先级（默认"normal"，同 call）
+            with_events: True 时返回 (result, events)（同 call）
 
         Returns:
             执行结果
@@ -524,50 +1051,626 @@ class JSEngine:
         """
         ...
 
+    def submit(
+        self,
+        func_name: str,
+        args: List[Any],
+        seed: Optional[int] = None,
+        affinity_key: Optional[str] = None,
+        priority: Priority = "normal",
+    ) -> "JSFuture":
+        """
+        提交函数调用，立即返回可取消的 JSFuture
+
+        future.cancel() 会移除排队中的任务或终止执行中的任务；JSFuture 可以直接 await，
+        asyncio 协程被取消时任务随之取消。提交本身遵循 submit_policy
+        （"block" 策略在队列已满时会阻塞直到有空位）。
+
+        Args:
+            func_name: 函数名
+            args: 参数列表
+            seed: 本次调用使用的随机数种子（可选）
+            affinity_key: 亲和 key（可选，同 call）
+            priority: 任务优先级（默认"normal"，同 call）
+
+        Example:
+            >>> future = engine.submit("sign", [data])
+            >>> result = future.result(timeout=5)
+            >>> result = await asyncio.wait_for(engine.submit("sign", [data]), timeout=5)
+        """
+        ...
+
+    def call_on(self, worker_id: int, func_name: str, args: List[Any], seed: Optional[int] = None) -> Any:
+        """
+        在指定Worker上调用JavaScript函数
+
+        任务以 normal 优先级在该Worker上排队，占用队列名额（遵循 max_queue_size 和 submit_policy）。
+
+        Args:
+            worker_id: Worker的ID（0 到 max_workers-1）
+            func_name: 函数名
+            args: 参数列表
+            seed: 本次调用使用的随机数种子（可选）
+
+        Raises:
+            ValueError: worker_id 超出范围
+            RuntimeError: 指定Worker已退出（正在重建）
+
+        Example:
+            >>> token = engine.call_on(0, "getToken", [])
+        """
+        ...
+
+    def call_many(
+        self,
+        func_name: str,
+        args_list: Iterable[List[Any]],
+        ordered: bool = True,
+        return_exceptions: bool = False,
+        chunk_size: Optional[int] = None,
+        priority: Priority = "normal",
+    ) -> List[Any]:
+        """
+        批量调用JavaScript函数
+
+        将调用分块提交给Worker，同一分块在一个Worker上依次执行，只释放一次GIL，
+        比逐个调用 call() 开销小得多。
+
+        Args:
+            func_name: 函数名
+            args_list: 参数列表的列表，每项是一次调用的参数
+            ordered: True 按输入顺序返回结果；False 按完成顺序返回（默认True）
+            return_exceptions: True 时失败项以异常对象放入结果列表；
+                               False 时任一调用失败则抛出异常（默认False）
+            chunk_size: 每个分块的调用数量（默认按Worker数量自动计算）
+            priority: 任务优先级（默认"normal"，同 call），后台批量任务通常使用 "low"
+
+        Returns:
+            结果列表
+
+        Example:
+            >>> results = engine.call_many("encrypt", [["a", 1], ["b", 2]])
+            >>> results = engine.call_many("parse", inputs, return_exceptions=True)
+            >>> errors = [r for r in results if isinstance(r, Exception)]
+            >>> results = engine.call_many("encrypt", backlog, priority="low")  # 不阻塞交互请求
+        """
+        ...
+
+    def map(
+        self,
+        func_name: str,
+        items: Iterable[Any],
+        ordered: bool = True,
+        return_exceptions: bool = False,
+        chunk_size: Optional[int] = None,
+        priority: Priority = "normal",
+    ) -> List[Any]:
+        """
+        对每个元素调用JavaScript函数（每个元素作为唯一参数）
+
+        等价于 call_many(func_name, [[item] for item in items], ...)
+
+        Example:
+            >>> signs = engine.map("sign", ["a", "b", "c"])
+        """
+        ...
+
+    def broadcast(
+        self,
+        code: str,
+        persist: bool = True,
+        return_exceptions: bool = False,
+    ) -> List[Any]:
+        """
+        在每个Worker上执行一次代码，返回每个Worker的结果（按worker_id排序；弹性伸缩时只包含当前启用的Worker，之后启动的Worker通过回放获得 persist=True 的广播）
+
+        用于更新所有Worker的状态（轮换密钥、刷新Cookie、修补环境等）。
+        广播不受 max_queue_size 限制；正在重建的Worker会在重建完成后执行。
+
+        Args:
+            code: JavaScript代码
+            persist: 是否记录到回放日志，Worker重建后自动重新执行（默认True）
+            return_exceptions: True 时失败的Worker以异常对象放入结果列表，
+                否则抛出异常并指出失败的Worker（默认False）
+
+        Example:
+            >>> engine.broadcast("globalThis.API_KEY = 'new-key'")
+        """
+        ...
+
+    def broadcast_call(
+        self,
+        func_name: str,
+        args: List[Any],
+        persist: bool = True,
+        return_exceptions: bool = False,
+    ) -> List[Any]:
+        """
+        在每个Worker上调用一次函数（参数含义同 broadcast）
+
+        Example:
+            >>> engine.broadcast_call("setCookie", ["sid=abc"])
+        """
+        ...
+
+    def reload(self, code: str, drain: bool = True) -> None:
+        """
+        热重载初始化代码（目标网站更新脚本时无需重建引擎）
+
+        Worker在当前任务完成后使用新代码重建JsRuntime（持久化的广播会重新执行），
+        排队中的任务不会丢失。
+
+        Args:
+            code: 新的初始化代码
+            drain: True 逐个Worker切换，其余Worker继续处理任务（默认）；
+                False 所有Worker同时切换，更快但切换期间暂停处理任务
+
+        Raises:
+            RuntimeError: 任一Worker加载新代码失败（已切换的Worker回滚到旧代码，引擎继续可用）
+
+        Example:
+            >>> engine.reload(open("new_sign.js").read())
+        """
+        ...
+
     @property
     def workers(self) -> int:
-        """Worker数量"""
+        """当前Worker数量（弹性伸缩时随负载在 min_workers 和 max_workers 之间变化）"""
+        ...
+
+    @property
+    def min_workers(self) -> int:
+        """最少保持的Worker数（固定大小的引擎等于 max_workers）"""
+        ...
+
+    @property
+    def max_workers(self) -> int:
+        """Worker数上限（worker_id 的取值范围为 0 到 max_workers-1）"""
+        ...
+
+    @property
+    def queue_size(self) -> int:
+        """当前排队等待Worker处理的任务数"""
+        ...
+
+    @property
+    def max_queue_size(self) -> Optional[int]:
+        """任务队列最大长度（None表示不限制）"""
+        ...
+
+    @property
+    def rejected_tasks(self) -> int:
+        """因队列已满被拒绝的任务数"""
+        ...
+
+    @property
+    def recycled_workers(self) -> int:
+        """Worker因 max_tasks_per_worker / max_worker_heap_mb 重建的次数"""
+        ...
+
+    @property
+    def healthy_workers(self) -> int:
+        """
+        已初始化且仍在运行的Worker数
+
+        Worker初始化失败或崩溃退出后由监督线程按退避时间（100ms 起，最长 10s）自动重建，
+        重建完成前此值小于 workers；为 0 时 call() 等方法立即抛出 RuntimeError。
+        """
+        ...
+
+    @property
+    def respawned_workers(self) -> int:
+        """Worker因初始化失败或崩溃被重建的次数"""
+        ...
+
+    def stats(self) -> EngineStats:
+        """
+        获取运行指标
+
+        延迟为Worker执行任务的耗时（毫秒，不含排队时间）；
+        in_flight 为正在执行的任务数，submit_timeouts 为 submit_policy="timeout" 等待超时的次数。
+
+        Example:
+            >>> stats = engine.stats()
+            >>> print(stats["queue_size"], stats["latency_ms"]["p99"])
+        """
+        ...
+
+    def prometheus_metrics(
+        self,
+        namespace: str = "never_jscore",
+        labels: Optional[Dict[str, str]] = None,
+    ) -> str:
+        """
+        以 Prometheus 文本格式导出运行指标（可直接作为 /metrics 接口的响应体）
+
+        Args:
+            namespace: 指标名前缀
+            labels: 附加到每个指标的标签，用于区分多个引擎（worker、le 为保留标签）
+
+        Raises:
+            ValueError: namespace 或标签名不合法
+
+        Example:
+            >>> text = engine.prometheus_metrics(labels={"pool": "sign"})
+        """
         ...
 
     def get_hook_data(self, worker_id: int) -> Optional[str]:
         """
-        获取指定Worker的Hook数据
+        获取指定Worker最近一次的Hook数据（兼容旧版本，不推荐使用）
 
-        当调用返回 {"__hook__": true, "worker_id": N} 时，
-        使用此方法获取实际的hook数据
+        $terminate() 拦截的数据随调用抛出的 HookTerminated 异常返回（e.data），
+        不需要再调用此方法。这里的数据按 worker_id 保存在进程全局存储中，
+        多个 JSEngine 的同号Worker会互相覆盖。
 
         Args:
-            worker_id: Worker的ID（从返回结果中获取）
+            worker_id: Worker的ID（HookTerminated.worker_id）
 
         Returns:
             Hook数据的JSON字符串，如果没有数据则返回None
 
         Example:
-            >>> result = engine.call("hookFunc", [data])
-            >>> if isinstance(result, dict) and result.get("__hook__"):
-            ...     worker_id = result["worker_id"]
-            ...     hook_data = engine.get_hook_data(worker_id)
-            ...     if hook_data:
-            ...         import json
-            ...         data = json.loads(hook_data)
+            >>> try:
+            ...     engine.call("hookFunc", [data])
+            ... except never_jscore.HookTerminated as e:
+            ...     data = e.data  # 推荐
+            ...     raw = engine.get_hook_data(e.worker_id)  # 兼容：JSON字符串
         """
         ...
 
     def clear_hook_data(self, worker_id: int) -> None:
         """
-        清空指定Worker的Hook数据
+        清空指定Worker的Hook数据（兼容旧版本，见 get_hook_data）
 
         Args:
             worker_id: Worker的ID
         """
         ...
 
+    def close(self, wait: bool = True, timeout: Optional[float] = None) -> bool:
+        """
+        关闭引擎：停止接收新任务，Worker线程退出并释放isolate（重复调用是安全的）
+
+        关闭后再调用 call() 等方法抛出 RuntimeError。
+
+        Args:
+            wait: True 时先处理完已排队的任务（默认）；
+                False 时立即取消已排队的任务（等待结果的调用抛出异常），不等待Worker退出
+            timeout: wait=True 时最多等待的秒数（默认一直等待），超时后取消剩余任务
+
+        Returns:
+            所有Worker线程都已退出时返回 True
+
+        Example:
+            >>> engine.close(timeout=5.0)
+        """
+        ...
+
+    @property
+    def closed(self) -> bool:
+        """引擎是否已关闭"""
+        ...
+
     def __enter__(self) -> "JSEngine":
         """上下文管理器入口"""
         ...
 
     def __exit__(self, exc_type: Any, exc_value: Any, traceback: Any) -> bool:
-        """上下文管理器退出"""
+        """上下文管理器退出（调用 close()，等待已排队的任务完成）"""
+        ...
+
+    def __repr__(self) -> str:
+        """字符串表示"""
+        ...
+
+
+class Trace:
+    """
+    函数调用跟踪结果（由 Context.trace() 创建）
+
+    时间单位为毫秒，从调用 trace() 时开始计时。with 语句退出时停止记录并还原被跟踪的函数。
+
+    Example:
+        >>> with ctx.trace(["CryptoJS.AES"]) as trace:
+        ...     ctx.call("login", ["user", "pass"])
+        >>> len(trace)
+        >>> trace.save("trace.json")
+    """
+
+    def calls(self) -> List[TraceCall]:
+        """
+        获取调用树
+
+        Returns:
+            顶层调用列表，children 为该调用直接调用的被跟踪函数。
+            未记录的参数/返回值为 None；返回 Promise 的调用 "return" 为 resolve 的值，
+            并额外包含 "settled"（Promise 完成的时间）
+        """
+        ...
+
+    def events(self) -> List[Dict[str, Any]]:
+        """按开始顺序排列的扁平调用列表，字段同 calls()（不含 children），另有 id / parent / depth"""
+        ...
+
+    def to_chrome_trace(self) -> str:
+        """导出为 Chrome trace-event JSON 字符串（可在 https://ui.perfetto.dev 或 chrome://tracing 打开）"""
+        ...
+
+    def save(self, path: str) -> None:
+        """将 Chrome trace-event JSON 写入文件"""
+        ...
+
+    def stop(self) -> None:
+        """停止记录并还原被跟踪的函数（已记录的调用保留，之后可以重新跟踪，不会重复包装）"""
+        ...
+
+    @property
+    def active(self) -> bool:
+        """是否仍在记录"""
+        ...
+
+    @property
+    def dropped(self) -> int:
+        """超过 max_events 而未记录的调用数"""
+        ...
+
+    def __len__(self) -> int: ...
+    def __enter__(self) -> "Trace": ...
+    def __exit__(self, exc_type: Any, exc_val: Any, exc_tb: Any) -> bool: ...
+
+class JSFuture:
+    """
+    可取消的 JSEngine 任务（由 JSEngine.submit() 创建）
+
+    接口与 concurrent.futures.Future 一致，也可以直接 await。
+
+    Example:
+        >>> future = engine.submit("sign", [data])
+        >>> try:
+        ...     result = future.result(timeout=5)
+        ... except TimeoutError:
+        ...     future.cancel()  # 排队中的任务被移除，执行中的任务被终止
+    """
+
+    def result(self, timeout: Optional[float] = None) -> Any:
+        """
+        获取任务结果
+
+        Args:
+            timeout: 最多等待的秒数（默认一直等待）
+
+        Raises:
+            TimeoutError: 超时未完成（任务不会被取消）
+            concurrent.futures.CancelledError: 任务已取消
+            Exception: JS执行失败
+        """
+        ...
+
+    def exception(self, timeout: Optional[float] = None) -> Optional[BaseException]:
+        """获取任务抛出的异常（成功时返回 None；参数和异常同 result()）"""
+        ...
+
+    def events(self, timeout: Optional[float] = None) -> List[JSEvent]:
+        """
+        等待任务结束，返回执行期间 $emit() 发送的事件（任务失败时同样可用）
+
+        Raises:
+            TimeoutError: 超时未完成
+        """
+        ...
+
+    def cancel(self) -> bool:
+        """
+        取消任务：排队中的任务不会再执行，执行中的任务被终止（Worker随后重建JsRuntime）
+
+        Returns:
+            任务已完成时返回 False，否则返回 True
+        """
+        ...
+
+    def cancelled(self) -> bool:
+        """任务是否已取消"""
+        ...
+
+    def running(self) -> bool:
+        """任务是否正在Worker上执行"""
+        ...
+
+    def done(self) -> bool:
+        """任务是否已结束（完成、失败或取消）"""
+        ...
+
+    def add_done_callback(self, fn: Callable[["JSFuture"], Any]) -> None:
+        """添加完成回调（已完成时立即调用，否则在后台完成线程上调用）"""
+        ...
+
+    def __await__(self) -> Generator[Any, None, Any]:
+        """支持 await（asyncio 协程被取消时任务随之取消）"""
+        ...
+
+    def __repr__(self) -> str:
+        """字符串表示"""
+        ...
+
+class ThreadedContext:
+    """
+    线程安全的 JavaScript 执行上下文
+
+    Context 绑定创建它的线程，跨线程使用会 panic。ThreadedContext 在一个专用线程中
+    持有 Context，任意 Python 线程都可以调用 eval/call 等方法，调用按提交顺序串行执行。
+    适用于 Web 服务器等需要在多个请求线程间共享同一份 JS 状态的场景；
+    需要并行执行时请使用 JSEngine。
+
+    Example:
+        >>> from concurrent.futures import ThreadPoolExecutor
+        >>> ctx = ThreadedContext()
+        >>> ctx.compile("let counter = 0; function inc() { return ++counter; }")
+        >>> with ThreadPoolExecutor(8) as pool:
+        ...     results = list(pool.map(lambda _: ctx.call("inc", []), range(100)))
+        >>> sorted(results) == list(range(1, 101))
+        True
+    """
+
+    def __init__(
+        self,
+        enable_extensions: bool = True,
+        enable_logging: bool = False,
+        random_seed: Optional[int] = None,
+        enable_node_compat: bool = False,
+        fast_return: bool = False,
+        deterministic: bool = False,
+        on_error: Optional[Callable[[JSErrorReport], Any]] = None,
+        strict_errors: bool = False,
+        on_event: Optional[Callable[[JSEvent], Any]] = None
+    ) -> None:
+        """参数与 Context 相同；on_error / on_event 在发起调用的线程中执行"""
+        ...
+
+    def compile(self, code: str, name: Optional[str] = None) -> None:
+        """编译 JavaScript 代码，函数/变量加入全局作用域（同 Context.compile）"""
+        ...
+
+    def hook(
+        self,
+        path: str,
+        action: HookAction = "log",
+        replacement: Optional[Union[str, Callable[..., Any]]] = None,
+        capture_args: bool = True,
+        capture_return: bool = True
+    ) -> None:
+        """为全局函数安装 Hook（同 Context.hook），Python 回调在 Context 线程上调用"""
+        ...
+
+    def trace(
+        self,
+        targets: List[str],
+        max_events: int = 100000,
+        capture_args: bool = True,
+        capture_return: bool = True
+    ) -> "Trace":
+        """跟踪函数调用（同 Context.trace）"""
+        ...
+
+    def call(
+        self,
+        name: str,
+        args: List[Any],
+        auto_await: Optional[bool] = None,
+        wait_timers: Optional[WaitTimers] = None
+    ) -> Any:
+        """调用 JavaScript 函数（同 Context.call）"""
+        ...
+
+    def eval(
+        self,
+        code: str,
+        return_value: bool = False,
+        auto_await: Optional[bool] = None,
+        wait_timers: Optional[WaitTimers] = None
+    ) -> Any:
+        """执行代码并加入全局作用域（同 Context.eval）"""
+        ...
+
+    def evaluate(
+        self,
+        code: str,
+        auto_await: Optional[bool] = None,
+        wait_timers: Optional[WaitTimers] = None
+    ) -> Any:
+        """执行代码并返回结果，不影响全局作用域（同 Context.evaluate）"""
+        ...
+
+    def run_until_idle(self, timeout_ms: Optional[int] = None) -> bool:
+        """运行事件循环直到空闲（同 Context.run_until_idle）"""
+        ...
+
+    @property
+    def errors(self) -> List[JSErrorReport]:
+        """已收集的未处理错误（同 Context.errors）"""
+        ...
+
+    def clear_errors(self) -> None:
+        """清空 errors"""
+        ...
+
+    def events(self, channel: Optional[str] = None) -> List[JSEvent]:
+        """已收集的 $emit() 事件（同 Context.events）"""
+        ...
+
+    def clear_events(self) -> None:
+        """清空已收集的 $emit() 事件"""
+        ...
+
+    def gc(self) -> None:
+        """请求 V8 垃圾回收"""
+        ...
+
+    def close(self) -> None:
+        """
+        关闭上下文：等待已提交的调用完成后结束专用线程
+
+        之后的调用抛出 RuntimeError，可重复调用。
+        """
+        ...
+
+    @property
+    def closed(self) -> bool:
+        """是否已关闭"""
+        ...
+
+    def __enter__(self) -> "ThreadedContext":
+        """上下文管理器入口"""
+        ...
+
+    def __exit__(self, exc_type: Any, exc_value: Any, traceback: Any) -> bool:
+        """上下文管理器退出，关闭上下文"""
+        ...
+
+    def __repr__(self) -> str:
+        """字符串表示"""
+        ...
+
+
+class Realm:
+    """
+    轻量级 realm，由 Context.create_realm() 创建
+
+    与父 Context 共享 Isolate 和事件循环（定时器、Promise 在同一事件循环中运行），
+    拥有独立的全局对象。不能跨线程使用。
+    """
+
+    def eval(self, code: str, auto_await: Optional[bool] = None) -> Any:
+        """
+        在 realm 中执行代码并返回最后一个表达式的值
+
+        顶层 var/function 声明进入 realm 的全局作用域，可在之后的调用中使用；
+        顶层 let/const 不保留。
+
+        Args:
+            code: JavaScript 代码
+            auto_await: 是否自动等待 Promise（默认 True）
+        """
+        ...
+
+    def call(self, name: str, args: List[Any], auto_await: Optional[bool] = None) -> Any:
+        """
+        调用 realm 全局作用域中的函数
+
+        Args:
+            name: 函数名称
+            args: 参数列表
+            auto_await: 是否自动等待 Promise（默认 True）
+        """
+        ...
+
+    @property
+    def id(self) -> int:
+        """realm 编号（在父 Context 内唯一）"""
+        ...
+
+    def close(self) -> None:
+        """释放 realm，之后的调用抛出异常"""
         ...
 
     def __repr__(self) -> str:
@@ -585,5 +1688,9 @@ __version__: str = "2.5.0"
 __all__ = [
     "Context",
     "JSEngine",
+    "ThreadedContext",
+    "Realm",
+    "PoolOverloadedError",
+    "HookTerminated",
     "JSValue",
 ]
@@ -1,15 +1,18 @@
 use anyhow::{Result, anyhow};
 use deno_core::{JsRuntime, RuntimeOptions, error::JsError};
-use pyo3::exceptions::PyException;
+use pyo3::exceptions::{PyException, PyRuntimeError};
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
+use pyo3::types::{PyDict, PyList, PyTuple};
 use serde_json::Value as JsonValue;
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::Arc;
 
 
 use crate::convert::{json_to_python, python_to_json};
 use crate::storage::ResultStorage;
+use crate::ext::hook::{HookCallback, HookReplacement, HookSpec};
+use crate::ext::hook::trace::SharedTrace;
 
 #[cfg(feature = "deno_web_api")]
 use crate::permissions::create_allow_all_permissions;
@@ -66,8 +69,99 @@ pub struct Context {
     logging_enabled: bool,
     random_seed: Option<u32>,  // Store seed for deferred initialization (for deno_crypto)
     fast_return: bool,  // 快速返回模式，函数return后立即返回不等待定时器
+    deterministic: bool,  // 确定性模式，固定时间/随机数/定时器顺序
+    strict_errors: bool,  // 严格模式，未处理的 rejection/定时器异常使当前调用失败
+    /// 错误回调，每条未处理错误调用一次（参数为 dict）
+    on_error: Option<Py<PyAny>>,
+    /// 已收集的未处理错误（ctx.errors）
+    errors: RefCell<Vec<JsonValue>>,
+    /// 事件回调，每个 $emit() 事件调用一次（参数为 dict）
+    on_event: Option<Py<PyAny>>,
+    /// 已收集的 $emit() 事件（ctx.events()）
+    events: RefCell<Vec<JsonValue>>,
+    /// 最近一次 $terminate() 保存的数据（兼容 ctx.get_hook_data()）
+    last_hook_data: RefCell<Option<String>>,
+    /// 用于 Ctrl+C 中断正在执行的 JS
+    isolate_handle: deno_core::v8::IsolateHandle,
+    /// Ctrl+C 中断注册（仅主线程上创建的 Context，None 表示不响应 Ctrl+C）
+    interrupt: Option<crate::interrupt::InterruptRegistration>,
+    /// ctx.create_realm() 创建的 V8 Context（下标为 realm id，关闭后为 None）
+    realms: RefCell<Vec<Option<deno_core::v8::Global<deno_core::v8::Context>>>>,
 }
 
+/// 调用返回后如何处理本次调用创建的定时器
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WaitTimers {
+    /// 拿到结果立即返回，保留定时器（之后可用 run_until_idle() 继续执行）
+    None,
+    /// 拿到结果后继续运行事件循环，直到所有定时器/异步操作完成
+    All,
+    /// 拿到结果立即返回，并取消本次调用创建的定时器（默认，兼容原有行为）
+    ///
+    /// 同步模式（auto_await=False）下与原有行为一致，取消所有定时器，
+    /// 包括 compile() 期间创建的。
+    #[default]
+    UntilResult,
+}
+
+impl WaitTimers {
+    /// 解析 Python 传入的策略字符串，None 表示默认策略
+    pub fn parse(value: Option<&str>) -> PyResult<Self> {
+        match value {
+            None | Some("until_result") => Ok(WaitTimers::UntilResult),
+            Some("none") => Ok(WaitTimers::None),
+            Some("all") => Ok(WaitTimers::All),
+            Some(other) => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "wait_timers must be 'none', 'all' or 'until_result', got '{}'",
+                other
+            ))),
+        }
+    }
+
+    fn cancels_timers(self) -> bool {
+        self == WaitTimers::UntilResult
+    }
+}
+
+/// 记录调用开始时的定时器序号（未加载扩展时退回记录定时器 ID）
+const TIMER_MARK_JS: &str = r#"
+                        const __eventLoop = globalThis.__NEVER_JSCORE_EVENT_LOOP__;
+                        const __timerMark = __eventLoop ? __eventLoop.mark() : setTimeout(() => {}, 0);
+                        if (!__eventLoop) clearTimeout(__timerMark);"#;
+
+/// 根据 wait_timers 策略生成取消本次调用定时器的代码
+///
+/// 同步模式（sync=true）取消所有定时器，异步模式只取消本次调用创建的定时器。
+fn timer_cancel_js(wait_timers: WaitTimers, sync: bool) -> &'static str {
+    if !wait_timers.cancels_timers() {
+        return "";
+    }
+    if sync {
+        return r#"
+                    if (__eventLoop) {
+                        __eventLoop.cancelSince(0);
+                    } else {
+                        // 清除所有定时器（包括 compile 期间创建的）
+                        const __timerEndId = setTimeout(() => {}, 0);
+                        clearTimeout(__timerEndId);
+                        for (let i = 0; i <= __timerEndId + 1000; i++) {
+                            clearTimeout(i);
+                            clearInterval(i);
+                        }
+                    }"#;
+    }
+    r#"
+                        if (__eventLoop) {
+                            __eventLoop.cancelSince(__timerMark);
+                        } else {
+                            // 未加载扩展（没有定时器注册表）时按 ID 区间取消
+                            const __timerEndId = setTimeout(() => {}, 0);
+                            for (let i = __timerMark; i <= __timerEndId; i++) {
+                                clearTimeout(i);
+                                clearInterval(i);
+                            }
+                        }"#
+}
 
 /// 格式化 JavaScript 错误为人类可读的字符串
 ///
@@ -235,19 +329,29 @@ impl Context {
     /// * `random_seed` - 随机数种子（可选）。如果提供，所有随机数 API 将使用固定种子
     /// * `enable_node_compat` - 是否启用 Node.js 兼容层（require() 支持）
     /// * `fast_return` - 快速返回模式，函数return后立即返回不等待定时器
+    /// * `deterministic` - 确定性模式，固定 Date/performance.now/定时器顺序，未指定种子时使用默认种子
+    /// * `strict_errors` - 严格模式，未处理的 Promise rejection / 定时器异常使当前调用失败
     pub fn new(
         enable_extensions: bool,
         enable_logging: bool,
         random_seed: Option<u32>,
         enable_node_compat: bool,
         fast_return: bool,
+        deterministic: bool,
+        strict_errors: bool,
     ) -> PyResult<Self> {
         let storage = Rc::new(ResultStorage::new());
 
         // Use the new modular extension system
         let mut ext_options = crate::ext::ExtensionOptions::new(storage.clone())
             .with_logging(enable_logging)
-            .with_extensions(enable_extensions);
+            .with_extensions(enable_extensions)
+            .with_deterministic(deterministic);
+
+        // 确定性模式必须固定随机数，未指定种子时使用默认种子
+        let random_seed = random_seed.or_else(|| {
+            deterministic.then_some(crate::ext::deterministic::DEFAULT_SEED as u32)
+        });
 
         // Apply random seed if provided
         ext_options = if let Some(seed) = random_seed {
@@ -301,11 +405,19 @@ impl Context {
         {
             let op_state = runtime.op_state();
             let mut op_state_mut = op_state.borrow_mut();
-            op_state_mut.put(isolate_handle);
+            op_state_mut.put(isolate_handle.clone());
 
             // 添加 FastReturnMode 到 OpState
             op_state_mut.put(crate::ext::core::FastReturnMode::new(fast_return));
 
+            // 收集未处理的 Promise rejection / 定时器异常
+            op_state_mut.put(crate::ext::event_loop::ErrorReporting::new(strict_errors));
+
+            // 收集 $emit() 事件
+            op_state_mut.put(crate::ext::hook::EmittedEvents::default());
+            op_state_mut.put(crate::ext::hook::HookCallbacks::default());
+            op_state_mut.put(crate::ext::hook::trace::Traces::default());
+
             // 初始化 deno_web 需要的权限系统
             #[cfg(feature = "deno_web_api")]
             {
@@ -336,12 +448,22 @@ impl Context {
             logging_enabled: enable_logging,
             random_seed,
             fast_return,
+            deterministic,
+            strict_errors,
+            on_error: None,
+            errors: RefCell::new(Vec::new()),
+            on_event: None,
+            events: RefCell::new(Vec::new()),
+            last_hook_data: RefCell::new(None),
+            isolate_handle,
+            interrupt: None,
+            realms: RefCell::new(Vec::new()),
         })
     }
 
     /// Load JavaScript extension initialization scripts
     ///
-    /// This method loads all JS-based extensions (core, hook, random, XHR, protection)
+    /// This method loads all JS-based extensions (native registry, core, hook, random, XHR, protection)
     /// Should only be called once on first execution
     ///
     /// # DRY Improvement
@@ -354,6 +476,12 @@ impl Context {
                 .map_err(|e| anyhow!("Failed to set logging flag: {}", format_error(e.into())))?;
         }
 
+        // Load native-function registry (used by hooks and protection)
+        let native_init = crate::ext::protection::get_native_js();
+        runtime
+            .execute_script("<init_native>", native_init.to_string())
+            .map_err(|e| anyhow!("Failed to load native registry: {}", format_error(e.into())))?;
+
         // Load core extension functions ($return, $exit, etc.)
         let core_init = crate::ext::core::get_init_js();
         runtime
@@ -372,6 +500,20 @@ impl Context {
             .execute_script("<init_random>", random_init.to_string())
             .map_err(|e| anyhow!("Failed to load random extension: {}", format_error(e.into())))?;
 
+        // Load deterministic mode (virtual clock for Date/performance.now/timers)
+        if self.deterministic {
+            let deterministic_init = crate::ext::deterministic::get_init_js();
+            runtime
+                .execute_script("<init_deterministic>", deterministic_init.to_string())
+                .map_err(|e| anyhow!("Failed to load deterministic extension: {}", format_error(e.into())))?;
+        }
+
+        // Load event loop registry (tracks timers for pending_tasks / wait_timers)
+        let event_loop_init = crate::ext::event_loop::get_init_js();
+        runtime
+            .execute_script("<init_event_loop>", event_loop_init.to_string())
+            .map_err(|e| anyhow!("Failed to load event loop extension: {}", format_error(e.into())))?;
+
         // Load XMLHttpRequest polyfill (fetch-based implementation)
         let xhr_init = crate::ext::xhr::get_init_js();
         runtime
@@ -436,7 +578,7 @@ impl Context {
     /// 执行脚本，将代码加入全局作用域（不返回值）
     ///
     /// 这个方法会直接执行代码并将定义的函数/变量加入全局作用域
-    fn exec_script(&self, code: &str) -> Result<()> {
+    pub(crate) fn exec_script(&self, code: &str) -> Result<()> {
         // RAII guard ensures isolate.exit() is always called, even on panic
         let _guard = IsolateGuard::new(self);
 
@@ -512,7 +654,7 @@ impl Context {
     /// - 当 JS 调用 __neverjscore_return__(value) 时，会抛出 EarlyReturnError
     /// - 该错误会携带返回值并中断 JS 执行
     /// - Rust 侧通过 downcast 检测并提取返回值
-    fn execute_js(&self, code: &str, auto_await: bool) -> Result<String> {
+    pub(crate) fn execute_js(&self, code: &str, auto_await: bool, wait_timers: WaitTimers) -> Result<String> {
         // RAII guard ensures isolate.exit() is always called
         let _guard = IsolateGuard::new(self);
 
@@ -542,11 +684,9 @@ impl Context {
                 let wrapped_code = format!(
                     r#"
                     (async function() {{
-                        // 记录执行前的定时器 ID 基准
-                        const __timerBaseId = setTimeout(() => {{}}, 0);
-                        clearTimeout(__timerBaseId);
+                        {timer_mark}
 
-                        const code = {};
+                        const code = {code};
                         let __result;
                         let __error;
 
@@ -556,18 +696,14 @@ impl Context {
                             __error = e;
                         }}
 
-                        // Cancel only timers created during this execution window.
-                        // __timerBaseId was sampled before eval; __timerEndId is sampled now.
-                        // This keeps cancellation O(timers created here) instead of O(all-time timer count).
-                        const __timerEndId = setTimeout(() => {{}}, 0);
-                        clearTimeout(__timerEndId);
-                        for (let i = __timerBaseId; i <= __timerEndId; i++) {{
-                            clearTimeout(i);
-                            clearInterval(i);
-                        }}
+                        {timer_cancel}
 
-                        // 如果有错误，重新抛出
+                        // 如果有错误，重新抛出（标记为本次调用的错误，不作为未处理 rejection 上报）
                         if (__error) {{
+                            const __eventLoop = globalThis.__NEVER_JSCORE_EVENT_LOOP__;
+                            if (__eventLoop) {{
+                                __eventLoop.markCallError(__error);
+                            }}
                             throw __error;
                         }}
 
@@ -587,7 +723,9 @@ impl Context {
                         }}
                     }})()
                     "#,
-                    code_json
+                    timer_mark = TIMER_MARK_JS,
+                    code = code_json,
+                    timer_cancel = timer_cancel_js(wait_timers, false),
                 );
 
                 // 执行脚本
@@ -650,15 +788,21 @@ impl Context {
                         return Ok(result);
                     }
 
-                    // Poll event loop 一次
-                    let poll_result = futures::future::poll_fn(|cx| {
-                        runtime.poll_event_loop(
-                            cx,
-                            deno_core::PollEventLoopOptions {
-                                wait_for_inspector: false,
-                            },
-                        )
-                    }).await;
+                    // Poll event loop 一次（等待定时器/IO 期间响应 Ctrl+C）
+                    let poll_result = tokio::select! {
+                        result = futures::future::poll_fn(|cx| {
+                            runtime.poll_event_loop(
+                                cx,
+                                deno_core::PollEventLoopOptions {
+                                    wait_for_inspector: false,
+                                },
+                            )
+                        }) => result,
+                        _ = crate::interrupt::wait_for_interrupt(self.interrupt.is_some()) => {
+                            last_error = Some(anyhow!("execution interrupted"));
+                            break;
+                        }
+                    };
 
                     match poll_result {
                         Ok(()) => {
@@ -707,7 +851,12 @@ impl Context {
                 Ok(result)
             });
 
-            result
+            let result = result?;
+            if wait_timers == WaitTimers::All {
+                drop(tokio_rt);
+                self.drain_event_loop(None)?;
+            }
+            Ok(result)
             // RAII guard exits isolate here
         } else {
             // 同步模式：不等待 Promise
@@ -719,11 +868,9 @@ impl Context {
             let wrapped_code = format!(
                 r#"
                 (function() {{
-                    // 记录执行前的定时器 ID 基准
-                    const __timerBaseId = setTimeout(() => {{}}, 0);
-                    clearTimeout(__timerBaseId);
+                    {timer_mark}
 
-                    const code = {};
+                    const code = {code};
                     let __result;
                     let __error;
 
@@ -733,13 +880,7 @@ impl Context {
                         __error = e;
                     }}
 
-                    // 清除所有定时器（包括 compile 期间创建的）
-                    const __timerEndId = setTimeout(() => {{}}, 0);
-                    clearTimeout(__timerEndId);
-                    for (let i = 0; i <= __timerEndId + 1000; i++) {{
-                        clearTimeout(i);
-                        clearInterval(i);
-                    }}
+                    {timer_cancel}
 
                     // 如果有错误，重新抛出
                     if (__error) {{
@@ -761,7 +902,9 @@ impl Context {
                     }}
                 }})()
                 "#,
-                code_json
+                timer_mark = TIMER_MARK_JS,
+                code = code_json,
+                timer_cancel = timer_cancel_js(wait_timers, true),
             );
 
             let execute_result = runtime.execute_script("<eval_sync>", wrapped_code);
@@ -801,15 +944,335 @@ impl Context {
 
             let mut count = self.exec_count.borrow_mut();
             *count += 1;
+            drop(count);
+            drop(runtime);
 
+            if wait_timers == WaitTimers::All {
+                self.drain_event_loop(None)?;
+            }
             Ok(result)
             // RAII guard exits isolate here
         }
     }
 
+    /// 运行事件循环直到空闲（没有定时器、Promise、异步操作）
+    ///
+    /// 返回 true 表示已空闲，false 表示在 timeout_ms 内未能空闲（例如存在 setInterval）。
+    /// 调用方需已进入 isolate（IsolateGuard）。
+    fn drain_event_loop(&self, timeout_ms: Option<u64>) -> Result<bool> {
+        let tokio_rt = self.tokio_runtime.borrow();
+        tokio_rt.block_on(async {
+            let mut runtime = self.runtime.borrow_mut();
+            let drain = runtime.run_event_loop(deno_core::PollEventLoopOptions::default());
+
+            let drain = async {
+                tokio::select! {
+                    result = drain => Some(result),
+                    _ = crate::interrupt::wait_for_interrupt(self.interrupt.is_some()) => None,
+                }
+            };
+
+            let result = match timeout_ms {
+                Some(ms) => {
+                    match tokio::time::timeout(std::time::Duration::from_millis(ms), drain).await {
+                        Ok(result) => result,
+                        Err(_) => return Ok(false),
+                    }
+                }
+                None => drain.await,
+            };
+
+            // Ctrl+C：由调用方恢复 isolate 并抛出 KeyboardInterrupt
+            let Some(result) = result else {
+                return Ok(false);
+            };
+
+            match result {
+                Ok(()) => Ok(true),
+                Err(e) => {
+                    let error_msg = format_error(e.into());
+                    if error_msg.contains("execution terminated") {
+                        // fast_return / $terminate 触发的终止，恢复 isolate 以便继续使用
+                        runtime.v8_isolate().cancel_terminate_execution();
+                        return Ok(false);
+                    }
+                    Err(anyhow!("{}", error_msg))
+                }
+            }
+        })
+    }
+
+    /// 运行事件循环直到空闲（供 Python run_until_idle 使用）
+    pub(crate) fn run_until_idle_inner(&self, timeout_ms: Option<u64>) -> Result<bool> {
+        let _guard = IsolateGuard::new(self);
+        self.drain_event_loop(timeout_ms)
+    }
+
+    /// 只执行一次微任务检查点（Promise 回调），不触发定时器
+    fn run_microtasks_inner(&self) {
+        let _guard = IsolateGuard::new(self);
+        let mut runtime = self.runtime.borrow_mut();
+        runtime.v8_isolate().perform_microtask_checkpoint();
+    }
+
+    /// 创建 realm，返回 realm id
+    fn create_realm_inner(&self, expose_parent: bool) -> Result<usize> {
+        // realm 从主 realm 复制 Web API 全局对象，先确保扩展已加载
+        if *self.exec_count.borrow() == 0 {
+            self.exec_script("")?;
+        }
+
+        let _guard = IsolateGuard::new(self);
+        let realm = crate::realm::create(&mut self.runtime.borrow_mut(), expose_parent)?;
+        let mut realms = self.realms.borrow_mut();
+        realms.push(Some(realm));
+        Ok(realms.len() - 1)
+    }
+
+    /// 在 realm 中执行代码，返回结果 JSON
+    pub(crate) fn realm_execute(&self, id: usize, code: &str, auto_await: bool) -> Result<String> {
+        let _guard = IsolateGuard::new(self);
+        let realm = self
+            .realms
+            .borrow()
+            .get(id)
+            .cloned()
+            .flatten()
+            .ok_or_else(|| anyhow!("Realm has been closed"))?;
+        let source = crate::realm::wrap_code(code, auto_await)?;
+
+        let tokio_rt = self.tokio_runtime.borrow();
+        tokio_rt.block_on(async {
+            let mut runtime = self.runtime.borrow_mut();
+            let output = match crate::realm::execute(&mut runtime, &realm, &source)? {
+                crate::realm::Completion::Ready(output) => output,
+                crate::realm::Completion::Pending(promise) => {
+                    crate::realm::resolve(&mut runtime, &realm, promise).await?
+                }
+            };
+            crate::realm::decode_output(output)
+        })
+    }
+
+    /// 释放 realm
+    pub(crate) fn close_realm(&self, id: usize) {
+        let _guard = IsolateGuard::new(self);
+        if let Some(realm) = self.realms.borrow_mut().get_mut(id) {
+            realm.take();
+        }
+    }
+
+    /// 释放 GIL 执行 f，期间 Ctrl+C 会终止 JS 并交给 Python 的 SIGINT 处理函数
+    ///
+    /// 只有主线程上创建的 Context 可被中断（Python 只在主线程处理信号）。
+    /// 被中断后 isolate 已恢复、本次调用遗留的状态已丢弃，Context 可以继续使用。
+    /// 外层 Err 为处理函数抛出的异常（默认 KeyboardInterrupt）；处理函数没有抛出异常时为 RuntimeError，
+    /// 内层为 JS 执行结果。
+    pub(crate) fn allow_threads_interruptible<T, F>(&self, py: Python, f: F) -> PyResult<Result<T>>
+    where
+        T: Send,
+        F: FnOnce(&Context) -> Result<T> + Send,
+    {
+        let guard = self.interrupt.as_ref().map(|interrupt| interrupt.begin());
+
+        // 使用SendPtr绕过Send约束，释放GIL提升多线程性能
+        // 这是安全的，因为allow_threads不会跨线程执行代码，只是释放GIL
+        let self_ptr = SendPtr(self as *const Context);
+        let result = py.allow_threads(move || {
+            let ctx = unsafe { self_ptr.as_ref() };
+            f(ctx)
+        });
+
+        if guard.is_some_and(|guard| guard.finish()) {
+            // 恢复 isolate 状态，允许后续执行
+            self.runtime.borrow_mut().v8_isolate().cancel_terminate_execution();
+            // 丢弃被中断的调用留下的结果、Hook 数据、事件、错误上报和未结束的跟踪调用，
+            // 避免它们混入下一次调用
+            self.result_storage.clear();
+            self.reset_trace_stacks();
+            self.take_events();
+            self.take_error_reports();
+            // 交给 Python 自己的 SIGINT 处理函数（默认抛出 KeyboardInterrupt，尊重用户自定义的 handler）
+            py.check_signals()?;
+            // 处理函数没有抛出异常（例如只记录日志），JS 已被终止，仍然需要让本次调用失败
+            return Err(PyRuntimeError::new_err("JavaScript execution interrupted"));
+        }
+        Ok(result)
+    }
+
+    /// 取出 JS 上报的未处理错误（JSON 解析失败的条目会被忽略）
+    pub(crate) fn take_error_reports(&self) -> Vec<JsonValue> {
+        let runtime = self.runtime.borrow();
+        let op_state = runtime.op_state();
+        let reports = crate::ext::event_loop::take_error_reports(&mut op_state.borrow_mut());
+        reports
+            .iter()
+            .filter_map(|json| serde_json::from_str(json).ok())
+            .collect()
+    }
+
+    /// 在全局作用域安装声明式 Hook（替换目标函数）
+    pub(crate) fn install_hook(&self, hook: &HookSpec) -> Result<()> {
+        if !self.extensions_loaded {
+            return Err(anyhow!("hook() requires enable_extensions=True"));
+        }
+        let script = {
+            let runtime = self.runtime.borrow();
+            let op_state = runtime.op_state();
+            let script = hook.install_script(&mut op_state.borrow_mut());
+            script
+        };
+        self.exec_script(&script)
+    }
+
+    /// 开始跟踪目标函数的调用，返回 trace id 和记录调用的缓冲区
+    ///
+    /// 目标可以是函数路径、对象路径（跟踪其所有方法）或 "script:名称"（该脚本定义的全局函数）。
+    pub(crate) fn install_trace(
+        &self,
+        targets: &[String],
+        max_events: usize,
+        capture_args: bool,
+        capture_return: bool,
+    ) -> Result<(usize, SharedTrace)> {
+        if !self.extensions_loaded {
+            return Err(anyhow!("trace() requires enable_extensions=True"));
+        }
+        let (trace_id, trace) = {
+            let runtime = self.runtime.borrow();
+            let op_state = runtime.op_state();
+            let registered = crate::ext::hook::trace::register(&mut op_state.borrow_mut(), max_events);
+            registered
+        };
+        let script = format!(
+            "globalThis.__neverjscore_trace__({}, {}, {});",
+            trace_id,
+            serde_json::to_string(targets)?,
+            serde_json::json!({ "captureArgs": capture_args, "captureReturn": capture_return }),
+        );
+        if let Err(e) = self.exec_script(&script) {
+            trace.lock().unwrap_or_else(std::sync::PoisonError::into_inner).stop();
+            // 还原出错前已经包装的函数
+            let _ = self.uninstall_trace(trace_id);
+            return Err(e);
+        }
+        Ok((trace_id, trace))
+    }
+
+    /// 停止跟踪：还原被包装的函数（之后再次跟踪不会重复包装）
+    pub(crate) fn uninstall_trace(&self, trace_id: usize) -> Result<()> {
+        self.exec_script(&format!("globalThis.__neverjscore_untrace__({});", trace_id))
+    }
+
+    /// 停止跟踪并还原被包装的函数（由 Trace.stop() 调用）
+    pub(crate) fn stop_trace(&self, py: Python, trace_id: usize) -> PyResult<()> {
+        let result = self
+            .allow_threads_interruptible(py, move |ctx| ctx.uninstall_trace(trace_id))?
+            .map_err(|e| PyException::new_err(format!("Trace error: {}", e)));
+        self.handle_error_reports(py, result)
+    }
+
+    /// 执行命名脚本，记录它定义的全局函数（供 trace("script:名称") 使用）
+    ///
+    /// 代码末尾追加 //# sourceURL，错误堆栈中显示为该名称。
+    pub(crate) fn exec_named_script(&self, code: &str, name: &str) -> Result<()> {
+        if !self.extensions_loaded {
+            return self.exec_script(&format!("{}\n//# sourceURL={}", code, name));
+        }
+        self.exec_script("globalThis.__neverjscore_script_begin__();")?;
+        self.exec_script(&format!("{}\n//# sourceURL={}", code, name))?;
+        self.exec_script(&format!(
+            "globalThis.__neverjscore_script_end__({});",
+            serde_json::to_string(name)?
+        ))
+    }
+
+    /// 关闭被 $terminate() 或异常中断而未结束的跟踪调用（每次调用结束后执行）
+    pub(crate) fn reset_trace_stacks(&self) {
+        let runtime = self.runtime.borrow();
+        let op_state = runtime.op_state();
+        crate::ext::hook::trace::reset_stacks(&mut op_state.borrow_mut());
+    }
+
+    /// 取出 JS 通过 $emit() 发送的事件（按发送顺序）
+    pub(crate) fn take_events(&self) -> Vec<JsonValue> {
+        let runtime = self.runtime.borrow();
+        let op_state = runtime.op_state();
+        let events = crate::ext::hook::take_events(&mut op_state.borrow_mut());
+        events
+    }
+
+    /// 处理本次调用期间上报的错误和事件（持有 GIL 时调用）
+    ///
+    /// 记录到 ctx.errors 并逐条调用 on_error；严格模式下如果调用本身成功，
+    /// 则把第一条错误作为本次调用的异常抛出。
+    pub(crate) fn handle_error_reports<T>(&self, py: Python, result: PyResult<T>) -> PyResult<T> {
+        // $terminate() 终止的调用抛出 HookTerminated，携带本次调用保存的数据
+        let result = match (result, self.take_call_hook_data()) {
+            (Err(_), Some(data)) => Err(crate::errors::hook_terminated(py, &data, None)),
+            (result, _) => result,
+        };
+
+        self.reset_trace_stacks();
+
+        // 设置了 on_event 时事件只交给回调，不再记录（长时间运行时不会无限增长）
+        let events = self.take_events();
+        let dispatched = match &self.on_event {
+            Some(callback) => dispatch_events(py, &events, callback),
+            None => {
+                self.events.borrow_mut().extend(events);
+                Ok(())
+            }
+        };
+
+        let reports = self.take_error_reports();
+        let result = if reports.is_empty() {
+            result
+        } else {
+            self.errors.borrow_mut().extend(reports.iter().cloned());
+            dispatch_error_reports(py, &reports, self.on_error.as_ref(), self.strict_errors, result)
+        };
+        // 事件回调抛出的异常在错误上报处理完之后再抛出
+        dispatched.and(result)
+    }
+
+    /// 取出本次调用 $terminate() 保存的 Hook 数据（同时保留一份供 get_hook_data() 读取）
+    pub(crate) fn take_call_hook_data(&self) -> Option<String> {
+        let data = self.result_storage.take_hook_data()?;
+        *self.last_hook_data.borrow_mut() = Some(data.clone());
+        Some(data)
+    }
+
+    /// 收集待处理任务：JS 注册表中的定时器/Promise + deno_core 统计的异步操作/资源
+    fn pending_tasks_inner(&self) -> Result<(JsonValue, crate::ext::event_loop::ActivityCounts)> {
+        let _guard = IsolateGuard::new(self);
+        let mut runtime = self.runtime.borrow_mut();
+
+        let report = if *self.exec_count.borrow() > 0 && self.extensions_loaded {
+            runtime
+                .execute_script("<pending_tasks>", crate::ext::event_loop::REPORT_SCRIPT)
+                .map_err(|e| anyhow!("{}", format_error(e.into())))?;
+            let op_state = runtime.op_state();
+            let report = op_state
+                .borrow_mut()
+                .try_take::<crate::ext::event_loop::PendingTasksReport>();
+            match report {
+                Some(report) => serde_json::from_str(&report.0)
+                    .map_err(|e| anyhow!("Failed to parse pending tasks: {}", e))?,
+                None => serde_json::json!({ "timers": [], "promises": null }),
+            }
+        } else {
+            // 扩展尚未加载（首次执行前或禁用扩展），没有定时器注册表
+            serde_json::json!({ "timers": [], "promises": null })
+        };
+
+        let counts = crate::ext::event_loop::activity_counts(&runtime);
+        Ok((report, counts))
+    }
+
 
     /// 请求垃圾回收
-    fn request_gc(&self) -> Result<()> {
+    pub(crate) fn request_gc(&self) -> Result<()> {
         let _guard = IsolateGuard::new(self);
         let mut runtime = self.runtime.borrow_mut();
         let _ =
@@ -845,11 +1308,118 @@ impl Context {
     }
 }
 
+/// 按发送顺序调用 on_event 回调（持有 GIL 时调用）
+pub(crate) fn dispatch_events(py: Python, events: &[JsonValue], on_event: &Py<PyAny>) -> PyResult<()> {
+    for event in events {
+        on_event.call1(py, (json_to_python(py, event)?,))?;
+    }
+    Ok(())
+}
+
+/// 解析 Python 传入的 Hook 配置
+///
+/// replacement 为字符串时作为 JS 函数源码，为可调用对象时以 JS 参数调用（返回值传回 JS）
+pub(crate) fn hook_spec_from_python(
+    path: String,
+    action: &str,
+    replacement: Option<&Bound<'_, PyAny>>,
+    capture_args: bool,
+    capture_return: bool,
+) -> PyResult<HookSpec> {
+    let replacement = match replacement {
+        None => None,
+        Some(obj) if obj.is_none() => None,
+        Some(obj) => {
+            if let Ok(source) = obj.extract::<String>() {
+                Some(HookReplacement::Js(source))
+            } else if obj.is_callable() {
+                Some(HookReplacement::Callback(python_hook_callback(obj.clone().unbind())))
+            } else {
+                return Err(pyo3::exceptions::PyTypeError::new_err(
+                    "replacement must be JavaScript function source (str) or a Python callable",
+                ));
+            }
+        }
+    };
+    HookSpec::new(path, action, replacement, capture_args, capture_return)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Python 函数包装为 Hook 回调（在 JS 线程上获取 GIL 调用，参数按位置传入）
+fn python_hook_callback(callable: Py<PyAny>) -> HookCallback {
+    Arc::new(move |args| {
+        Python::with_gil(|py| {
+            let args = args
+                .iter()
+                .map(|arg| json_to_python(py, arg))
+                .collect::<PyResult<Vec<_>>>()
+                .map_err(|e| e.to_string())?;
+            let args = PyTuple::new(py, args).map_err(|e| e.to_string())?;
+            let result = callable.call1(py, args).map_err(|e| e.to_string())?;
+            python_to_json(result.bind(py)).map_err(|e| e.to_string())
+        })
+    })
+}
+
+/// 调用 on_error 回调，严格模式下把第一条错误转换为本次调用的异常（持有 GIL 时调用）
+pub(crate) fn dispatch_error_reports<T>(
+    py: Python,
+    reports: &[JsonValue],
+    on_error: Option<&Py<PyAny>>,
+    strict_errors: bool,
+    result: PyResult<T>,
+) -> PyResult<T> {
+    if let Some(callback) = on_error {
+        for report in reports {
+            callback.call1(py, (json_to_python(py, report)?,))?;
+        }
+    }
+
+    if let (true, Some(report), Ok(_)) = (strict_errors, reports.first(), &result) {
+        let kind = match report["type"].as_str() {
+            Some("uncaught_exception") => "Uncaught exception",
+            _ => "Unhandled promise rejection",
+        };
+        return Err(PyException::new_err(format!(
+            "{}: {}: {}",
+            kind,
+            report["name"].as_str().unwrap_or("Error"),
+            report["message"].as_str().unwrap_or("")
+        )));
+    }
+    result
+}
+
+/// 构造函数调用代码 name(arg1, arg2, ...)（持有 GIL 时调用）
+///
+/// args 为列表时逐个作为参数，否则作为单个参数
+pub(crate) fn build_call_code(name: &str, args: &Bound<'_, PyAny>) -> PyResult<String> {
+    let json_args = if args.is_instance_of::<PyList>() {
+        let list = args.downcast::<PyList>()?;
+        let mut vec_args = Vec::with_capacity(list.len());
+        for item in list.iter() {
+            vec_args.push(python_to_json(&item)?);
+        }
+        vec_args
+    } else {
+        vec![python_to_json(args)?]
+    };
+
+    let args_json: Vec<String> = json_args
+        .iter()
+        .map(|arg| serde_json::to_string(arg).unwrap())
+        .collect();
+    Ok(format!("{}({})", name, args_json.join(", ")))
+}
+
 impl Drop for Context {
     fn drop(&mut self) {
         // V8 runtime 会在 RefCell 销毁时自动清理
         // 注意：不要在这里调用 gc()，因为 Drop 可能在不同线程上被调用
         // 如果需要手动 GC，请在业务代码中显式调用 ctx.gc() 或使用 with 语句
+
+        // realm 的 v8::Global 必须在 isolate 销毁（runtime 字段 drop）之前释放
+        self.realms.get_mut().clear();
     }
 }
 
@@ -875,6 +1445,23 @@ impl Context {
     ///                  - int: 使用固定种子（确定性）
     ///                    所有随机数 API（Math.random、crypto.getRandomValues 等）
     ///                    将基于此种子生成，方便调试和算法对比
+    ///     deterministic: 确定性模式，默认 False
+    ///                    - True: 固定 Date、performance.now、crypto.randomUUID、
+    ///                      crypto.subtle.generateKey（AES/HMAC）、定时器执行顺序、
+    ///                      navigator 中与宿主相关的值；未指定 random_seed 时使用默认种子。
+    ///                      V8 哈希种子在第一次构造时固定（之后才使用 deterministic=True
+    ///                      时发出 RuntimeWarning），也可以用环境变量 NEVER_JSCORE_HASH_SEED 指定
+    ///     on_error: 错误回调（可选），每当 Promise rejection 未被处理或定时器回调抛出
+    ///               异常时调用，参数为 dict：
+    ///               {"type": "unhandled_rejection" | "uncaught_exception",
+    ///                "source": "promise" | "timer" | "interval", "name", "message", "stack"}
+    ///               所有错误同时记录在 ctx.errors 中
+    ///     strict_errors: 严格模式，默认 False
+    ///                    - False: 未处理错误只上报，不影响当前调用（类似浏览器控制台）
+    ///                    - True: 未处理错误使当前调用失败
+    ///     on_event: 事件回调（可选），JS 每调用一次 $emit(channel, data) 调用一次，
+    ///               参数为 dict：{"channel", "data"}；在调用返回前按发送顺序调用。
+    ///               设置后事件只交给回调，不再记录到 ctx.events() 中
     ///
     /// Example:
     ///     ```python
@@ -903,18 +1490,47 @@ impl Context {
     ///     # 创建快速返回模式的上下文（适用于有定时器的JS代码）
     ///     ctx_fast = never_jscore.Context(fast_return=True)
     ///     # 函数return后立即返回，不等待setTimeout/setInterval
+    ///
+    ///     # 创建确定性上下文（回归测试：相同脚本+输入得到逐字节相同的输出）
+    ///     ctx_det = never_jscore.Context(deterministic=True)
+    ///     ctx_det.evaluate("[Date.now(), performance.now(), Math.random()]")
+    ///
+    ///     # 收集未处理的 Promise rejection / 定时器异常
+    ///     ctx_err = never_jscore.Context(on_error=lambda e: print(e["message"]))
+    ///     ctx_err.evaluate("Promise.reject(new Error('lost')); 1")
+    ///     print(ctx_err.errors)
+    ///
+    ///     # 收集 $emit() 发送的中间值，脚本继续执行
+    ///     ctx_ev = never_jscore.Context(on_event=lambda e: print(e["channel"], e["data"]))
+    ///     ctx_ev.evaluate("for (let i = 0; i < 3; i++) $emit('round', i); 'done'")
+    ///     print(ctx_ev.events("round"))
     ///     ```
     #[new]
-    #[pyo3(signature = (enable_extensions=true, enable_logging=false, random_seed=None, enable_node_compat=false, fast_return=false))]
+    #[pyo3(signature = (enable_extensions=true, enable_logging=false, random_seed=None, enable_node_compat=false, fast_return=false, deterministic=false, on_error=None, strict_errors=false, on_event=None))]
     fn py_new(
+        py: Python,
         enable_extensions: bool,
         enable_logging: bool,
         random_seed: Option<u32>,
         enable_node_compat: bool,
         fast_return: bool,
+        deterministic: bool,
+        on_error: Option<Py<PyAny>>,
+        strict_errors: bool,
+        on_event: Option<Py<PyAny>>,
     ) -> PyResult<Self> {
-        crate::runtime::ensure_v8_initialized();
-        Self::new(enable_extensions, enable_logging, random_seed, enable_node_compat, fast_return)
+        crate::runtime::init_v8_for(py, deterministic)?;
+        let mut ctx = Self::new(enable_extensions, enable_logging, random_seed, enable_node_compat, fast_return, deterministic, strict_errors)?;
+        ctx.on_error = on_error;
+        ctx.on_event = on_event;
+
+        // Ctrl+C 只会在主线程上抛出 KeyboardInterrupt
+        let threading = py.import("threading")?;
+        let main_thread = threading.call_method0("main_thread")?;
+        if main_thread.is(&threading.call_method0("current_thread")?) {
+            ctx.interrupt = crate::interrupt::InterruptRegistration::new(ctx.isolate_handle.clone());
+        }
+        Ok(ctx)
     }
 
     /// 编译JavaScript代码（便捷方法）
@@ -924,6 +1540,8 @@ impl Context {
     ///
     /// Args:
     ///     code: JavaScript 代码字符串
+    ///     name: 脚本名称（可选）。错误堆栈中显示为该名称，
+    ///           并可用 trace("script:名称") 跟踪该脚本定义的全局函数
     ///
     /// Returns:
     ///     None
@@ -937,16 +1555,14 @@ impl Context {
     ///     ''')
     ///     result = ctx.call("add", [5, 3])
     ///     ```
-    #[pyo3(signature = (code))]
-    pub fn compile(&self, py: Python, code: String) -> PyResult<()> {
-        // 使用SendPtr绕过Send约束，释放GIL提升多线程性能
-        // 这是安全的，因为allow_threads不会跨线程执行代码，只是释放GIL
-        let self_ptr = SendPtr(self as *const Context);
-        py.allow_threads(move || {
-            let ctx = unsafe { self_ptr.as_ref() };
-            ctx.exec_script(&code)
-        }).map_err(|e| PyException::new_err(format!("Compile error: {}", e)))?;
-        Ok(())
+    #[pyo3(signature = (code, name=None))]
+    pub fn compile(&self, py: Python, code: String, name: Option<String>) -> PyResult<()> {
+        // 释放GIL执行（提升多线程性能）
+        let result = self.allow_threads_interruptible(py, move |ctx| match &name {
+            Some(name) => ctx.exec_named_script(&code, name),
+            None => ctx.exec_script(&code),
+        })?.map_err(|e| PyException::new_err(format!("Compile error: {}", e)));
+        self.handle_error_reports(py, result)
     }
 
     /// 调用 JavaScript 函数
@@ -955,42 +1571,36 @@ impl Context {
     ///     name: 函数名称
     ///     args: 参数列表
     ///     auto_await: 是否自动等待 Promise（默认 True）
+    ///     wait_timers: 本次调用创建的定时器如何处理（默认 "until_result"）
+    ///         - "until_result": 拿到结果立即返回，并取消本次调用创建的定时器
+    ///           （auto_await=False 时取消所有定时器，包括 compile() 创建的）
+    ///         - "none": 拿到结果立即返回，定时器保留，可稍后 run_until_idle()
+    ///         - "all": 等待所有定时器/异步操作完成后再返回
     ///
     /// Returns:
     ///     函数返回值，自动转换为 Python 对象
-    #[pyo3(signature = (name, args, auto_await=None))]
+    ///
+    /// Raises:
+    ///     HookTerminated: JS 调用了 $terminate(data)，e.data 为保存的数据
+    #[pyo3(signature = (name, args, auto_await=None, wait_timers=None))]
     pub fn call<'py>(
         &self,
         py: Python<'py>,
         name: String,
         args: &Bound<'_, PyAny>,
         auto_await: Option<bool>,
+        wait_timers: Option<&str>,
     ) -> PyResult<Bound<'py, PyAny>> {
-        // 准备参数（在持有GIL时）
-        let json_args = if args.is_instance_of::<PyList>() {
-            let list = args.downcast::<PyList>()?;
-            let mut vec_args = Vec::with_capacity(list.len());
-            for item in list.iter() {
-                vec_args.push(python_to_json(&item)?);
-            }
-            vec_args
-        } else {
-            vec![python_to_json(args)?]
-        };
+        let wait_timers = WaitTimers::parse(wait_timers)?;
 
-        let args_json: Vec<String> = json_args
-            .iter()
-            .map(|arg| serde_json::to_string(arg).unwrap())
-            .collect();
-        let args_str = args_json.join(", ");
-        let call_code = format!("{}({})", name, args_str);
+        // 准备参数（在持有GIL时）
+        let call_code = build_call_code(&name, args)?;
 
         // 释放GIL执行JavaScript（提升多线程性能）
-        let self_ptr = SendPtr(self as *const Context);
-        let result_json = py.allow_threads(move || {
-            let ctx = unsafe { self_ptr.as_ref() };
-            ctx.execute_js(&call_code, auto_await.unwrap_or(true))
-        }).map_err(|e| PyException::new_err(format!("Call error: {}", e)))?;
+        let result = self.allow_threads_interruptible(py, move |ctx| {
+            ctx.execute_js(&call_code, auto_await.unwrap_or(true), wait_timers)
+        })?.map_err(|e| PyException::new_err(format!("Call error: {}", e)));
+        let result_json = self.handle_error_reports(py, result)?;
 
         // 转换结果（在持有GIL时）
         let result: JsonValue = serde_json::from_str(&result_json)
@@ -1008,6 +1618,7 @@ impl Context {
     ///     code: JavaScript 代码
     ///     return_value: 是否返回最后一个表达式的值（默认 False）
     ///     auto_await: 是否自动等待 Promise（默认 True）
+    ///     wait_timers: 定时器处理策略，仅 return_value=True 时生效（见 call()）
     ///
     /// Returns:
     ///     如果 return_value=True，返回最后一个表达式的值；否则返回 None
@@ -1018,21 +1629,22 @@ impl Context {
     ///     ctx.eval("function add(a, b) { return a + b; }")
     ///     result = ctx.call("add", [1, 2])  # 可以调用，因为add在全局作用域
     ///     ```
-    #[pyo3(signature = (code, return_value=false, auto_await=None))]
+    #[pyo3(signature = (code, return_value=false, auto_await=None, wait_timers=None))]
     pub fn eval<'py>(
         &self,
         py: Python<'py>,
         code: String,
         return_value: bool,
         auto_await: Option<bool>,
+        wait_timers: Option<&str>,
     ) -> PyResult<Bound<'py, PyAny>> {
+        let wait_timers = WaitTimers::parse(wait_timers)?;
         if return_value {
             // 需要返回值：使用包装的execute_js，释放GIL
-            let self_ptr = SendPtr(self as *const Context);
-            let result_json = py.allow_threads(move || {
-                let ctx = unsafe { self_ptr.as_ref() };
-                ctx.execute_js(&code, auto_await.unwrap_or(true))
-            }).map_err(|e| PyException::new_err(format!("Eval error: {}", e)))?;
+            let result = self.allow_threads_interruptible(py, move |ctx| {
+                ctx.execute_js(&code, auto_await.unwrap_or(true), wait_timers)
+            })?.map_err(|e| PyException::new_err(format!("Eval error: {}", e)));
+            let result_json = self.handle_error_reports(py, result)?;
 
             let result: JsonValue = serde_json::from_str(&result_json)
                 .map_err(|e| PyException::new_err(format!("JSON parse error: {}", e)))?;
@@ -1040,11 +1652,10 @@ impl Context {
             json_to_python(py, &result)
         } else {
             // 不需要返回值：直接执行脚本，释放GIL
-            let self_ptr = SendPtr(self as *const Context);
-            py.allow_threads(move || {
-                let ctx = unsafe { self_ptr.as_ref() };
+            let result = self.allow_threads_interruptible(py, move |ctx| {
                 ctx.exec_script(&code)
-            }).map_err(|e| PyException::new_err(format!("Eval error: {}", e)))?;
+            })?.map_err(|e| PyException::new_err(format!("Eval error: {}", e)));
+            self.handle_error_reports(py, result)?;
 
             Ok(py.None().into_bound(py))
         }
@@ -1057,22 +1668,24 @@ impl Context {
     /// Args:
     ///     code: JavaScript 代码
     ///     auto_await: 是否自动等待 Promise（默认 True）
+    ///     wait_timers: 定时器处理策略（见 call()，默认 "until_result"）
     ///
     /// Returns:
     ///     表达式的值
-    #[pyo3(signature = (code, auto_await=None))]
+    #[pyo3(signature = (code, auto_await=None, wait_timers=None))]
     pub fn evaluate<'py>(
         &self,
         py: Python<'py>,
         code: String,
         auto_await: Option<bool>,
+        wait_timers: Option<&str>,
     ) -> PyResult<Bound<'py, PyAny>> {
+        let wait_timers = WaitTimers::parse(wait_timers)?;
         // 释放GIL执行JavaScript（提升多线程性能）
-        let self_ptr = SendPtr(self as *const Context);
-        let result_json = py.allow_threads(move || {
-            let ctx = unsafe { self_ptr.as_ref() };
-            ctx.execute_js(&code, auto_await.unwrap_or(true))
-        }).map_err(|e| PyException::new_err(format!("Evaluate error: {}", e)))?;
+        let result = self.allow_threads_interruptible(py, move |ctx| {
+            ctx.execute_js(&code, auto_await.unwrap_or(true), wait_timers)
+        })?.map_err(|e| PyException::new_err(format!("Evaluate error: {}", e)));
+        let result_json = self.handle_error_reports(py, result)?;
 
         let result: JsonValue = serde_json::from_str(&result_json)
             .map_err(|e| PyException::new_err(format!("JSON parse error: {}", e)))?;
@@ -1080,6 +1693,230 @@ impl Context {
         json_to_python(py, &result)
     }
 
+    /// 列出事件循环中待处理的任务
+    ///
+    /// Returns:
+    ///     字典：
+    ///     - timers: [{"id", "type": "timeout" | "interval", "delay"}]，JS 创建的定时器
+    ///     - promises: 尚未 settle 的 Promise 数量（自首次调用 pending_tasks() 起开始统计，
+    ///       之前为 None）
+    ///     - ops: 进行中的异步操作数量（fetch 等）
+    ///     - resources: 打开的资源数量
+    ///     - native_timers: deno_core 层面的定时器数量
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.evaluate("setInterval(() => {}, 1000); 1", wait_timers="none")
+    ///     print(ctx.pending_tasks()["timers"])
+    ///     # [{'id': 1, 'type': 'interval', 'delay': 1000}]
+    ///     ```
+    fn pending_tasks(&self, py: Python) -> PyResult<Py<PyDict>> {
+        let (report, counts) = self.pending_tasks_inner()
+            .map_err(|e| PyException::new_err(format!("Pending tasks error: {}", e)))?;
+
+        let dict = PyDict::new(py);
+        dict.set_item("timers", json_to_python(py, &report["timers"])?)?;
+        dict.set_item("promises", json_to_python(py, &report["promises"])?)?;
+        dict.set_item("ops", counts.ops)?;
+        dict.set_item("resources", counts.resources)?;
+        dict.set_item("native_timers", counts.timers)?;
+        Ok(dict.into())
+    }
+
+    /// 运行事件循环直到空闲
+    ///
+    /// 执行所有剩余的定时器、Promise 和异步操作，常与 wait_timers="none" 配合使用。
+    ///
+    /// Args:
+    ///     timeout_ms: 最长等待时间（毫秒），None 表示一直等待
+    ///
+    /// Returns:
+    ///     True 表示事件循环已空闲；False 表示超时（例如仍有 setInterval）
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.evaluate("setTimeout(() => globalThis.done = true, 100); 1", wait_timers="none")
+    ///     ctx.run_until_idle(timeout_ms=1000)
+    ///     ctx.evaluate("globalThis.done")  # True
+    ///     ```
+    #[pyo3(signature = (timeout_ms=None))]
+    fn run_until_idle(&self, py: Python, timeout_ms: Option<u64>) -> PyResult<bool> {
+        let result = self.allow_threads_interruptible(py, move |ctx| {
+            ctx.run_until_idle_inner(timeout_ms)
+        })?.map_err(|e| PyException::new_err(format!("Event loop error: {}", e)));
+        self.handle_error_reports(py, result)
+    }
+
+    /// 执行一次微任务检查点
+    ///
+    /// 只运行已排队的 Promise 回调（then/catch/await 续体），不触发任何定时器。
+    fn run_microtasks(&self, py: Python) -> PyResult<()> {
+        self.run_microtasks_inner();
+        self.handle_error_reports(py, Ok(()))
+    }
+
+    /// 已收集的未处理错误
+    ///
+    /// 每条为 dict：{"type", "source", "name", "message", "stack"}（见构造函数 on_error）
+    #[getter]
+    fn errors(&self, py: Python) -> PyResult<Py<PyList>> {
+        let list = PyList::empty(py);
+        for error in self.errors.borrow().iter() {
+            list.append(json_to_python(py, error)?)?;
+        }
+        Ok(list.into())
+    }
+
+    /// 清空已收集的未处理错误
+    fn clear_errors(&self) {
+        self.errors.borrow_mut().clear();
+    }
+
+    /// 已收集的 $emit() 事件
+    ///
+    /// JS 调用 $emit(channel, data) 不会中断执行，事件按发送顺序记录
+    /// （设置了 on_event 时事件只交给回调，不在这里记录）
+    /// （被 $terminate() 终止的调用在终止前发送的事件同样会记录，被 Ctrl+C 中断的调用发送的事件会被丢弃）。
+    ///
+    /// Args:
+    ///     channel: 只返回该频道的事件（默认返回全部）
+    ///
+    /// Returns:
+    ///     事件列表，每条为 dict：{"channel", "data"}
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.evaluate("keys.forEach((k, i) => $emit('round_key', {i, k})); 1")
+    ///     round_keys = [e["data"]["k"] for e in ctx.events("round_key")]
+    ///     ```
+    #[pyo3(signature = (channel=None))]
+    fn events(&self, py: Python, channel: Option<&str>) -> PyResult<Py<PyList>> {
+        let list = PyList::empty(py);
+        for event in self.events.borrow().iter() {
+            if channel.is_none_or(|channel| event["channel"] == channel) {
+                list.append(json_to_python(py, event)?)?;
+            }
+        }
+        Ok(list.into())
+    }
+
+    /// 清空已收集的 $emit() 事件
+    fn clear_events(&self) {
+        self.events.borrow_mut().clear();
+    }
+
+    /// 为全局函数安装 Hook，不需要手写 monkey-patch 代码
+    ///
+    /// 目标按路径从 globalThis 查找（如 "CryptoJS.AES.encrypt"），必须已经定义。
+    /// 包装后的函数保留原函数的 name / length，toString() 显示为 [native code]。
+    /// 每次调用作为 "hook" 频道的事件上报（见 events() / on_event）：
+    /// {"path", "args"?, "return"? | "error"?}，返回 Promise 时上报 resolve 的值。
+    ///
+    /// Args:
+    ///     path: 目标函数路径
+    ///     action: 调用时的行为（默认 "log"）
+    ///         - "log": 调用原函数并上报
+    ///         - "terminate": 调用 $terminate({"path", "args"})，本次调用抛出 HookTerminated
+    ///         - "replace": 以 replacement 代替原函数并上报
+    ///     replacement: action="replace" 时必填。str 为 JS 函数源码（以原来的 this 和参数调用）；
+    ///                  Python 可调用对象以 JS 参数按位置调用，返回值传回 JS，抛出的异常在 JS 中抛出
+    ///     capture_args: 上报参数（默认 True）
+    ///     capture_return: 上报返回值（默认 True）
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.hook("CryptoJS.AES.encrypt")
+    ///     ctx.call("login", ["user", "pass"])
+    ///     print(ctx.events("hook")[0]["data"]["args"])
+    ///
+    ///     ctx.hook("getTimestamp", action="replace", replacement=lambda: 1700000000000)
+    ///     ctx.hook("sign", action="replace", replacement="function (s) { return 'fixed'; }")
+    ///     ```
+    #[pyo3(signature = (path, action="log", replacement=None, capture_args=true, capture_return=true))]
+    fn hook(
+        &self,
+        py: Python,
+        path: String,
+        action: &str,
+        replacement: Option<&Bound<'_, PyAny>>,
+        capture_args: bool,
+        capture_return: bool,
+    ) -> PyResult<()> {
+        let hook = hook_spec_from_python(path, action, replacement, capture_args, capture_return)?;
+        let result = self.allow_threads_interruptible(py, move |ctx| {
+            ctx.install_hook(&hook)
+        })?.map_err(|e| PyException::new_err(format!("Hook error: {}", e)));
+        self.handle_error_reports(py, result)
+    }
+
+    /// 跟踪函数调用，记录参数、返回值、调用者和时间
+    ///
+    /// 被跟踪的函数包装后仍显示为 [native code]。调用记录在 Rust 侧缓冲区中，
+    /// 组成调用树：调用者为调用开始时仍在执行的最内层被跟踪调用。
+    ///
+    /// Args:
+    ///     targets: 跟踪目标列表，每项为
+    ///         - 函数路径，如 "CryptoJS.AES.encrypt"
+    ///         - 对象路径，跟踪该对象自身的所有方法，如 "CryptoJS.AES"
+    ///         - "script:名称"，跟踪 compile(code, name=名称) 定义的所有全局函数
+    ///           （只包括 var / function 声明；顶层 const / let / class 不是全局对象的属性，
+    ///           无法从脚本外部替换，需要跟踪时改用对象路径或改为 var 声明）
+    ///     max_events: 最多记录的调用数（默认 100000），超出的调用计入 trace.dropped
+    ///     capture_args: 记录参数（默认 True）
+    ///     capture_return: 记录返回值（默认 True），返回 Promise 时记录 resolve 的值
+    ///
+    /// Returns:
+    ///     Trace 对象，calls() 返回调用树，save() 导出 Chrome trace-event JSON；
+    ///     trace.stop()（或退出 with 语句）时还原被包装的函数
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx.compile(code, name="app.js")
+    ///     with ctx.trace(["script:app.js"]) as trace:
+    ///         ctx.call("login", ["user", "pass"])
+    ///     for call in trace.calls():
+    ///         print(call["name"], call["args"], call["return"])
+    ///     trace.save("trace.json")  # 在 https://ui.perfetto.dev 打开
+    ///     ```
+    #[pyo3(signature = (targets, max_events=100000, capture_args=true, capture_return=true))]
+    fn trace(
+        slf: &Bound<'_, Self>,
+        py: Python,
+        targets: Vec<String>,
+        max_events: usize,
+        capture_args: bool,
+        capture_return: bool,
+    ) -> PyResult<crate::trace::Trace> {
+        let ctx = slf.borrow();
+        let result = ctx.allow_threads_interruptible(py, move |ctx| {
+            ctx.install_trace(&targets, max_events, capture_args, capture_return)
+        })?.map_err(|e| PyException::new_err(format!("Trace error: {}", e)));
+        let (trace_id, buffer) = ctx.handle_error_reports(py, result)?;
+        let owner = crate::trace::TraceOwner::Context(slf.clone().unbind());
+        Ok(crate::trace::Trace::new(buffer, trace_id, owner))
+    }
+
+    /// 创建轻量级 realm（同一 Isolate 中的新 V8 Context）
+    ///
+    /// realm 共享本 Context 的 Isolate 和事件循环，但拥有独立的全局对象和
+    /// ECMAScript 内置对象（类似 iframe），并安装了相同的 Web API 全局对象。
+    /// 比创建新的 Context 快得多。Web API 对象与本 Context 共享，realm 不是安全沙箱。
+    ///
+    /// Args:
+    ///     expose_parent: realm 中的 parent / top 是否指向本 Context 的全局对象
+    ///                    （默认 False，指向 realm 自身）
+    ///
+    /// Returns:
+    ///     Realm 对象，使用 realm.eval() / realm.call() 执行代码
+    #[pyo3(signature = (expose_parent=false))]
+    fn create_realm(slf: &Bound<'_, Self>, expose_parent: bool) -> PyResult<crate::realm::Realm> {
+        let id = slf
+            .borrow()
+            .create_realm_inner(expose_parent)
+            .map_err(|e| PyException::new_err(format!("Create realm error: {}", e)))?;
+        Ok(crate::realm::Realm::new(slf.clone().unbind(), id))
+    }
+
     /// 请求垃圾回收
     ///
     /// 注意：这只是向 V8 发送 GC 请求，V8 会根据自己的策略决定是否执行。
@@ -1102,6 +1939,179 @@ impl Context {
         Ok(())
     }
 
+    /// 运行时重设随机数种子
+    ///
+    /// 同时作用于 Math.random、crypto.getRandomValues 和 crypto.randomUUID，
+    /// 无需重建 Context（已加载的 JS 代码保持不变）。
+    ///
+    /// Args:
+    ///     seed: 新种子；传入 None 恢复为系统随机数
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context()
+    ///     ctx.set_random_seed(42)
+    ///     a = ctx.evaluate("Math.random()")
+    ///     ctx.set_random_seed(42)
+    ///     b = ctx.evaluate("Math.random()")  # a == b
+    ///     ```
+    #[pyo3(signature = (seed))]
+    fn set_random_seed(&self, seed: Option<u64>) {
+        let runtime = self.runtime.borrow();
+        let op_state = runtime.op_state();
+        crate::ext::random::reseed(&mut op_state.borrow_mut(), seed);
+    }
+
+    /// 获取当前随机数生成器状态
+    ///
+    /// 返回的字典可以传给 set_rng_state() 恢复到完全相同的位置，
+    /// 用于在某个中间点保存/回放随机序列。
+    ///
+    /// Returns:
+    ///     字典：{"seed": int | None, "math_pos": int, "crypto_pos": int,
+    ///            "v8": (s0, s1, index) | None}
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context(random_seed=1)
+    ///     ctx.evaluate("Math.random()")
+    ///     state = ctx.get_rng_state()
+    ///     a = ctx.evaluate("[Math.random(), crypto.randomUUID()]")
+    ///     ctx.set_rng_state(state)
+    ///     b = ctx.evaluate("[Math.random(), crypto.randomUUID()]")  # a == b
+    ///     ```
+    fn get_rng_state(&self, py: Python) -> PyResult<Py<PyDict>> {
+        let runtime = self.runtime.borrow();
+        let op_state = runtime.op_state();
+        let snapshot = crate::ext::random::snapshot(&op_state.borrow());
+
+        let dict = PyDict::new(py);
+        dict.set_item("seed", snapshot.seed)?;
+        dict.set_item("math_pos", snapshot.math_pos)?;
+        dict.set_item("crypto_pos", snapshot.crypto_pos)?;
+        dict.set_item("v8", snapshot.v8_state)?;
+        Ok(dict.into())
+    }
+
+    /// 恢复随机数生成器状态
+    ///
+    /// Args:
+    ///     state: get_rng_state() 返回的字典
+    fn set_rng_state(&self, state: &Bound<'_, PyDict>) -> PyResult<()> {
+        let seed: Option<u64> = match state.get_item("seed")? {
+            Some(v) if !v.is_none() => Some(v.extract()?),
+            _ => None,
+        };
+        let math_pos: u128 = match state.get_item("math_pos")? {
+            Some(v) => v.extract()?,
+            None => 0,
+        };
+        let crypto_pos: u128 = match state.get_item("crypto_pos")? {
+            Some(v) => v.extract()?,
+            None => 0,
+        };
+        let v8_state: Option<(u64, u64, usize)> = match state.get_item("v8")? {
+            Some(v) if !v.is_none() => Some(v.extract()?),
+            _ => None,
+        };
+
+        let snapshot = crate::ext::random::RngSnapshot { seed, math_pos, crypto_pos, v8_state };
+        let runtime = self.runtime.borrow();
+        let op_state = runtime.op_state();
+        crate::ext::random::restore(&mut op_state.borrow_mut(), &snapshot);
+        Ok(())
+    }
+
+    /// 设置 V8 Math.random 内部状态（xorshift128+）
+    ///
+    /// 切换到 V8 模拟模式后 Math.random() 与 Chrome/Node 在相同内部状态下
+    /// 的输出逐位一致（包括 64 个值的缓存及其倒序消费顺序）。
+    /// crypto.getRandomValues/randomUUID 不受影响；调用 set_random_seed() 退出此模式。
+    ///
+    /// Args:
+    ///     s0: V8 内部 state0（最近一次缓存填充后的状态）
+    ///     s1: V8 内部 state1
+    ///     index: 缓存中剩余未消费的数量（0-64），0 表示下次调用时重新填充
+    ///
+    /// Example:
+    ///     ```python
+    ///     ctx = never_jscore.Context()
+    ///     ctx.set_v8_random_state(0x1234, 0x5678)
+    ///     ctx.evaluate("Math.random()")
+    ///     ```
+    #[pyo3(signature = (s0, s1, index=0))]
+    fn set_v8_random_state(&self, s0: u64, s1: u64, index: usize) -> PyResult<()> {
+        let rng = crate::ext::random::V8MathRandom::from_state(s0, s1, index)
+            .map_err(PyException::new_err)?;
+        let runtime = self.runtime.borrow();
+        let op_state = runtime.op_state();
+        crate::ext::random::set_v8_rng(&mut op_state.borrow_mut(), rng);
+        Ok(())
+    }
+
+    /// 按 V8 的 --random-seed 规则设置 Math.random 状态
+    ///
+    /// 结果与 `node --random-seed=<seed>` 中的 Math.random() 序列一致。
+    ///
+    /// Args:
+    ///     seed: 与 --random-seed 相同的整数种子
+    fn set_v8_random_seed(&self, seed: i64) {
+        let rng = crate::ext::random::V8MathRandom::from_seed(seed);
+        let runtime = self.runtime.borrow();
+        let op_state = runtime.op_state();
+        crate::ext::random::set_v8_rng(&mut op_state.borrow_mut(), rng);
+    }
+
+    /// 获取 V8 Math.random 模拟状态
+    ///
+    /// Returns:
+    ///     (s0, s1, index)，未启用 V8 模式时返回 None
+    fn get_v8_random_state(&self) -> Option<(u64, u64, usize)> {
+        let runtime = self.runtime.borrow();
+        let op_state = runtime.op_state();
+        let snapshot = crate::ext::random::snapshot(&op_state.borrow());
+        snapshot.v8_state
+    }
+
+    /// 从浏览器中观察到的连续 Math.random() 输出恢复 V8 内部状态
+    ///
+    /// 每个输出泄露 state0 的高 52 位，xorshift128+ 在 GF(2) 上是线性的，
+    /// 通过高斯消元求解。通常 4-5 个连续输出即可唯一确定状态。
+    ///
+    /// Args:
+    ///     outputs: 按调用顺序排列的连续 Math.random() 返回值
+    ///     cache_offset: outputs[0] 之前当前缓存块已被消费的数量（新页面/新进程为 0）；
+    ///                   所有输出必须位于同一个 64 值缓存块内
+    ///     apply: 是否立即应用到当前 Context（默认 True），
+    ///            之后 Math.random() 返回浏览器中紧随其后的值
+    ///
+    /// Returns:
+    ///     (s0, s1, index)，可传给 set_v8_random_state()
+    ///
+    /// Example:
+    ///     ```python
+    ///     observed = [0.9044192244068718, 0.5645421251397142, 0.062021669432033466, ...]
+    ///     ctx.recover_v8_random_state(observed)
+    ///     next_value = ctx.evaluate("Math.random()")  # 与浏览器中下一个值相同
+    ///     ```
+    #[pyo3(signature = (outputs, cache_offset=0, apply=true))]
+    fn recover_v8_random_state(
+        &self,
+        outputs: Vec<f64>,
+        cache_offset: usize,
+        apply: bool,
+    ) -> PyResult<(u64, u64, usize)> {
+        let rng = crate::ext::random::V8MathRandom::recover(&outputs, cache_offset)
+            .map_err(PyException::new_err)?;
+        let state = rng.state();
+        if apply {
+            let runtime = self.runtime.borrow();
+            let op_state = runtime.op_state();
+            crate::ext::random::set_v8_rng(&mut op_state.borrow_mut(), rng);
+        }
+        Ok(state)
+    }
+
     /// 获取 V8 堆内存统计信息
     ///
     /// 返回当前 JavaScript 运行时的详细内存使用情况。
@@ -1220,10 +2230,11 @@ impl Context {
         Ok(())
     }
 
-    /// 获取 Hook 拦截的数据
+    /// 获取最近一次 Hook 拦截的数据（兼容旧版本）
     ///
-    /// 当 JavaScript 调用 __saveAndTerminate__() 或 $terminate() 时，
-    /// 数据会保存到全局存储中。使用此方法可以在 JS 被终止后获取保存的数据。
+    /// JS 调用 $terminate() / __saveAndTerminate__() 时，当前调用抛出 HookTerminated，
+    /// 数据在异常的 data 属性中（推荐）。此方法返回本 Context 最近一次保存的数据，
+    /// 各 Context 相互独立。
     ///
     /// Returns:
     ///     Option<String>: 如果有保存的数据则返回 JSON 字符串，否则返回 None
@@ -1231,66 +2242,40 @@ impl Context {
     /// Example:
     ///     ```python
     ///     import never_jscore
-    ///     import json
     ///
     ///     ctx = never_jscore.Context()
     ///
     ///     # Hook XMLHttpRequest.send
-    ///     hook_code = '''
+    ///     ctx.compile('''
     ///         XMLHttpRequest.prototype.send = function(body) {
-    ///             __saveAndTerminate__({
-    ///                 url: this._url,
-    ///                 body: body,
-    ///                 timestamp: Date.now()
-    ///             });
+    ///             $terminate({ url: this._url, body: body });
     ///         };
-    ///     '''
-    ///     ctx.compile(hook_code)
+    ///     ''')
     ///
-    ///     # 执行会触发 Hook 的代码
     ///     try:
     ///         ctx.evaluate('''
     ///             const xhr = new XMLHttpRequest();
     ///             xhr.open('POST', '/api/login');
     ///             xhr.send('{"user":"admin"}');  // 触发 Hook
     ///         ''')
-    ///     except Exception as e:
-    ///         # JS 被 terminate，会抛出异常
-    ///         print(f"JS terminated: {e}")
-    ///
-    ///     # 获取 Hook 拦截的数据
-    ///     hook_data = ctx.get_hook_data()
-    ///     if hook_data:
-    ///         data = json.loads(hook_data)
-    ///         print(f"Intercepted URL: {data['url']}")
-    ///         print(f"Intercepted Body: {data['body']}")
+    ///     except never_jscore.HookTerminated as e:
+    ///         print(f"Intercepted URL: {e.data['url']}")
+    ///
+    ///     raw = ctx.get_hook_data()  # 兼容：同样的数据（JSON 字符串）
     ///     ```
     fn get_hook_data(&self) -> Option<String> {
-        crate::storage::get_hook_data()
+        self.last_hook_data.borrow().clone()
     }
 
-    /// 清空保存的 Hook 数据
-    ///
-    /// 在开始新的 JS 执行前调用，避免读取到旧数据。
+    /// 清空 get_hook_data() 保存的数据
     ///
     /// Example:
     ///     ```python
-    ///     ctx = never_jscore.Context()
-    ///
-    ///     # 清空之前的数据
     ///     ctx.clear_hook_data()
-    ///
-    ///     # 执行新的 Hook 拦截
-    ///     try:
-    ///         ctx.evaluate('...')
-    ///     except:
-    ///         pass
-    ///
-    ///     # 获取新的数据
-    ///     data = ctx.get_hook_data()
+    ///     assert ctx.get_hook_data() is None
     ///     ```
     fn clear_hook_data(&self) {
-        crate::storage::clear_hook_data();
+        self.last_hook_data.borrow_mut().take();
     }
 
     /// 上下文管理器支持：__enter__
@@ -4,12 +4,21 @@
 //! 核心优势：JS代码只加载一次，多线程复用
 
 use pyo3::prelude::*;
-use pyo3::types::PyList;
+use pyo3::IntoPyObjectExt;
+use pyo3::types::{PyDict, PyList, PyString};
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde_json::Value as JsonValue;
 use std::cell::OnceCell;
 use std::sync::Arc;
 use tokio::sync::oneshot;
 
-use crate::worker_pool::{WorkerPool, WorkerPoolConfig, Task, TaskType};
+use crate::worker_pool::{WorkerPool, WorkerPoolConfig, Task, TaskType, TaskError, TaskResult, SubmitPolicy, Target, CancelToken, EventSink};
+use crate::task_queue::Priority;
+use crate::errors::{submit_error_to_py, task_error_to_py};
+use crate::future::{events_to_python, JSFuture};
+use crate::context::hook_spec_from_python;
+use crate::ext::hook::HookSpec;
+use crate::metrics::HistogramSnapshot;
 use crate::convert::{json_to_python, python_to_json};
 use crate::storage::{get_hook_data_for_worker, clear_hook_data_for_worker};
 
@@ -67,6 +76,305 @@ pub struct JSEngine {
     pool: Arc<WorkerPool>,
 }
 
+/// 默认分块：每个Worker约分到4块，兼顾调度开销和负载均衡
+const CHUNKS_PER_WORKER: usize = 4;
+
+
+/// 延迟分布转换为Python字典（毫秒；没有样本时各分位数为 None）
+fn latency_to_dict<'py>(py: Python<'py>, latency: &HistogramSnapshot) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("count", latency.count())?;
+    dict.set_item("mean", latency.mean_ms())?;
+    dict.set_item("p50", latency.percentile(0.50))?;
+    dict.set_item("p95", latency.percentile(0.95))?;
+    dict.set_item("p99", latency.percentile(0.99))?;
+    dict.set_item("max", (latency.count() > 0).then_some(latency.max_ms))?;
+    Ok(dict)
+}
+
+/// 检查 Prometheus 指标名/标签名是否合法
+fn is_prometheus_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 解析提交策略参数
+fn parse_submit_policy(policy: &str, timeout: Option<f64>) -> PyResult<SubmitPolicy> {
+    let invalid = |msg: String| PyErr::new::<pyo3::exceptions::PyValueError, _>(msg);
+    match (policy, timeout) {
+        ("block", None) => Ok(SubmitPolicy::Block),
+        ("reject", None) => Ok(SubmitPolicy::Reject),
+        ("timeout", Some(seconds)) if seconds.is_finite() && seconds > 0.0 => {
+            Ok(SubmitPolicy::Timeout(std::time::Duration::from_secs_f64(seconds)))
+        }
+        ("timeout", _) => Err(invalid("submit_policy='timeout' requires a positive submit_timeout".to_string())),
+        ("block" | "reject", Some(_)) => Err(invalid("submit_timeout requires submit_policy='timeout'".to_string())),
+        (other, _) => Err(invalid(format!(
+            "Invalid submit_policy: '{}' (expected 'block', 'reject' or 'timeout')",
+            other
+        ))),
+    }
+}
+
+/// 解析任务优先级参数
+fn parse_priority(priority: &str) -> PyResult<Priority> {
+    Priority::ALL
+        .into_iter()
+        .find(|p| p.as_str() == priority)
+        .ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid priority: '{}' (expected 'high', 'normal' or 'low')",
+                priority
+            ))
+        })
+}
+
+/// 解析 hooks 参数中的一项（键同 Context.hook() 的参数）
+fn parse_hook(hook: &Bound<'_, PyDict>) -> PyResult<HookSpec> {
+    const KEYS: [&str; 5] = ["path", "action", "replacement", "capture_args", "capture_return"];
+    for key in hook.keys() {
+        let key: String = key.extract()?;
+        if !KEYS.contains(&key.as_str()) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Unknown hook option: '{}'", key)));
+        }
+    }
+    let path: String = hook
+        .get_item("path")?
+        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>("hook requires 'path'"))?
+        .extract()?;
+    let action: String = match hook.get_item("action")? {
+        Some(action) => action.extract()?,
+        None => "log".to_string(),
+    };
+    let flag = |key: &str| -> PyResult<bool> {
+        hook.get_item(key)?.map_or(Ok(true), |value| value.extract())
+    };
+    let replacement = hook.get_item("replacement")?;
+    hook_spec_from_python(path, &action, replacement.as_ref(), flag("capture_args")?, flag("capture_return")?)
+}
+
+impl JSEngine {
+    /// 提交任务并等待结果（释放GIL；队列已满时按提交策略等待或拒绝）
+    ///
+    /// target 指定Worker或亲和 key 时只交给对应Worker执行（同样遵循队列名额和优先级）；
+    /// events 为 Some 时收集本次调用 $emit() 发送的事件
+    fn submit_and_wait(
+        &self,
+        py: Python,
+        task_type: TaskType,
+        seed: Option<u64>,
+        target: Target,
+        priority: Priority,
+        events: Option<EventSink>,
+    ) -> PyResult<JsonValue> {
+        let pool = Arc::clone(&self.pool);
+        let result = py.allow_threads(move || {
+            run_with_engine_runtime(async move {
+                let (tx, rx) = oneshot::channel();
+                let task = Task {
+                    task_type,
+                    seed,
+                    cancel: None,
+                    events,
+                    tx,
+                };
+                pool.submit(task, priority, target).await.map_err(submit_error_to_py)?;
+
+                rx.await.map_err(|_| {
+                    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Worker died before returning result")
+                })
+            })
+        })?;
+        // $terminate() 拦截转换为 HookTerminated（需要GIL设置异常属性）
+        result.map_err(|e| task_error_to_py(py, &e))
+    }
+
+    /// 提交任务并等待结果，返回 (结果, 事件列表)
+    ///
+    /// 失败时事件列表设置在异常的 events 属性上
+    fn submit_with_events(
+        &self,
+        py: Python,
+        task_type: TaskType,
+        seed: Option<u64>,
+        target: Target,
+        priority: Priority,
+    ) -> PyResult<Py<PyAny>> {
+        let sink = EventSink::default();
+        let result = self.submit_and_wait(py, task_type, seed, target, priority, Some(Arc::clone(&sink)));
+        let events = events_to_python(py, &sink)?;
+        match result {
+            Ok(value) => (json_to_python(py, &value)?, events).into_py_any(py),
+            Err(e) => {
+                // 设置属性失败时仍然抛出原异常
+                let _ = e.value(py).setattr("events", events);
+                Err(e)
+            }
+        }
+    }
+
+    /// 亲和 key 对应的提交目标（None 表示交给任意空闲Worker）
+    fn affinity_target(affinity_key: Option<&str>) -> Target {
+        affinity_key.map_or(Target::Any, |key| Target::Key(key.to_string()))
+    }
+
+    /// 分块提交批量调用，等待全部完成
+    ///
+    /// 返回 (输入下标, 结果) 列表：ordered 时按输入顺序，否则按完成顺序。
+    /// 某个分块提交失败（队列已满被拒绝、Worker池已关闭）时：return_exceptions 为 true 则
+    /// 剩余的调用逐项记为提交失败，已提交的分块照常收集；否则取消已提交的分块后抛出异常。
+    #[allow(clippy::too_many_arguments)]
+    fn run_batch(
+        &self,
+        py: Python,
+        func_name: String,
+        args_list: Vec<Vec<JsonValue>>,
+        ordered: bool,
+        return_exceptions: bool,
+        chunk_size: Option<usize>,
+        priority: Priority,
+    ) -> PyResult<Vec<(usize, TaskResult)>> {
+        let total = args_list.len();
+        let chunk_size = match chunk_size {
+            Some(0) => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("chunk_size must be positive"));
+            }
+            Some(size) => size,
+            // 按Worker数上限分块，弹性伸缩时新增的Worker也能分到任务
+            None => total.div_ceil(self.pool.max_workers() * CHUNKS_PER_WORKER).max(1),
+        };
+
+        // 释放GIL，提交所有分块并等待完成
+        let pool = Arc::clone(&self.pool);
+        let results = py.allow_threads(move || {
+            run_with_engine_runtime(async move {
+                let mut waiting = FuturesUnordered::new();
+                let mut tokens = Vec::new();
+                let mut results = Vec::with_capacity(total);
+                let mut args_iter = args_list.into_iter();
+                let mut start = 0;
+                while start < total {
+                    let chunk: Vec<Vec<JsonValue>> = args_iter.by_ref().take(chunk_size).collect();
+                    let len = chunk.len();
+
+                    let token = Arc::new(CancelToken::new());
+                    let (tx, rx) = oneshot::channel();
+                    let task = Task {
+                        task_type: TaskType::CallBatch {
+                            func_name: func_name.clone(),
+                            args_list: chunk,
+                        },
+                        seed: None,
+                        cancel: Some(Arc::clone(&token)),
+                        events: None,
+                        tx,
+                    };
+                    if let Err(e) = pool.submit(task, priority, Target::Any).await {
+                        if !return_exceptions {
+                            // 已提交的分块结果不会再被使用，取消后抛出异常
+                            for token in &tokens {
+                                token.cancel();
+                            }
+                            return Err(submit_error_to_py(e));
+                        }
+                        let error = TaskError::Unsubmitted(e);
+                        results.extend((start..total).map(|index| (index, Err(error.clone()))));
+                        break;
+                    }
+
+                    tokens.push(token);
+                    waiting.push(async move { (start, len, rx.await) });
+                    start += len;
+                }
+
+                while let Some((start, len, received)) = waiting.next().await {
+                    let items: Vec<TaskResult> = match received {
+                        Ok(Ok(JsonValue::Array(items))) if items.len() == len => items
+                            .into_iter()
+                            .map(|item| match item {
+                                JsonValue::Object(mut map) => {
+                                    if let Some(error) = map.remove("error") {
+                                        Err(TaskError::Failed(error.as_str().unwrap_or_default().to_string()))
+                                    } else if let Some(data) = map.remove("hook") {
+                                        Err(TaskError::Hook {
+                                            worker_id: map.get("worker_id").and_then(JsonValue::as_u64).unwrap_or_default() as usize,
+                                            data: data.as_str().unwrap_or_default().to_string(),
+                                        })
+                                    } else {
+                                        Ok(map.remove("value").unwrap_or(JsonValue::Null))
+                                    }
+                                }
+                                other => Ok(other),
+                            })
+                            .collect(),
+                        Ok(Ok(_)) => vec![Err("Invalid batch result from worker".to_string().into()); len],
+                        Ok(Err(e)) => vec![Err(e); len],
+                        Err(_) => vec![Err("Worker died before returning result".to_string().into()); len],
+                    };
+                    results.extend((start..start + len).zip(items));
+                }
+                Ok::<_, PyErr>(results)
+            })
+        })?;
+
+        let mut results = results;
+        if ordered {
+            results.sort_unstable_by_key(|(index, _)| *index);
+        }
+        Ok(results)
+    }
+
+    /// 广播任务到每个Worker并等待全部完成，返回 (worker_id, 结果) 列表
+    fn run_broadcast(
+        &self,
+        py: Python,
+        task_type: TaskType,
+        persist: bool,
+    ) -> PyResult<Vec<(usize, TaskResult)>> {
+        let receivers = self.pool.broadcast(task_type, persist).map_err(submit_error_to_py)?;
+
+        // 释放GIL，等待所有Worker完成
+        let results = py.allow_threads(move || {
+            run_with_engine_runtime(futures::future::join_all(receivers.into_iter().map(|(worker_id, rx)| async move {
+                let result = rx
+                    .await
+                    .unwrap_or_else(|_| Err("Worker died before returning result".to_string().into()));
+                (worker_id, result)
+            })))
+        });
+        Ok(results)
+    }
+
+    /// 将批量结果转换为Python列表
+    ///
+    /// return_exceptions 时失败项为异常对象，否则遇到第一个失败项抛出异常
+    /// （错误信息为 "{name} failed for {label} {index}: ..."；$terminate() 拦截直接抛出 HookTerminated）
+    fn batch_to_python(
+        py: Python,
+        name: &str,
+        label: &str,
+        results: Vec<(usize, TaskResult)>,
+        return_exceptions: bool,
+    ) -> PyResult<Py<PyList>> {
+        let list = PyList::empty(py);
+        for (index, result) in results {
+            match result {
+                Ok(value) => list.append(json_to_python(py, &value)?)?,
+                Err(e) if return_exceptions => list.append(task_error_to_py(py, &e).into_value(py))?,
+                Err(e @ TaskError::Hook { .. }) => return Err(task_error_to_py(py, &e)),
+                Err(e) => {
+                    return Err(PyErr::new::<pyo3::exceptions::PyException, _>(format!(
+                        "{} failed for {} {}: {}",
+                        name, label, index, e
+                    )));
+                }
+            }
+        }
+        Ok(list.unbind())
+    }
+}
+
 #[pymethods]
 impl JSEngine {
     /// 创建JavaScript引擎
@@ -78,7 +386,27 @@ impl JSEngine {
     ///     enable_node_compat: 启用Node.js兼容（默认False）
     ///     enable_logging: 启用调试日志（默认False）
     ///     random_seed: 随机数种子（默认None）
+    ///     per_worker_seed: 每个Worker使用不同的种子 random_seed + worker_id（默认False，所有Worker序列相同）
     ///     fast_return: 快速返回模式，函数return后立即返回不等待定时器（默认False）
+    ///     deterministic: 确定性模式，固定时间/随机数/定时器顺序，所有Worker输出一致（默认False）
+    ///     max_queue_size: 任务队列最大长度（默认None，不限制）
+    ///     submit_policy: 队列已满时的提交策略（默认"block"）
+    ///                    - "block": 等待队列有空位
+    ///                    - "reject": 立即抛出 PoolOverloadedError
+    ///                    - "timeout": 最多等待 submit_timeout 秒，超时抛出 PoolOverloadedError
+    ///     submit_timeout: "timeout" 策略的等待时间（秒）
+    ///     max_tasks_per_worker: 每个Worker处理多少个任务后重建JsRuntime（默认None，不限制）
+    ///     max_worker_heap_mb: Worker堆内存超过该值（MB）时，在当前任务完成后重建JsRuntime（默认None）
+    ///     min_workers: 弹性伸缩时最少保持的Worker数（默认None，固定 workers 个Worker；
+    ///                  只指定 max_workers 时为1）
+    ///     max_workers: 弹性伸缩时的Worker数上限（默认等于 workers，两者只需指定一个）。
+    ///                  启动 min_workers 个Worker，排队任务多于空闲Worker时按需增加，
+    ///                  新Worker使用相同的配置和初始化代码
+    ///     idle_timeout: 弹性伸缩时空闲超过该秒数的Worker退出，不少于 min_workers 个（默认60）
+    ///     hooks: 在每个Worker加载初始化代码后安装的 Hook 列表（默认None），每项为 dict：
+    ///            {"path", "action", "replacement", "capture_args", "capture_return"}，
+    ///            含义同 Context.hook()；重建JsRuntime后重新安装。
+    ///            上报的事件随本次调用返回（call(..., with_events=True)）
     ///
     /// Returns:
     ///     JSEngine实例
@@ -90,9 +418,21 @@ impl JSEngine {
         enable_node_compat=false,
         enable_logging=false,
         random_seed=None,
-        fast_return=false
+        fast_return=false,
+        deterministic=false,
+        per_worker_seed=false,
+        max_queue_size=None,
+        submit_policy="block",
+        submit_timeout=None,
+        max_tasks_per_worker=None,
+        max_worker_heap_mb=None,
+        min_workers=None,
+        max_workers=None,
+        idle_timeout=60.0,
+        hooks=None
     ))]
     fn new(
+        py: Python,
         code: String,
         workers: Option<usize>,
         enable_extensions: bool,
@@ -100,12 +440,62 @@ impl JSEngine {
         enable_logging: bool,
         random_seed: Option<u32>,
         fast_return: bool,
+        deterministic: bool,
+        per_worker_seed: bool,
+        max_queue_size: Option<usize>,
+        submit_policy: &str,
+        submit_timeout: Option<f64>,
+        max_tasks_per_worker: Option<usize>,
+        max_worker_heap_mb: Option<usize>,
+        min_workers: Option<usize>,
+        max_workers: Option<usize>,
+        idle_timeout: f64,
+        hooks: Option<Vec<Bound<'_, PyDict>>>,
     ) -> PyResult<Self> {
-        let worker_count = workers.unwrap_or_else(|| {
-            std::thread::available_parallelism()
-                .map(|n| n.get())
-                .unwrap_or(4)
-        });
+        let submit_policy = parse_submit_policy(submit_policy, submit_timeout)?;
+        for (name, value) in [
+            ("max_queue_size", max_queue_size),
+            ("max_tasks_per_worker", max_tasks_per_worker),
+            ("max_worker_heap_mb", max_worker_heap_mb),
+        ] {
+            if value == Some(0) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{} must be positive", name)));
+            }
+        }
+
+        let invalid = |msg: String| PyErr::new::<pyo3::exceptions::PyValueError, _>(msg);
+        let worker_count = match (workers, max_workers) {
+            (Some(workers), Some(max_workers)) if workers != max_workers => {
+                return Err(invalid("specify either workers or max_workers, not both".to_string()));
+            }
+            (workers, max_workers) => max_workers.or(workers).unwrap_or_else(|| {
+                std::thread::available_parallelism()
+                    .map(|n| n.get())
+                    .unwrap_or(4)
+            }),
+        };
+        // 只指定 max_workers 时从1个Worker开始伸缩
+        let min_workers = min_workers.or(max_workers.map(|_| 1));
+        if let Some(min) = min_workers {
+            if min == 0 || min > worker_count {
+                return Err(invalid(format!(
+                    "min_workers must be between 1 and max_workers ({}), got {}",
+                    worker_count, min
+                )));
+            }
+        }
+        if !(idle_timeout.is_finite() && idle_timeout > 0.0) {
+            return Err(invalid("idle_timeout must be a positive number".to_string()));
+        }
+        let hooks = hooks
+            .unwrap_or_default()
+            .iter()
+            .map(parse_hook)
+            .collect::<PyResult<Vec<_>>>()?;
+        if !hooks.is_empty() && !enable_extensions {
+            return Err(invalid("hooks require enable_extensions=True".to_string()));
+        }
+        crate::runtime::init_v8_for(py, deterministic)?;
 
         let mut config = WorkerPoolConfig {
             worker_count,
@@ -113,8 +503,17 @@ impl JSEngine {
             enable_extensions,
             enable_logging,
             random_seed,
+            per_worker_seed,
             enable_node_compat,
             fast_return,
+            deterministic,
+            max_queue_size,
+            submit_policy,
+            max_tasks_per_worker,
+            max_worker_heap_mb,
+            min_workers,
+            idle_timeout: std::time::Duration::from_secs_f64(idle_timeout),
+            hooks,
             #[cfg(feature = "node_compat")]
             node_compat_options: None,
         };
@@ -125,7 +524,9 @@ impl JSEngine {
             config.node_compat_options = Some(NodeCompatOptions::default());
         }
 
-        let pool = WorkerPool::new(config)
+        // 等待所有Worker加载初始化代码（释放GIL），初始化失败时抛出异常
+        let pool = py
+            .allow_threads(move || WorkerPool::new(config))
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e))?;
 
         Ok(JSEngine {
@@ -138,46 +539,61 @@ impl JSEngine {
     /// Args:
     ///     func_name: 函数名
     ///     args: 参数列表
+    ///     seed: 本次调用使用的随机数种子（可选），无论分配到哪个Worker结果都相同
+    ///     affinity_key: 亲和 key（可选），相同 key 的调用始终交给同一个Worker，
+    ///                   用于有状态的脚本（会话计数器、缓存的token等）；
+    ///                   弹性伸缩时 key 的归属不变（对应Worker按需启动且不做空闲回收）；
+    ///                   该Worker意外退出时自动迁移到其他Worker，重建后迁回
+    ///     priority: 任务优先级 "high" / "normal" / "low"（默认"normal"）。
+    ///               排队时高优先级任务先执行，低优先级任务按较小的份额继续执行，不会被饿死；
+    ///               指定了 affinity_key 的任务在对应Worker上按同样的规则排队
+    ///     with_events: True 时返回 (result, events)，events 为本次调用 $emit() 发送的
+    ///                  事件列表 [{"channel": ..., "data": ...}]；调用失败时事件列表
+    ///                  设置在异常的 events 属性上（默认False）
     ///
     /// Returns:
-    ///     函数返回值
+    ///     函数返回值（with_events=True 时为 (返回值, 事件列表)）
+    ///
+    /// Raises:
+    ///     HookTerminated: JS调用了 $terminate(data)，e.data 为保存的数据，e.worker_id 为执行的Worker
     ///
     /// Example:
     ///     ```python
     ///     result = engine.call("encrypt", ["hello"])
+    ///     result = engine.call("encrypt", ["hello"], seed=42)  # 可重现
+    ///     result = engine.call("nextNonce", [], affinity_key="session-123")
+    ///     result = engine.call("sign", [payload], priority="high")  # 交互请求优先于批量任务
+    ///     result, events = engine.call("encrypt", ["hello"], with_events=True)
     ///     ```
-    fn call(&self, py: Python, func_name: String, args: &Bound<PyList>) -> PyResult<Py<PyAny>> {
+    #[pyo3(signature = (func_name, args, seed=None, affinity_key=None, priority="normal", with_events=false))]
+    fn call(
+        &self,
+        py: Python,
+        func_name: String,
+        args: &Bound<PyList>,
+        seed: Option<u64>,
+        affinity_key: Option<&str>,
+        priority: &str,
+        with_events: bool,
+    ) -> PyResult<Py<PyAny>> {
+        let priority = parse_priority(priority)?;
+        let target = Self::affinity_target(affinity_key);
+
         // 转换参数为JSON
         let json_args: Vec<serde_json::Value> = args
             .iter()
             .map(|item| python_to_json(&item))
             .collect::<PyResult<Vec<_>>>()?;
 
-        // 创建任务
-        let (tx, rx) = oneshot::channel();
-        let task = Task {
-            task_type: TaskType::Call {
-                func_name,
-                args: json_args,
-            },
-            tx,
+        // 提交任务并等待结果
+        let task_type = TaskType::Call {
+            func_name,
+            args: json_args,
         };
-
-        // 提交任务
-        self.pool
-            .submit(task)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e))?;
-
-        // 释放GIL并等待结果
-        let json_result = py.allow_threads(|| {
-            let rx_result = run_with_engine_runtime(async { rx.await });
-
-            rx_result
-                .map_err(|_| {
-                    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Worker died before returning result")
-                })?
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e))
-        })?;
+        if with_events {
+            return self.submit_with_events(py, task_type, seed, target, priority);
+        }
+        let json_result = self.submit_and_wait(py, task_type, seed, target, priority, None)?;
 
         // 转换结果为Python对象
         let bound = json_to_python(py, &json_result)?;
@@ -188,6 +604,10 @@ impl JSEngine {
     ///
     /// Args:
     ///     code: JavaScript代码
+    ///     seed: 本次执行使用的随机数种子（可选）
+    ///     affinity_key: 亲和 key（可选，同 call）
+    ///     priority: 任务优先级（默认"normal"，同 call）
+    ///     with_events: True 时返回 (result, events)（同 call）
     ///
     /// Returns:
     ///     执行结果
@@ -196,67 +616,501 @@ impl JSEngine {
     ///     ```python
     ///     result = engine.execute("Math.sqrt(16)")
     ///     ```
-    fn execute(&self, py: Python, code: String) -> PyResult<Py<PyAny>> {
-        // 创建任务
+    #[pyo3(signature = (code, seed=None, affinity_key=None, priority="normal", with_events=false))]
+    fn execute(
+        &self,
+        py: Python,
+        code: String,
+        seed: Option<u64>,
+        affinity_key: Option<&str>,
+        priority: &str,
+        with_events: bool,
+    ) -> PyResult<Py<PyAny>> {
+        let priority = parse_priority(priority)?;
+        let target = Self::affinity_target(affinity_key);
+
+        // 提交任务并等待结果
+        let task_type = TaskType::Execute { code };
+        if with_events {
+            return self.submit_with_events(py, task_type, seed, target, priority);
+        }
+        let json_result = self.submit_and_wait(py, task_type, seed, target, priority, None)?;
+
+        // 转换结果为Python对象
+        let bound = json_to_python(py, &json_result)?;
+        Ok(bound.unbind())
+    }
+
+    /// 提交函数调用，立即返回可取消的 JSFuture
+    ///
+    /// 适合调用方可能放弃等待的场景（例如HTTP客户端已断开）：future.cancel() 会移除
+    /// 排队中的任务或终止执行中的任务。JSFuture 可以直接 await，
+    /// asyncio 协程被取消时任务随之取消。
+    /// 提交本身遵循 submit_policy（"block" 策略在队列已满时会阻塞直到有空位）。
+    ///
+    /// Args:
+    ///     func_name: 函数名
+    ///     args: 参数列表
+    ///     seed: 本次调用使用的随机数种子（可选）
+    ///     affinity_key: 亲和 key（可选，同 call）
+    ///     priority: 任务优先级（默认"normal"，同 call）
+    ///
+    /// Returns:
+    ///     JSFuture
+    ///
+    /// Example:
+    ///     ```python
+    ///     future = engine.submit("sign", [data])
+    ///     result = future.result(timeout=5)
+    ///
+    ///     result = await asyncio.wait_for(engine.submit("sign", [data]), timeout=5)
+    ///     ```
+    #[pyo3(signature = (func_name, args, seed=None, affinity_key=None, priority="normal"))]
+    fn submit(
+        &self,
+        py: Python,
+        func_name: String,
+        args: &Bound<PyList>,
+        seed: Option<u64>,
+        affinity_key: Option<&str>,
+        priority: &str,
+    ) -> PyResult<JSFuture> {
+        let priority = parse_priority(priority)?;
+        let target = Self::affinity_target(affinity_key);
+        let json_args: Vec<serde_json::Value> = args
+            .iter()
+            .map(|item| python_to_json(&item))
+            .collect::<PyResult<Vec<_>>>()?;
+
+        let token = Arc::new(CancelToken::new());
+        let events = EventSink::default();
         let (tx, rx) = oneshot::channel();
         let task = Task {
-            task_type: TaskType::Execute { code },
+            task_type: TaskType::Call {
+                func_name,
+                args: json_args,
+            },
+            seed,
+            cancel: Some(Arc::clone(&token)),
+            events: Some(Arc::clone(&events)),
             tx,
         };
 
-        // 提交任务
-        self.pool
-            .submit(task)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e))?;
+        let pool = Arc::clone(&self.pool);
+        py.allow_threads(move || {
+            run_with_engine_runtime(async move {
+                pool.submit(task, priority, target).await
+            })
+        })
+        .map_err(submit_error_to_py)?;
 
-        // 释放GIL并等待结果
-        let json_result = py.allow_threads(|| {
-            let rx_result = run_with_engine_runtime(async { rx.await });
+        Ok(JSFuture::new(token, events, rx))
+    }
 
-            rx_result
-                .map_err(|_| {
-                    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Worker died before returning result")
-                })?
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(e))
-        })?;
+    /// 在指定Worker上调用JavaScript函数
+    ///
+    /// 任务以 normal 优先级在该Worker上排队，占用队列名额（遵循 max_queue_size 和 submit_policy）。
+    /// 指定Worker已退出（正在重建）时抛出 RuntimeError。
+    ///
+    /// Args:
+    ///     worker_id: Worker的ID（0 到 max_workers-1）
+    ///     func_name: 函数名
+    ///     args: 参数列表
+    ///     seed: 本次调用使用的随机数种子（可选）
+    ///
+    /// Returns:
+    ///     函数返回值
+    ///
+    /// Example:
+    ///     ```python
+    ///     token = engine.call_on(0, "getToken", [])
+    ///     ```
+    #[pyo3(signature = (worker_id, func_name, args, seed=None))]
+    fn call_on(
+        &self,
+        py: Python,
+        worker_id: usize,
+        func_name: String,
+        args: &Bound<PyList>,
+        seed: Option<u64>,
+    ) -> PyResult<Py<PyAny>> {
+        if worker_id >= self.pool.max_workers() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "worker_id {} out of range (engine has {} workers)",
+                worker_id,
+                self.pool.max_workers()
+            )));
+        }
+
+        let json_args: Vec<serde_json::Value> = args
+            .iter()
+            .map(|item| python_to_json(&item))
+            .collect::<PyResult<Vec<_>>>()?;
+
+        let json_result = self.submit_and_wait(
+            py,
+            TaskType::Call {
+                func_name,
+                args: json_args,
+            },
+            seed,
+            Target::Worker(worker_id),
+            Priority::Normal,
+            None,
+        )?;
 
-        // 转换结果为Python对象
         let bound = json_to_python(py, &json_result)?;
         Ok(bound.unbind())
     }
 
-    /// 获取Worker数量
+    /// 批量调用JavaScript函数
+    ///
+    /// 将调用分块提交给Worker，同一分块在一个Worker上依次执行，
+    /// 只需释放一次GIL，避免逐个调用 call() 的调度开销。
+    ///
+    /// Args:
+    ///     func_name: 函数名
+    ///     args_list: 参数列表的列表，每项是一次调用的参数
+    ///     ordered: True 按输入顺序返回结果；False 按完成顺序返回（默认True）
+    ///     return_exceptions: True 时失败项以异常对象放入结果列表；
+    ///                        False 时任一调用失败则抛出异常（默认False）
+    ///     chunk_size: 每个分块的调用数量（默认按Worker数量自动计算）
+    ///     priority: 任务优先级（默认"normal"，同 call），批量任务通常使用 "low"
+    ///
+    /// Returns:
+    ///     结果列表
+    ///
+    /// Example:
+    ///     ```python
+    ///     results = engine.call_many("encrypt", [["a", 1], ["b", 2]])
+    ///     results = engine.call_many("parse", inputs, return_exceptions=True)
+    ///     errors = [r for r in results if isinstance(r, Exception)]
+    ///     results = engine.call_many("encrypt", backlog, priority="low")  # 不阻塞交互请求
+    ///     ```
+    #[pyo3(signature = (func_name, args_list, ordered=true, return_exceptions=false, chunk_size=None, priority="normal"))]
+    fn call_many(
+        &self,
+        py: Python,
+        func_name: String,
+        args_list: &Bound<PyAny>,
+        ordered: bool,
+        return_exceptions: bool,
+        chunk_size: Option<usize>,
+        priority: &str,
+    ) -> PyResult<Py<PyList>> {
+        let priority = parse_priority(priority)?;
+        let not_a_list = || {
+            PyErr::new::<pyo3::exceptions::PyTypeError, _>("call_many() expects each item to be a list of arguments")
+        };
+
+        let mut json_args_list = Vec::new();
+        for args in args_list.try_iter()? {
+            let args = args?;
+            // 字符串也可迭代，但几乎总是漏写了外层列表
+            if args.is_instance_of::<PyString>() {
+                return Err(not_a_list());
+            }
+            let json_args = args
+                .try_iter()
+                .map_err(|_| not_a_list())?
+                .map(|item| python_to_json(&item?))
+                .collect::<PyResult<Vec<_>>>()?;
+            json_args_list.push(json_args);
+        }
+
+        let results = self.run_batch(py, func_name.clone(), json_args_list, ordered, return_exceptions, chunk_size, priority)?;
+        Self::batch_to_python(py, &func_name, "item", results, return_exceptions)
+    }
+
+    /// 对每个元素调用JavaScript函数（每个元素作为唯一参数）
+    ///
+    /// 等价于 `call_many(func_name, [[item] for item in items], ...)`
+    ///
+    /// Example:
+    ///     ```python
+    ///     signs = engine.map("sign", ["a", "b", "c"])
+    ///     ```
+    #[pyo3(signature = (func_name, items, ordered=true, return_exceptions=false, chunk_size=None, priority="normal"))]
+    fn map(
+        &self,
+        py: Python,
+        func_name: String,
+        items: &Bound<PyAny>,
+        ordered: bool,
+        return_exceptions: bool,
+        chunk_size: Option<usize>,
+        priority: &str,
+    ) -> PyResult<Py<PyList>> {
+        let priority = parse_priority(priority)?;
+        let json_args_list = items
+            .try_iter()?
+            .map(|item| Ok(vec![python_to_json(&item?)?]))
+            .collect::<PyResult<Vec<_>>>()?;
+
+        let results = self.run_batch(py, func_name.clone(), json_args_list, ordered, return_exceptions, chunk_size, priority)?;
+        Self::batch_to_python(py, &func_name, "item", results, return_exceptions)
+    }
+
+    /// 在每个Worker上执行一次代码
+    ///
+    /// 用于更新所有Worker的状态（轮换密钥、刷新Cookie、修补环境等）。
+    /// persist=True 时记录到回放日志：Worker因生命周期策略或崩溃重建后会自动重新执行，
+    /// 保证所有Worker状态一致。广播不受 max_queue_size 限制；
+    /// 正在重建的Worker会在重建完成后执行，届时才返回结果。
+    ///
+    /// Args:
+    ///     code: JavaScript代码
+    ///     persist: 是否在Worker重建后回放（默认True）
+    ///     return_exceptions: True 时失败的Worker以异常对象放入结果列表（默认False）
+    ///
+    /// Returns:
+    ///     每个Worker的执行结果列表（按worker_id排序；弹性伸缩时只包含当前启用的Worker）
+    ///
+    /// Example:
+    ///     ```python
+    ///     engine.broadcast("globalThis.API_KEY = 'new-key'")
+    ///     ```
+    #[pyo3(signature = (code, persist=true, return_exceptions=false))]
+    fn broadcast(&self, py: Python, code: String, persist: bool, return_exceptions: bool) -> PyResult<Py<PyList>> {
+        let results = self.run_broadcast(py, TaskType::Execute { code }, persist)?;
+        Self::batch_to_python(py, "broadcast", "worker", results, return_exceptions)
+    }
+
+    /// 在每个Worker上调用一次函数（参数含义同 broadcast）
+    ///
+    /// Example:
+    ///     ```python
+    ///     engine.broadcast_call("setCookie", ["sid=abc"])
+    ///     ```
+    #[pyo3(signature = (func_name, args, persist=true, return_exceptions=false))]
+    fn broadcast_call(
+        &self,
+        py: Python,
+        func_name: String,
+        args: &Bound<PyList>,
+        persist: bool,
+        return_exceptions: bool,
+    ) -> PyResult<Py<PyList>> {
+        let json_args = args
+            .iter()
+            .map(|item| python_to_json(&item))
+            .collect::<PyResult<Vec<_>>>()?;
+        let task_type = TaskType::Call {
+            func_name: func_name.clone(),
+            args: json_args,
+        };
+        let results = self.run_broadcast(py, task_type, persist)?;
+        Self::batch_to_python(py, &func_name, "worker", results, return_exceptions)
+    }
+
+    /// 热重载初始化代码
+    ///
+    /// 目标网站更新混淆脚本时无需重建引擎：Worker在当前任务完成后使用新代码重建JsRuntime
+    /// （持久化的广播会重新执行），排队中的任务不会丢失。
+    /// 任一Worker加载新代码失败时，已切换的Worker回滚到旧代码并抛出 RuntimeError，
+    /// 引擎继续使用旧代码服务。
+    ///
+    /// Args:
+    ///     code: 新的初始化代码
+    ///     drain: True 逐个Worker切换，其余Worker继续处理任务（默认）；
+    ///            False 所有Worker同时切换，更快但切换期间暂停处理任务
+    ///
+    /// Example:
+    ///     ```python
+    ///     engine.reload(open("new_sign.js").read())
+    ///     ```
+    #[pyo3(signature = (code, drain=true))]
+    fn reload(&self, py: Python, code: String, drain: bool) -> PyResult<()> {
+        let pool = Arc::clone(&self.pool);
+        py.allow_threads(move || run_with_engine_runtime(pool.reload(Some(code), drain)))
+            .map_err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>)
+    }
+
+    /// 当前Worker数量（弹性伸缩时随负载变化）
     #[getter]
     fn workers(&self) -> usize {
         self.pool.worker_count()
     }
 
-    /// 获取指定Worker的Hook数据
+    /// 最少保持的Worker数（固定大小的引擎等于 max_workers）
+    #[getter]
+    fn min_workers(&self) -> usize {
+        self.pool.min_workers()
+    }
+
+    /// Worker数上限（worker_id 的取值范围为 0 到 max_workers-1）
+    #[getter]
+    fn max_workers(&self) -> usize {
+        self.pool.max_workers()
+    }
+
+    /// 当前排队等待Worker处理的任务数
+    #[getter]
+    fn queue_size(&self) -> usize {
+        self.pool.queue_size()
+    }
+
+    /// 任务队列最大长度（None表示不限制）
+    #[getter]
+    fn max_queue_size(&self) -> Option<usize> {
+        self.pool.max_queue_size()
+    }
+
+    /// 因队列已满被拒绝的任务数
+    #[getter]
+    fn rejected_tasks(&self) -> u64 {
+        self.pool.rejected_tasks()
+    }
+
+    /// Worker因 max_tasks_per_worker / max_worker_heap_mb 重建JsRuntime的次数
+    #[getter]
+    fn recycled_workers(&self) -> u64 {
+        self.pool.recycled_workers()
+    }
+
+    /// 已初始化且仍在运行的Worker数
     ///
-    /// 当调用返回 `{"__hook__": true, "worker_id": N}` 时，
-    /// 使用此方法获取实际的hook数据
+    /// Worker初始化失败或崩溃退出后由监督线程按退避时间自动重建，
+    /// 重建完成前此值小于 workers。
+    #[getter]
+    fn healthy_workers(&self) -> usize {
+        self.pool.healthy_workers()
+    }
+
+    /// Worker因初始化失败或崩溃被重建的次数
+    #[getter]
+    fn respawned_workers(&self) -> u64 {
+        self.pool.respawned_workers()
+    }
+
+    /// 获取运行指标
+    ///
+    /// 延迟为Worker执行任务的耗时（毫秒，不含排队时间），分位数按直方图分桶估算。
+    /// 计数从引擎创建开始累计，Worker重建后继续累计。
+    ///
+    /// Returns:
+    ///     dict: workers（当前Worker数）、min_workers、max_workers、scaled_up_workers、scaled_down_workers
+    ///     （弹性伸缩增加/回收的Worker数）、healthy_workers、queue_size、queue_size_by_priority（high/normal/low）、
+    ///     max_queue_size、in_flight（正在执行的任务数）、
+    ///     completed、errors、rejected、submit_timeouts、recycled_workers、respawned_workers、
+    ///     heap_used_bytes、uptime_seconds、latency_ms（count/mean/p50/p95/p99/max）、
+    ///     per_worker（每个Worker的 worker_id/healthy/busy/tasks/errors/heap_used_bytes/
+    ///     heap_total_bytes/latency_ms）
+    ///
+    /// Example:
+    ///     ```python
+    ///     stats = engine.stats()
+    ///     print(stats["queue_size"], stats["latency_ms"]["p99"])
+    ///     ```
+    fn stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let stats = self.pool.stats();
+        let counters = &stats.counters;
+
+        let dict = PyDict::new(py);
+        dict.set_item("workers", counters.workers)?;
+        dict.set_item("min_workers", self.pool.min_workers())?;
+        dict.set_item("max_workers", self.pool.max_workers())?;
+        dict.set_item("scaled_up_workers", self.pool.scaled_up_workers())?;
+        dict.set_item("scaled_down_workers", self.pool.scaled_down_workers())?;
+        dict.set_item("healthy_workers", counters.healthy_workers)?;
+        dict.set_item("queue_size", counters.queue_size)?;
+        let by_priority = PyDict::new(py);
+        for (priority, size) in Priority::ALL.into_iter().zip(self.pool.queue_sizes()) {
+            by_priority.set_item(priority.as_str(), size)?;
+        }
+        dict.set_item("queue_size_by_priority", by_priority)?;
+        dict.set_item("max_queue_size", counters.max_queue_size)?;
+        dict.set_item("in_flight", stats.in_flight)?;
+        dict.set_item("completed", stats.completed)?;
+        dict.set_item("errors", stats.errors)?;
+        dict.set_item("rejected", counters.rejected)?;
+        dict.set_item("submit_timeouts", stats.submit_timeouts)?;
+        dict.set_item("recycled_workers", counters.recycled)?;
+        dict.set_item("respawned_workers", counters.respawned)?;
+        dict.set_item("heap_used_bytes", stats.heap_used)?;
+        dict.set_item("uptime_seconds", stats.uptime.as_secs_f64())?;
+        dict.set_item("latency_ms", latency_to_dict(py, &stats.latency)?)?;
+
+        let per_worker = PyList::empty(py);
+        for worker in &stats.workers {
+            let item = PyDict::new(py);
+            item.set_item("worker_id", worker.worker_id)?;
+            item.set_item("healthy", worker.healthy)?;
+            item.set_item("busy", worker.busy)?;
+            item.set_item("tasks", worker.tasks)?;
+            item.set_item("errors", worker.errors)?;
+            item.set_item("heap_used_bytes", worker.heap_used)?;
+            item.set_item("heap_total_bytes", worker.heap_total)?;
+            item.set_item("latency_ms", latency_to_dict(py, &worker.latency)?)?;
+            per_worker.append(item)?;
+        }
+        dict.set_item("per_worker", per_worker)?;
+        Ok(dict)
+    }
+
+    /// 以 Prometheus 文本格式导出运行指标
+    ///
+    /// 可直接作为 /metrics 接口的响应体（Content-Type: text/plain; version=0.0.4）。
     ///
     /// Args:
-    ///     worker_id: Worker的ID（从返回结果中获取）
+    ///     namespace: 指标名前缀（默认 "never_jscore"）
+    ///     labels: 附加到每个指标的标签（可选），用于区分多个引擎
+    ///
+    /// Example:
+    ///     ```python
+    ///     text = engine.prometheus_metrics(labels={"pool": "sign"})
+    ///     ```
+    #[pyo3(signature = (namespace="never_jscore", labels=None))]
+    fn prometheus_metrics(
+        &self,
+        namespace: &str,
+        labels: Option<std::collections::BTreeMap<String, String>>,
+    ) -> PyResult<String> {
+        let labels: Vec<(String, String)> = labels.unwrap_or_default().into_iter().collect();
+        // worker 和 le 是导出时使用的保留标签
+        let invalid = std::iter::once(namespace)
+            .filter(|name| !is_prometheus_name(name))
+            .chain(
+                labels
+                    .iter()
+                    .map(|(key, _)| key.as_str())
+                    .filter(|key| !is_prometheus_name(key) || *key == "worker" || *key == "le"),
+            )
+            .next();
+        if let Some(name) = invalid {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid Prometheus name: '{}'",
+                name
+            )));
+        }
+        Ok(self.pool.stats().to_prometheus(namespace, &labels))
+    }
+
+    /// 获取指定Worker最近一次的Hook数据（兼容旧版本，不推荐使用）
+    ///
+    /// $terminate() 拦截的数据随调用抛出的 HookTerminated 异常返回（e.data），
+    /// 不需要再调用此方法。这里的数据按 worker_id 保存在进程全局存储中，
+    /// 多个 JSEngine 的同号Worker会互相覆盖。
+    ///
+    /// Args:
+    ///     worker_id: Worker的ID（HookTerminated.worker_id）
     ///
     /// Returns:
     ///     Hook数据的JSON字符串，如果没有数据则返回None
     ///
     /// Example:
     ///     ```python
-    ///     result = engine.call("hookFunc", [data])
-    ///     if isinstance(result, dict) and result.get("__hook__"):
-    ///         worker_id = result["worker_id"]
-    ///         hook_data = engine.get_hook_data(worker_id)
-    ///         if hook_data:
-    ///             import json
-    ///             data = json.loads(hook_data)
+    ///     try:
+    ///         engine.call("hookFunc", [data])
+    ///     except never_jscore.HookTerminated as e:
+    ///         data = e.data  # 推荐
+    ///         raw = engine.get_hook_data(e.worker_id)  # 兼容：JSON字符串
     ///     ```
     fn get_hook_data(&self, worker_id: usize) -> Option<String> {
         get_hook_data_for_worker(worker_id)
     }
 
-    /// 清空指定Worker的Hook数据
+    /// 清空指定Worker的Hook数据（兼容旧版本，见 get_hook_data）
     ///
     /// Args:
     ///     worker_id: Worker的ID
@@ -269,24 +1123,86 @@ impl JSEngine {
         clear_hook_data_for_worker(worker_id);
     }
 
+    /// 关闭引擎
+    ///
+    /// 停止接收新任务（之后的调用抛出 RuntimeError），Worker线程退出并释放isolate。
+    /// 重复调用是安全的。
+    ///
+    /// Args:
+    ///     wait: True 时先处理完已排队的任务再退出（默认）；
+    ///           False 时立即取消已排队的任务（等待结果的调用抛出异常），不等待Worker退出
+    ///     timeout: wait=True 时最多等待的秒数（默认一直等待），超时后取消剩余任务
+    ///
+    /// Returns:
+    ///     所有Worker线程都已退出时返回 True
+    ///
+    /// Example:
+    ///     ```python
+    ///     engine.close(timeout=5.0)
+    ///     ```
+    #[pyo3(signature = (wait=true, timeout=None))]
+    fn close(&self, py: Python, wait: bool, timeout: Option<f64>) -> PyResult<bool> {
+        let timeout = timeout
+            .map(|seconds| {
+                if seconds.is_finite() && seconds >= 0.0 {
+                    Ok(std::time::Duration::from_secs_f64(seconds))
+                } else {
+                    Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        "timeout must be a non-negative number",
+                    ))
+                }
+            })
+            .transpose()?;
+
+        // 等待Worker期间释放GIL，排队中的任务需要GIL转换结果
+        let pool = Arc::clone(&self.pool);
+        Ok(py.allow_threads(move || pool.close(wait, timeout)))
+    }
+
+    /// 引擎是否已关闭
+    #[getter]
+    fn closed(&self) -> bool {
+        self.pool.is_closed()
+    }
+
     /// 上下文管理器支持 - __enter__
     fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
         slf
     }
 
     /// 上下文管理器支持 - __exit__
+    ///
+    /// 退出时关闭引擎，等待已排队的任务完成
     fn __exit__(
         &self,
+        py: Python,
         _exc_type: &Bound<PyAny>,
         _exc_value: &Bound<PyAny>,
         _traceback: &Bound<PyAny>,
     ) -> PyResult<bool> {
+        self.close(py, true, None)?;
         // 返回False表示不抑制异常
         Ok(false)
     }
 
     /// 字符串表示
     fn __repr__(&self) -> String {
-        format!("JSEngine(workers={})", self.pool.worker_count())
+        if self.pool.is_closed() {
+            return format!("JSEngine(workers={}, closed)", self.pool.worker_count());
+        }
+        if self.pool.min_workers() < self.pool.max_workers() {
+            return format!(
+                "JSEngine(workers={}, min_workers={}, max_workers={}, healthy={})",
+                self.pool.worker_count(),
+                self.pool.min_workers(),
+                self.pool.max_workers(),
+                self.pool.healthy_workers()
+            );
+        }
+        format!(
+            "JSEngine(workers={}, healthy={})",
+            self.pool.worker_count(),
+            self.pool.healthy_workers()
+        )
     }
 }
@@ -0,0 +1,59 @@
+//! 自定义 Python 异常类型
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyRuntimeError};
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use serde_json::Value as JsonValue;
+
+use crate::convert::json_to_python;
+use crate::worker_pool::{SubmitError, TaskError};
+
+create_exception!(
+    never_jscore,
+    PoolOverloadedError,
+    PyRuntimeError,
+    "JSEngine 任务队列已满，任务被拒绝（submit_policy 为 reject 或 timeout）"
+);
+
+create_exception!(
+    never_jscore,
+    HookTerminated,
+    PyException,
+    "JS 调用 $terminate() 终止了执行，data 属性为保存的 Hook 数据，worker_id 属性为执行的 Worker（Context 为 None）"
+);
+
+/// 创建 HookTerminated 异常，附带本次调用保存的 Hook 数据
+///
+/// data 为 JSON 字符串，解析为 Python 对象后设置到 data 属性（解析失败时保留原始字符串）
+pub fn hook_terminated(py: Python, data: &str, worker_id: Option<usize>) -> PyErr {
+    let error = HookTerminated::new_err("JavaScript execution terminated by $terminate()");
+    let value = error.value(py);
+    let parsed = serde_json::from_str::<JsonValue>(data)
+        .ok()
+        .and_then(|json| json_to_python(py, &json).ok())
+        .unwrap_or_else(|| PyString::new(py, data).into_any());
+    // 设置属性失败时仍然抛出异常本身
+    let _ = value.setattr("data", parsed);
+    let _ = value.setattr("worker_id", worker_id);
+    error
+}
+
+/// 提交失败转换为Python异常：队列已满为 PoolOverloadedError，其他为 RuntimeError
+pub fn submit_error_to_py(error: SubmitError) -> PyErr {
+    match error {
+        SubmitError::Overloaded(msg) => PoolOverloadedError::new_err(msg),
+        SubmitError::Closed | SubmitError::Unavailable | SubmitError::WorkerUnavailable(_) => {
+            PyRuntimeError::new_err(error.to_string())
+        }
+    }
+}
+
+/// 任务失败转换为Python异常：Hook 终止为 HookTerminated，没有提交成功的同提交失败，其他为 Exception
+pub fn task_error_to_py(py: Python, error: &TaskError) -> PyErr {
+    match error {
+        TaskError::Failed(msg) => PyException::new_err(msg.clone()),
+        TaskError::Hook { worker_id, data } => hook_terminated(py, data, Some(*worker_id)),
+        TaskError::Unsubmitted(e) => submit_error_to_py(e.clone()),
+    }
+}
@@ -0,0 +1,276 @@
+// Deterministic execution mode
+// Pins every time source and host-dependent value so that the same script plus
+// the same inputs produces byte-identical output across runs and pool workers.
+// Only takes effect when the runtime was created with deterministic=True.
+
+(() => {
+    if (typeof Deno === 'undefined' || !Deno.core || !Deno.core.ops.op_deterministic_enabled) {
+        return;
+    }
+    const ops = Deno.core.ops;
+    if (!ops.op_deterministic_enabled()) {
+        return;
+    }
+
+    const originalDefineProperty = Object.defineProperty;
+
+    // Functions replaced here are registered as native (init_native.js)
+    const registerNative = globalThis.__NEVER_JSCORE_NATIVE__.register;
+
+    function defineHidden(target, key, value) {
+        originalDefineProperty(target, key, {
+            value,
+            writable: true,
+            enumerable: false,
+            configurable: true
+        });
+    }
+
+    // ========================================================================
+    // Date - virtual wall clock
+    // ========================================================================
+    const OriginalDate = globalThis.Date;
+    const virtualNow = () => Math.floor(ops.op_deterministic_now());
+
+    function Date(...args) {
+        if (new.target === undefined) {
+            // Date() called as a function returns a string
+            return new OriginalDate(virtualNow()).toString();
+        }
+        if (args.length === 0) {
+            return Reflect.construct(OriginalDate, [virtualNow()], new.target);
+        }
+        return Reflect.construct(OriginalDate, args, new.target);
+    }
+
+    originalDefineProperty(Date, 'prototype', {
+        value: OriginalDate.prototype,
+        writable: false,
+        enumerable: false,
+        configurable: false
+    });
+    originalDefineProperty(Date, 'length', { value: 7, configurable: true });
+    defineHidden(Date, 'now', function now() {
+        return virtualNow();
+    });
+    defineHidden(Date, 'parse', OriginalDate.parse);
+    defineHidden(Date, 'UTC', OriginalDate.UTC);
+    defineHidden(OriginalDate.prototype, 'constructor', Date);
+    defineHidden(globalThis, 'Date', Date);
+    registerNative(Date, 'Date');
+    registerNative(Date.now, 'now');
+
+    // ========================================================================
+    // performance.now / timeOrigin
+    // ========================================================================
+    if (typeof performance !== 'undefined' && performance) {
+        const timeOrigin = virtualNow();
+        const now = function now() {
+            return ops.op_deterministic_elapsed();
+        };
+        try {
+            performance.now = now;
+            originalDefineProperty(performance, 'timeOrigin', {
+                get: () => timeOrigin,
+                enumerable: true,
+                configurable: true
+            });
+        } catch (e) {}
+    }
+
+    // ========================================================================
+    // Timers - virtual queue ordered by (due time, creation order)
+    // ========================================================================
+    // Real timers fire according to host wall-clock time, so two timers created
+    // around a slow computation can swap order between runs. In deterministic
+    // mode timers live in a virtual queue: the earliest due entry always runs
+    // first, and the virtual clock jumps to its due time (no real waiting).
+    if (typeof globalThis.setTimeout === 'function') {
+        const realSetTimeout = globalThis.setTimeout;
+        const queue = [];
+        const timers = new Map();
+        let nextId = 1;
+        let nextSeq = 0;
+        let pumpScheduled = false;
+
+        function compare(a, b) {
+            return a.due - b.due || a.seq - b.seq;
+        }
+
+        function enqueue(entry) {
+            let lo = 0;
+            let hi = queue.length;
+            while (lo < hi) {
+                const mid = (lo + hi) >>> 1;
+                if (compare(queue[mid], entry) <= 0) {
+                    lo = mid + 1;
+                } else {
+                    hi = mid;
+                }
+            }
+            queue.splice(lo, 0, entry);
+            schedulePump();
+        }
+
+        function schedulePump() {
+            if (!pumpScheduled && queue.length > 0) {
+                pumpScheduled = true;
+                realSetTimeout(pump, 0);
+            }
+        }
+
+        function pump() {
+            pumpScheduled = false;
+            const entry = queue.shift();
+            if (!entry) {
+                return;
+            }
+            ops.op_deterministic_advance_to(entry.due);
+            if (entry.repeat) {
+                entry.due += entry.delay;
+                entry.seq = nextSeq++;
+                enqueue(entry);
+            } else {
+                timers.delete(entry.id);
+            }
+            try {
+                entry.callback.apply(globalThis, entry.args);
+            } finally {
+                schedulePump();
+            }
+        }
+
+        function createTimer(callback, delay, args, repeat) {
+            if (typeof callback !== 'function') {
+                const source = String(callback);
+                callback = () => (0, eval)(source);
+            }
+            delay = Number(delay);
+            if (!(delay > 0)) {
+                delay = 0;
+            }
+            // Intervals need a positive period, otherwise they would starve the queue
+            if (repeat && delay < 1) {
+                delay = 1;
+            }
+            const entry = {
+                id: nextId++,
+                seq: nextSeq++,
+                due: ops.op_deterministic_peek() + delay,
+                delay,
+                callback,
+                args,
+                repeat,
+            };
+            timers.set(entry.id, entry);
+            enqueue(entry);
+            return entry.id;
+        }
+
+        function cancelTimer(id) {
+            const entry = timers.get(id);
+            if (!entry) {
+                return;
+            }
+            timers.delete(id);
+            const index = queue.indexOf(entry);
+            if (index !== -1) {
+                queue.splice(index, 1);
+            }
+        }
+
+        globalThis.setTimeout = function setTimeout(callback, delay = 0, ...args) {
+            return createTimer(callback, delay, args, false);
+        };
+        globalThis.setInterval = function setInterval(callback, delay = 0, ...args) {
+            return createTimer(callback, delay, args, true);
+        };
+        globalThis.clearTimeout = function clearTimeout(id = 0) {
+            cancelTimer(id);
+        };
+        globalThis.clearInterval = function clearInterval(id = 0) {
+            cancelTimer(id);
+        };
+    }
+
+    // ========================================================================
+    // crypto.subtle.generateKey - derive symmetric keys from the seeded RNG
+    // ========================================================================
+    // getRandomValues is already seeded (random_seed); AES and HMAC keys are
+    // built from it so generated keys are reproducible as well.
+    if (typeof crypto !== 'undefined' && crypto.subtle && typeof crypto.subtle.generateKey === 'function') {
+        const subtle = crypto.subtle;
+        const originalGenerateKey = subtle.generateKey;
+
+        function hmacDefaultLength(hash) {
+            const hashName = (typeof hash === 'string' ? hash : hash?.name || '').toUpperCase();
+            return hashName === 'SHA-384' || hashName === 'SHA-512' ? 1024 : 512;
+        }
+
+        subtle.generateKey = function generateKey(algorithm, extractable, keyUsages) {
+            const name = (typeof algorithm === 'string' ? algorithm : algorithm?.name || '').toUpperCase();
+            if (name.startsWith('AES-') && algorithm.length) {
+                const raw = crypto.getRandomValues(new Uint8Array(algorithm.length / 8));
+                return subtle.importKey('raw', raw, { name: algorithm.name }, extractable, keyUsages);
+            }
+            if (name === 'HMAC') {
+                const length = algorithm.length || hmacDefaultLength(algorithm.hash);
+                const raw = crypto.getRandomValues(new Uint8Array(Math.ceil(length / 8)));
+                return subtle.importKey(
+                    'raw',
+                    raw,
+                    { name: 'HMAC', hash: algorithm.hash, length },
+                    extractable,
+                    keyUsages,
+                );
+            }
+            return originalGenerateKey.call(this, algorithm, extractable, keyUsages);
+        };
+    }
+
+    // ========================================================================
+    // Host-visible values
+    // ========================================================================
+    if (typeof globalThis.navigator === 'object' && globalThis.navigator !== null) {
+        const pinned = { hardwareConcurrency: 8, deviceMemory: 8 };
+        for (const [key, value] of Object.entries(pinned)) {
+            if (key in globalThis.navigator) {
+                try {
+                    originalDefineProperty(globalThis.navigator, key, {
+                        get: () => value,
+                        enumerable: true,
+                        configurable: true
+                    });
+                } catch (e) {}
+            }
+        }
+    }
+
+    // Node.js compat: process.hrtime / uptime follow the virtual clock
+    if (typeof globalThis.process === 'object' && globalThis.process !== null &&
+        typeof globalThis.process.hrtime === 'function') {
+        const hrtime = function hrtime(previous) {
+            const ms = ops.op_deterministic_elapsed();
+            let seconds = Math.floor(ms / 1000);
+            let nanos = Math.round((ms % 1000) * 1e6);
+            if (Array.isArray(previous)) {
+                seconds -= previous[0];
+                nanos -= previous[1];
+                if (nanos < 0) {
+                    seconds -= 1;
+                    nanos += 1e9;
+                }
+            }
+            return [seconds, nanos];
+        };
+        hrtime.bigint = function bigint() {
+            return BigInt(Math.round(ops.op_deterministic_elapsed() * 1e6));
+        };
+        try {
+            globalThis.process.hrtime = hrtime;
+            globalThis.process.uptime = function uptime() {
+                return ops.op_deterministic_peek() / 1000;
+            };
+        } catch (e) {}
+    }
+})();
@@ -0,0 +1,144 @@
+// Deterministic extension for reproducible execution
+// Provides a virtual clock used to pin Date, performance.now and timer ordering
+
+use deno_core::{extension, Extension, OpState};
+
+/// 确定性模式下 Date.now() 的起始时间（2023-11-14T22:13:20.000Z）
+pub const DEFAULT_EPOCH_MS: f64 = 1_700_000_000_000.0;
+
+/// 每次读取时钟时虚拟时间前进的毫秒数
+///
+/// 时钟必须单调前进，否则 `while (Date.now() - start < 50) {}` 这类忙等循环永远不会结束。
+pub const DEFAULT_TICK_MS: f64 = 1.0;
+
+/// 确定性模式下未指定 random_seed 时使用的默认种子
+pub const DEFAULT_SEED: u64 = 0;
+
+/// 虚拟时钟
+///
+/// 确定性模式下所有时间源（Date、performance.now、定时器）都从这里读取，
+/// 与宿主机的真实时间完全无关，相同脚本每次运行得到的时间序列完全一致。
+pub struct DeterministicClock {
+    /// 虚拟时间零点对应的 Unix 毫秒时间戳
+    pub epoch_ms: f64,
+    /// 从零点开始经过的虚拟毫秒数
+    pub elapsed_ms: f64,
+    /// 每次读取时钟前进的毫秒数
+    pub tick_ms: f64,
+}
+
+impl DeterministicClock {
+    pub fn new(epoch_ms: f64, tick_ms: f64) -> Self {
+        Self {
+            epoch_ms,
+            elapsed_ms: 0.0,
+            tick_ms,
+        }
+    }
+
+    /// 读取时钟并前进一个 tick，返回读取前的虚拟经过时间
+    pub fn tick(&mut self) -> f64 {
+        let current = self.elapsed_ms;
+        self.elapsed_ms += self.tick_ms;
+        current
+    }
+
+    /// 将时钟推进到指定的虚拟时间（不会倒退）
+    pub fn advance_to(&mut self, elapsed_ms: f64) {
+        if elapsed_ms > self.elapsed_ms {
+            self.elapsed_ms = elapsed_ms;
+        }
+    }
+
+    /// 回到虚拟时间零点
+    pub fn reset(&mut self) {
+        self.elapsed_ms = 0.0;
+    }
+}
+
+/// 将 OpState 中的虚拟时钟重置到零点（未启用确定性模式时无效果）
+///
+/// JSEngine 在每个任务开始前调用，任务的输出与Worker之前执行过多少任务无关。
+pub fn reset_clock(state: &mut OpState) {
+    if let Some(clock) = state.try_borrow_mut::<DeterministicClock>() {
+        clock.reset();
+    }
+}
+
+impl Default for DeterministicClock {
+    fn default() -> Self {
+        Self::new(DEFAULT_EPOCH_MS, DEFAULT_TICK_MS)
+    }
+}
+
+#[deno_core::op2(fast)]
+/// Whether deterministic mode is enabled for this runtime
+pub fn op_deterministic_enabled(state: &mut OpState) -> bool {
+    state.has::<DeterministicClock>()
+}
+
+#[deno_core::op2(fast)]
+/// Virtual wall-clock time in milliseconds (used by Date)
+pub fn op_deterministic_now(state: &mut OpState) -> f64 {
+    match state.try_borrow_mut::<DeterministicClock>() {
+        Some(clock) => clock.epoch_ms + clock.tick(),
+        None => 0.0,
+    }
+}
+
+#[deno_core::op2(fast)]
+/// Virtual elapsed time in milliseconds (used by performance.now)
+pub fn op_deterministic_elapsed(state: &mut OpState) -> f64 {
+    match state.try_borrow_mut::<DeterministicClock>() {
+        Some(clock) => clock.tick(),
+        None => 0.0,
+    }
+}
+
+#[deno_core::op2(fast)]
+/// Current virtual elapsed time without advancing the clock (used to schedule timers)
+pub fn op_deterministic_peek(state: &mut OpState) -> f64 {
+    state
+        .try_borrow::<DeterministicClock>()
+        .map(|clock| clock.elapsed_ms)
+        .unwrap_or(0.0)
+}
+
+#[deno_core::op2(fast)]
+/// Advance the virtual clock to a timer's due time before its callback runs
+pub fn op_deterministic_advance_to(state: &mut OpState, elapsed_ms: f64) {
+    if let Some(clock) = state.try_borrow_mut::<DeterministicClock>() {
+        clock.advance_to(elapsed_ms);
+    }
+}
+
+// Deterministic extension definition using deno_core::extension! macro
+extension!(
+    never_jscore_deterministic,
+    ops = [
+        op_deterministic_enabled,
+        op_deterministic_now,
+        op_deterministic_elapsed,
+        op_deterministic_peek,
+        op_deterministic_advance_to,
+    ],
+    options = {
+        enabled: bool,
+    },
+    state = |state, options| {
+        if options.enabled {
+            state.put(DeterministicClock::default());
+        }
+    }
+);
+
+/// Build deterministic extension from ExtensionOptions
+pub fn extensions(options: &crate::ext::ExtensionOptions, _is_snapshot: bool) -> Vec<Extension> {
+    vec![never_jscore_deterministic::init(options.deterministic)]
+}
+
+/// Get JavaScript initialization code for deterministic mode
+/// This overrides Date, performance.now, timers and key generation when enabled
+pub fn get_init_js() -> &'static str {
+    include_str!("init_deterministic.js")
+}
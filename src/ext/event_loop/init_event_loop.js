@@ -0,0 +1,185 @@
+// Event loop introspection
+// Tracks pending timers/intervals (and optionally Promises) so that Python can
+// inspect them (ctx.pending_tasks()) and so that a call can cancel exactly the
+// timers it created (wait_timers="until_result") instead of guessing ID ranges.
+// Loaded after init_deterministic.js so the virtual timers are the ones tracked.
+//
+// When the owner collects errors (Context), unhandled Promise rejections and
+// exceptions thrown by timer callbacks are reported to Rust as structured data.
+// In collect mode they are swallowed (like a browser console); in strict mode
+// they are rethrown so they fail the current call.
+
+(() => {
+    if (typeof setTimeout !== 'function' || typeof Deno === 'undefined' || !Deno.core) {
+        return;
+    }
+    const core = Deno.core;
+
+    const originalSetTimeout = globalThis.setTimeout;
+    const originalSetInterval = globalThis.setInterval;
+    const originalClearTimeout = globalThis.clearTimeout;
+    const originalClearInterval = globalThis.clearInterval;
+
+    // id -> { id, type, delay, seq }
+    const timers = new Map();
+    let seq = 0;
+
+    // ========================================================================
+    // Error reporting
+    // ========================================================================
+    const REPORTING_STRICT = 2;
+    const reporting = typeof core.ops.op_error_reporting_mode === 'function'
+        ? core.ops.op_error_reporting_mode()
+        : 0;
+
+    // Errors rethrown by the call wrapper itself: they are the call's own
+    // failure, not fire-and-forget rejections
+    const callErrors = new Set();
+
+    function describe(error) {
+        if (error instanceof Error) {
+            return {
+                name: error.name,
+                message: error.message,
+                stack: typeof error.stack === 'string' ? error.stack : null,
+            };
+        }
+        let message;
+        try {
+            message = typeof error === 'string' ? error : JSON.stringify(error);
+        } catch (e) {
+            message = String(error);
+        }
+        return { name: typeof error, message: String(message), stack: null };
+    }
+
+    function reportError(type, source, error) {
+        try {
+            core.ops.op_report_error(JSON.stringify({ type, source, ...describe(error) }));
+        } catch (e) {
+            // Never let reporting break the event loop
+        }
+    }
+
+    function guardCallback(fn, source) {
+        if (!reporting) {
+            return fn;
+        }
+        return function() {
+            try {
+                return fn.apply(this, arguments);
+            } catch (error) {
+                reportError('uncaught_exception', source, error);
+                if (reporting === REPORTING_STRICT) {
+                    throw error;
+                }
+            }
+        };
+    }
+
+    if (reporting && typeof core.setUnhandledPromiseRejectionHandler === 'function') {
+        core.setUnhandledPromiseRejectionHandler((promise, reason) => {
+            if (callErrors.delete(reason)) {
+                return false;  // the call's own rejection, surfaced by Rust as usual
+            }
+            reportError('unhandled_rejection', 'promise', reason);
+            // true = handled: keep the event loop running
+            return reporting !== REPORTING_STRICT;
+        });
+    }
+
+    function toCallback(callback) {
+        if (typeof callback === 'function') {
+            return callback;
+        }
+        const source = String(callback);
+        return () => (0, eval)(source);
+    }
+
+    globalThis.setTimeout = function setTimeout(callback, delay = 0, ...args) {
+        const fn = guardCallback(toCallback(callback), 'timer');
+        const id = originalSetTimeout(function() {
+            timers.delete(id);
+            return fn.apply(this, arguments);
+        }, delay, ...args);
+        timers.set(id, { id, type: 'timeout', delay: Number(delay) || 0, seq: ++seq });
+        return id;
+    };
+
+    globalThis.setInterval = function setInterval(callback, delay = 0, ...args) {
+        const id = originalSetInterval(guardCallback(toCallback(callback), 'interval'), delay, ...args);
+        timers.set(id, { id, type: 'interval', delay: Number(delay) || 0, seq: ++seq });
+        return id;
+    };
+
+    globalThis.clearTimeout = function clearTimeout(id) {
+        timers.delete(id);
+        return originalClearTimeout(id);
+    };
+
+    globalThis.clearInterval = function clearInterval(id) {
+        timers.delete(id);
+        return originalClearInterval(id);
+    };
+
+    // Promise tracking is started lazily (promise hooks cost a callback per Promise)
+    let promiseTracking = false;
+    let promisesCreated = 0;
+    let promisesSettled = 0;
+
+    function startPromiseTracking() {
+        if (promiseTracking || typeof core.setPromiseHooks !== 'function') {
+            return;
+        }
+        promiseTracking = true;
+        core.setPromiseHooks(
+            () => { promisesCreated++; },   // init
+            undefined,                      // before
+            undefined,                      // after
+            () => { promisesSettled++; },   // resolve
+        );
+    }
+
+    const registry = {
+        // Sequence number marking the start of a call
+        mark() {
+            return seq;
+        },
+
+        // Cancel timers/intervals created after `mark`
+        cancelSince(mark) {
+            for (const timer of Array.from(timers.values())) {
+                if (timer.seq > mark) {
+                    timers.delete(timer.id);
+                    originalClearTimeout(timer.id);
+                    originalClearInterval(timer.id);
+                }
+            }
+        },
+
+        // Mark an error rethrown by the call wrapper (not reported as unhandled)
+        markCallError(error) {
+            if (reporting) {
+                callErrors.add(error);
+            }
+        },
+
+        // Snapshot for ctx.pending_tasks(); promises is null until tracking has
+        // started (the first snapshot starts it, later ones report the count)
+        pending() {
+            const tracking = promiseTracking;
+            startPromiseTracking();
+            return {
+                timers: Array.from(timers.values(), ({ id, type, delay }) => ({ id, type, delay })),
+                promises: tracking ? Math.max(0, promisesCreated - promisesSettled) : null,
+            };
+        },
+    };
+
+    Object.defineProperty(globalThis, '__NEVER_JSCORE_EVENT_LOOP__', {
+        value: registry,
+        writable: false,
+        enumerable: false,
+        configurable: false
+    });
+})();
@@ -0,0 +1,117 @@
+// Event loop introspection extension
+// Timer/Promise registry in JS, async op/resource counts from deno_core stats,
+// and reporting of unhandled rejections / uncaught timer exceptions
+
+use deno_core::stats::{RuntimeActivityStatsFilter, RuntimeActivityType};
+use deno_core::{extension, Extension, JsRuntime, OpState};
+
+/// Latest registry snapshot reported by JS (JSON string)
+///
+/// Kept separate from ResultStorage: op_store_result terminates execution
+/// in fast_return mode, which must not happen for introspection calls.
+pub struct PendingTasksReport(pub String);
+
+/// Global error reporting state
+///
+/// Only present when the runtime owner collects errors (Context); without it
+/// JS keeps deno_core's default behavior (unhandled rejections fail the event loop).
+#[derive(Default)]
+pub struct ErrorReporting {
+    /// Strict mode: reported errors also fail the current call
+    pub strict: bool,
+    /// Structured reports (JSON strings) not yet collected by the owner
+    pub reports: Vec<String>,
+}
+
+impl ErrorReporting {
+    pub fn new(strict: bool) -> Self {
+        Self {
+            strict,
+            reports: Vec::new(),
+        }
+    }
+}
+
+/// Take all reports collected since the last call
+pub fn take_error_reports(state: &mut OpState) -> Vec<String> {
+    state
+        .try_borrow_mut::<ErrorReporting>()
+        .map(|reporting| std::mem::take(&mut reporting.reports))
+        .unwrap_or_default()
+}
+
+#[deno_core::op2(fast)]
+/// Store the JS registry snapshot for ctx.pending_tasks()
+pub fn op_report_pending_tasks(state: &mut OpState, #[string] json: String) {
+    state.put(PendingTasksReport(json));
+}
+
+#[deno_core::op2(fast)]
+/// Error reporting mode: 0 = disabled, 1 = collect, 2 = strict
+pub fn op_error_reporting_mode(state: &mut OpState) -> u32 {
+    match state.try_borrow::<ErrorReporting>() {
+        None => 0,
+        Some(reporting) if reporting.strict => 2,
+        Some(_) => 1,
+    }
+}
+
+#[deno_core::op2(fast)]
+/// Record a structured error report (unhandled rejection / uncaught exception)
+pub fn op_report_error(state: &mut OpState, #[string] json: String) {
+    if let Some(reporting) = state.try_borrow_mut::<ErrorReporting>() {
+        reporting.reports.push(json);
+    }
+}
+
+extension!(
+    never_jscore_event_loop,
+    ops = [op_report_pending_tasks, op_error_reporting_mode, op_report_error],
+);
+
+/// Build event loop extension
+pub fn extensions(_options: &crate::ext::ExtensionOptions, _is_snapshot: bool) -> Vec<Extension> {
+    vec![never_jscore_event_loop::init()]
+}
+
+/// Get JavaScript initialization code for the timer/Promise registry
+pub fn get_init_js() -> &'static str {
+    include_str!("init_event_loop.js")
+}
+
+/// Script that reports the registry snapshot through op_report_pending_tasks
+pub const REPORT_SCRIPT: &str = r#"
+    (() => {
+        const registry = globalThis.__NEVER_JSCORE_EVENT_LOOP__;
+        const report = registry ? registry.pending() : { timers: [], promises: null };
+        __getDeno().core.ops.op_report_pending_tasks(JSON.stringify(report));
+    })();
+"#;
+
+/// Native activity tracked by deno_core itself
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ActivityCounts {
+    /// In-flight async ops (fetch, file IO, ...)
+    pub ops: usize,
+    /// Open resources (sockets, streams, ...)
+    pub resources: usize,
+    /// Native timers and intervals
+    pub timers: usize,
+}
+
+/// Count pending async ops, resources and native timers of a runtime
+pub fn activity_counts(runtime: &JsRuntime) -> ActivityCounts {
+    let stats = runtime
+        .runtime_activity_stats_factory()
+        .capture(&RuntimeActivityStatsFilter::all());
+
+    let mut counts = ActivityCounts::default();
+    for activity in stats.dump().active.iter() {
+        match activity.activity() {
+            RuntimeActivityType::AsyncOp => counts.ops += 1,
+            RuntimeActivityType::Resource => counts.resources += 1,
+            RuntimeActivityType::Timer | RuntimeActivityType::Interval => counts.timers += 1,
+        }
+    }
+    counts
+}
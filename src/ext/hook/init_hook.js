@@ -31,6 +31,351 @@ function __saveAndTerminate__(data) {
     $terminate(data);
 }
 
+/**
+ * Send a structured event to Python without terminating execution
+ *
+ * Events are collected in emit order: ctx.events() / the on_event callback
+ * for Context, and the event list of the call result for JSEngine.
+ *
+ * @param {string} channel - Event channel name (e.g. "round_key", "xhr")
+ * @param {any} data - Event payload (will be JSON stringified)
+ *
+ * @example
+ * // Capture every round key and keep running
+ * for (let i = 0; i < rounds; i++) {
+ *     $emit('round_key', { round: i, key: keys[i] });
+ * }
+ */
+function $emit(channel, data) {
+    let jsonData;
+    try {
+        // functions / symbols stringify to undefined
+        jsonData = JSON.stringify(data === undefined ? null : data) ?? 'null';
+    } catch (e) {
+        jsonData = JSON.stringify(String(data));
+    }
+    Deno.core.ops.op_emit_event(String(channel), jsonData);
+}
+
+// ============================================================================
+// Declarative hooks and call tracing (installed from Python: ctx.hook() / ctx.trace())
+// ============================================================================
+(() => {
+    const ops = Deno.core.ops;
+
+    function defineHidden(name, value) {
+        Object.defineProperty(globalThis, name, {
+            value,
+            writable: false,
+            enumerable: false,
+            configurable: true
+        });
+    }
+
+    // Payloads must survive JSON: functions, cycles and BigInt become strings
+    function toEventValue(value) {
+        if (value === undefined) return null;
+        if (typeof value === 'function') return `[Function: ${value.name || 'anonymous'}]`;
+        try {
+            const json = JSON.stringify(value);
+            return json === undefined ? null : JSON.parse(json);
+        } catch (e) {
+            return String(value);
+        }
+    }
+
+    function errorMessage(error) {
+        return String(error && error.message !== undefined ? error.message : error);
+    }
+
+    /**
+     * Resolve a dotted path from globalThis
+     * @returns {{ owner: object, key: string, value: any }}
+     */
+    function resolve(path, action) {
+        const parts = String(path).split('.');
+        const key = parts.pop();
+        let owner = globalThis;
+        for (const part of parts) {
+            owner = owner[part];
+            if (owner === null || (typeof owner !== 'object' && typeof owner !== 'function')) {
+                throw new TypeError(`Cannot ${action} ${path}: ${part} is not an object`);
+            }
+        }
+        return { owner, key, value: owner[key] };
+    }
+
+    function callOriginal(original, thisArg, args, newTarget) {
+        return newTarget
+            ? Reflect.construct(original, args, newTarget)
+            : original.apply(thisArg, args);
+    }
+
+    /**
+     * Make the wrapper look like the original (name, length, prototype, native toString)
+     * and put it in place of owner[key]
+     */
+    function replaceFunction(owner, key, original, wrapper, path, action) {
+        Object.setPrototypeOf(wrapper, Object.getPrototypeOf(original));
+        Object.defineProperty(wrapper, 'length', { value: original.length, configurable: true });
+        if (original.prototype !== undefined) {
+            wrapper.prototype = original.prototype;
+        }
+        // Registered in init_native.js: native toString without an own toString property
+        globalThis.__NEVER_JSCORE_NATIVE__.register(wrapper, original.name || key);
+
+        const descriptor = Object.getOwnPropertyDescriptor(owner, key);
+        if (descriptor && 'value' in descriptor) {
+            Object.defineProperty(owner, key, { ...descriptor, value: wrapper });
+        } else {
+            owner[key] = wrapper;
+        }
+        if (owner[key] !== wrapper) {
+            throw new TypeError(`Cannot ${action} ${path}: property is read-only`);
+        }
+    }
+
+    /**
+     * Install a declarative hook on a function reachable from globalThis
+     *
+     * Every call of the target is reported as a "hook" event:
+     * { path, args?, return? | error? } (args / return depend on the capture options).
+     *
+     * @param {string} path - Dotted path of the target (e.g. "CryptoJS.AES.encrypt")
+     * @param {object} options - { action: "log" | "terminate" | "replace",
+     *                             captureArgs, captureReturn, callbackId }
+     * @param {Function|null} replacement - JavaScript replacement for action "replace"
+     */
+    function hook(path, options, replacement) {
+        const { owner, key, value: original } = resolve(path, 'hook');
+        if (typeof original !== 'function') {
+            throw new TypeError(`Cannot hook ${path}: target is not a function`);
+        }
+        if (options.action === 'replace' && options.callbackId == null && typeof replacement !== 'function') {
+            throw new TypeError(`Cannot hook ${path}: replacement is not a function`);
+        }
+
+        const wrapper = function (...args) {
+            const capturedArgs = options.captureArgs ? args.map(toEventValue) : undefined;
+            const report = (outcome) => {
+                const data = { path };
+                if (capturedArgs) data.args = capturedArgs;
+                $emit('hook', Object.assign(data, outcome));
+            };
+
+            if (options.action === 'terminate') {
+                $terminate(capturedArgs ? { path, args: capturedArgs } : { path });
+            }
+
+            let result;
+            try {
+                if (options.callbackId != null) {
+                    const reply = JSON.parse(ops.op_hook_invoke(
+                        options.callbackId, JSON.stringify(args.map(toEventValue))));
+                    if ('error' in reply) {
+                        throw new Error(`Hook replacement for ${path} failed: ${reply.error}`);
+                    }
+                    result = reply.value;
+                } else if (replacement) {
+                    result = replacement.apply(this, args);
+                } else {
+                    result = callOriginal(original, this, args, new.target);
+                }
+            } catch (error) {
+                report({ error: errorMessage(error) });
+                throw error;
+            }
+
+            if (!options.captureReturn) {
+                report({});
+            } else if (result instanceof Promise) {
+                // Report the settled value without changing the returned Promise
+                result.then(
+                    (value) => report({ return: toEventValue(value) }),
+                    (error) => report({ error: errorMessage(error) })
+                );
+            } else {
+                report({ return: toEventValue(result) });
+            }
+            return result;
+        };
+
+        replaceFunction(owner, key, original, wrapper, path, 'hook');
+    }
+
+    // Global names added by scripts compiled with a name (ctx.compile(code, name=...)).
+    // Top-level const / let / class bindings are not properties of globalThis and
+    // cannot be reassigned from outside the script, so they are not included.
+    const scriptGlobals = new Map();
+    let scriptBaseline = null;
+
+    function scriptBegin() {
+        scriptBaseline = new Set(Object.getOwnPropertyNames(globalThis));
+    }
+
+    function scriptEnd(name) {
+        const added = Object.getOwnPropertyNames(globalThis)
+            .filter((key) => !scriptBaseline || !scriptBaseline.has(key));
+        scriptGlobals.set(name, added);
+        scriptBaseline = null;
+    }
+
+    /**
+     * Expand trace targets into [owner, key, path] entries
+     *
+     * - "a.b.fn": the function itself
+     * - "a.b": every own method of the object
+     * - "script:name": every top-level function (and methods of top-level objects)
+     *   a named script added to globalThis (var / function declarations only)
+     */
+    function traceTargets(targets) {
+        const entries = [];
+        const seen = new Set();
+        const add = (owner, key, path) => {
+            const value = unwrapStopped(owner[key]);
+            if (typeof value === 'function' && !seen.has(value)) {
+                seen.add(value);
+                entries.push([owner, key, path]);
+            }
+        };
+        const addMethods = (object, prefix) => {
+            for (const key of Object.getOwnPropertyNames(object)) {
+                const descriptor = Object.getOwnPropertyDescriptor(object, key);
+                if (key !== 'constructor' && descriptor && typeof descriptor.value === 'function') {
+                    add(object, key, `${prefix}.${key}`);
+                }
+            }
+        };
+
+        for (const target of targets) {
+            if (target.startsWith('script:')) {
+                const name = target.slice('script:'.length);
+                const globals = scriptGlobals.get(name);
+                if (!globals) {
+                    throw new TypeError(`Cannot trace ${target}: no script compiled with name '${name}'`);
+                }
+                for (const key of globals) {
+                    const value = globalThis[key];
+                    if (typeof value === 'function') {
+                        add(globalThis, key, key);
+                    } else if (value !== null && typeof value === 'object') {
+                        addMethods(value, key);
+                    }
+                }
+                continue;
+            }
+
+            const { owner, key, value } = resolve(target, 'trace');
+            if (typeof value === 'function') {
+                add(owner, key, target);
+            } else if (value !== null && typeof value === 'object') {
+                addMethods(value, target);
+            } else {
+                throw new TypeError(`Cannot trace ${target}: target is not a function or object`);
+            }
+        }
+        return entries;
+    }
+
+    // Trace wrapper -> { original, state } (state.active is cleared by untrace)
+    const traceWrappers = new WeakMap();
+    // traceId -> { state, entries: [owner, key, wrapper][] } to restore on untrace
+    const installedTraces = new Map();
+
+    /**
+     * Skip wrappers of stopped traces that could not be removed
+     */
+    function unwrapStopped(value) {
+        let wrapped = traceWrappers.get(value);
+        while (wrapped && !wrapped.state.active) {
+            value = wrapped.original;
+            wrapped = traceWrappers.get(value);
+        }
+        return value;
+    }
+
+    /**
+     * Wrap the targets so every call is recorded in the Rust-side trace buffer
+     *
+     * @param {number} traceId - Trace buffer id
+     * @param {string[]} targets - Function / object paths or "script:name"
+     * @param {object} options - { captureArgs, captureReturn }
+     */
+    function trace(traceId, targets, options) {
+        const state = { active: true };
+        const entries = [];
+        installedTraces.set(traceId, { state, entries });
+        for (const [owner, key, path] of traceTargets(targets)) {
+            const original = unwrapStopped(owner[key]);
+            const wrapper = function (...args) {
+                if (!state.active) {
+                    return callOriginal(original, this, args, new.target);
+                }
+                const callId = ops.op_trace_enter(
+                    traceId, path, options.captureArgs ? JSON.stringify(args.map(toEventValue)) : '');
+                if (callId < 0) {
+                    return callOriginal(original, this, args, new.target);
+                }
+
+                let result;
+                try {
+                    result = callOriginal(original, this, args, new.target);
+                } catch (error) {
+                    ops.op_trace_exit(traceId, callId, '', errorMessage(error));
+                    throw error;
+                }
+
+                const capture = options.captureReturn && !(result instanceof Promise);
+                ops.op_trace_exit(traceId, callId, capture ? JSON.stringify(toEventValue(result)) : '', '');
+                if (options.captureReturn && result instanceof Promise) {
+                    result.then(
+                        (value) => ops.op_trace_settle(traceId, callId, JSON.stringify(toEventValue(value)), ''),
+                        (error) => ops.op_trace_settle(traceId, callId, '', errorMessage(error))
+                    );
+                }
+                return result;
+            };
+            replaceFunction(owner, key, original, wrapper, path, 'trace');
+            traceWrappers.set(wrapper, { original, state });
+            entries.push([owner, key, wrapper]);
+        }
+    }
+
+    /**
+     * Put the original functions of a trace back
+     *
+     * A wrapper that was wrapped again afterwards (by a hook or another trace)
+     * stays in place but calls straight through to the original.
+     *
+     * @param {number} traceId - Trace buffer id
+     */
+    function untrace(traceId) {
+        const installed = installedTraces.get(traceId);
+        if (!installed) {
+            return;
+        }
+        installedTraces.delete(traceId);
+        installed.state.active = false;
+        for (const [owner, key, wrapper] of installed.entries.reverse()) {
+            const descriptor = Object.getOwnPropertyDescriptor(owner, key);
+            if (!descriptor || descriptor.value !== wrapper) {
+                continue;
+            }
+            const original = unwrapStopped(wrapper);
+            if (descriptor.configurable || descriptor.writable) {
+                Object.defineProperty(owner, key, { ...descriptor, value: original });
+            }
+        }
+    }
+
+    defineHidden('__neverjscore_hook__', hook);
+    defineHidden('__neverjscore_trace__', trace);
+    defineHidden('__neverjscore_untrace__', untrace);
+    defineHidden('__neverjscore_script_begin__', scriptBegin);
+    defineHidden('__neverjscore_script_end__', scriptEnd);
+})();
+
 // Make functions globally available
 globalThis.$terminate = $terminate;
 globalThis.__saveAndTerminate__ = __saveAndTerminate__;
+globalThis.$emit = $emit;
@@ -1,22 +1,211 @@
+pub mod trace;
+
 use deno_core::{extension, Extension, OpState};
 use super::ExtensionTrait;
 
-use crate::storage::{save_hook_data, save_hook_data_for_worker, WorkerId};
+use serde_json::Value as JsonValue;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::storage::ResultStorage;
+
+/// Events emitted by $emit() that the runtime owner has not collected yet
+///
+/// Only present when the owner collects events (Context / Worker); without it
+/// $emit() is a no-op.
+#[derive(Default)]
+pub struct EmittedEvents(pub Vec<EmittedEvent>);
+
+/// A single $emit(channel, data) event
+pub struct EmittedEvent {
+    pub channel: String,
+    /// JSON string produced by JSON.stringify
+    pub data: String,
+}
+
+impl EmittedEvent {
+    /// Convert to `{"channel": ..., "data": ...}` (data kept as a string if it is not valid JSON)
+    pub fn to_json(&self) -> JsonValue {
+        let data = serde_json::from_str(&self.data).unwrap_or_else(|_| JsonValue::String(self.data.clone()));
+        serde_json::json!({ "channel": self.channel, "data": data })
+    }
+}
+
+/// Take all events emitted since the last call, in emit order
+pub fn take_events(state: &mut OpState) -> Vec<JsonValue> {
+    state
+        .try_borrow_mut::<EmittedEvents>()
+        .map(|events| std::mem::take(&mut events.0))
+        .unwrap_or_default()
+        .iter()
+        .map(EmittedEvent::to_json)
+        .collect()
+}
+
+/// Op: Queue a structured event without interrupting execution
+#[deno_core::op2(fast)]
+pub fn op_emit_event(state: &mut OpState, #[string] channel: String, #[string] data: String) {
+    if let Some(events) = state.try_borrow_mut::<EmittedEvents>() {
+        events.0.push(EmittedEvent { channel, data });
+    }
+}
+
+/// Python callback used as a hook replacement: receives the call arguments,
+/// returns the value handed back to JavaScript (or an error message to throw)
+pub type HookCallback = Arc<dyn Fn(Vec<JsonValue>) -> Result<JsonValue, String> + Send + Sync>;
+
+/// Hook callbacks registered in this runtime, indexed by the callbackId passed to JS
+#[derive(Default)]
+pub struct HookCallbacks(pub Vec<HookCallback>);
+
+/// What a declarative hook does when the target function is called
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookAction {
+    /// Call the original function and report the call as a "hook" event
+    Log,
+    /// Stop execution with $terminate({path, args})
+    Terminate,
+    /// Call the replacement instead of the original and report the call
+    Replace,
+}
+
+impl HookAction {
+    pub fn parse(action: &str) -> Result<Self, String> {
+        match action {
+            "log" => Ok(HookAction::Log),
+            "terminate" => Ok(HookAction::Terminate),
+            "replace" => Ok(HookAction::Replace),
+            other => Err(format!(
+                "Invalid hook action: '{}' (expected 'log', 'terminate' or 'replace')",
+                other
+            )),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HookAction::Log => "log",
+            HookAction::Terminate => "terminate",
+            HookAction::Replace => "replace",
+        }
+    }
+}
+
+/// Replacement implementation for HookAction::Replace
+#[derive(Clone)]
+pub enum HookReplacement {
+    /// JavaScript function source, called with the original `this` and arguments
+    Js(String),
+    /// Host callback, called with the JSON-converted arguments
+    Callback(HookCallback),
+}
+
+/// A declarative function hook (ctx.hook() / JSEngine hooks option)
+#[derive(Clone)]
+pub struct HookSpec {
+    /// Dotted path from globalThis, e.g. "CryptoJS.AES.encrypt"
+    pub path: String,
+    pub action: HookAction,
+    pub replacement: Option<HookReplacement>,
+    /// Include the call arguments in the reported event / terminate data
+    pub capture_args: bool,
+    /// Include the return value (resolved value for Promises) in the reported event
+    pub capture_return: bool,
+}
+
+impl HookSpec {
+    pub fn new(
+        path: String,
+        action: &str,
+        replacement: Option<HookReplacement>,
+        capture_args: bool,
+        capture_return: bool,
+    ) -> Result<Self, String> {
+        let action = HookAction::parse(action)?;
+        if path.is_empty() || path.split('.').any(str::is_empty) {
+            return Err(format!("Invalid hook path: '{}'", path));
+        }
+        match (action, &replacement) {
+            (HookAction::Replace, None) => return Err("action 'replace' requires a replacement".to_string()),
+            (HookAction::Log | HookAction::Terminate, Some(_)) => {
+                return Err(format!("replacement is only used with action 'replace', not '{}'", action.as_str()));
+            }
+            _ => {}
+        }
+        Ok(HookSpec {
+            path,
+            action,
+            replacement,
+            capture_args,
+            capture_return,
+        })
+    }
 
-/// Op: Save Hook intercepted data to global storage (new version, used with terminate_execution)
+    /// Build the script that installs this hook, registering its callback in `state`
+    pub fn install_script(&self, state: &mut OpState) -> String {
+        let mut callback_id = None;
+        let mut replacement = "null".to_string();
+        match &self.replacement {
+            Some(HookReplacement::Js(source)) => replacement = format!("(\n{}\n)", source),
+            Some(HookReplacement::Callback(callback)) => {
+                if !state.has::<HookCallbacks>() {
+                    state.put(HookCallbacks::default());
+                }
+                let callbacks = state.borrow_mut::<HookCallbacks>();
+                callbacks.0.push(Arc::clone(callback));
+                callback_id = Some(callbacks.0.len() - 1);
+            }
+            None => {}
+        }
+        let options = serde_json::json!({
+            "action": self.action.as_str(),
+            "captureArgs": self.capture_args,
+            "captureReturn": self.capture_return,
+            "callbackId": callback_id,
+        });
+        format!(
+            "globalThis.__neverjscore_hook__({}, {}, {});",
+            JsonValue::String(self.path.clone()),
+            options,
+            replacement
+        )
+    }
+}
+
+/// Op: Call a host hook callback with JSON arguments
+///
+/// Returns `{"value": ...}` or `{"error": "..."}`; the JS wrapper throws on error.
+/// OpState is not borrowed while the callback runs.
+#[deno_core::op2]
+#[string]
+pub fn op_hook_invoke(state: Rc<RefCell<OpState>>, #[smi] callback_id: u32, #[string] args: String) -> String {
+    let callback = state
+        .borrow()
+        .try_borrow::<HookCallbacks>()
+        .and_then(|callbacks| callbacks.0.get(callback_id as usize).cloned());
+    let result = match (callback, serde_json::from_str::<Vec<JsonValue>>(&args)) {
+        (Some(callback), Ok(args)) => callback(args),
+        (None, _) => Err(format!("Unknown hook callback {}", callback_id)),
+        (_, Err(e)) => Err(format!("Invalid hook arguments: {}", e)),
+    };
+    match result {
+        Ok(value) => serde_json::json!({ "value": value }),
+        Err(error) => serde_json::json!({ "error": error }),
+    }
+    .to_string()
+}
+
+/// Op: Save Hook intercepted data for the current call (used with terminate_execution)
 ///
 /// Save data before calling op_terminate_execution.
-/// Data is saved to global static HashMap, keyed by worker_id for thread safety.
+/// Data is kept in this runtime's ResultStorage, so each Context / Worker call
+/// gets its own hook data instead of sharing a global slot.
 #[deno_core::op2]
 #[string]
 pub fn op_save_hook_data(state: &mut OpState, #[string] data: String) -> String {
-    // 尝试从 OpState 获取 WorkerId（Worker Pool 场景）
-    if let Some(worker_id) = state.try_borrow::<WorkerId>() {
-        // Worker Pool 场景：使用 worker_id 作为 key
-        save_hook_data_for_worker(worker_id.0, data.clone());
-    } else {
-        // Context 场景：使用默认的 worker_id = 0
-        save_hook_data(data.clone());
+    if let Some(storage) = state.try_borrow::<Rc<ResultStorage>>() {
+        storage.store_hook_data(data.clone());
     }
     data
 }
@@ -36,10 +225,19 @@ pub fn op_terminate_execution(state: &mut OpState) {
     }
 }
 
-// Hook extension - provides JavaScript termination and data interception
+// Hook extension - provides JavaScript termination, data interception, event streaming,
+// declarative function hooks and call tracing
 extension!(
     init_hook,
-    ops = [op_terminate_execution, op_save_hook_data]
+    ops = [
+        op_terminate_execution,
+        op_save_hook_data,
+        op_emit_event,
+        op_hook_invoke,
+        trace::op_trace_enter,
+        trace::op_trace_exit,
+        trace::op_trace_settle,
+    ]
 );
 
 impl ExtensionTrait<()> for init_hook {
@@ -57,6 +255,3 @@ pub fn get_init_js() -> &'static str {
 pub fn extensions(_options: (), is_snapshot: bool) -> Vec<Extension> {
     vec![init_hook::build((), is_snapshot)]
 }
-
-// Re-export hook utility functions for use in context.rs
-pub use crate::storage::{get_hook_data as get_hook_data_storage, clear_hook_data as clear_hook_data_storage};
@@ -0,0 +1,252 @@
+//! Function call tracing
+//!
+//! Traced functions are wrapped by `__neverjscore_trace__` (init_hook.js), which reports
+//! every call through op_trace_enter / op_trace_exit / op_trace_settle. Calls are kept
+//! in a Rust-side buffer per trace, forming a call tree: the parent of a call is the
+//! innermost traced call that was still running when it started.
+
+use deno_core::OpState;
+use serde_json::Value as JsonValue;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
+
+/// Traces started in this runtime, indexed by the traceId passed to JS
+#[derive(Default)]
+pub struct Traces(pub Vec<SharedTrace>);
+
+/// Trace buffer shared between the runtime and its Python handle
+pub type SharedTrace = Arc<Mutex<TraceBuffer>>;
+
+/// One recorded call
+#[derive(Debug, Clone)]
+pub struct TraceCall {
+    /// Target path, e.g. "CryptoJS.AES.encrypt"
+    pub name: String,
+    /// Index of the parent call in the buffer
+    pub parent: Option<usize>,
+    pub depth: usize,
+    /// Arguments (None when not captured)
+    pub args: Option<JsonValue>,
+    /// Return value, or the resolved value for Promises (None when not captured)
+    pub result: Option<JsonValue>,
+    /// Message of the thrown error / rejection reason
+    pub error: Option<String>,
+    /// Microseconds since the trace started
+    pub start_us: f64,
+    /// Time the function returned (None while still running)
+    pub end_us: Option<f64>,
+    /// Time the returned Promise settled (async calls only)
+    pub settle_us: Option<f64>,
+}
+
+/// Calls recorded for one trace
+#[derive(Debug)]
+pub struct TraceBuffer {
+    started: Instant,
+    max_events: usize,
+    active: bool,
+    calls: Vec<TraceCall>,
+    /// Calls currently on the JS stack
+    stack: Vec<usize>,
+    /// Calls not recorded because the buffer was full
+    dropped: usize,
+}
+
+impl TraceBuffer {
+    pub fn new(max_events: usize) -> Self {
+        TraceBuffer {
+            started: Instant::now(),
+            max_events,
+            active: true,
+            calls: Vec::new(),
+            stack: Vec::new(),
+            dropped: 0,
+        }
+    }
+
+    fn now_us(&self) -> f64 {
+        self.started.elapsed().as_secs_f64() * 1_000_000.0
+    }
+
+    /// Record the start of a call, returns its index (None if not recorded)
+    pub fn enter(&mut self, name: String, args: Option<JsonValue>) -> Option<usize> {
+        if !self.active {
+            return None;
+        }
+        if self.calls.len() >= self.max_events {
+            self.dropped += 1;
+            return None;
+        }
+        let id = self.calls.len();
+        self.calls.push(TraceCall {
+            name,
+            parent: self.stack.last().copied(),
+            depth: self.stack.len(),
+            args,
+            result: None,
+            error: None,
+            start_us: self.now_us(),
+            end_us: None,
+            settle_us: None,
+        });
+        self.stack.push(id);
+        Some(id)
+    }
+
+    /// Record the end of a call (also closes calls skipped by a throw)
+    pub fn exit(&mut self, id: usize, result: Option<JsonValue>, error: Option<String>) {
+        let now = self.now_us();
+        if let Some(pos) = self.stack.iter().rposition(|&open| open == id) {
+            self.stack.truncate(pos);
+        }
+        if let Some(call) = self.calls.get_mut(id) {
+            call.end_us = Some(now);
+            call.result = result;
+            call.error = error;
+        }
+    }
+
+    /// Record the settled value of a Promise returned by a call
+    pub fn settle(&mut self, id: usize, result: Option<JsonValue>, error: Option<String>) {
+        let now = self.now_us();
+        if let Some(call) = self.calls.get_mut(id) {
+            call.settle_us = Some(now);
+            call.result = result;
+            call.error = error;
+        }
+    }
+
+    /// Forget calls left open by an execution that was terminated
+    pub fn reset_stack(&mut self) {
+        self.stack.clear();
+    }
+
+    /// Stop recording (the wrappers are removed by `__neverjscore_untrace__`)
+    pub fn stop(&mut self) {
+        self.active = false;
+        self.stack.clear();
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn calls(&self) -> &[TraceCall] {
+        &self.calls
+    }
+
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Export as Chrome trace-event JSON (viewable in Perfetto / chrome://tracing)
+    ///
+    /// Each call is a complete ("X") event on one thread; unfinished calls end at the
+    /// last recorded timestamp.
+    pub fn to_chrome_trace(&self) -> JsonValue {
+        let last_us = self
+            .calls
+            .iter()
+            .filter_map(|call| call.settle_us.or(call.end_us))
+            .fold(0.0, f64::max);
+        let events: Vec<JsonValue> = self
+            .calls
+            .iter()
+            .map(|call| {
+                let mut args = serde_json::Map::new();
+                if let Some(value) = &call.args {
+                    args.insert("args".to_string(), value.clone());
+                }
+                if let Some(value) = &call.result {
+                    args.insert("return".to_string(), value.clone());
+                }
+                if let Some(error) = &call.error {
+                    args.insert("error".to_string(), JsonValue::String(error.clone()));
+                }
+                let end_us = call.end_us.unwrap_or(last_us).max(call.start_us);
+                serde_json::json!({
+                    "name": call.name,
+                    "cat": "js",
+                    "ph": "X",
+                    "ts": call.start_us,
+                    "dur": end_us - call.start_us,
+                    "pid": 1,
+                    "tid": 1,
+                    "args": args,
+                })
+            })
+            .collect();
+        serde_json::json!({ "traceEvents": events, "displayTimeUnit": "ms" })
+    }
+}
+
+/// Register a new trace, returns its id and buffer
+pub fn register(state: &mut OpState, max_events: usize) -> (usize, SharedTrace) {
+    if !state.has::<Traces>() {
+        state.put(Traces::default());
+    }
+    let traces = state.borrow_mut::<Traces>();
+    let trace = Arc::new(Mutex::new(TraceBuffer::new(max_events)));
+    traces.0.push(Arc::clone(&trace));
+    (traces.0.len() - 1, trace)
+}
+
+/// Forget open calls of every trace (after an execution ends, possibly by $terminate())
+pub fn reset_stacks(state: &mut OpState) {
+    if let Some(traces) = state.try_borrow::<Traces>() {
+        for trace in &traces.0 {
+            trace.lock().unwrap_or_else(PoisonError::into_inner).reset_stack();
+        }
+    }
+}
+
+fn with_trace<T>(state: &OpState, trace_id: u32, f: impl FnOnce(&mut TraceBuffer) -> T) -> Option<T> {
+    let trace = state.try_borrow::<Traces>()?.0.get(trace_id as usize)?;
+    Some(f(&mut trace.lock().unwrap_or_else(PoisonError::into_inner)))
+}
+
+/// Empty string means "not captured"
+fn parse_value(json: &str) -> Option<JsonValue> {
+    (!json.is_empty()).then(|| serde_json::from_str(json).unwrap_or_else(|_| JsonValue::String(json.to_string())))
+}
+
+fn parse_error(error: &str) -> Option<String> {
+    (!error.is_empty()).then(|| error.to_string())
+}
+
+/// Op: Record the start of a traced call, returns the call id (-1 when not recorded)
+#[deno_core::op2(fast)]
+#[smi]
+pub fn op_trace_enter(state: &mut OpState, #[smi] trace_id: u32, #[string] name: String, #[string] args: String) -> i32 {
+    with_trace(state, trace_id, |trace| trace.enter(name, parse_value(&args)))
+        .flatten()
+        .map_or(-1, |id| id as i32)
+}
+
+/// Op: Record the end of a traced call
+#[deno_core::op2(fast)]
+pub fn op_trace_exit(
+    state: &mut OpState,
+    #[smi] trace_id: u32,
+    #[smi] call_id: u32,
+    #[string] result: String,
+    #[string] error: String,
+) {
+    with_trace(state, trace_id, |trace| {
+        trace.exit(call_id as usize, parse_value(&result), parse_error(&error))
+    });
+}
+
+/// Op: Record the settled value of a Promise returned by a traced call
+#[deno_core::op2(fast)]
+pub fn op_trace_settle(
+    state: &mut OpState,
+    #[smi] trace_id: u32,
+    #[smi] call_id: u32,
+    #[string] result: String,
+    #[string] error: String,
+) {
+    with_trace(state, trace_id, |trace| {
+        trace.settle(call_id as usize, parse_value(&result), parse_error(&error))
+    });
+}
@@ -11,6 +11,8 @@ use deno_permissions::PermissionsContainer;
 pub mod core;
 pub mod hook;
 pub mod random;
+pub mod deterministic;
+pub mod event_loop;
 pub mod xhr;
 pub mod protection;
 
@@ -79,6 +81,9 @@ pub struct ExtensionOptions {
     /// Random seed for deterministic random numbers
     pub random_seed: Option<u64>,
 
+    /// Deterministic mode: pin Date, performance.now, timer ordering, etc.
+    pub deterministic: bool,
+
     /// Enable extensions (polyfills/deno_web)
     pub enable_extensions: bool,
 
@@ -106,6 +111,7 @@ impl ExtensionOptions {
         Self {
             enable_logging: false,
             random_seed: None,
+            deterministic: false,
             enable_extensions: true,
             storage,
             #[cfg(feature = "deno_web_api")]
@@ -139,6 +145,12 @@ impl ExtensionOptions {
         self
     }
 
+    /// Enable deterministic execution mode
+    pub fn with_deterministic(mut self, enable: bool) -> Self {
+        self.deterministic = enable;
+        self
+    }
+
     /// Enable or disable extensions
     pub fn with_extensions(mut self, enable: bool) -> Self {
         self.enable_extensions = enable;
@@ -248,6 +260,12 @@ pub(crate) fn all_extensions(options: ExtensionOptions, is_snapshot: bool) -> Ve
     // Random extension for seedable Math.random()
     extensions.extend(random::extensions(&options, is_snapshot));
 
+    // Deterministic extension (virtual clock for Date/performance/timers)
+    extensions.extend(deterministic::extensions(&options, is_snapshot));
+
+    // Event loop introspection (pending timers/Promises report)
+    extensions.extend(event_loop::extensions(&options, is_snapshot));
+
     // Canvas 2D extension (if enabled)
     #[cfg(feature = "canvas")]
     {
@@ -0,0 +1,55 @@
+// Native-looking functions for never-jscore
+// Loaded first in every runtime (Context and JSEngine workers). Functions
+// registered here print as "function name() { [native code] }" through
+// Function.prototype.toString without getting an own toString property.
+// This is the only native-function registry: hooks, deterministic mode and
+// init_protection.js all register through __NEVER_JSCORE_NATIVE__.
+
+(() => {
+    'use strict';
+
+    const originalFunctionToString = Function.prototype.toString;
+    const originalDefineProperty = Object.defineProperty;
+    const nativeFunctions = new WeakSet();
+
+    const toString = {
+        toString() {
+            if (nativeFunctions.has(this)) {
+                return `function ${this.name || ''}() { [native code] }`;
+            }
+            return originalFunctionToString.call(this);
+        }
+    }.toString;
+    nativeFunctions.add(toString);
+    originalDefineProperty(Function.prototype, 'toString', {
+        value: toString,
+        writable: true,
+        enumerable: false,
+        configurable: true
+    });
+
+    /**
+     * Register fn as native; name (if given) replaces fn.name
+     */
+    function register(fn, name) {
+        if (typeof fn !== 'function') {
+            return fn;
+        }
+        if (name !== undefined) {
+            try {
+                originalDefineProperty(fn, 'name', { value: String(name), configurable: true });
+            } catch (e) {
+                // Non-configurable name, keep it
+            }
+        }
+        nativeFunctions.add(fn);
+        return fn;
+    }
+
+    originalDefineProperty(globalThis, '__NEVER_JSCORE_NATIVE__', {
+        value: Object.freeze({ register }),
+        writable: false,
+        enumerable: false,
+        configurable: false
+    });
+})();
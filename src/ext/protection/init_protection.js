@@ -10,11 +10,8 @@
     // ========================================================================
     const OriginalFunction = Function;
     const originalFunctionToString = Function.prototype.toString;
-    const originalObjectKeys = Object.keys;
     const originalGetOwnPropertyNames = Object.getOwnPropertyNames;
     const originalGetOwnPropertyDescriptor = Object.getOwnPropertyDescriptor;
-    const originalGetOwnPropertyDescriptors = Object.getOwnPropertyDescriptors;
-    const originalReflectOwnKeys = Reflect.ownKeys;
     const originalGetPrototypeOf = Object.getPrototypeOf;
     const originalDefineProperty = Object.defineProperty;
 
@@ -89,19 +86,17 @@
     }
 
     // ========================================================================
-    // Step 4: Native code protection - Unified implementation
+    // Step 4: Native code protection - registered in init_native.js
     // ========================================================================
-    const protectedFunctions = new WeakSet();
+    const nativeRegistry = globalThis.__NEVER_JSCORE_NATIVE__;
     const originalFunctions = new WeakMap(); // Store original for internal use
-    const nativeCodeString = (name) => `function ${name || ''}() { [native code] }`;
 
     /**
      * Make a function appear as native code with optional logging
-     * Uses defineProperty approach (more reliable than Proxy)
+     * Registered in init_native.js: no own toString property is added
      */
     function makeNative(fn, name, enableLogging = false) {
         if (!fn || typeof fn !== 'function') return fn;
-        if (protectedFunctions.has(fn)) return fn;
 
         const targetName = name || fn.name || 'anonymous';
         let wrappedFn = fn;
@@ -128,31 +123,7 @@
             } catch (e) {}
         }
 
-        try {
-            // Override toString
-            originalDefineProperty(wrappedFn, 'toString', {
-                value: function() {
-                    return nativeCodeString(targetName);
-                },
-                writable: false,
-                enumerable: false,
-                configurable: true
-            });
-
-            // Fix name
-            originalDefineProperty(wrappedFn, 'name', {
-                value: targetName,
-                writable: false,
-                enumerable: false,
-                configurable: true
-            });
-
-            protectedFunctions.add(wrappedFn);
-        } catch (e) {
-            // Some built-in functions can't be modified
-        }
-
-        return wrappedFn;
+        return nativeRegistry.register(wrappedFn, targetName);
     }
 
     /**
@@ -214,19 +185,6 @@
         return ClassConstructor;
     }
 
-    // ========================================================================
-    // Step 4: Override Function.prototype.toString globally
-    // ========================================================================
-    Function.prototype.toString = function() {
-        // If this function is protected, return native code string
-        if (protectedFunctions.has(this)) {
-            return nativeCodeString(this.name);
-        }
-        // Otherwise use original toString
-        return originalFunctionToString.call(this);
-    };
-    protectedFunctions.add(Function.prototype.toString);
-
     // ========================================================================
     // Step 5: Protect all Web APIs
     // ========================================================================
@@ -300,6 +258,7 @@
         'structuredClone',
         '$return', '$exit', '$terminate', '$storeResult',
         '__neverjscore_return__', '__saveAndTerminate__', '__getDeno',
+        '$emit',
     ];
 
     // Protect classes
@@ -396,54 +355,76 @@
     const hiddenGlobalProps = [
         // Deno internals
         'Deno', '__deno_core__', '__deno_internal__', '__NEVER_JSCORE_LOGGING__',
+        '__NEVER_JSCORE_EVENT_LOOP__', '__NEVER_JSCORE_NATIVE__', '__NEVER_JSCORE_PROTECT_REALM__',
+        '__neverjscore_hook__', '__neverjscore_trace__', '__neverjscore_untrace__',
+        '__neverjscore_script_begin__', '__neverjscore_script_end__',
         // Node.js globals that betray a non-browser environment
         'global', 'Buffer', 'require', 'module', '__dirname', '__filename',
         'setImmediate', 'clearImmediate', 'process',
     ];
 
-    // Object.keys - hide Deno-related properties
-    Object.keys = function(obj) {
-        const keys = originalObjectKeys(obj);
-        if (obj === globalThis) {
-            return keys.filter(key => !hiddenGlobalProps.includes(key));
-        }
-        return keys;
-    };
-    makeNative(Object.keys, 'keys');
+    /**
+     * Filter hidden names out of the global object's reflection results.
+     * Self-contained (free names only) so protectRealm can re-evaluate it
+     * inside a realm against that realm's own Object / Reflect / globalThis.
+     */
+    function installGlobalFilters(hidden, register) {
+        const objectKeys = Object.keys;
+        const getOwnPropertyNames = Object.getOwnPropertyNames;
+        const getOwnPropertyDescriptors = Object.getOwnPropertyDescriptors;
+        const reflectOwnKeys = Reflect.ownKeys;
+        const isHidden = (key) => typeof key === 'string' && hidden.includes(key);
+
+        const objectFilters = {
+            keys(obj) {
+                const keys = objectKeys(obj);
+                return obj === globalThis ? keys.filter((key) => !isHidden(key)) : keys;
+            },
+            getOwnPropertyNames(obj) {
+                const names = getOwnPropertyNames(obj);
+                return obj === globalThis ? names.filter((name) => !isHidden(name)) : names;
+            },
+            getOwnPropertyDescriptors(obj) {
+                const descriptors = getOwnPropertyDescriptors(obj);
+                if (obj === globalThis) {
+                    for (const prop of hidden) {
+                        delete descriptors[prop];
+                    }
+                }
+                return descriptors;
+            },
+        };
+        const reflectFilters = {
+            ownKeys(obj) {
+                const keys = reflectOwnKeys(obj);
+                return obj === globalThis ? keys.filter((key) => !isHidden(key)) : keys;
+            },
+        };
 
-    // Object.getOwnPropertyNames - hide Deno-related properties
-    Object.getOwnPropertyNames = function(obj) {
-        const names = originalGetOwnPropertyNames(obj);
-        if (obj === globalThis) {
-            return names.filter(name => !hiddenGlobalProps.includes(name));
-        }
-        return names;
-    };
-    makeNative(Object.getOwnPropertyNames, 'getOwnPropertyNames');
-
-    // Object.getOwnPropertyDescriptors - hide Deno-related properties
-    Object.getOwnPropertyDescriptors = function(obj) {
-        const descriptors = originalGetOwnPropertyDescriptors(obj);
-        if (obj === globalThis) {
-            for (const prop of hiddenGlobalProps) {
-                delete descriptors[prop];
+        for (const [holder, filters] of [[Object, objectFilters], [Reflect, reflectFilters]]) {
+            for (const name of objectKeys(filters)) {
+                holder[name] = register(filters[name], name);
+                Object.freeze(holder[name]);
             }
         }
-        return descriptors;
-    };
-    makeNative(Object.getOwnPropertyDescriptors, 'getOwnPropertyDescriptors');
-
-    // Reflect.ownKeys - hide Deno-related properties
-    Reflect.ownKeys = function(obj) {
-        const keys = originalReflectOwnKeys(obj);
-        if (obj === globalThis) {
-            return keys.filter(key =>
-                typeof key !== 'string' || !hiddenGlobalProps.includes(key)
-            );
-        }
-        return keys;
-    };
-    makeNative(Reflect.ownKeys, 'ownKeys');
+    }
+
+    installGlobalFilters(hiddenGlobalProps, (fn, name) => makeNative(fn, name));
+
+    /**
+     * Apply the same filters to a realm created by ctx.create_realm()
+     * (called from realm.js with the realm's global object)
+     */
+    function protectRealm(target) {
+        const install = target.eval('(' + originalFunctionToString.call(installGlobalFilters) + ')');
+        install(hiddenGlobalProps, nativeRegistry.register);
+    }
+    originalDefineProperty(globalThis, '__NEVER_JSCORE_PROTECT_REALM__', {
+        value: makeNative(protectRealm, 'protectRealm'),
+        writable: false,
+        enumerable: false,
+        configurable: false
+    });
 
     // ========================================================================
     // Step 7: Error.stack cleanup
@@ -539,10 +520,7 @@
     // ========================================================================
     // Step 9: Freeze critical protections
     // ========================================================================
-    Object.freeze(Object.keys);
-    Object.freeze(Object.getOwnPropertyNames);
-    Object.freeze(Object.getOwnPropertyDescriptors);
-    Object.freeze(Reflect.ownKeys);
+    // (the reflection filters are frozen by installGlobalFilters)
     Object.freeze(Function.prototype.toString);
 
 })();
@@ -12,6 +12,14 @@ pub fn get_init_js() -> &'static str {
     include_str!("init_protection.js")
 }
 
+/// Get the native-function registry JavaScript
+///
+/// Loaded before every other extension script, in Context and in JSEngine workers
+/// (which do not load the full protection script)
+pub fn get_native_js() -> &'static str {
+    include_str!("init_native.js")
+}
+
 /// Create browser protection extensions
 pub fn extensions(_options: &crate::ext::ExtensionOptions, _is_snapshot: bool) -> Vec<Extension> {
     vec![]
@@ -15,4 +15,30 @@
             }
         };
     }
+
+    // Route crypto.getRandomValues() / randomUUID() through the same RngState,
+    // so ctx.set_random_seed() and per-call seeds apply to them as well.
+    // The seed is checked on every call: unseeded contexts keep the original behaviour.
+    if (typeof crypto !== 'undefined' && typeof Deno !== 'undefined' && Deno.core && Deno.core.ops.op_crypto_fill_seeded) {
+        const ops = Deno.core.ops;
+
+        if (typeof crypto.getRandomValues === 'function') {
+            const originalGetRandomValues = crypto.getRandomValues;
+
+            crypto.getRandomValues = function getRandomValues(array) {
+                // The original performs type and quota (65536 bytes) validation
+                const result = originalGetRandomValues.call(crypto, array);
+                ops.op_crypto_fill_seeded(new Uint8Array(array.buffer, array.byteOffset, array.byteLength));
+                return result;
+            };
+        }
+
+        if (typeof crypto.randomUUID === 'function') {
+            const originalRandomUUID = crypto.randomUUID;
+
+            crypto.randomUUID = function randomUUID() {
+                return ops.op_crypto_seeded_uuid() || originalRandomUUID.call(crypto);
+            };
+        }
+    }
 })();
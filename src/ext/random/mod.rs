@@ -1,17 +1,46 @@
 // Random extension for seedable random number generation
 // Provides op_crypto_random for overriding Math.random()
+// and seeded fills for crypto.getRandomValues() / crypto.randomUUID()
 
 use deno_core::{extension, Extension, OpState};
-use rand::{Rng, SeedableRng};
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
 use std::cell::RefCell;
 use std::rc::Rc;
 
+pub mod xorshift;
+
+pub use xorshift::V8MathRandom;
+
+/// Stream id used by crypto.getRandomValues() / randomUUID()
+///
+/// Math.random() uses stream 0, which produces exactly the same sequence as
+/// `StdRng::seed_from_u64(seed)`, so existing seeded scripts are unaffected.
+const CRYPTO_STREAM: u64 = 1;
+
 /// RNG state that can be seeded
 pub struct RngState {
     /// Current seed (if set)
     pub seed: Option<u64>,
-    /// Seeded RNG instance
-    pub seeded_rng: Option<rand::rngs::StdRng>,
+    /// Seeded RNG instance for Math.random()
+    pub seeded_rng: Option<ChaCha12Rng>,
+    /// Seeded RNG instance for crypto.getRandomValues() / randomUUID()
+    pub crypto_rng: Option<ChaCha12Rng>,
+    /// V8 xorshift128+ emulation for Math.random() (takes precedence when set)
+    pub v8_rng: Option<V8MathRandom>,
+}
+
+/// Serializable position of a seeded RngState
+///
+/// ChaCha is a counter-based generator, so seed + word position fully
+/// describes the state and can be restored in O(1).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RngSnapshot {
+    pub seed: Option<u64>,
+    pub math_pos: u128,
+    pub crypto_pos: u128,
+    /// V8 Math.random() state (state0, state1, index) when V8 mode is active
+    pub v8_state: Option<(u64, u64, usize)>,
 }
 
 impl Default for RngState {
@@ -19,29 +48,179 @@ impl Default for RngState {
         Self {
             seed: None,
             seeded_rng: None,
+            crypto_rng: None,
+            v8_rng: None,
         }
     }
 }
 
 impl RngState {
     pub fn new(seed: Option<u64>) -> Self {
-        if let Some(s) = seed {
-            Self {
-                seed: Some(s),
-                seeded_rng: Some(rand::rngs::StdRng::seed_from_u64(s)),
-            }
-        } else {
-            Self::default()
+        let mut state = Self::default();
+        state.reseed(seed);
+        state
+    }
+
+    /// Reseed both streams (None switches back to thread_rng)
+    ///
+    /// Also leaves V8 Math.random() mode.
+    pub fn reseed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+        self.v8_rng = None;
+        self.seeded_rng = seed.map(ChaCha12Rng::seed_from_u64);
+        self.crypto_rng = seed.map(|s| {
+            let mut rng = ChaCha12Rng::seed_from_u64(s);
+            rng.set_stream(CRYPTO_STREAM);
+            rng
+        });
+    }
+
+    /// Capture the current position of both streams
+    pub fn snapshot(&self) -> RngSnapshot {
+        RngSnapshot {
+            seed: self.seed,
+            math_pos: self.seeded_rng.as_ref().map_or(0, |r| r.get_word_pos()),
+            crypto_pos: self.crypto_rng.as_ref().map_or(0, |r| r.get_word_pos()),
+            v8_state: self.v8_rng.as_ref().map(|r| r.state()),
+        }
+    }
+
+    /// Restore a previously captured position
+    pub fn restore(&mut self, snapshot: &RngSnapshot) {
+        self.reseed(snapshot.seed);
+        if let Some(ref mut rng) = self.seeded_rng {
+            rng.set_word_pos(snapshot.math_pos);
+        }
+        if let Some(ref mut rng) = self.crypto_rng {
+            rng.set_word_pos(snapshot.crypto_pos);
+        }
+        self.v8_rng = snapshot
+            .v8_state
+            .and_then(|(s0, s1, index)| V8MathRandom::from_state(s0, s1, index).ok());
+    }
+}
+
+/// Reseed the RNG stored in OpState
+///
+/// Also reseeds deno_crypto's internal `StdRng`, which backs the native
+/// key generation paths that are not routed through our ops.
+pub fn reseed(state: &mut OpState, seed: Option<u64>) {
+    if let Some(rng_state) = state.try_borrow::<Rc<RefCell<RngState>>>() {
+        rng_state.borrow_mut().reseed(seed);
+    }
+    reseed_deno_crypto(state, seed);
+}
+
+/// Switch Math.random() to V8 xorshift128+ emulation
+///
+/// crypto.getRandomValues() / randomUUID() keep their current stream.
+pub fn set_v8_rng(state: &mut OpState, rng: V8MathRandom) {
+    if let Some(rng_state) = state.try_borrow::<Rc<RefCell<RngState>>>() {
+        rng_state.borrow_mut().v8_rng = Some(rng);
+    }
+}
+
+/// Capture the RNG position stored in OpState
+pub fn snapshot(state: &OpState) -> RngSnapshot {
+    state
+        .try_borrow::<Rc<RefCell<RngState>>>()
+        .map(|rng_state| rng_state.borrow().snapshot())
+        .unwrap_or_default()
+}
+
+/// Restore the RNG position stored in OpState
+///
+/// deno_crypto's internal `StdRng` does not expose its position. It keeps
+/// running from where it is when the seed is unchanged, and is only
+/// reseeded when `snapshot.seed` switches to a different seed.
+pub fn restore(state: &mut OpState, snapshot: &RngSnapshot) {
+    let previous_seed = snapshot_seed(state);
+    if let Some(rng_state) = state.try_borrow::<Rc<RefCell<RngState>>>() {
+        rng_state.borrow_mut().restore(snapshot);
+    }
+    if previous_seed != snapshot.seed {
+        reseed_deno_crypto(state, snapshot.seed);
+    }
+}
+
+fn snapshot_seed(state: &OpState) -> Option<u64> {
+    state
+        .try_borrow::<Rc<RefCell<RngState>>>()
+        .and_then(|rng_state| rng_state.borrow().seed)
+}
+
+/// RNG state put aside while a task runs with its own seed
+pub struct SavedRng {
+    snapshot: RngSnapshot,
+    /// deno_crypto's `StdRng` instance, put back as-is afterwards
+    deno_crypto: Option<rand::rngs::StdRng>,
+}
+
+/// Switch to a per-call seed, returning the state to put back with `end_call_seed`
+pub fn begin_call_seed(state: &mut OpState, seed: u64) -> SavedRng {
+    let saved = SavedRng {
+        snapshot: snapshot(state),
+        deno_crypto: take_deno_crypto(state),
+    };
+    reseed(state, Some(seed));
+    saved
+}
+
+/// Put back the RNG state saved by `begin_call_seed`
+///
+/// deno_crypto continues from the saved instance instead of restarting from
+/// the worker seed, so native key generation never repeats across calls.
+pub fn end_call_seed(state: &mut OpState, saved: SavedRng) {
+    if let Some(rng_state) = state.try_borrow::<Rc<RefCell<RngState>>>() {
+        rng_state.borrow_mut().restore(&saved.snapshot);
+    }
+    take_deno_crypto(state);
+    if let Some(rng) = saved.deno_crypto {
+        put_deno_crypto(state, rng);
+    }
+}
+
+#[cfg(feature = "deno_web_api")]
+fn reseed_deno_crypto(state: &mut OpState, seed: Option<u64>) {
+    match seed {
+        Some(s) => state.put(rand::rngs::StdRng::seed_from_u64(s)),
+        None => {
+            state.try_take::<rand::rngs::StdRng>();
         }
     }
 }
 
+#[cfg(feature = "deno_web_api")]
+fn take_deno_crypto(state: &mut OpState) -> Option<rand::rngs::StdRng> {
+    state.try_take::<rand::rngs::StdRng>()
+}
+
+#[cfg(feature = "deno_web_api")]
+fn put_deno_crypto(state: &mut OpState, rng: rand::rngs::StdRng) {
+    state.put(rng);
+}
+
+#[cfg(not(feature = "deno_web_api"))]
+fn reseed_deno_crypto(_state: &mut OpState, _seed: Option<u64>) {}
+
+#[cfg(not(feature = "deno_web_api"))]
+fn take_deno_crypto(_state: &mut OpState) -> Option<rand::rngs::StdRng> {
+    None
+}
+
+#[cfg(not(feature = "deno_web_api"))]
+fn put_deno_crypto(_state: &mut OpState, _rng: rand::rngs::StdRng) {}
+
 #[deno_core::op2(fast)]
 /// Generate a random number (0.0 to 1.0) using seeded RNG if available
 /// This is used to override Math.random() via JavaScript
 pub fn op_crypto_random(state: &mut OpState) -> f64 {
     if let Some(rng_state) = state.try_borrow_mut::<Rc<RefCell<RngState>>>() {
         let mut rng = rng_state.borrow_mut();
+        if let Some(ref mut v8_rng) = rng.v8_rng {
+            // Reproduce V8's own sequence (browser-compatible)
+            return v8_rng.next_f64();
+        }
         if let Some(ref mut seeded_rng) = rng.seeded_rng {
             // Use seeded RNG for deterministic randomness
             return seeded_rng.gen::<f64>();
@@ -52,10 +231,53 @@ pub fn op_crypto_random(state: &mut OpState) -> f64 {
     rand::thread_rng().gen::<f64>()
 }
 
+#[deno_core::op2(fast)]
+/// Fill a buffer from the seeded crypto stream
+/// Returns false when no seed is set, so JS keeps the original implementation
+pub fn op_crypto_fill_seeded(state: &mut OpState, #[buffer] buf: &mut [u8]) -> bool {
+    if let Some(rng_state) = state.try_borrow_mut::<Rc<RefCell<RngState>>>() {
+        let mut rng = rng_state.borrow_mut();
+        if let Some(ref mut crypto_rng) = rng.crypto_rng {
+            crypto_rng.fill_bytes(buf);
+            return true;
+        }
+    }
+    false
+}
+
+#[deno_core::op2]
+#[string]
+/// Generate a v4 UUID from the seeded crypto stream
+/// Returns an empty string when no seed is set
+pub fn op_crypto_seeded_uuid(state: &mut OpState) -> String {
+    let Some(rng_state) = state.try_borrow_mut::<Rc<RefCell<RngState>>>() else {
+        return String::new();
+    };
+    let mut rng = rng_state.borrow_mut();
+    let Some(ref mut crypto_rng) = rng.crypto_rng else {
+        return String::new();
+    };
+
+    let mut bytes = [0u8; 16];
+    crypto_rng.fill_bytes(&mut bytes);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40; // version 4
+    bytes[8] = (bytes[8] & 0x3f) | 0x80; // variant 10
+
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
 // Random extension definition using deno_core::extension! macro
 extension!(
     never_jscore_random,
-    ops = [op_crypto_random],
+    ops = [op_crypto_random, op_crypto_fill_seeded, op_crypto_seeded_uuid],
     options = {
         seed: Option<u64>,
     },
@@ -0,0 +1,251 @@
+// V8 Math.random() emulation (xorshift128+ with a 64-entry cache)
+//
+// Reproduces V8's MathRandom::RefillCache exactly:
+// - the cache is refilled with 64 values in generation order,
+// - Math.random() consumes it from the end (cache[--index]),
+// - the internal state (s0, s1) is the state after the last refill.
+// Given the same (s0, s1, index) the output matches Chrome/Node bit for bit.
+
+/// V8 kCacheSize
+pub const CACHE_SIZE: usize = 64;
+
+/// Number of mantissa bits revealed by each Math.random() output
+const MANTISSA_BITS: u32 = 52;
+
+/// V8 base::RandomNumberGenerator::XorShift128
+#[inline]
+fn xorshift128(state0: &mut u64, state1: &mut u64) {
+    let mut s1 = *state0;
+    let s0 = *state1;
+    *state0 = s0;
+    s1 ^= s1 << 23;
+    s1 ^= s1 >> 17;
+    s1 ^= s0;
+    s1 ^= s0 >> 26;
+    *state1 = s1;
+}
+
+/// V8 base::RandomNumberGenerator::ToDouble
+#[inline]
+fn to_double(state0: u64) -> f64 {
+    f64::from_bits((state0 >> 12) | 0x3FF0_0000_0000_0000) - 1.0
+}
+
+/// Inverse of `to_double`: the 52 high bits of state0
+#[inline]
+fn mantissa(value: f64) -> u64 {
+    (value + 1.0).to_bits() & ((1u64 << MANTISSA_BITS) - 1)
+}
+
+/// V8 base::RandomNumberGenerator::MurmurHash3 (fmix64)
+#[inline]
+fn murmur_hash3(mut h: u64) -> u64 {
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xC4CE_B9FE_1A85_EC53);
+    h ^= h >> 33;
+    h
+}
+
+/// Given g[n] and g[n+1] of the state0 sequence, return g[n-1]
+///
+/// g[n+1] = L(g[n-1]) ^ g[n] ^ (g[n] >> 26), L(x) = t ^ (t >> 17), t = x ^ (x << 23)
+#[inline]
+fn step_back(g_n: u64, g_next: u64) -> u64 {
+    let y = g_next ^ g_n ^ (g_n >> 26);
+    let t = y ^ (y >> 17) ^ (y >> 34) ^ (y >> 51);
+    t ^ (t << 23) ^ (t << 46)
+}
+
+/// Emulated V8 MathRandom state
+#[derive(Clone)]
+pub struct V8MathRandom {
+    state0: u64,
+    state1: u64,
+    cache: [f64; CACHE_SIZE],
+    index: usize,
+}
+
+impl V8MathRandom {
+    /// Create from V8's internal state
+    ///
+    /// `(state0, state1)` is the state stored after the last refill and `index`
+    /// is `math_random_index` (number of cached values not yet consumed).
+    /// With `index == 0` the next call refills the cache, as in a fresh isolate.
+    pub fn from_state(state0: u64, state1: u64, index: usize) -> Result<Self, String> {
+        if state0 == 0 && state1 == 0 {
+            return Err("xorshift128+ state must not be all zero".to_string());
+        }
+        if index > CACHE_SIZE {
+            return Err(format!("index must be in 0..={}", CACHE_SIZE));
+        }
+
+        let mut rng = Self {
+            state0,
+            state1,
+            cache: [0.0; CACHE_SIZE],
+            index,
+        };
+
+        // cache[63] was generated with state0 == s0; walk backwards for the rest
+        let (mut g, mut g_next) = (state0, state1);
+        for i in (0..CACHE_SIZE).rev() {
+            rng.cache[i] = to_double(g);
+            let prev = step_back(g, g_next);
+            g_next = g;
+            g = prev;
+        }
+
+        Ok(rng)
+    }
+
+    /// Seed the same way as V8's `--random-seed` flag
+    pub fn from_seed(seed: i64) -> Self {
+        let state0 = murmur_hash3(seed as u64);
+        let state1 = murmur_hash3(!(seed as u64));
+        // MurmurHash3 is a bijection, so both words cannot be zero
+        Self::from_state(state0, state1, 0).expect("murmur state is never zero")
+    }
+
+    /// Current V8 internal state: (state0, state1, index)
+    pub fn state(&self) -> (u64, u64, usize) {
+        (self.state0, self.state1, self.index)
+    }
+
+    /// Math.random()
+    pub fn next_f64(&mut self) -> f64 {
+        if self.index == 0 {
+            self.refill();
+        }
+        self.index -= 1;
+        self.cache[self.index]
+    }
+
+    /// V8 MathRandom::RefillCache
+    fn refill(&mut self) {
+        for i in 0..CACHE_SIZE {
+            xorshift128(&mut self.state0, &mut self.state1);
+            self.cache[i] = to_double(self.state0);
+        }
+        self.index = CACHE_SIZE;
+    }
+
+    /// Recover the internal state from consecutive Math.random() outputs
+    ///
+    /// `outputs` are in the order they were returned by Math.random().
+    /// `cache_offset` is how many values of the current cache block were
+    /// consumed before `outputs[0]` (0 when observing a fresh isolate).
+    /// All outputs must come from the same cache block.
+    ///
+    /// Every output reveals the 52 high bits of state0; the recurrence is
+    /// linear over GF(2), so the state is solved by Gaussian elimination.
+    pub fn recover(outputs: &[f64], cache_offset: usize) -> Result<Self, String> {
+        let k = outputs.len();
+        if k < 3 {
+            return Err("at least 3 outputs are required to recover the state".to_string());
+        }
+        if cache_offset + k > CACHE_SIZE {
+            return Err(format!(
+                "cache_offset + len(outputs) must not exceed {} (outputs cannot span a cache refill)",
+                CACHE_SIZE
+            ));
+        }
+        if let Some(v) = outputs.iter().find(|v| !(0.0..1.0).contains(*v)) {
+            return Err(format!("{} is not a Math.random() output", v));
+        }
+
+        // Generation order is the reverse of consumption order within a block.
+        // Unknowns: g0 (bits 0..64) and g1 (bits 64..128) of the earliest generated value.
+        let mut g_prev: [u128; 64] = std::array::from_fn(|i| 1u128 << i);
+        let mut g_cur: [u128; 64] = std::array::from_fn(|i| 1u128 << (64 + i));
+
+        let mut rows: Vec<(u128, bool)> = Vec::with_capacity(k * MANTISSA_BITS as usize);
+        for (j, value) in outputs.iter().rev().enumerate() {
+            let sym = match j {
+                0 => g_prev,
+                1 => g_cur,
+                _ => {
+                    let next = sym_step(&g_prev, &g_cur);
+                    g_prev = g_cur;
+                    g_cur = next;
+                    g_cur
+                }
+            };
+            let m = mantissa(*value);
+            for bit in 0..MANTISSA_BITS as usize {
+                rows.push((sym[bit + 12], (m >> bit) & 1 == 1));
+            }
+        }
+
+        let solution = solve_gf2(rows)?;
+        let g0 = solution as u64;
+        let g1 = (solution >> 64) as u64;
+
+        // g0 sits at generation index `base`; walk forward to the end of the block
+        let base = CACHE_SIZE - cache_offset - k;
+        let (mut g, mut g_next) = (g0, g1);
+        for _ in base..CACHE_SIZE - 1 {
+            xorshift128(&mut g, &mut g_next);
+        }
+
+        Self::from_state(g, g_next, base)
+    }
+}
+
+/// Symbolic xorshift step: g[n+2] from g[n], g[n+1]
+fn sym_step(g_n: &[u128; 64], g_next: &[u128; 64]) -> [u128; 64] {
+    let shl = |x: &[u128; 64], n: usize| -> [u128; 64] {
+        std::array::from_fn(|i| if i >= n { x[i - n] } else { 0 })
+    };
+    let shr = |x: &[u128; 64], n: usize| -> [u128; 64] {
+        std::array::from_fn(|i| if i + n < 64 { x[i + n] } else { 0 })
+    };
+    let xor = |a: [u128; 64], b: [u128; 64]| -> [u128; 64] { std::array::from_fn(|i| a[i] ^ b[i]) };
+
+    let mut s1 = xor(*g_n, shl(g_n, 23));
+    s1 = xor(s1, shr(&s1, 17));
+    s1 = xor(s1, *g_next);
+    xor(s1, shr(g_next, 26))
+}
+
+/// Solve a linear system over GF(2) with 128 unknowns
+fn solve_gf2(mut rows: Vec<(u128, bool)>) -> Result<u128, String> {
+    let mut pivots: Vec<(usize, u128, bool)> = Vec::with_capacity(128);
+
+    for col in 0..128 {
+        let bit = 1u128 << col;
+        let Some(pos) = rows.iter().position(|(mask, _)| mask & bit != 0) else {
+            continue;
+        };
+        let (p_mask, p_rhs) = rows.swap_remove(pos);
+        for row in rows.iter_mut() {
+            if row.0 & bit != 0 {
+                row.0 ^= p_mask;
+                row.1 ^= p_rhs;
+            }
+        }
+        for pivot in pivots.iter_mut() {
+            if pivot.1 & bit != 0 {
+                pivot.1 ^= p_mask;
+                pivot.2 ^= p_rhs;
+            }
+        }
+        pivots.push((col, p_mask, p_rhs));
+    }
+
+    if rows.iter().any(|(mask, rhs)| *mask == 0 && *rhs) {
+        return Err(
+            "outputs are inconsistent: not consecutive V8 Math.random() values from one cache block"
+                .to_string(),
+        );
+    }
+    if pivots.len() < 128 {
+        return Err("not enough outputs to determine the state, provide more values".to_string());
+    }
+
+    Ok(pivots
+        .iter()
+        .filter(|(_, _, rhs)| *rhs)
+        .fold(0u128, |acc, (col, _, _)| acc | (1u128 << col)))
+}
@@ -0,0 +1,335 @@
+//! JSFuture - 可取消的 JSEngine 任务句柄
+//!
+//! `engine.submit()` 提交任务后立即返回 JSFuture，接口与 concurrent.futures.Future 一致：
+//! result(timeout)、exception(timeout)、cancel()、cancelled()、running()、done()、
+//! add_done_callback(fn)。也可以直接 await（asyncio 协程被取消时任务随之取消）。
+//!
+//! Worker返回的结果由后台完成线程接收，写入 JSFuture 后唤醒等待方并调用回调，
+//! 不需要为每个 JSFuture 占用一个线程。
+//! 任务执行期间 $emit() 发送的事件在结果之前写入，完成后可以通过 events() 获取。
+
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+use crate::convert::json_to_python;
+use crate::errors::task_error_to_py;
+use crate::worker_pool::{CancelToken, EventSink, TaskResult, TASK_CANCELLED_ERROR};
+
+/// 完成线程的 Tokio Runtime：等待Worker返回结果并写入 JSFuture
+static COMPLETION_RUNTIME: Lazy<tokio::runtime::Handle> = Lazy::new(|| {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to create tokio runtime for JSFuture");
+    let handle = rt.handle().clone();
+    std::thread::Builder::new()
+        .name("jscore_futures".to_string())
+        .spawn(move || rt.block_on(std::future::pending::<()>()))
+        .expect("Failed to spawn JSFuture completion thread");
+    handle
+});
+
+/// 收集的 $emit() 事件转换为Python列表
+pub(crate) fn events_to_python<'py>(py: Python<'py>, sink: &EventSink) -> PyResult<Bound<'py, PyList>> {
+    let list = PyList::empty(py);
+    for event in sink.lock().unwrap_or_else(PoisonError::into_inner).iter() {
+        list.append(json_to_python(py, event)?)?;
+    }
+    Ok(list)
+}
+
+/// 任务状态
+enum Status {
+    Pending,
+    Done(TaskResult),
+    Cancelled,
+}
+
+struct Inner {
+    status: Status,
+    /// 完成时调用的回调及传给回调的 JSFuture 对象
+    callbacks: Vec<(Py<PyAny>, Py<PyAny>)>,
+}
+
+struct FutureState {
+    inner: Mutex<Inner>,
+    done: Condvar,
+    token: Arc<CancelToken>,
+    events: EventSink,
+}
+
+impl FutureState {
+    /// 设置最终状态并唤醒等待方，已完成时返回 false
+    fn complete(&self, status: Status) -> bool {
+        let callbacks = {
+            let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+            if !matches!(inner.status, Status::Pending) {
+                return false;
+            }
+            inner.status = status;
+            std::mem::take(&mut inner.callbacks)
+        };
+        self.done.notify_all();
+
+        if !callbacks.is_empty() {
+            Python::with_gil(|py| {
+                for (callback, future) in callbacks {
+                    if let Err(e) = callback.call1(py, (future,)) {
+                        e.write_unraisable(py, None);
+                    }
+                }
+            });
+        }
+        true
+    }
+}
+
+/// 可取消的 JSEngine 任务
+///
+/// Example:
+///     ```python
+///     future = engine.submit("sign", [data])
+///     try:
+///         result = future.result(timeout=5)
+///     except TimeoutError:
+///         future.cancel()  # 排队中的任务被移除，执行中的任务被终止
+///
+///     result = await engine.submit("sign", [data])  # asyncio
+///     ```
+#[pyclass]
+pub struct JSFuture {
+    state: Arc<FutureState>,
+}
+
+impl JSFuture {
+    /// 创建 JSFuture，在完成线程上等待Worker返回结果
+    pub(crate) fn new(token: Arc<CancelToken>, events: EventSink, rx: oneshot::Receiver<TaskResult>) -> Self {
+        let state = Arc::new(FutureState {
+            inner: Mutex::new(Inner {
+                status: Status::Pending,
+                callbacks: Vec::new(),
+            }),
+            done: Condvar::new(),
+            token,
+            events,
+        });
+
+        let completion = Arc::clone(&state);
+        COMPLETION_RUNTIME.spawn(async move {
+            let result = rx
+                .await
+                .unwrap_or_else(|_| Err("Worker died before returning result".to_string().into()));
+            // 执行中被取消的任务以取消状态结束（cancel() 已设置）
+            if result.as_ref().is_err_and(|e| e.is_cancelled()) {
+                completion.complete(Status::Cancelled);
+            } else {
+                completion.complete(Status::Done(result));
+            }
+        });
+
+        JSFuture { state }
+    }
+
+    /// 等待完成（释放GIL），超时抛出 TimeoutError
+    fn wait(&self, py: Python, timeout: Option<f64>) -> PyResult<()> {
+        let timeout = match timeout {
+            Some(seconds) if !(seconds.is_finite() && seconds >= 0.0) => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "timeout must be a non-negative number",
+                ));
+            }
+            Some(seconds) => Some(Duration::from_secs_f64(seconds)),
+            None => None,
+        };
+
+        let state = Arc::clone(&self.state);
+        let finished = py.allow_threads(move || {
+            let deadline = timeout.map(|timeout| Instant::now() + timeout);
+            let mut inner = state.inner.lock().unwrap_or_else(PoisonError::into_inner);
+            while matches!(inner.status, Status::Pending) {
+                inner = match deadline {
+                    Some(deadline) => {
+                        let remaining = deadline.saturating_duration_since(Instant::now());
+                        if remaining.is_zero() {
+                            return false;
+                        }
+                        state
+                            .done
+                            .wait_timeout(inner, remaining)
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .0
+                    }
+                    None => state.done.wait(inner).unwrap_or_else(PoisonError::into_inner),
+                };
+            }
+            true
+        });
+
+        if finished {
+            Ok(())
+        } else {
+            Err(PyErr::new::<pyo3::exceptions::PyTimeoutError, _>("JSFuture result timed out"))
+        }
+    }
+
+    /// concurrent.futures.CancelledError
+    fn cancelled_error(py: Python) -> PyErr {
+        match py
+            .import("concurrent.futures")
+            .and_then(|module| module.getattr("CancelledError"))
+            .and_then(|cls| cls.call1((TASK_CANCELLED_ERROR,)))
+        {
+            Ok(error) => PyErr::from_value(error),
+            Err(e) => e,
+        }
+    }
+}
+
+#[pymethods]
+impl JSFuture {
+    /// 获取任务结果
+    ///
+    /// Args:
+    ///     timeout: 最多等待的秒数（默认一直等待）
+    ///
+    /// Returns:
+    ///     函数返回值
+    ///
+    /// Raises:
+    ///     TimeoutError: 超时未完成（任务不会被取消）
+    ///     concurrent.futures.CancelledError: 任务已取消
+    ///     HookTerminated: JS调用了 $terminate()
+    ///     Exception: JS执行失败
+    #[pyo3(signature = (timeout=None))]
+    fn result(&self, py: Python, timeout: Option<f64>) -> PyResult<Py<PyAny>> {
+        self.wait(py, timeout)?;
+        let inner = self.state.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        match &inner.status {
+            Status::Done(Ok(value)) => Ok(json_to_python(py, value)?.unbind()),
+            Status::Done(Err(e)) => Err(task_error_to_py(py, e)),
+            Status::Cancelled => Err(Self::cancelled_error(py)),
+            Status::Pending => unreachable!("wait() returned before completion"),
+        }
+    }
+
+    /// 获取任务抛出的异常（成功时返回 None）
+    ///
+    /// 参数和异常同 result()
+    #[pyo3(signature = (timeout=None))]
+    fn exception(&self, py: Python, timeout: Option<f64>) -> PyResult<Option<Py<PyAny>>> {
+        match self.result(py, timeout) {
+            Ok(_) => Ok(None),
+            Err(e) if e.is_instance_of::<pyo3::exceptions::PyTimeoutError>(py) => Err(e),
+            Err(e) if matches!(self.state.inner.lock().unwrap_or_else(PoisonError::into_inner).status, Status::Cancelled) => {
+                Err(e)
+            }
+            Err(e) => Ok(Some(e.into_value(py).into_any())),
+        }
+    }
+
+    /// 获取任务执行期间 $emit() 发送的事件
+    ///
+    /// Args:
+    ///     timeout: 最多等待的秒数（默认一直等待）
+    ///
+    /// Returns:
+    ///     事件列表 [{"channel": ..., "data": ...}]，任务失败时同样可用；
+    ///     任务被取消时事件可能不完整
+    ///
+    /// Raises:
+    ///     TimeoutError: 超时未完成
+    #[pyo3(signature = (timeout=None))]
+    fn events(&self, py: Python, timeout: Option<f64>) -> PyResult<Py<PyList>> {
+        self.wait(py, timeout)?;
+        Ok(events_to_python(py, &self.state.events)?.unbind())
+    }
+
+    /// 取消任务
+    ///
+    /// 排队中的任务不会再执行；执行中的任务被终止（Worker随后重建JsRuntime）。
+    ///
+    /// Returns:
+    ///     任务已完成时返回 False，否则返回 True
+    fn cancel(&self) -> bool {
+        // 先标记取消，Worker在出队或执行结束时检查
+        {
+            let inner = self.state.inner.lock().unwrap_or_else(PoisonError::into_inner);
+            match inner.status {
+                Status::Done(_) => return false,
+                Status::Cancelled => return true,
+                Status::Pending => {}
+            }
+        }
+        self.state.token.cancel();
+        self.state.complete(Status::Cancelled);
+        // 取消前任务可能刚好完成
+        matches!(
+            self.state.inner.lock().unwrap_or_else(PoisonError::into_inner).status,
+            Status::Cancelled
+        )
+    }
+
+    /// 任务是否已取消
+    fn cancelled(&self) -> bool {
+        matches!(
+            self.state.inner.lock().unwrap_or_else(PoisonError::into_inner).status,
+            Status::Cancelled
+        )
+    }
+
+    /// 任务是否正在Worker上执行
+    fn running(&self) -> bool {
+        !self.done() && self.state.token.is_running()
+    }
+
+    /// 任务是否已结束（完成、失败或取消）
+    fn done(&self) -> bool {
+        !matches!(
+            self.state.inner.lock().unwrap_or_else(PoisonError::into_inner).status,
+            Status::Pending
+        )
+    }
+
+    /// 添加完成回调，回调参数为此 JSFuture
+    ///
+    /// 已完成时立即调用；否则在完成线程上调用（回调中的异常被打印后忽略）。
+    fn add_done_callback(slf: &Bound<'_, Self>, callback: Py<PyAny>) -> PyResult<()> {
+        let py = slf.py();
+        {
+            let this = slf.borrow();
+            let mut inner = this.state.inner.lock().unwrap_or_else(PoisonError::into_inner);
+            if matches!(inner.status, Status::Pending) {
+                inner.callbacks.push((callback, slf.clone().into_any().unbind()));
+                return Ok(());
+            }
+        }
+        callback.call1(py, (slf,))?;
+        Ok(())
+    }
+
+    /// 支持 await（asyncio 协程被取消时任务随之取消）
+    fn __await__(slf: &Bound<'_, Self>) -> PyResult<Py<PyAny>> {
+        let py = slf.py();
+        let awaitable = py
+            .import("never_jscore._asyncio")?
+            .getattr("wrap_future")?
+            .call1((slf,))?;
+        Ok(awaitable.call_method0("__await__")?.unbind())
+    }
+
+    fn __repr__(&self) -> String {
+        let inner = self.state.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        let status = match &inner.status {
+            Status::Pending if self.state.token.is_running() => "running",
+            Status::Pending => "pending",
+            Status::Done(Ok(_)) => "finished",
+            Status::Done(Err(_)) => "failed",
+            Status::Cancelled => "cancelled",
+        };
+        format!("JSFuture(state={})", status)
+    }
+}
@@ -0,0 +1,374 @@
+//! Ctrl+C（SIGINT）中断支持
+//!
+//! Python 的信号处理函数只在主线程执行 Python 字节码时运行，而 JS 在
+//! `py.allow_threads` 中执行，Ctrl+C 要等 JS 结束后才会生效。
+//!
+//! 第一个可中断 Context（主线程上创建）注册时安装自己的 SIGINT 处理函数：记录标志、
+//! 唤醒后台看门狗，再链式调用 Python 原有的处理函数。看门狗终止正在执行 JS 的
+//! isolate，调用结束后由 Context 恢复 isolate 并抛出 KeyboardInterrupt。
+//! 最后一个可中断 Context 销毁时恢复原处理函数。
+//!
+//! Unix 上用 sigaction 保存/恢复完整的处理函数（包括 sa_flags 和 sa_mask）：
+//! Python 安装处理函数时不带 SA_RESTART，Ctrl+C 才能打断 input()、socket.recv() 等阻塞调用。
+//!
+//! 每次调用只修改本 Context 的状态，不安装处理函数，也不唤醒看门狗。
+//! 创建 Context 之后用 signal.signal() 替换 SIGINT 处理函数，JS 执行期间的 Ctrl+C
+//! 要等调用结束才生效，直到再创建一个可中断的 Context。
+
+use deno_core::v8;
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+/// 事件循环检查 Ctrl+C 的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// 调用期间是否收到 SIGINT（由信号处理函数设置）
+static SIGINT_FLAG: AtomicBool = AtomicBool::new(false);
+
+/// 一个可中断 Context 的 isolate 及其调用状态
+struct Entry {
+    handle: v8::IsolateHandle,
+    state: Mutex<CallState>,
+}
+
+#[derive(Default)]
+struct CallState {
+    /// 是否正在执行可中断调用
+    running: bool,
+    /// 本次调用中看门狗是否已终止 isolate
+    terminated: bool,
+}
+
+/// 已注册的可中断 Context，及安装处理函数前的原处理函数
+struct Registry {
+    entries: Vec<Arc<Entry>>,
+    prev: Option<sys::SavedHandler>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    entries: Vec::new(),
+    prev: None,
+});
+
+static WATCHDOG: Lazy<()> = Lazy::new(|| {
+    std::thread::Builder::new()
+        .name("never_jscore-interrupt".to_string())
+        .spawn(watchdog_loop)
+        .expect("failed to spawn interrupt watchdog thread");
+});
+
+/// 等待信号处理函数唤醒，终止正在执行可中断调用的 isolate
+fn watchdog_loop() {
+    loop {
+        sys::wait_for_wakeup();
+        if !SIGINT_FLAG.load(Ordering::SeqCst) {
+            continue;
+        }
+        let registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+        for entry in &registry.entries {
+            // 持有调用状态的锁时调用结束不了，不会终止已经结束调用的 isolate
+            let mut state = entry.state.lock().unwrap_or_else(PoisonError::into_inner);
+            if state.running && !state.terminated {
+                entry.handle.terminate_execution();
+                state.terminated = true;
+            }
+        }
+    }
+}
+
+/// 可中断 Context 的注册（Context 存活期间持有）
+///
+/// 创建时安装 SIGINT 处理函数（已安装则复用），销毁时注销，
+/// 最后一个注册销毁时恢复原处理函数。
+pub struct InterruptRegistration {
+    entry: Arc<Entry>,
+}
+
+impl InterruptRegistration {
+    /// 注册 isolate；SIGINT 被忽略（SIG_IGN）或安装失败时返回 None
+    pub fn new(handle: v8::IsolateHandle) -> Option<Self> {
+        let mut registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+        match sys::install()? {
+            // 新安装（包括 signal.signal() 替换了之前安装的处理函数）
+            Some(prev) => registry.prev = Some(prev),
+            None if registry.prev.is_none() => return None,
+            None => {}
+        }
+        Lazy::force(&WATCHDOG);
+
+        let entry = Arc::new(Entry {
+            handle,
+            state: Mutex::new(CallState::default()),
+        });
+        registry.entries.push(Arc::clone(&entry));
+        Some(Self { entry })
+    }
+
+    /// 开始一次可中断调用
+    pub fn begin(&self) -> InterruptGuard<'_> {
+        // 上一次调用之外（Python 代码执行期间）收到的 Ctrl+C 已由 Python 处理
+        SIGINT_FLAG.store(false, Ordering::SeqCst);
+        let mut state = self.entry.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.running = true;
+        state.terminated = false;
+        InterruptGuard { entry: &self.entry, armed: true }
+    }
+}
+
+impl Drop for InterruptRegistration {
+    fn drop(&mut self) {
+        let mut registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+        registry.entries.retain(|entry| !Arc::ptr_eq(entry, &self.entry));
+        if registry.entries.is_empty() {
+            if let Some(prev) = registry.prev.take() {
+                sys::restore(&prev);
+            }
+        }
+    }
+}
+
+/// 一次可中断调用的作用域
+///
+/// `finish()` 返回调用期间是否收到 Ctrl+C（此时调用方需要 cancel_terminate_execution）。
+pub struct InterruptGuard<'a> {
+    entry: &'a Entry,
+    armed: bool,
+}
+
+impl InterruptGuard<'_> {
+    /// 结束作用域，返回是否被 Ctrl+C 中断
+    pub fn finish(mut self) -> bool {
+        self.disarm()
+    }
+
+    fn disarm(&mut self) -> bool {
+        if !self.armed {
+            return false;
+        }
+        self.armed = false;
+
+        // 持锁期间看门狗不会再调用 terminate_execution
+        let mut state = self.entry.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.running = false;
+        // 事件循环可能先于看门狗发现标志而提前退出，此时 isolate 未被终止但调用仍算中断
+        let terminated = std::mem::take(&mut state.terminated);
+        SIGINT_FLAG.swap(false, Ordering::SeqCst) || terminated
+    }
+}
+
+impl Drop for InterruptGuard<'_> {
+    fn drop(&mut self) {
+        self.disarm();
+    }
+}
+
+/// 等待 Ctrl+C（供事件循环在等待定时器/IO 时及时退出）
+///
+/// `enabled` 为 false 时永不完成。
+pub async fn wait_for_interrupt(enabled: bool) {
+    if !enabled {
+        return futures::future::pending().await;
+    }
+    while !SIGINT_FLAG.load(Ordering::SeqCst) {
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use super::SIGINT_FLAG;
+    use std::sync::atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering};
+    use std::sync::Once;
+
+    /// 安装前的 SIGINT 处理函数（通常是 Python 的 signal_handler）
+    pub struct SavedHandler(libc::sigaction);
+
+    // sigaction 只包含处理函数地址、标志和信号掩码
+    unsafe impl Send for SavedHandler {}
+
+    /// 原处理函数地址及是否为三参数形式（SA_SIGINFO），供信号处理函数链式调用
+    static PREV_HANDLER: AtomicUsize = AtomicUsize::new(libc::SIG_DFL);
+    static PREV_SIGINFO: AtomicBool = AtomicBool::new(false);
+
+    /// 唤醒看门狗的管道（self-pipe，write 是 async-signal-safe 的）
+    static WAKE_READ: AtomicI32 = AtomicI32::new(-1);
+    static WAKE_WRITE: AtomicI32 = AtomicI32::new(-1);
+    static PIPE_INIT: Once = Once::new();
+
+    fn init_pipe() {
+        PIPE_INIT.call_once(|| unsafe {
+            let mut fds = [0 as libc::c_int; 2];
+            if libc::pipe(fds.as_mut_ptr()) != 0 {
+                return;
+            }
+            for fd in fds {
+                libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+            }
+            // 写端非阻塞：管道写满时丢弃，看门狗已经有待处理的唤醒
+            libc::fcntl(fds[1], libc::F_SETFL, libc::fcntl(fds[1], libc::F_GETFL) | libc::O_NONBLOCK);
+            WAKE_READ.store(fds[0], Ordering::SeqCst);
+            WAKE_WRITE.store(fds[1], Ordering::SeqCst);
+        });
+    }
+
+    /// SIGINT 处理函数：只做 async-signal-safe 的操作
+    extern "C" fn on_sigint(signum: libc::c_int, info: *mut libc::siginfo_t, context: *mut libc::c_void) {
+        SIGINT_FLAG.store(true, Ordering::SeqCst);
+
+        let fd = WAKE_WRITE.load(Ordering::SeqCst);
+        if fd >= 0 {
+            let byte = 1u8;
+            // 写入成功不修改 errno；失败（管道已满）时看门狗反正会被唤醒
+            unsafe { libc::write(fd, &byte as *const u8 as *const libc::c_void, 1) };
+        }
+
+        // 链式调用 Python 的处理函数，使其记录待处理信号（之后由 check_signals 抛出异常）
+        let prev = PREV_HANDLER.load(Ordering::SeqCst);
+        if prev == libc::SIG_DFL || prev == libc::SIG_IGN || prev == handler_address() {
+            return;
+        }
+        unsafe {
+            if PREV_SIGINFO.load(Ordering::SeqCst) {
+                let handler: extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void) =
+                    std::mem::transmute(prev);
+                handler(signum, info, context);
+            } else {
+                let handler: extern "C" fn(libc::c_int) = std::mem::transmute(prev);
+                handler(signum);
+            }
+        }
+    }
+
+    fn handler_address() -> libc::sighandler_t {
+        on_sigint as extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void) as libc::sighandler_t
+    }
+
+    /// 安装处理函数，沿用原处理函数的 sa_flags 和 sa_mask
+    ///
+    /// 返回 None 表示失败或 SIGINT 被忽略，Some(None) 表示已经安装过，
+    /// Some(Some(prev)) 表示新安装并返回原处理函数。
+    pub fn install() -> Option<Option<SavedHandler>> {
+        init_pipe();
+        if WAKE_READ.load(Ordering::SeqCst) < 0 {
+            return None;
+        }
+        unsafe {
+            let mut prev: libc::sigaction = std::mem::zeroed();
+            if libc::sigaction(libc::SIGINT, std::ptr::null(), &mut prev) != 0 {
+                return None;
+            }
+            if prev.sa_sigaction == handler_address() {
+                return Some(None);
+            }
+            if prev.sa_sigaction == libc::SIG_IGN {
+                // 用户显式忽略了 Ctrl+C：保持原样，不中断
+                return None;
+            }
+
+            PREV_HANDLER.store(prev.sa_sigaction, Ordering::SeqCst);
+            PREV_SIGINFO.store(prev.sa_flags & libc::SA_SIGINFO != 0, Ordering::SeqCst);
+
+            let mut action = prev;
+            action.sa_sigaction = handler_address();
+            action.sa_flags = prev.sa_flags | libc::SA_SIGINFO;
+            if libc::sigaction(libc::SIGINT, &action, std::ptr::null_mut()) != 0 {
+                return None;
+            }
+            Some(Some(SavedHandler(prev)))
+        }
+    }
+
+    /// 恢复原处理函数（已被 signal.signal() 替换时保持不变）
+    pub fn restore(prev: &SavedHandler) {
+        unsafe {
+            let mut current: libc::sigaction = std::mem::zeroed();
+            if libc::sigaction(libc::SIGINT, std::ptr::null(), &mut current) == 0
+                && current.sa_sigaction == handler_address()
+            {
+                libc::sigaction(libc::SIGINT, &prev.0, std::ptr::null_mut());
+            }
+        }
+    }
+
+    /// 阻塞直到信号处理函数写入管道
+    pub fn wait_for_wakeup() {
+        let fd = WAKE_READ.load(Ordering::SeqCst);
+        let mut buf = [0u8; 64];
+        unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    //! Windows 的 CRT 在单独的线程上调用 SIGINT 处理函数，且调用前重置为 SIG_DFL，
+    //! 处理函数中可以直接唤醒看门狗并重新安装自己。
+
+    use super::SIGINT_FLAG;
+    use once_cell::sync::Lazy;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Condvar, Mutex, PoisonError};
+
+    pub struct SavedHandler(libc::sighandler_t);
+
+    static PREV_HANDLER: AtomicUsize = AtomicUsize::new(libc::SIG_DFL);
+
+    static WAKEUP: Lazy<(Mutex<bool>, Condvar)> = Lazy::new(|| (Mutex::new(false), Condvar::new()));
+
+    extern "C" fn on_sigint(signum: libc::c_int) {
+        SIGINT_FLAG.store(true, Ordering::SeqCst);
+        {
+            let (pending, cvar) = &*WAKEUP;
+            *pending.lock().unwrap_or_else(PoisonError::into_inner) = true;
+            cvar.notify_one();
+        }
+
+        let prev = PREV_HANDLER.load(Ordering::SeqCst);
+        if prev != libc::SIG_DFL && prev != libc::SIG_IGN && prev != handler_address() {
+            let handler: extern "C" fn(libc::c_int) = unsafe { std::mem::transmute(prev) };
+            handler(signum);
+        }
+        unsafe { libc::signal(libc::SIGINT, handler_address()) };
+    }
+
+    fn handler_address() -> libc::sighandler_t {
+        on_sigint as extern "C" fn(libc::c_int) as libc::sighandler_t
+    }
+
+    /// 语义同 Unix 版本
+    pub fn install() -> Option<Option<SavedHandler>> {
+        let prev = unsafe { libc::signal(libc::SIGINT, handler_address()) };
+        if prev == libc::SIG_ERR {
+            return None;
+        }
+        if prev == handler_address() {
+            return Some(None);
+        }
+        if prev == libc::SIG_IGN {
+            unsafe { libc::signal(libc::SIGINT, prev) };
+            return None;
+        }
+        PREV_HANDLER.store(prev, Ordering::SeqCst);
+        Some(Some(SavedHandler(prev)))
+    }
+
+    pub fn restore(prev: &SavedHandler) {
+        unsafe {
+            let current = libc::signal(libc::SIGINT, prev.0);
+            if current != handler_address() && current != libc::SIG_ERR {
+                // 已被 signal.signal() 替换，放回去
+                libc::signal(libc::SIGINT, current);
+            }
+        }
+    }
+
+    pub fn wait_for_wakeup() {
+        let (pending, cvar) = &*WAKEUP;
+        let mut pending = pending.lock().unwrap_or_else(PoisonError::into_inner);
+        while !*pending {
+            pending = cvar.wait(pending).unwrap_or_else(PoisonError::into_inner);
+        }
+        *pending = false;
+    }
+}
@@ -9,6 +9,14 @@ mod node_compat;
 mod module_loader;
 mod worker_pool;
 mod engine;
+mod threaded_context;
+mod interrupt;
+mod realm;
+mod errors;
+mod metrics;
+mod task_queue;
+mod future;
+mod trace;
 
 #[cfg(feature = "deno_web_api")]
 mod permissions;
@@ -17,26 +25,13 @@ mod permissions;
 mod transpile;
 
 use pyo3::prelude::*;
-use std::sync::Once;
 
 use context::Context;
 use engine::JSEngine;
-
-// V8 platform initialization - must happen exactly once
-static INIT: Once = Once::new();
-
-fn ensure_v8_initialized() {
-    INIT.call_once(|| {
-        // Initialize V8 platform (required before creating any isolates)
-        deno_core::JsRuntime::init_platform(None);
-
-        // Initialize rustls CryptoProvider for HTTPS support
-        #[cfg(feature = "deno_web_api")]
-        {
-            let _ = rustls::crypto::ring::default_provider().install_default();
-        }
-    });
-}
+use threaded_context::ThreadedContext;
+use realm::Realm;
+use future::JSFuture;
+use trace::Trace;
 
 /// never_jscore Python 模块
 ///
@@ -63,8 +58,7 @@ fn ensure_v8_initialized() {
 ///     ```
 #[pymodule]
 fn never_jscore(m: &Bound<'_, PyModule>) -> PyResult<()> {
-    // Initialize V8 platform when module is first imported
-    ensure_v8_initialized();
+    // V8 平台在第一次构造 Context / ThreadedContext / JSEngine 时初始化（见 runtime::init_v8_for）
 
     // 导出新API - JSEngine (v3.0推荐)
     m.add_class::<JSEngine>()?;
@@ -72,5 +66,21 @@ fn never_jscore(m: &Bound<'_, PyModule>) -> PyResult<()> {
     // 导出旧API - Context (向后兼容)
     m.add_class::<Context>()?;
 
+    // 导出线程安全的 Context（可在任意 Python 线程中调用）
+    m.add_class::<ThreadedContext>()?;
+
+    // 导出 Realm（由 Context.create_realm() 创建）
+    m.add_class::<Realm>()?;
+
+    // 导出 JSFuture（由 JSEngine.submit() 创建）
+    m.add_class::<JSFuture>()?;
+
+    // 导出 Trace（由 Context.trace() 创建）
+    m.add_class::<Trace>()?;
+
+    // 导出异常类型
+    m.add("PoolOverloadedError", m.py().get_type::<errors::PoolOverloadedError>())?;
+    m.add("HookTerminated", m.py().get_type::<errors::HookTerminated>())?;
+
     Ok(())
 }
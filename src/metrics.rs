@@ -0,0 +1,371 @@
+//! Worker池运行指标
+//!
+//! Worker线程在每个任务完成后更新自己的计数器和延迟直方图（只用原子操作，不加锁），
+//! `PoolMetrics::snapshot` 汇总为 `PoolStats`，可转换为 Python 字典或 Prometheus 文本格式。
+//!
+//! 延迟直方图使用固定分桶（毫秒），分位数在桶内线性插值估算，
+//! 精度取决于分桶宽度，适合监控而不是精确测量。
+
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// 延迟直方图分桶上限（毫秒），最后还有一个 +Inf 桶
+pub const LATENCY_BUCKETS_MS: [f64; 18] = [
+    0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0,
+    30000.0, 60000.0,
+];
+
+/// 无锁延迟直方图
+pub struct LatencyHistogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+    sum_us: AtomicU64,
+    max_us: AtomicU64,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            sum_us: AtomicU64::new(0),
+            max_us: AtomicU64::new(0),
+        }
+    }
+
+    /// 记录一次耗时
+    pub fn record(&self, elapsed: Duration) {
+        let ms = elapsed.as_secs_f64() * 1000.0;
+        let index = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|&bound| ms <= bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.buckets[index].fetch_add(1, Ordering::Relaxed);
+
+        let us = elapsed.as_micros() as u64;
+        self.sum_us.fetch_add(us, Ordering::Relaxed);
+        self.max_us.fetch_max(us, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        HistogramSnapshot {
+            buckets: self.buckets.iter().map(|b| b.load(Ordering::Relaxed)).collect(),
+            sum_ms: self.sum_us.load(Ordering::Relaxed) as f64 / 1000.0,
+            max_ms: self.max_us.load(Ordering::Relaxed) as f64 / 1000.0,
+        }
+    }
+}
+
+/// 直方图快照（各桶计数，非累计）
+#[derive(Clone, Debug, Default)]
+pub struct HistogramSnapshot {
+    pub buckets: Vec<u64>,
+    pub sum_ms: f64,
+    pub max_ms: f64,
+}
+
+impl HistogramSnapshot {
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    pub fn mean_ms(&self) -> Option<f64> {
+        let count = self.count();
+        (count > 0).then(|| self.sum_ms / count as f64)
+    }
+
+    /// 估算分位数（q 取 0~1），没有样本时返回 None
+    ///
+    /// 在目标样本所在的桶内线性插值；+Inf 桶用记录到的最大值作为上限。
+    pub fn percentile(&self, q: f64) -> Option<f64> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+
+        let rank = (q.clamp(0.0, 1.0) * count as f64).max(1.0);
+        let mut seen = 0u64;
+        for (index, &bucket) in self.buckets.iter().enumerate() {
+            if bucket == 0 {
+                continue;
+            }
+            if (seen + bucket) as f64 >= rank {
+                let lower = if index == 0 { 0.0 } else { LATENCY_BUCKETS_MS[index - 1] };
+                let upper = LATENCY_BUCKETS_MS.get(index).copied().unwrap_or(self.max_ms).min(self.max_ms);
+                let fraction = (rank - seen as f64) / bucket as f64;
+                return Some(lower + (upper.max(lower) - lower) * fraction);
+            }
+            seen += bucket;
+        }
+        Some(self.max_ms)
+    }
+
+    /// 合并另一个直方图
+    pub fn merge(&mut self, other: &HistogramSnapshot) {
+        if self.buckets.len() < other.buckets.len() {
+            self.buckets.resize(other.buckets.len(), 0);
+        }
+        for (total, count) in self.buckets.iter_mut().zip(&other.buckets) {
+            *total += count;
+        }
+        self.sum_ms += other.sum_ms;
+        self.max_ms = self.max_ms.max(other.max_ms);
+    }
+}
+
+/// 单个Worker的指标（Worker重建后继续累计）
+pub struct WorkerMetrics {
+    pub tasks: AtomicU64,
+    pub errors: AtomicU64,
+    pub busy: AtomicBool,
+    pub heap_used: AtomicU64,
+    pub heap_total: AtomicU64,
+    pub latency: LatencyHistogram,
+}
+
+impl WorkerMetrics {
+    fn new() -> Self {
+        Self {
+            tasks: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            busy: AtomicBool::new(false),
+            heap_used: AtomicU64::new(0),
+            heap_total: AtomicU64::new(0),
+            latency: LatencyHistogram::new(),
+        }
+    }
+
+    /// 记录任务开始执行
+    pub fn task_started(&self) {
+        self.busy.store(true, Ordering::Relaxed);
+    }
+
+    /// 记录任务完成（耗时、是否失败、执行后的堆内存）
+    pub fn task_finished(&self, elapsed: Duration, failed: bool, heap_used: usize, heap_total: usize) {
+        self.tasks.fetch_add(1, Ordering::Relaxed);
+        if failed {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        self.latency.record(elapsed);
+        self.heap_used.store(heap_used as u64, Ordering::Relaxed);
+        self.heap_total.store(heap_total as u64, Ordering::Relaxed);
+        self.busy.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Worker池指标
+pub struct PoolMetrics {
+    pub workers: Vec<WorkerMetrics>,
+    /// 因 submit_policy='timeout' 等待超时被拒绝的任务数
+    pub submit_timeouts: AtomicU64,
+    started: Instant,
+}
+
+impl PoolMetrics {
+    pub fn new(worker_count: usize) -> Self {
+        Self {
+            workers: (0..worker_count).map(|_| WorkerMetrics::new()).collect(),
+            submit_timeouts: AtomicU64::new(0),
+            started: Instant::now(),
+        }
+    }
+
+    /// 汇总指标；pool 提供Worker池自身的计数（队列长度、健康状态等）
+    pub fn snapshot(&self, pool: PoolCounters, healthy: impl Fn(usize) -> bool) -> PoolStats {
+        let workers: Vec<WorkerStats> = self
+            .workers
+            .iter()
+            .enumerate()
+            .map(|(worker_id, metrics)| WorkerStats {
+                worker_id,
+                healthy: healthy(worker_id),
+                busy: metrics.busy.load(Ordering::Relaxed),
+                tasks: metrics.tasks.load(Ordering::Relaxed),
+                errors: metrics.errors.load(Ordering::Relaxed),
+                heap_used: metrics.heap_used.load(Ordering::Relaxed),
+                heap_total: metrics.heap_total.load(Ordering::Relaxed),
+                latency: metrics.latency.snapshot(),
+            })
+            .collect();
+
+        let mut latency = HistogramSnapshot::default();
+        for worker in &workers {
+            latency.merge(&worker.latency);
+        }
+
+        PoolStats {
+            uptime: self.started.elapsed(),
+            counters: pool,
+            in_flight: workers.iter().filter(|w| w.busy).count(),
+            completed: workers.iter().map(|w| w.tasks).sum(),
+            errors: workers.iter().map(|w| w.errors).sum(),
+            submit_timeouts: self.submit_timeouts.load(Ordering::Relaxed),
+            heap_used: workers.iter().map(|w| w.heap_used).sum(),
+            latency,
+            workers,
+        }
+    }
+}
+
+/// Worker池自身维护的计数
+#[derive(Clone, Debug, Default)]
+pub struct PoolCounters {
+    pub workers: usize,
+    pub healthy_workers: usize,
+    pub queue_size: usize,
+    pub max_queue_size: Option<usize>,
+    pub rejected: u64,
+    pub recycled: u64,
+    pub respawned: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct WorkerStats {
+    pub worker_id: usize,
+    pub healthy: bool,
+    pub busy: bool,
+    pub tasks: u64,
+    pub errors: u64,
+    pub heap_used: u64,
+    pub heap_total: u64,
+    pub latency: HistogramSnapshot,
+}
+
+/// Worker池指标快照
+#[derive(Clone, Debug)]
+pub struct PoolStats {
+    pub uptime: Duration,
+    pub counters: PoolCounters,
+    /// 正在执行任务的Worker数
+    pub in_flight: usize,
+    pub completed: u64,
+    pub errors: u64,
+    pub submit_timeouts: u64,
+    pub heap_used: u64,
+    pub latency: HistogramSnapshot,
+    pub workers: Vec<WorkerStats>,
+}
+
+impl PoolStats {
+    /// 转换为 Prometheus 文本格式（text/plain; version=0.0.4）
+    pub fn to_prometheus(&self, namespace: &str, labels: &[(String, String)]) -> String {
+        let mut out = String::new();
+        let base = format_labels(labels, None);
+        let c = &self.counters;
+
+        let gauges: [(&str, &str, f64); 7] = [
+            ("workers", "Number of workers", c.workers as f64),
+            ("healthy_workers", "Workers that are initialized and running", c.healthy_workers as f64),
+            ("queue_size", "Tasks waiting in the task queues", c.queue_size as f64),
+            ("in_flight_tasks", "Tasks currently executing", self.in_flight as f64),
+            ("heap_used_bytes", "V8 heap used by all workers", self.heap_used as f64),
+            ("uptime_seconds", "Seconds since the engine was created", self.uptime.as_secs_f64()),
+            (
+                "max_queue_size",
+                "Configured task queue limit (-1 when unbounded)",
+                c.max_queue_size.map_or(-1.0, |n| n as f64),
+            ),
+        ];
+        for (name, help, value) in gauges {
+            write_metric(&mut out, namespace, name, "gauge", help, &[(base.clone(), value)]);
+        }
+
+        let counters: [(&str, &str, u64); 6] = [
+            ("tasks_total", "Tasks completed by workers", self.completed),
+            ("task_errors_total", "Tasks that finished with an error", self.errors),
+            ("rejected_tasks_total", "Tasks rejected because the queue was full", c.rejected),
+            ("submit_timeouts_total", "Tasks rejected after waiting submit_timeout", self.submit_timeouts),
+            ("recycled_workers_total", "Worker runtimes recycled by lifecycle limits", c.recycled),
+            ("respawned_workers_total", "Workers respawned after a failure", c.respawned),
+        ];
+        for (name, help, value) in counters {
+            write_metric(&mut out, namespace, name, "counter", help, &[(base.clone(), value as f64)]);
+        }
+
+        let per_worker = |value: fn(&WorkerStats) -> f64| -> Vec<(String, f64)> {
+            self.workers
+                .iter()
+                .map(|w| (format_labels(labels, Some(w.worker_id)), value(w)))
+                .collect()
+        };
+        write_metric(
+            &mut out,
+            namespace,
+            "worker_tasks_total",
+            "counter",
+            "Tasks completed by each worker",
+            &per_worker(|w| w.tasks as f64),
+        );
+        write_metric(
+            &mut out,
+            namespace,
+            "worker_heap_used_bytes",
+            "gauge",
+            "V8 heap used by each worker",
+            &per_worker(|w| w.heap_used as f64),
+        );
+        write_metric(
+            &mut out,
+            namespace,
+            "worker_healthy",
+            "gauge",
+            "Whether each worker is initialized and running",
+            &per_worker(|w| if w.healthy { 1.0 } else { 0.0 }),
+        );
+
+        // 延迟直方图（秒，累计分桶）
+        let name = format!("{}_task_duration_seconds", namespace);
+        let _ = writeln!(out, "# HELP {} Task execution time", name);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        let mut cumulative = 0u64;
+        for (index, count) in self.latency.buckets.iter().enumerate() {
+            cumulative += count;
+            let le = LATENCY_BUCKETS_MS
+                .get(index)
+                .map_or("+Inf".to_string(), |ms| format_value(ms / 1000.0));
+            let mut bucket_labels = labels.to_vec();
+            bucket_labels.push(("le".to_string(), le));
+            let _ = writeln!(out, "{}_bucket{} {}", name, format_labels(&bucket_labels, None), cumulative);
+        }
+        let _ = writeln!(out, "{}_sum{} {}", name, base, format_value(self.latency.sum_ms / 1000.0));
+        let _ = writeln!(out, "{}_count{} {}", name, base, self.latency.count());
+        out
+    }
+}
+
+/// 写入一个指标（HELP、TYPE 和各样本）
+fn write_metric(out: &mut String, namespace: &str, name: &str, kind: &str, help: &str, samples: &[(String, f64)]) {
+    let name = format!("{}_{}", namespace, name);
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    for (labels, value) in samples {
+        let _ = writeln!(out, "{}{} {}", name, labels, format_value(*value));
+    }
+}
+
+/// 格式化标签集，如 `{pool="sign",worker="0"}`；没有标签时为空字符串
+fn format_labels(labels: &[(String, String)], worker_id: Option<usize>) -> String {
+    let mut parts: Vec<String> = labels
+        .iter()
+        .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+        .collect();
+    if let Some(worker_id) = worker_id {
+        parts.push(format!("worker=\"{}\"", worker_id));
+    }
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", parts.join(","))
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{}", value)
+    }
+}
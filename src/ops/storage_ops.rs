@@ -1,7 +1,7 @@
 use deno_core::{OpState, extension, op2};
 use std::rc::Rc;
 
-use crate::storage::{ResultStorage, save_hook_data};
+use crate::storage::ResultStorage;
 
 /// Op: 存储 JavaScript 执行结果
 ///
@@ -29,14 +29,16 @@ pub fn op_early_return(state: &mut OpState, #[string] value: String) {
     }
 }
 
-/// Op: 保存 Hook 数据到全局存储（新版本，配合 terminate_execution 使用）
+/// Op: 保存 Hook 数据（新版本，配合 terminate_execution 使用）
 ///
 /// 在调用 op_terminate_execution 前保存数据。
-/// 数据保存到全局静态变量，即使 isolate 被终止也能访问。
+/// 数据保存到当前 runtime 的 ResultStorage，即使 isolate 被终止也能访问。
 #[op2]
 #[string]
-pub fn op_save_hook_data(#[string] data: String) -> String {
-    save_hook_data(data.clone());
+pub fn op_save_hook_data(state: &mut OpState, #[string] data: String) -> String {
+    if let Some(storage) = state.try_borrow::<Rc<ResultStorage>>() {
+        storage.store_hook_data(data.clone());
+    }
     data
 }
 
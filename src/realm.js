@@ -0,0 +1,129 @@
+// Realm initialization for never-jscore
+// Evaluated in the main realm; the resulting function installs the host's
+// globals (Web APIs, never-jscore helpers) on a freshly created realm,
+// the way a same-origin iframe sees its own builtins plus the browser APIs.
+
+(function installRealmGlobals(target, exposeParent) {
+    const host = globalThis;
+
+    // The realm keeps its own ECMAScript builtins (Object, Array, Date, Promise, ...)
+    // so prototype pollution inside the realm does not leak into the host.
+    // Web API objects (URL, crypto, fetch, ...) are the host's own and are shared.
+    // Runtime internals (Deno, registries, hook/trace plumbing) stay in the host:
+    // the helpers that use them resolve these names through the host global.
+    const isInternal = (key) => typeof key === 'string' && (
+        key === 'Deno' || key === '__getDeno' || key === '__bootstrap' ||
+        key === '__saveAndTerminate__' || key.startsWith('__deno') ||
+        key.startsWith('__NEVER_JSCORE_') || key.startsWith('__neverjscore_')
+    );
+    for (const key of Reflect.ownKeys(host)) {
+        if (key in target || isInternal(key)) {
+            continue;
+        }
+        const descriptor = Reflect.getOwnPropertyDescriptor(host, key);
+        try {
+            Reflect.defineProperty(target, key, descriptor);
+        } catch (e) {
+            // Non-transferable property, skip
+        }
+    }
+
+    // Math.random is patched in the host (seeded / V8-emulated). The realm gets
+    // its own wrapper so it never holds the host's function object.
+    // Function.prototype.toString likewise gets a realm-own wrapper that defers
+    // to the host's, so the native-function registry applies inside the realm.
+    const makeRealmFunctions = target.eval(`(function (hostRandom, hostToString, virtualNow) {
+        const realmFunctions = {
+            random: function random() {
+                return hostRandom();
+            },
+            toString: {
+                toString() {
+                    return hostToString.call(this);
+                },
+            }.toString,
+        };
+        if (virtualNow) {
+            // Deterministic mode: realm-own Date driven by the shared virtual clock
+            const OriginalDate = Date;
+            const VirtualDate = function Date(...args) {
+                if (new.target === undefined) {
+                    return new OriginalDate(virtualNow()).toString();
+                }
+                if (args.length === 0) {
+                    return Reflect.construct(OriginalDate, [virtualNow()], new.target);
+                }
+                return Reflect.construct(OriginalDate, args, new.target);
+            };
+            Object.defineProperty(VirtualDate, 'prototype', { value: OriginalDate.prototype });
+            Object.defineProperty(VirtualDate, 'length', { value: 7, configurable: true });
+            for (const [key, value] of [
+                ['now', function now() { return virtualNow(); }],
+                ['parse', OriginalDate.parse],
+                ['UTC', OriginalDate.UTC],
+            ]) {
+                Object.defineProperty(VirtualDate, key, { value, writable: true, configurable: true });
+            }
+            Object.defineProperty(OriginalDate.prototype, 'constructor', {
+                value: VirtualDate, writable: true, configurable: true,
+            });
+            realmFunctions.Date = VirtualDate;
+        }
+        return realmFunctions;
+    })`);
+
+    const ops = host.Deno && host.Deno.core && host.Deno.core.ops;
+    const deterministic = !!(ops && ops.op_deterministic_enabled && ops.op_deterministic_enabled());
+    const hostRandom = host.Math.random;
+    const hostToString = host.Function.prototype.toString;
+    const realmFunctions = makeRealmFunctions(
+        () => hostRandom(),
+        hostToString,
+        deterministic ? () => Math.floor(ops.op_deterministic_now()) : null,
+    );
+
+    const native = host.__NEVER_JSCORE_NATIVE__;
+    if (native) {
+        native.register(realmFunctions.random, 'random');
+        native.register(realmFunctions.toString, 'toString');
+        if (realmFunctions.Date) {
+            native.register(realmFunctions.Date, 'Date');
+            native.register(realmFunctions.Date.now, 'now');
+        }
+    }
+
+    const alias = (name, value) => {
+        Reflect.defineProperty(target, name, {
+            value,
+            writable: true,
+            enumerable: false,
+            configurable: true,
+        });
+    };
+    target.Math.random = realmFunctions.random;
+    if (realmFunctions.Date) {
+        alias('Date', realmFunctions.Date);
+    }
+    Reflect.defineProperty(target.Function.prototype, 'toString', {
+        value: target.Object.freeze(realmFunctions.toString),
+        writable: true,
+        enumerable: false,
+        configurable: true,
+    });
+
+    // Hide the same globals from the realm's own reflection APIs
+    if (typeof host.__NEVER_JSCORE_PROTECT_REALM__ === 'function') {
+        host.__NEVER_JSCORE_PROTECT_REALM__(target);
+    }
+
+    // Window-like aliases refer to the realm's own global. parent/top refer to
+    // the host only when the caller opts in (create_realm(expose_parent=True)),
+    // otherwise the realm behaves like a top-level window.
+    alias('self', target);
+    if ('window' in host) {
+        alias('window', target);
+        alias('frames', target);
+        alias('parent', exposeParent ? host : target);
+        alias('top', exposeParent ? host : target);
+    }
+})
@@ -0,0 +1,257 @@
+//! Realm - 同一 Isolate 中的多个 V8 Context
+//!
+//! 每个 Context 都持有完整的 JsRuntime 并加载全部扩展，开销较大。
+//! Realm 与父 Context 共享 Isolate 和事件循环，但拥有独立的全局对象
+//! （类似 iframe / ShadowRealm）：
+//! - ECMAScript 内置对象（Object、Array、Date、Promise...）是 realm 自己的，
+//!   修改其原型不会影响父 Context
+//! - Web API（fetch、URL、crypto、定时器...）是父 Context 的同一批对象，修改它们对双方可见
+//! - Math.random / 确定性模式下的 Date 是 realm 自己的包装函数，委托给父 Context 的实现
+//! - parent / top 默认指向 realm 自身，expose_parent=True 时才指向父 Context 的全局对象
+//!
+//! realm 不是安全沙箱：共享的 Web API 对象仍可被篡改。适合模拟 iframe.contentWindow
+//! 或隔离全局变量，不适合运行恶意脚本。
+
+use anyhow::{anyhow, Result};
+use deno_core::{v8, JsRuntime, PollEventLoopOptions};
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use serde_json::Value as JsonValue;
+
+use crate::context::{build_call_code, Context};
+use crate::convert::json_to_python;
+
+/// 在主 realm 中求值，得到安装全局对象的函数
+const INSTALL_GLOBALS_JS: &str = include_str!("realm.js");
+
+/// realm 内执行的结果
+pub enum Completion {
+    /// 同步结果（已编码的输出）
+    Ready(String),
+    /// Promise，需要运行事件循环等待
+    Pending(v8::Global<v8::Value>),
+}
+
+/// 创建新的 V8 Context，并安装主 realm 的 Web API 全局对象
+///
+/// expose_parent 为 true 时 realm 中的 parent / top 指向主 realm 的全局对象
+pub fn create(runtime: &mut JsRuntime, expose_parent: bool) -> Result<v8::Global<v8::Context>> {
+    let main_context = runtime.main_context();
+    v8::scope!(let scope, runtime.v8_isolate());
+    let main = v8::Local::new(scope, &main_context);
+    let scope = &mut v8::ContextScope::new(scope, main);
+
+    let realm = v8::Context::new(scope, Default::default());
+    // 相同的安全令牌：两个 realm 可以互相访问对方的全局对象（同源 iframe）
+    let token = main.get_security_token(scope);
+    realm.set_security_token(token);
+
+    let source = v8::String::new(scope, INSTALL_GLOBALS_JS)
+        .ok_or_else(|| anyhow!("Failed to allocate realm init script"))?;
+    let installer = v8::Script::compile(scope, source, None)
+        .and_then(|script| script.run(scope))
+        .and_then(|value| v8::Local::<v8::Function>::try_from(value).ok())
+        .ok_or_else(|| anyhow!("Failed to compile realm init script"))?;
+
+    let recv: v8::Local<v8::Value> = v8::undefined(scope).into();
+    let global: v8::Local<v8::Value> = realm.global(scope).into();
+    let expose_parent: v8::Local<v8::Value> = v8::Boolean::new(scope, expose_parent).into();
+    installer
+        .call(scope, recv, &[global, expose_parent])
+        .ok_or_else(|| anyhow!("Failed to install realm globals"))?;
+
+    Ok(v8::Global::new(scope, realm))
+}
+
+/// 生成在 realm 中执行的包装代码
+///
+/// 代码以间接 eval 执行（顶层 var/function 进入 realm 的全局作用域）。
+/// 输出编码为 "1" + JSON（成功）或 "0" + 错误信息（失败），
+/// 异常在 realm 内部捕获，不需要 TryCatch。
+pub fn wrap_code(code: &str, auto_await: bool) -> Result<String> {
+    let code_json =
+        serde_json::to_string(code).map_err(|e| anyhow!("Failed to serialize code: {}", e))?;
+    Ok(format!(
+        r#"
+        (() => {{
+            const toJson = (value) => {{
+                if (value === undefined) {{
+                    return 'null';
+                }}
+                try {{
+                    const json = JSON.stringify(value);
+                    return json === undefined ? 'null' : json;
+                }} catch (e) {{
+                    return JSON.stringify(String(value));
+                }}
+            }};
+            const describe = (error) => (error && error.stack) ? String(error.stack) : String(error);
+
+            let result;
+            try {{
+                result = (0, eval)({code});
+            }} catch (e) {{
+                return '0' + describe(e);
+            }}
+            if ({auto_await} && result && typeof result.then === 'function') {{
+                return Promise.resolve(result).then(
+                    (value) => '1' + toJson(value),
+                    (error) => '0' + describe(error),
+                );
+            }}
+            return '1' + toJson(result);
+        }})()
+        "#,
+        code = code_json,
+        auto_await = auto_await,
+    ))
+}
+
+/// 在 realm 中执行包装代码
+pub fn execute(
+    runtime: &mut JsRuntime,
+    realm: &v8::Global<v8::Context>,
+    source: &str,
+) -> Result<Completion> {
+    v8::scope!(let scope, runtime.v8_isolate());
+    let context = v8::Local::new(scope, realm);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let source =
+        v8::String::new(scope, source).ok_or_else(|| anyhow!("Failed to allocate realm script"))?;
+    // 异常已在包装代码中捕获，这里返回 None 只可能是 isolate 被终止
+    let value = v8::Script::compile(scope, source, None)
+        .and_then(|script| script.run(scope))
+        .ok_or_else(|| anyhow!("execution terminated"))?;
+
+    if value.is_promise() {
+        return Ok(Completion::Pending(v8::Global::new(scope, value)));
+    }
+    Ok(Completion::Ready(value.to_rust_string_lossy(scope)))
+}
+
+/// 运行事件循环直到 Promise 完成，返回编码后的输出
+pub async fn resolve(
+    runtime: &mut JsRuntime,
+    realm: &v8::Global<v8::Context>,
+    promise: v8::Global<v8::Value>,
+) -> Result<String> {
+    let resolve = runtime.resolve(promise);
+    let value = runtime
+        .with_event_loop_promise(resolve, PollEventLoopOptions::default())
+        .await
+        .map_err(|e| anyhow!("{}", e))?;
+
+    v8::scope!(let scope, runtime.v8_isolate());
+    let context = v8::Local::new(scope, realm);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    let value = v8::Local::new(scope, value);
+    Ok(value.to_rust_string_lossy(scope))
+}
+
+/// 解码输出：成功返回 JSON 字符串，失败返回 JS 错误
+pub fn decode_output(output: String) -> Result<String> {
+    match output.split_at_checked(1) {
+        Some(("1", json)) => Ok(json.to_string()),
+        Some(("0", error)) => Err(anyhow!("{}", error)),
+        _ => Err(anyhow!("Unexpected realm output: {}", output)),
+    }
+}
+
+/// 轻量级 realm：共享父 Context 的 Isolate 和事件循环，拥有独立的全局对象
+///
+/// 由 `Context.create_realm()` 创建。
+///
+/// # Example
+///
+/// ```python
+/// ctx = never_jscore.Context()
+/// realm = ctx.create_realm()
+/// realm.eval("Array.prototype.evil = 1; var token = 'realm'")
+/// ctx.evaluate("[].evil")          # None：内置对象的原型互不影响
+/// realm.call("btoa", ["hi"])       # Web API 可用
+/// ```
+#[pyclass(unsendable)]
+pub struct Realm {
+    context: Py<Context>,
+    id: usize,
+}
+
+impl Realm {
+    pub fn new(context: Py<Context>, id: usize) -> Self {
+        Self { context, id }
+    }
+
+    fn execute(
+        &self,
+        py: Python,
+        code: String,
+        auto_await: bool,
+        prefix: &str,
+    ) -> PyResult<Py<PyAny>> {
+        let ctx = self.context.borrow(py);
+        let id = self.id;
+        let result = ctx
+            .allow_threads_interruptible(py, move |ctx| ctx.realm_execute(id, &code, auto_await))?
+            .map_err(|e| PyException::new_err(format!("{}: {}", prefix, e)));
+        let result_json = ctx.handle_error_reports(py, result)?;
+
+        let result: JsonValue = serde_json::from_str(&result_json)
+            .map_err(|e| PyException::new_err(format!("JSON parse error: {}", e)))?;
+        Ok(json_to_python(py, &result)?.unbind())
+    }
+}
+
+#[pymethods]
+impl Realm {
+    /// 在 realm 中执行代码并返回最后一个表达式的值
+    ///
+    /// 顶层 var/function 声明进入 realm 的全局作用域（顶层 let/const 不保留）。
+    ///
+    /// Args:
+    ///     code: JavaScript 代码
+    ///     auto_await: 是否自动等待 Promise（默认 True）
+    #[pyo3(signature = (code, auto_await=None))]
+    fn eval(&self, py: Python, code: String, auto_await: Option<bool>) -> PyResult<Py<PyAny>> {
+        self.execute(py, code, auto_await.unwrap_or(true), "Realm eval error")
+    }
+
+    /// 调用 realm 全局作用域中的函数
+    ///
+    /// Args:
+    ///     name: 函数名称
+    ///     args: 参数列表
+    ///     auto_await: 是否自动等待 Promise（默认 True）
+    #[pyo3(signature = (name, args, auto_await=None))]
+    fn call(
+        &self,
+        py: Python,
+        name: String,
+        args: &Bound<'_, PyAny>,
+        auto_await: Option<bool>,
+    ) -> PyResult<Py<PyAny>> {
+        let call_code = build_call_code(&name, args)?;
+        self.execute(
+            py,
+            call_code,
+            auto_await.unwrap_or(true),
+            "Realm call error",
+        )
+    }
+
+    /// realm 编号（在父 Context 内唯一）
+    #[getter]
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    /// 释放 realm（之后的调用抛出异常）
+    fn close(&self, py: Python) {
+        self.context.borrow(py).close_realm(self.id);
+    }
+
+    /// 字符串表示
+    fn __repr__(&self) -> String {
+        format!("Realm(id={})", self.id)
+    }
+}
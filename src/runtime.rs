@@ -1,4 +1,7 @@
+use pyo3::exceptions::{PyRuntimeWarning, PyValueError};
+use pyo3::prelude::*;
 use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::OnceLock;
 
 /// 全局 V8 平台初始化标志
@@ -6,6 +9,65 @@ use std::sync::OnceLock;
 /// V8 平台只能初始化一次，使用 OnceLock 确保线程安全。
 static V8_INITIALIZED: OnceLock<()> = OnceLock::new();
 
+/// 平台初始化时是否固定了哈希种子
+static HASH_SEED_PINNED: AtomicBool = AtomicBool::new(false);
+
+/// 固定 V8 哈希种子的环境变量（值为种子，如 `NEVER_JSCORE_HASH_SEED=1302`）
+const HASH_SEED_ENV: &str = "NEVER_JSCORE_HASH_SEED";
+
+/// 确定性模式未设置环境变量时使用的哈希种子
+const DETERMINISTIC_HASH_SEED: u64 = 1302;
+
+/// 读取环境变量中的哈希种子（无效值抛出 ValueError）
+fn hash_seed_from_env() -> PyResult<Option<u64>> {
+    let Ok(value) = std::env::var(HASH_SEED_ENV) else {
+        return Ok(None);
+    };
+    value.trim().parse::<u64>().map(Some).map_err(|_| {
+        PyValueError::new_err(format!("Invalid {}={:?} (expected an integer)", HASH_SEED_ENV, value))
+    })
+}
+
+/// 构造 Context / ThreadedContext / JSEngine 前初始化 V8 平台
+///
+/// V8 默认为每个进程随机选取哈希种子（防御 hash flooding），`--hash-seed` 只能在
+/// 平台初始化前设置且对整个进程生效。平台在第一次构造时初始化：
+/// - 设置了 NEVER_JSCORE_HASH_SEED 时固定为该种子
+/// - 否则第一次构造使用 deterministic=True 时固定为默认种子
+///
+/// 平台已经以随机种子初始化后再使用 deterministic=True，发出 RuntimeWarning：
+/// 对象哈希种子未固定，不同进程之间的输出可能不一致。
+pub fn init_v8_for(py: Python, deterministic: bool) -> PyResult<()> {
+    let seed = hash_seed_from_env()?.or(deterministic.then_some(DETERMINISTIC_HASH_SEED));
+    initialize(seed);
+    if deterministic && !HASH_SEED_PINNED.load(Ordering::SeqCst) {
+        PyErr::warn(
+            py,
+            &py.get_type::<PyRuntimeWarning>(),
+            c"deterministic=True: V8 was already initialized with a random hash seed; create the deterministic Context/JSEngine first or set NEVER_JSCORE_HASH_SEED",
+            1,
+        )?;
+    }
+    Ok(())
+}
+
+/// 初始化 V8 平台（只执行一次），seed 为需要固定的哈希种子
+fn initialize(seed: Option<u64>) {
+    V8_INITIALIZED.get_or_init(|| {
+        if let Some(seed) = seed {
+            deno_core::v8::V8::set_flags_from_string(&format!("--hash-seed={}", seed));
+            HASH_SEED_PINNED.store(true, Ordering::SeqCst);
+        }
+        deno_core::JsRuntime::init_platform(None);
+
+        // Initialize rustls CryptoProvider for HTTPS support
+        #[cfg(feature = "deno_web_api")]
+        {
+            let _ = rustls::crypto::ring::default_provider().install_default();
+        }
+    });
+}
+
 /// 线程本地 Tokio Runtime
 ///
 /// 每个线程有自己独立的单线程 Tokio runtime。
@@ -18,7 +80,8 @@ thread_local! {
 /// 确保 V8 平台已初始化
 ///
 /// 这个函数是幂等的，可以多次调用，但只会初始化一次。
-/// 必须在创建任何 JsRuntime 之前调用。
+/// 必须在创建任何 JsRuntime 之前调用；Python 侧的构造函数先调用 `init_v8_for`，
+/// 这里不会再设置哈希种子。
 ///
 /// # 多线程支持
 ///
@@ -30,10 +93,7 @@ thread_local! {
 /// - 不同线程的 Context 可以并行执行
 /// - 单个 Context 不应跨线程共享（V8 Isolate 限制）
 pub fn ensure_v8_initialized() {
-    // 初始化 V8 平台（全局只执行一次）
-    V8_INITIALIZED.get_or_init(|| {
-        deno_core::JsRuntime::init_platform(None);
-    });
+    initialize(None);
 }
 
 /// 在当前线程的 Tokio Runtime 上执行异步代码
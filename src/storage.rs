@@ -12,8 +12,8 @@ pub struct WorkerId(pub usize);
 
 /// 全局 Hook 数据存储 - 按Worker ID分别存储
 ///
-/// 在调用 terminate_execution() 前保存 Hook 拦截的数据。
-/// 使用全局静态HashMap，每个Worker有独立的存储空间，避免数据竞争。
+/// 仅用于兼容 `JSEngine.get_hook_data(worker_id)`：Hook 数据随调用结果返回
+/// （见 `ResultStorage::take_hook_data`），Worker 同时在这里保留一份最近的数据。
 static HOOK_DATA: Lazy<Arc<Mutex<HashMap<usize, String>>>> = Lazy::new(|| Arc::new(Mutex::new(HashMap::with_capacity(16))));
 
 /// JavaScript 执行结果存储
@@ -26,6 +26,7 @@ pub struct ResultStorage {
     pub value: RefCell<Option<String>>,
     early_return: Cell<bool>,  // 标记是否是提前返回（用于Hook拦截）
     terminated: Cell<bool>,    // 标记是否应该终止runtime
+    hook_data: RefCell<Option<String>>,  // $terminate() 保存的数据（属于当前调用）
 }
 
 impl ResultStorage {
@@ -34,6 +35,7 @@ impl ResultStorage {
             value: RefCell::new(None),
             early_return: Cell::new(false),
             terminated: Cell::new(false),
+            hook_data: RefCell::new(None),
         }
     }
 
@@ -41,6 +43,7 @@ impl ResultStorage {
         *self.value.borrow_mut() = None;
         self.early_return.set(false);
         self.terminated.set(false);
+        *self.hook_data.borrow_mut() = None;
     }
 
     pub fn store(&self, value: String) {
@@ -75,6 +78,16 @@ impl ResultStorage {
     pub fn is_terminated(&self) -> bool {
         self.terminated.get()
     }
+
+    /// 保存 $terminate() 拦截的数据（JSON字符串）
+    pub fn store_hook_data(&self, data: String) {
+        *self.hook_data.borrow_mut() = Some(data);
+    }
+
+    /// 取出当前调用保存的 Hook 数据
+    pub fn take_hook_data(&self) -> Option<String> {
+        self.hook_data.borrow_mut().take()
+    }
 }
 
 impl Default for ResultStorage {
@@ -85,8 +98,7 @@ impl Default for ResultStorage {
 
 /// 保存 Hook 拦截的数据到全局存储
 ///
-/// 这个函数在 JS 调用 __saveAndTerminate__() 时被调用，
-/// 数据会保存到对应Worker ID的存储空间中，即使 V8 isolate 被终止也能访问。
+/// Worker 取得调用的 Hook 数据后调用，供兼容接口 `get_hook_data(worker_id)` 读取。
 ///
 /// # Arguments
 /// * `worker_id` - Worker的唯一标识符
@@ -96,10 +108,7 @@ pub fn save_hook_data_for_worker(worker_id: usize, data: String) {
     guard.insert(worker_id, data);
 }
 
-/// 获取指定Worker保存的 Hook 数据
-///
-/// 从全局存储中读取特定Worker保存的 Hook 数据。
-/// 通常在 JS 被 terminate_execution() 终止后调用。
+/// 获取指定Worker最近一次保存的 Hook 数据
 ///
 /// # Arguments
 /// * `worker_id` - Worker的唯一标识符
@@ -110,7 +119,7 @@ pub fn get_hook_data_for_worker(worker_id: usize) -> Option<String> {
 
 /// 清空指定Worker保存的 Hook 数据
 ///
-/// 在开始新的 JS 执行前调用，避免读取到旧数据。
+/// 供兼容接口 `clear_hook_data(worker_id)` 使用。
 ///
 /// # Arguments
 /// * `worker_id` - Worker的唯一标识符
@@ -118,24 +127,3 @@ pub fn clear_hook_data_for_worker(worker_id: usize) {
     let mut guard = HOOK_DATA.lock().unwrap();
     guard.remove(&worker_id);
 }
-
-/// 保存 Hook 拦截的数据到全局存储（Context API兼容）
-///
-/// 为了向后兼容Context API，使用特殊的worker_id = 0
-pub fn save_hook_data(data: String) {
-    save_hook_data_for_worker(0, data);
-}
-
-/// 获取保存的 Hook 数据（Context API兼容）
-///
-/// 为了向后兼容Context API，从worker_id = 0读取
-pub fn get_hook_data() -> Option<String> {
-    get_hook_data_for_worker(0)
-}
-
-/// 清空保存的 Hook 数据（Context API兼容）
-///
-/// 为了向后兼容Context API，清空worker_id = 0
-pub fn clear_hook_data() {
-    clear_hook_data_for_worker(0);
-}
@@ -0,0 +1,419 @@
+//! 任务调度：每个Worker一个本地队列，空闲Worker从其他队列窃取任务
+//!
+//! 提交任务时选择负载最低的Worker（优先选择正在等待任务的Worker），放入它的本地队列并唤醒它；
+//! Worker优先处理本地队列，本地队列中没有要取的优先级时从该优先级排队最多的队列窃取任务。
+//! 队列锁只在入队/出队时短暂持有（不跨 await），Worker之间不会因为等待同一把锁而串行，
+//! 已退出的Worker（崩溃重建中、已回收）留下的任务也会被其他Worker取走。
+//!
+//! 每个本地队列分为 high / normal / low 三条通道，整个池按加权轮转的顺序决定下一个任务的优先级：
+//! 三个优先级都有任务时，每 7 个任务中 high 占 4 个、normal 占 2 个、low 占 1 个；
+//! 某个优先级在所有队列中都为空时，它的份额按优先级顺序让给其他优先级。
+//! 选定优先级后先取本地队列的对应通道，为空时从其他队列窃取，
+//! 因此其他队列中的高优先级任务不会排在本地的低优先级任务之后；
+//! 低优先级任务在持续的高负载下也能保持进度，不会被饿死。
+//!
+//! 指定Worker的任务（call_on、亲和 key）放入该Worker本地队列的专属通道，不会被窃取，
+//! 同样参与加权轮转：同一优先级先取专属通道，再取本地的普通通道。
+//!
+//! max_queue_size 限制所有队列中的任务总数（共用一个信号量，任务出队时归还名额）。
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore, TryAcquireError};
+
+use crate::worker_pool::{SubmitPolicy, Task};
+
+/// 任务优先级
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Priority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+impl Priority {
+    /// 按优先级从高到低排列
+    pub const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Low];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Priority::High => "high",
+            Priority::Normal => "normal",
+            Priority::Low => "low",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// 加权轮转顺序：每次取任务时优先尝试的通道（high:normal:low = 4:2:1）
+const SCHEDULE: [Priority; 7] = [
+    Priority::High,
+    Priority::Normal,
+    Priority::High,
+    Priority::Low,
+    Priority::High,
+    Priority::Normal,
+    Priority::High,
+];
+
+/// 排队中的任务（持有队列名额，出队时归还）
+struct QueuedTask {
+    task: Task,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+/// 入队失败的原因
+#[derive(Debug)]
+pub enum EnqueueError {
+    /// 队列已关闭
+    Closed,
+    /// 队列已满（Reject 策略）
+    Full,
+    /// 等待队列名额超时（Timeout 策略）
+    Timeout,
+}
+
+/// 本地队列的通道（下标为优先级）
+#[derive(Default)]
+struct Lanes {
+    /// 可以被其他Worker窃取的任务
+    shared: [VecDeque<QueuedTask>; 3],
+    /// 指定由该Worker执行的任务
+    pinned: [VecDeque<QueuedTask>; 3],
+}
+
+impl Lanes {
+    fn get(&mut self, pinned: bool) -> &mut [VecDeque<QueuedTask>; 3] {
+        if pinned {
+            &mut self.pinned
+        } else {
+            &mut self.shared
+        }
+    }
+}
+
+/// 单个Worker的本地队列（按优先级分通道）
+#[derive(Default)]
+struct WorkerQueue {
+    lanes: Mutex<Lanes>,
+    /// 各通道中的任务数（选择Worker、挑选窃取目标时无锁读取）
+    lens: [AtomicUsize; 3],
+    /// 各专属通道中的任务数
+    pinned_lens: [AtomicUsize; 3],
+    notify: Notify,
+    /// Worker正在等待新任务
+    waiting: AtomicBool,
+    /// Worker已就绪，可以接收新任务（未启动、已退出或已回收时为 false）
+    accepting: AtomicBool,
+}
+
+/// Worker等待新任务期间设置 waiting 标记（等待被取消时同样清除）
+struct WaitingGuard<'a>(&'a AtomicBool);
+
+impl<'a> WaitingGuard<'a> {
+    fn new(waiting: &'a AtomicBool) -> Self {
+        waiting.store(true, Ordering::SeqCst);
+        Self(waiting)
+    }
+}
+
+impl Drop for WaitingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+impl WorkerQueue {
+    /// 队列中的任务总数（包括专属通道）
+    fn len(&self) -> usize {
+        self.lens.iter().chain(&self.pinned_lens).map(|len| len.load(Ordering::SeqCst)).sum()
+    }
+
+    fn lens(&self, pinned: bool) -> &[AtomicUsize; 3] {
+        if pinned {
+            &self.pinned_lens
+        } else {
+            &self.lens
+        }
+    }
+}
+
+/// Worker池的任务队列
+pub struct TaskQueue {
+    /// 每个Worker槽位的本地队列（下标为worker_id）
+    queues: Vec<WorkerQueue>,
+    /// 各优先级排队中的任务数（包括专属通道）
+    counts: [AtomicUsize; 3],
+    /// 专属通道中的任务总数
+    pinned: AtomicUsize,
+    /// 队列名额（None 表示不限制）
+    slots: Option<Arc<Semaphore>>,
+    closed: AtomicBool,
+    /// 选择Worker时的起始位置（轮转，避免任务总是落到同一个空闲Worker）
+    cursor: AtomicUsize,
+    /// 加权轮转的位置（所有Worker共用）
+    turn: AtomicUsize,
+}
+
+impl TaskQueue {
+    /// 创建任务队列，max_queue_size 为 None 时不限制长度
+    pub fn new(worker_count: usize, max_queue_size: Option<usize>) -> Self {
+        Self {
+            queues: (0..worker_count).map(|_| WorkerQueue::default()).collect(),
+            counts: Default::default(),
+            pinned: AtomicUsize::new(0),
+            slots: max_queue_size.map(|size| Arc::new(Semaphore::new(size.clamp(1, Semaphore::MAX_PERMITS)))),
+            closed: AtomicBool::new(false),
+            cursor: AtomicUsize::new(0),
+            turn: AtomicUsize::new(0),
+        }
+    }
+
+    /// 按提交策略等待队列名额（不限制长度时返回 None）
+    ///
+    /// 名额随任务入队，任务出队时归还；入队失败时随 permit 一起丢弃。
+    pub async fn acquire(&self, policy: SubmitPolicy) -> Result<Option<OwnedSemaphorePermit>, EnqueueError> {
+        Ok(match &self.slots {
+            None => None,
+            Some(slots) => Some(match policy {
+                SubmitPolicy::Block => Arc::clone(slots)
+                    .acquire_owned()
+                    .await
+                    .map_err(|_| EnqueueError::Closed)?,
+                SubmitPolicy::Reject => Arc::clone(slots).try_acquire_owned().map_err(|e| match e {
+                    TryAcquireError::NoPermits => EnqueueError::Full,
+                    TryAcquireError::Closed => EnqueueError::Closed,
+                })?,
+                SubmitPolicy::Timeout(timeout) => {
+                    match tokio::time::timeout(timeout, Arc::clone(slots).acquire_owned()).await {
+                        Ok(permit) => permit.map_err(|_| EnqueueError::Closed)?,
+                        Err(_) => return Err(EnqueueError::Timeout),
+                    }
+                }
+            }),
+        })
+    }
+
+    /// 入队（持有 acquire 取得的名额）
+    ///
+    /// worker_id 为 None 时选择负载最低的Worker，任务可以被窃取；
+    /// 为 Some 时放入该Worker的专属通道，只由它执行（Worker正在启动或重建时等待其就绪）。
+    pub fn enqueue(
+        &self,
+        task: Task,
+        priority: Priority,
+        permit: Option<OwnedSemaphorePermit>,
+        worker_id: Option<usize>,
+    ) -> Result<(), EnqueueError> {
+        let pinned = worker_id.is_some();
+        let worker_id = worker_id.unwrap_or_else(|| self.select_worker());
+        let queue = &self.queues[worker_id];
+        let idle = {
+            let mut lanes = queue.lanes.lock().unwrap_or_else(PoisonError::into_inner);
+            // 持锁检查：关闭后入队的任务不会再被处理
+            if self.closed.load(Ordering::SeqCst) {
+                return Err(EnqueueError::Closed);
+            }
+            lanes.get(pinned)[priority.index()].push_back(QueuedTask { task, _permit: permit });
+            queue.lens(pinned)[priority.index()].fetch_add(1, Ordering::SeqCst);
+            self.counts[priority.index()].fetch_add(1, Ordering::SeqCst);
+            if pinned {
+                self.pinned.fetch_add(1, Ordering::SeqCst);
+            }
+            queue.waiting.load(Ordering::SeqCst) && queue.accepting.load(Ordering::SeqCst)
+        };
+        queue.notify.notify_one();
+        // 目标Worker正忙（或刚退出）时，唤醒一个空闲Worker来窃取
+        if !idle && !pinned {
+            self.wake_idle(worker_id);
+        }
+        Ok(())
+    }
+
+    /// 选择接收新任务的Worker：优先选择正在等待且队列为空的Worker，否则选择负载最低的
+    fn select_worker(&self) -> usize {
+        let count = self.queues.len();
+        let start = self.cursor.fetch_add(1, Ordering::Relaxed) % count;
+        let mut best: Option<(usize, usize)> = None;
+        let mut shortest = (start, usize::MAX);
+        for offset in 0..count {
+            let worker_id = (start + offset) % count;
+            let queue = &self.queues[worker_id];
+            let len = queue.len();
+            if len < shortest.1 {
+                shortest = (worker_id, len);
+            }
+            if !queue.accepting.load(Ordering::SeqCst) {
+                continue;
+            }
+            let waiting = queue.waiting.load(Ordering::SeqCst);
+            if waiting && len == 0 {
+                return worker_id;
+            }
+            let load = len + usize::from(!waiting);
+            if best.is_none_or(|(_, best_load)| load < best_load) {
+                best = Some((worker_id, load));
+            }
+        }
+        // 没有就绪的Worker（全部在重建）：放入最短的队列，Worker就绪后取走
+        best.unwrap_or(shortest).0
+    }
+
+    /// 唤醒一个正在等待的Worker（不包括 except）
+    fn wake_idle(&self, except: usize) {
+        let idle = self
+            .queues
+            .iter()
+            .enumerate()
+            .find(|(worker_id, queue)| *worker_id != except && queue.waiting.load(Ordering::SeqCst));
+        if let Some((_, queue)) = idle {
+            queue.notify.notify_one();
+        }
+    }
+
+    /// 取下一个任务：先取本地队列，再从其他队列窃取；没有任务时等待
+    ///
+    /// 队列已关闭且所有队列都已取空时返回 None。
+    pub async fn recv(&self, worker_id: usize) -> Option<Task> {
+        let queue = &self.queues[worker_id];
+        loop {
+            if let Some(task) = self.take(worker_id) {
+                return Some(task);
+            }
+            if self.closed.load(Ordering::SeqCst) {
+                // 关闭前提交的任务都已入队，再检查一遍后退出
+                return self.take(worker_id);
+            }
+            let _waiting = WaitingGuard::new(&queue.waiting);
+            // 设置 waiting 后再检查一次，避免提交方在此之前跳过了唤醒
+            if let Some(task) = self.take(worker_id) {
+                return Some(task);
+            }
+            queue.notify.notified().await;
+        }
+    }
+
+    /// 立即取一个任务
+    ///
+    /// 按加权轮转选择优先级：先取本轮的优先级，所有队列中都没有时按优先级取其他的。
+    /// 同一优先级先取本地的专属通道和普通通道，其次窃取该优先级排队最多的队列。
+    fn take(&self, worker_id: usize) -> Option<Task> {
+        let preferred = SCHEDULE[self.turn.load(Ordering::Relaxed) % SCHEDULE.len()];
+        let order = std::iter::once(preferred).chain(Priority::ALL.into_iter().filter(|p| *p != preferred));
+        for priority in order {
+            if self.counts[priority.index()].load(Ordering::SeqCst) == 0 {
+                continue;
+            }
+            let task = self
+                .pop(worker_id, priority, true)
+                .or_else(|| self.pop(worker_id, priority, false))
+                .or_else(|| self.steal(worker_id, priority));
+            if let Some(task) = task {
+                self.turn.fetch_add(1, Ordering::Relaxed);
+                return Some(task);
+            }
+        }
+        None
+    }
+
+    /// 从其他队列窃取指定优先级的任务（该优先级排队最多的队列优先）
+    fn steal(&self, worker_id: usize, priority: Priority) -> Option<Task> {
+        let mut victims: Vec<(usize, usize)> = self
+            .queues
+            .iter()
+            .enumerate()
+            .filter(|(victim, _)| *victim != worker_id)
+            .map(|(victim, queue)| (victim, queue.lens[priority.index()].load(Ordering::SeqCst)))
+            .filter(|(_, len)| *len > 0)
+            .collect();
+        victims.sort_unstable_by_key(|(_, len)| std::cmp::Reverse(*len));
+        victims.into_iter().find_map(|(victim, _)| self.pop(victim, priority, false))
+    }
+
+    /// 从指定队列的指定通道（pinned 为 true 时取专属通道）取出一个任务，更新计数并归还队列名额
+    fn pop(&self, worker_id: usize, priority: Priority, pinned: bool) -> Option<Task> {
+        let queue = &self.queues[worker_id];
+        if queue.lens(pinned)[priority.index()].load(Ordering::SeqCst) == 0 {
+            return None;
+        }
+        let queued = queue.lanes.lock().unwrap_or_else(PoisonError::into_inner).get(pinned)[priority.index()].pop_front()?;
+        queue.lens(pinned)[priority.index()].fetch_sub(1, Ordering::SeqCst);
+        self.counts[priority.index()].fetch_sub(1, Ordering::SeqCst);
+        if pinned {
+            self.pinned.fetch_sub(1, Ordering::SeqCst);
+        }
+        Some(queued.task)
+    }
+
+    /// Worker就绪后开始接收新任务
+    pub fn start_accepting(&self, worker_id: usize) {
+        self.queues[worker_id].accepting.store(true, Ordering::SeqCst);
+    }
+
+    /// 本地队列为空时停止接收新任务（回收空闲Worker），返回是否成功
+    pub fn stop_accepting_if_empty(&self, worker_id: usize) -> bool {
+        let queue = &self.queues[worker_id];
+        let _lanes = queue.lanes.lock().unwrap_or_else(PoisonError::into_inner);
+        if queue.len() > 0 {
+            return false;
+        }
+        queue.accepting.store(false, Ordering::SeqCst);
+        true
+    }
+
+    /// Worker线程退出：停止接收新任务，留下的任务由其他Worker窃取（专属通道中的任务等待Worker重建）
+    pub fn worker_exited(&self, worker_id: usize) {
+        let queue = &self.queues[worker_id];
+        queue.accepting.store(false, Ordering::SeqCst);
+        if queue.len() > 0 {
+            self.wake_idle(worker_id);
+        }
+    }
+
+    /// 关闭队列：不再接收新任务，唤醒正在等待队列名额的提交方（返回 Closed）和等待任务的Worker
+    ///
+    /// Worker取完已排队的任务后 recv() 返回 None。
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        if let Some(slots) = &self.slots {
+            slots.close();
+        }
+        for queue in &self.queues {
+            queue.notify.notify_one();
+        }
+    }
+
+    /// 取出所有剩余任务（Worker全部退出后调用）
+    pub fn drain(&self) -> Vec<Task> {
+        (0..self.queues.len()).flat_map(|worker_id| self.drain_worker(worker_id)).collect()
+    }
+
+    /// 取出指定Worker本地队列中的所有任务（包括专属通道）
+    pub fn drain_worker(&self, worker_id: usize) -> Vec<Task> {
+        [true, false]
+            .into_iter()
+            .flat_map(|pinned| Priority::ALL.map(|priority| (priority, pinned)))
+            .flat_map(|(priority, pinned)| std::iter::from_fn(move || self.pop(worker_id, priority, pinned)))
+            .collect()
+    }
+
+    /// 排队中的任务总数
+    pub fn queued(&self) -> usize {
+        self.queued_by_priority().iter().sum()
+    }
+
+    /// 可以由任意Worker执行的排队任务数（不包括专属通道）
+    pub fn stealable(&self) -> usize {
+        self.queued().saturating_sub(self.pinned.load(Ordering::SeqCst))
+    }
+
+    /// 各优先级排队中的任务数（按 Priority::ALL 的顺序）
+    pub fn queued_by_priority(&self) -> [usize; 3] {
+        Priority::ALL.map(|priority| self.counts[priority.index()].load(Ordering::SeqCst))
+    }
+}
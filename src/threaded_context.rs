@@ -0,0 +1,426 @@
+//! ThreadedContext - 可在任意 Python 线程中使用的 Context
+//!
+//! `Context` 是 `unsendable` 的：V8 Isolate 绑定创建它的线程，
+//! 在 Web 服务器等多线程场景中跨线程共享会 panic。
+//!
+//! ThreadedContext 与 WorkerPool 的思路相同：
+//! - 一个专用 OS 线程持有 Context（及其 Isolate），Isolate 永不跨线程
+//! - Python 线程通过 Channel 提交请求，等待 oneshot 返回结果
+//! - 请求按提交顺序串行执行，全局状态（compile 定义的函数等）在调用之间保持
+
+use anyhow::Result;
+use pyo3::exceptions::{PyException, PyRuntimeError};
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+use serde_json::Value as JsonValue;
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::context::{build_call_code, dispatch_error_reports, dispatch_events, hook_spec_from_python, Context, WaitTimers};
+use crate::convert::json_to_python;
+use crate::errors::hook_terminated;
+
+/// 在 Context 线程上执行的请求
+type Job = Box<dyn FnOnce(&Context) + Send>;
+
+/// 请求结果
+struct JobResult<T> {
+    result: Result<T, String>,
+    /// 本次请求期间上报的未处理错误
+    reports: Vec<JsonValue>,
+    /// $emit() 发送的事件
+    events: Vec<JsonValue>,
+    /// $terminate() 保存的数据
+    hook_data: Option<String>,
+}
+
+/// 线程安全的 JavaScript 执行上下文
+///
+/// 与 Context API 相同，但可以在任意 Python 线程中调用（所有调用按顺序串行执行）。
+///
+/// # Example
+///
+/// ```python
+/// ctx = never_jscore.ThreadedContext()
+/// ctx.compile("function add(a, b) { return a + b; }")
+///
+/// # 在线程池 / Web 框架的请求线程中直接使用
+/// with ThreadPoolExecutor(8) as pool:
+///     results = list(pool.map(lambda i: ctx.call("add", [i, 1]), range(100)))
+/// ```
+#[pyclass]
+pub struct ThreadedContext {
+    tx: Mutex<Option<mpsc::UnboundedSender<Job>>>,
+    handle: Mutex<Option<thread::JoinHandle<()>>>,
+    on_error: Option<Py<PyAny>>,
+    strict_errors: bool,
+    errors: Mutex<Vec<JsonValue>>,
+    on_event: Option<Py<PyAny>>,
+    events: Mutex<Vec<JsonValue>>,
+}
+
+impl ThreadedContext {
+    /// 在 Context 线程上执行 f 并等待结果（释放 GIL）
+    fn run<T, F>(&self, py: Python, f: F) -> PyResult<JobResult<T>>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Context) -> Result<T> + Send + 'static,
+    {
+        let tx = self
+            .tx
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+            .ok_or_else(|| PyRuntimeError::new_err("ThreadedContext has been closed"))?;
+
+        let (result_tx, result_rx) = oneshot::channel::<JobResult<T>>();
+        let job: Job = Box::new(move |ctx: &Context| {
+            let result = f(ctx).map_err(|e| e.to_string());
+            ctx.reset_trace_stacks();
+            let _ = result_tx.send(JobResult {
+                result,
+                reports: ctx.take_error_reports(),
+                events: ctx.take_events(),
+                hook_data: ctx.take_call_hook_data(),
+            });
+        });
+
+        tx.send(job)
+            .map_err(|_| PyRuntimeError::new_err("ThreadedContext has been closed"))?;
+
+        py.allow_threads(move || result_rx.blocking_recv())
+            .map_err(|_| PyRuntimeError::new_err("Context thread died before returning result"))
+    }
+
+    /// 执行请求，记录并分发事件和未处理错误，错误信息加上前缀
+    fn run_reported<T, F>(&self, py: Python, prefix: &str, f: F) -> PyResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Context) -> Result<T> + Send + 'static,
+    {
+        let JobResult { result, reports, events, hook_data } = self.run(py, f)?;
+        // 设置了 on_event 时事件只交给回调，不再记录（同 Context）
+        let dispatched = match &self.on_event {
+            Some(callback) => dispatch_events(py, &events, callback),
+            None => {
+                self.events.lock().unwrap_or_else(PoisonError::into_inner).extend(events);
+                Ok(())
+            }
+        };
+        let result = match (result, hook_data) {
+            (Err(_), Some(data)) => Err(hook_terminated(py, &data, None)),
+            (result, _) => result.map_err(|e| PyException::new_err(format!("{}: {}", prefix, e))),
+        };
+        let result = if reports.is_empty() {
+            result
+        } else {
+            self.errors.lock().unwrap_or_else(PoisonError::into_inner).extend(reports.iter().cloned());
+            dispatch_error_reports(py, &reports, self.on_error.as_ref(), self.strict_errors, result)
+        };
+        // 事件回调抛出的异常在错误上报处理完之后再抛出
+        dispatched.and(result)
+    }
+
+    /// 停止跟踪并还原被包装的函数（由 Trace.stop() 调用，上下文已关闭时无需还原）
+    pub(crate) fn stop_trace(&self, py: Python, trace_id: usize) -> PyResult<()> {
+        if self.closed() {
+            return Ok(());
+        }
+        self.run_reported(py, "Trace error", move |ctx| ctx.uninstall_trace(trace_id))
+    }
+
+    /// 解析 execute_js 返回的 JSON 结果
+    fn parse_result<'py>(py: Python<'py>, result_json: &str) -> PyResult<Bound<'py, PyAny>> {
+        let result: JsonValue = serde_json::from_str(result_json)
+            .map_err(|e| PyException::new_err(format!("JSON parse error: {}", e)))?;
+        json_to_python(py, &result)
+    }
+}
+
+/// Context 线程主函数
+fn context_thread(
+    options: ThreadedOptions,
+    ready: oneshot::Sender<PyResult<()>>,
+    mut rx: mpsc::UnboundedReceiver<Job>,
+) {
+    let ctx = match Context::new(
+        options.enable_extensions,
+        options.enable_logging,
+        options.random_seed,
+        options.enable_node_compat,
+        options.fast_return,
+        options.deterministic,
+        options.strict_errors,
+    ) {
+        Ok(ctx) => {
+            let _ = ready.send(Ok(()));
+            ctx
+        }
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+
+    // 所有 Sender 被 drop（close() 或对象销毁）时退出
+    while let Some(job) = rx.blocking_recv() {
+        job(&ctx);
+    }
+}
+
+/// Context 构造参数
+struct ThreadedOptions {
+    enable_extensions: bool,
+    enable_logging: bool,
+    random_seed: Option<u32>,
+    enable_node_compat: bool,
+    fast_return: bool,
+    deterministic: bool,
+    strict_errors: bool,
+}
+
+#[pymethods]
+impl ThreadedContext {
+    /// 创建线程安全的 JavaScript 执行上下文
+    ///
+    /// 参数与 Context 相同。Context 在专用线程中创建，构造失败时在这里抛出异常。
+    #[new]
+    #[pyo3(signature = (enable_extensions=true, enable_logging=false, random_seed=None, enable_node_compat=false, fast_return=false, deterministic=false, on_error=None, strict_errors=false, on_event=None))]
+    fn new(
+        py: Python,
+        enable_extensions: bool,
+        enable_logging: bool,
+        random_seed: Option<u32>,
+        enable_node_compat: bool,
+        fast_return: bool,
+        deterministic: bool,
+        on_error: Option<Py<PyAny>>,
+        strict_errors: bool,
+        on_event: Option<Py<PyAny>>,
+    ) -> PyResult<Self> {
+        crate::runtime::init_v8_for(py, deterministic)?;
+
+        let options = ThreadedOptions {
+            enable_extensions,
+            enable_logging,
+            random_seed,
+            enable_node_compat,
+            fast_return,
+            deterministic,
+            strict_errors,
+        };
+
+        let (tx, rx) = mpsc::unbounded_channel::<Job>();
+        let (ready_tx, ready_rx) = oneshot::channel();
+
+        let handle = thread::Builder::new()
+            .name("jscore_threaded_context".to_string())
+            .spawn(move || context_thread(options, ready_tx, rx))
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to spawn context thread: {}", e)))?;
+
+        // 等待 Context 创建完成（扩展加载等）
+        py.allow_threads(move || ready_rx.blocking_recv())
+            .map_err(|_| PyRuntimeError::new_err("Context thread died during initialization"))??;
+
+        Ok(ThreadedContext {
+            tx: Mutex::new(Some(tx)),
+            handle: Mutex::new(Some(handle)),
+            on_error,
+            strict_errors,
+            errors: Mutex::new(Vec::new()),
+            on_event,
+            events: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// 编译JavaScript代码（等价于 eval(code)），函数/变量加入全局作用域
+    ///
+    /// name 参数含义同 Context.compile
+    #[pyo3(signature = (code, name=None))]
+    fn compile(&self, py: Python, code: String, name: Option<String>) -> PyResult<()> {
+        self.run_reported(py, "Compile error", move |ctx| match &name {
+            Some(name) => ctx.exec_named_script(&code, name),
+            None => ctx.exec_script(&code),
+        })
+    }
+
+    /// 为全局函数安装 Hook（参数含义同 Context.hook），Python 回调在 Context 线程上调用
+    #[pyo3(signature = (path, action="log", replacement=None, capture_args=true, capture_return=true))]
+    fn hook(
+        &self,
+        py: Python,
+        path: String,
+        action: &str,
+        replacement: Option<&Bound<'_, PyAny>>,
+        capture_args: bool,
+        capture_return: bool,
+    ) -> PyResult<()> {
+        let hook = hook_spec_from_python(path, action, replacement, capture_args, capture_return)?;
+        self.run_reported(py, "Hook error", move |ctx| ctx.install_hook(&hook))
+    }
+
+    /// 跟踪函数调用（参数含义同 Context.trace）
+    #[pyo3(signature = (targets, max_events=100000, capture_args=true, capture_return=true))]
+    fn trace(
+        slf: &Bound<'_, Self>,
+        py: Python,
+        targets: Vec<String>,
+        max_events: usize,
+        capture_args: bool,
+        capture_return: bool,
+    ) -> PyResult<crate::trace::Trace> {
+        let (trace_id, buffer) = slf.borrow().run_reported(py, "Trace error", move |ctx| {
+            ctx.install_trace(&targets, max_events, capture_args, capture_return)
+        })?;
+        let owner = crate::trace::TraceOwner::Threaded(slf.clone().unbind());
+        Ok(crate::trace::Trace::new(buffer, trace_id, owner))
+    }
+
+    /// 调用 JavaScript 函数（参数含义同 Context.call）
+    #[pyo3(signature = (name, args, auto_await=None, wait_timers=None))]
+    fn call<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        args: &Bound<'_, PyAny>,
+        auto_await: Option<bool>,
+        wait_timers: Option<&str>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let wait_timers = WaitTimers::parse(wait_timers)?;
+        let call_code = build_call_code(&name, args)?;
+        let result_json = self.run_reported(py, "Call error", move |ctx| {
+            ctx.execute_js(&call_code, auto_await.unwrap_or(true), wait_timers)
+        })?;
+        Self::parse_result(py, &result_json)
+    }
+
+    /// 执行代码并将其加入全局作用域（参数含义同 Context.eval）
+    #[pyo3(signature = (code, return_value=false, auto_await=None, wait_timers=None))]
+    fn eval<'py>(
+        &self,
+        py: Python<'py>,
+        code: String,
+        return_value: bool,
+        auto_await: Option<bool>,
+        wait_timers: Option<&str>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let wait_timers = WaitTimers::parse(wait_timers)?;
+        if return_value {
+            let result_json = self.run_reported(py, "Eval error", move |ctx| {
+                ctx.execute_js(&code, auto_await.unwrap_or(true), wait_timers)
+            })?;
+            Self::parse_result(py, &result_json)
+        } else {
+            self.run_reported(py, "Eval error", move |ctx| ctx.exec_script(&code))?;
+            Ok(py.None().into_bound(py))
+        }
+    }
+
+    /// 执行代码并返回结果，不影响全局作用域（参数含义同 Context.evaluate）
+    #[pyo3(signature = (code, auto_await=None, wait_timers=None))]
+    fn evaluate<'py>(
+        &self,
+        py: Python<'py>,
+        code: String,
+        auto_await: Option<bool>,
+        wait_timers: Option<&str>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let wait_timers = WaitTimers::parse(wait_timers)?;
+        let result_json = self.run_reported(py, "Evaluate error", move |ctx| {
+            ctx.execute_js(&code, auto_await.unwrap_or(true), wait_timers)
+        })?;
+        Self::parse_result(py, &result_json)
+    }
+
+    /// 运行事件循环直到空闲（同 Context.run_until_idle）
+    #[pyo3(signature = (timeout_ms=None))]
+    fn run_until_idle(&self, py: Python, timeout_ms: Option<u64>) -> PyResult<bool> {
+        self.run_reported(py, "Event loop error", move |ctx| ctx.run_until_idle_inner(timeout_ms))
+    }
+
+    /// 已收集的未处理错误（同 Context.errors）
+    #[getter]
+    fn errors(&self, py: Python) -> PyResult<Py<PyList>> {
+        let list = PyList::empty(py);
+        for error in self.errors.lock().unwrap_or_else(PoisonError::into_inner).iter() {
+            list.append(json_to_python(py, error)?)?;
+        }
+        Ok(list.into())
+    }
+
+    /// 清空已收集的未处理错误
+    fn clear_errors(&self) {
+        self.errors.lock().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+
+    /// 已收集的 $emit() 事件（同 Context.events）
+    #[pyo3(signature = (channel=None))]
+    fn events(&self, py: Python, channel: Option<&str>) -> PyResult<Py<PyList>> {
+        let list = PyList::empty(py);
+        for event in self.events.lock().unwrap_or_else(PoisonError::into_inner).iter() {
+            if channel.is_none_or(|channel| event["channel"] == channel) {
+                list.append(json_to_python(py, event)?)?;
+            }
+        }
+        Ok(list.into())
+    }
+
+    /// 清空已收集的 $emit() 事件
+    fn clear_events(&self) {
+        self.events.lock().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+
+    /// 请求垃圾回收
+    fn gc(&self, py: Python) -> PyResult<()> {
+        self.run_reported(py, "GC error", |ctx| ctx.request_gc())
+    }
+
+    /// 关闭上下文
+    ///
+    /// 等待已提交的请求执行完毕后结束 Context 线程；之后的调用抛出 RuntimeError。
+    /// 可重复调用。
+    fn close(&self, py: Python) {
+        drop(self.tx.lock().unwrap_or_else(PoisonError::into_inner).take());
+        if let Some(handle) = self.handle.lock().unwrap_or_else(PoisonError::into_inner).take() {
+            py.allow_threads(move || {
+                let _ = handle.join();
+            });
+        }
+    }
+
+    /// 是否已关闭
+    #[getter]
+    fn closed(&self) -> bool {
+        self.tx.lock().unwrap_or_else(PoisonError::into_inner).is_none()
+    }
+
+    /// 上下文管理器支持 - __enter__
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// 上下文管理器支持 - __exit__，关闭上下文
+    fn __exit__(
+        &self,
+        py: Python,
+        _exc_type: &Bound<PyAny>,
+        _exc_value: &Bound<PyAny>,
+        _traceback: &Bound<PyAny>,
+    ) -> PyResult<bool> {
+        self.close(py);
+        Ok(false)
+    }
+
+    /// 字符串表示
+    fn __repr__(&self) -> String {
+        format!("ThreadedContext(closed={})", self.closed())
+    }
+}
+
+impl Drop for ThreadedContext {
+    fn drop(&mut self) {
+        // drop Sender 后 Context 线程处理完剩余请求即退出（不在这里 join，避免阻塞 GC）
+        self.tx.lock().unwrap_or_else(PoisonError::into_inner).take();
+    }
+}
@@ -0,0 +1,201 @@
+//! Trace - 函数调用跟踪结果
+//!
+//! `ctx.trace(targets)` 返回 Trace 对象。被跟踪函数的每次调用都记录在 Rust 侧缓冲区中
+//! （见 ext/hook/trace.rs），Trace 只持有该缓冲区的引用，读取时才转换为 Python 对象：
+//! - calls(): 调用树（每个调用的 children 为它直接调用的被跟踪函数）
+//! - events(): 按开始顺序排列的扁平列表
+//! - to_chrome_trace() / save(): Chrome trace-event JSON，可在 Perfetto 中查看
+//!
+//! stop() 停止记录并还原被包装的函数，因此 Trace 还持有创建它的 Context / ThreadedContext。
+
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use serde_json::{json, Value as JsonValue};
+use std::sync::{MutexGuard, PoisonError};
+
+use crate::context::Context;
+use crate::convert::json_to_python;
+use crate::ext::hook::trace::{SharedTrace, TraceBuffer, TraceCall};
+use crate::threaded_context::ThreadedContext;
+
+fn us_to_ms(us: f64) -> f64 {
+    us / 1000.0
+}
+
+/// 单个调用转换为 JSON（不含 children）
+fn call_to_json(buffer: &TraceBuffer, call: &TraceCall) -> serde_json::Map<String, JsonValue> {
+    let caller = call.parent.and_then(|parent| buffer.calls().get(parent)).map(|parent| parent.name.clone());
+    let mut value = json!({
+        "name": call.name,
+        "args": call.args,
+        "return": call.result,
+        "error": call.error,
+        "caller": caller,
+        "start": us_to_ms(call.start_us),
+        "end": call.end_us.map(us_to_ms),
+        "duration": call.end_us.map(|end| us_to_ms(end - call.start_us)),
+    });
+    if let Some(settle_us) = call.settle_us {
+        value["settled"] = json!(us_to_ms(settle_us));
+    }
+    match value {
+        JsonValue::Object(map) => map,
+        _ => unreachable!(),
+    }
+}
+
+/// 创建 Trace 的上下文（停止时在其中还原被包装的函数）
+pub enum TraceOwner {
+    Context(Py<Context>),
+    Threaded(Py<ThreadedContext>),
+}
+
+/// 函数调用跟踪结果（由 Context.trace() 创建）
+///
+/// 时间单位为毫秒，从调用 trace() 时开始计时。
+#[pyclass]
+pub struct Trace {
+    buffer: SharedTrace,
+    trace_id: usize,
+    owner: TraceOwner,
+}
+
+impl Trace {
+    pub fn new(buffer: SharedTrace, trace_id: usize, owner: TraceOwner) -> Self {
+        Trace { buffer, trace_id, owner }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TraceBuffer> {
+        self.buffer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[pymethods]
+impl Trace {
+    /// 获取调用树
+    ///
+    /// Returns:
+    ///     顶层调用列表，每个调用为
+    ///     {"name", "args", "return", "error", "caller", "start", "end", "duration", "children"}。
+    ///     未记录的参数/返回值为 None；返回 Promise 的调用 "return" 为 resolve 的值，
+    ///     并额外包含 "settled"（Promise 完成的时间）
+    fn calls(&self, py: Python) -> PyResult<Py<PyAny>> {
+        let buffer = self.lock();
+        let calls = buffer.calls();
+
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); calls.len()];
+        let mut roots = Vec::new();
+        for (id, call) in calls.iter().enumerate() {
+            match call.parent {
+                Some(parent) => children[parent].push(id),
+                None => roots.push(id),
+            }
+        }
+
+        // 子调用的下标总是大于父调用，从后往前构建即可
+        let mut built: Vec<Option<JsonValue>> = vec![None; calls.len()];
+        for id in (0..calls.len()).rev() {
+            let mut value = call_to_json(&buffer, &calls[id]);
+            let nested: Vec<JsonValue> = children[id].iter().filter_map(|&child| built[child].take()).collect();
+            value.insert("children".to_string(), JsonValue::Array(nested));
+            built[id] = Some(JsonValue::Object(value));
+        }
+        let tree: Vec<JsonValue> = roots.iter().filter_map(|&id| built[id].take()).collect();
+        Ok(json_to_python(py, &JsonValue::Array(tree))?.unbind())
+    }
+
+    /// 获取按开始顺序排列的扁平调用列表
+    ///
+    /// Returns:
+    ///     调用列表，字段同 calls()（不含 children），另有 "id"、"parent"（父调用的 id）和 "depth"
+    fn events(&self, py: Python) -> PyResult<Py<PyAny>> {
+        let buffer = self.lock();
+        let events: Vec<JsonValue> = buffer
+            .calls()
+            .iter()
+            .enumerate()
+            .map(|(id, call)| {
+                let mut value = call_to_json(&buffer, call);
+                value.insert("id".to_string(), json!(id));
+                value.insert("parent".to_string(), json!(call.parent));
+                value.insert("depth".to_string(), json!(call.depth));
+                JsonValue::Object(value)
+            })
+            .collect();
+        Ok(json_to_python(py, &JsonValue::Array(events))?.unbind())
+    }
+
+    /// 导出为 Chrome trace-event JSON 字符串（可在 https://ui.perfetto.dev 或 chrome://tracing 打开）
+    fn to_chrome_trace(&self) -> String {
+        self.lock().to_chrome_trace().to_string()
+    }
+
+    /// 将 Chrome trace-event JSON 写入文件
+    ///
+    /// Args:
+    ///     path: 输出文件路径
+    fn save(&self, path: std::path::PathBuf) -> PyResult<()> {
+        std::fs::write(&path, self.to_chrome_trace())
+            .map_err(|e| PyException::new_err(format!("Failed to save trace to {}: {}", path.display(), e)))
+    }
+
+    /// 停止记录并还原被跟踪的函数（已记录的调用保留，重复调用无效果）
+    fn stop(&self, py: Python) -> PyResult<()> {
+        {
+            let mut buffer = self.lock();
+            if !buffer.is_active() {
+                return Ok(());
+            }
+            buffer.stop();
+        }
+        match &self.owner {
+            TraceOwner::Context(ctx) => ctx.borrow(py).stop_trace(py, self.trace_id),
+            TraceOwner::Threaded(ctx) => ctx.borrow(py).stop_trace(py, self.trace_id),
+        }
+    }
+
+    /// 是否仍在记录
+    #[getter]
+    fn active(&self) -> bool {
+        self.lock().is_active()
+    }
+
+    /// 超过 max_events 而未记录的调用数
+    #[getter]
+    fn dropped(&self) -> usize {
+        self.lock().dropped()
+    }
+
+    fn __len__(&self) -> usize {
+        self.lock().calls().len()
+    }
+
+    /// 上下文管理器支持 - __enter__
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// 上下文管理器支持 - __exit__
+    ///
+    /// 退出时停止记录并还原被跟踪的函数
+    fn __exit__(
+        &self,
+        py: Python,
+        _exc_type: &Bound<'_, PyAny>,
+        _exc_value: &Bound<'_, PyAny>,
+        _traceback: &Bound<'_, PyAny>,
+    ) -> PyResult<bool> {
+        self.stop(py)?;
+        Ok(false)
+    }
+
+    fn __repr__(&self) -> String {
+        let buffer = self.lock();
+        format!(
+            "Trace(calls={}, dropped={}, active={})",
+            buffer.calls().len(),
+            buffer.dropped(),
+            if buffer.is_active() { "True" } else { "False" }
+        )
+    }
+}
@@ -4,30 +4,51 @@
 //! - 管理多个持久化的Worker线程
 //! - 每个Worker持有一个预加载了JS代码的JsRuntime
 //! - Worker永久存活，重复使用，避免重复加载JS代码
-//! - 通过Channel队列分发任务到空闲Worker
+//! - 任务分发到各Worker的本地队列（优先交给空闲Worker），空闲Worker从其他队列窃取任务；
+//!   队列分为高/普通/低三个优先级，按加权轮转出队（见 task_queue）；
+//!   指定Worker和亲和 key 的任务放入该Worker不可窃取的专属通道
+//! - 可选生命周期策略：处理任务数/堆内存达到上限时重建JsRuntime（重新加载init_code）
+//! - 监督线程：Worker线程初始化失败或panic退出后按退避时间自动重建
+//! - 每个Worker有专属收件箱，用于广播（每个Worker执行一次）和热重载；
+//!   持久化的广播记录在回放日志中，重建JsRuntime后自动回放
+//! - 热重载：逐个Worker切换到新的初始化代码，其余Worker继续服务；失败时回滚到旧代码
+//! - 取消：排队中的任务出队时跳过，执行中的任务终止执行并重建JsRuntime
+//! - 关闭：停止接收新任务，处理完（或取消）已排队的任务后Worker退出并释放isolate
+//! - 亲和路由：相同 key 的任务始终交给同一个Worker（最高随机权重哈希），
+//!   该Worker意外退出时只有它负责的 key 迁移到其他Worker
+//! - 弹性伸缩：启动 min_workers 个Worker，排队任务多于空闲Worker时按需增加到 worker_count 个，
+//!   空闲超过 idle_timeout 的Worker退出（不少于 min_workers 个）；新Worker使用相同的配置和初始化代码
 //!
 //! 解决的问题：
 //! - V8 Isolate不能跨线程传输
 //! - JS代码重复加载导致性能低下
 //! - 多线程场景下的资源复用
 
-use tokio::sync::{mpsc, oneshot};
-use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, oneshot, watch, OwnedSemaphorePermit};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::thread;
+use std::time::{Duration, Instant};
 use std::rc::Rc;
 use serde_json::Value as JsonValue;
-use deno_core::{JsRuntime, RuntimeOptions, PollEventLoopOptions};
+use deno_core::{v8, JsRuntime, RuntimeOptions, PollEventLoopOptions};
 use anyhow::Result;
 
 use crate::ext::{ExtensionOptions, all_extensions};
-use crate::storage::{ResultStorage, WorkerId, get_hook_data_for_worker, clear_hook_data_for_worker};
+use crate::storage::{ResultStorage, WorkerId, save_hook_data_for_worker};
+use crate::ext::hook::HookSpec;
+use crate::metrics::{PoolCounters, PoolMetrics, PoolStats};
+use crate::task_queue::{EnqueueError, Priority, TaskQueue};
 use crate::runtime::ensure_v8_initialized;
 
 #[cfg(feature = "node_compat")]
 use crate::node_compat::NodeCompatOptions;
 
 /// 任务类型
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum TaskType {
     /// 执行代码片段
     Execute {
@@ -38,12 +59,400 @@ pub enum TaskType {
         func_name: String,
         args: Vec<JsonValue>,
     },
+    /// 在同一个Worker上依次调用同一函数（批量调用，分摊调度开销）
+    ///
+    /// 结果为数组，每项为 `{"value": ...}`、`{"error": "..."}` 或
+    /// `{"hook": "...", "worker_id": N}`（$terminate() 拦截），
+    /// 单项失败不影响同批次的其他调用。
+    CallBatch {
+        func_name: String,
+        args_list: Vec<Vec<JsonValue>>,
+    },
+    /// 广播到每个Worker的任务（只经由Worker收件箱投递）
+    ///
+    /// seq 为回放日志序号（None 表示不记录，重建JsRuntime后不回放）。
+    Broadcast {
+        seq: Option<u64>,
+        task: Box<TaskType>,
+    },
+    /// 使用新的初始化代码重建JsRuntime（只经由Worker收件箱投递）
+    Reload { init_code: Option<String> },
 }
 
 /// 任务定义
 pub struct Task {
     pub task_type: TaskType,
-    pub tx: oneshot::Sender<Result<JsonValue, String>>,
+    /// 本次调用使用的随机数种子（执行完后恢复Worker原有的随机数状态）
+    pub seed: Option<u64>,
+    /// 取消标记（可取消的任务，见 JSFuture）
+    pub cancel: Option<Arc<CancelToken>>,
+    /// 收集本次调用 $emit() 发送的事件（None 时丢弃）
+    pub events: Option<EventSink>,
+    pub tx: oneshot::Sender<TaskResult>,
+}
+
+/// $emit() 事件收集器，Worker在发送结果前写入
+pub type EventSink = Arc<Mutex<Vec<JsonValue>>>;
+
+/// 任务执行结果
+pub type TaskResult = Result<JsonValue, TaskError>;
+
+/// 任务失败原因
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskError {
+    /// JS异常或内部错误
+    Failed(String),
+    /// JS调用 $terminate() 终止了执行，data 为保存的 Hook 数据（JSON字符串）
+    Hook { worker_id: usize, data: String },
+    /// 任务没有提交到Worker池（批量调用中队列已满或Worker池已关闭后剩余的调用）
+    Unsubmitted(SubmitError),
+}
+
+impl TaskError {
+    /// 是否为执行中被取消的任务
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, TaskError::Failed(msg) if msg == TASK_CANCELLED_ERROR)
+    }
+}
+
+impl From<String> for TaskError {
+    fn from(msg: String) -> Self {
+        TaskError::Failed(msg)
+    }
+}
+
+impl fmt::Display for TaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskError::Failed(msg) => f.write_str(msg),
+            TaskError::Hook { worker_id, .. } => {
+                write!(f, "Execution terminated by $terminate() on worker {}", worker_id)
+            }
+            TaskError::Unsubmitted(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// 被取消的任务返回的错误
+pub const TASK_CANCELLED_ERROR: &str = "Task was cancelled";
+
+/// 任务取消标记
+///
+/// 排队中的任务被取消后，Worker出队时直接跳过；执行中的任务通过 IsolateHandle 终止
+/// 同步执行的JS，等待中的异步任务直接放弃。被取消的任务可能留下不完整的全局状态和
+/// 待执行的定时器，Worker随后重建JsRuntime。
+pub struct CancelToken {
+    cancelled: watch::Sender<bool>,
+    /// 正在执行该任务的isolate（执行前登记，执行后清除）
+    running: Mutex<Option<v8::IsolateHandle>>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self {
+            cancelled: watch::Sender::new(false),
+            running: Mutex::new(None),
+        }
+    }
+
+    /// 取消任务；正在执行时终止执行
+    pub fn cancel(&self) {
+        // 持锁期间终止，保证不会终止Worker已开始执行的下一个任务
+        let running = self.running.lock().unwrap_or_else(PoisonError::into_inner);
+        self.cancelled.send_replace(true);
+        if let Some(handle) = running.as_ref() {
+            handle.terminate_execution();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancelled.borrow()
+    }
+
+    /// 任务是否正在执行
+    pub fn is_running(&self) -> bool {
+        self.running.lock().unwrap_or_else(PoisonError::into_inner).is_some()
+    }
+
+    /// 登记开始执行，已取消时返回 false
+    fn start(&self, handle: v8::IsolateHandle) -> bool {
+        let mut running = self.running.lock().unwrap_or_else(PoisonError::into_inner);
+        if self.is_cancelled() {
+            return false;
+        }
+        *running = Some(handle);
+        true
+    }
+
+    /// 登记执行结束，返回执行期间是否被取消
+    fn finish(&self) -> bool {
+        let mut running = self.running.lock().unwrap_or_else(PoisonError::into_inner);
+        *running = None;
+        self.is_cancelled()
+    }
+
+    /// 等待任务被取消
+    async fn cancelled(&self) {
+        let mut rx = self.cancelled.subscribe();
+        let _ = rx.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+/// 队列已满时的提交策略
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SubmitPolicy {
+    /// 等待队列有空位（默认）
+    #[default]
+    Block,
+    /// 立即拒绝
+    Reject,
+    /// 最多等待指定时间，超时后拒绝
+    Timeout(Duration),
+}
+
+/// 任务交给哪个Worker执行
+#[derive(Clone, Debug)]
+pub enum Target {
+    /// 任意Worker（经由任务队列调度）
+    Any,
+    /// 指定Worker（call_on）
+    Worker(usize),
+    /// 按亲和 key 选择的Worker
+    Key(String),
+}
+
+/// 提交任务失败的原因
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubmitError {
+    /// Worker池已关闭
+    Closed,
+    /// 没有可用的Worker（全部退出，正在等待重建）
+    Unavailable,
+    /// 指定的Worker不可用（已退出，正在等待重建）
+    WorkerUnavailable(usize),
+    /// 队列已满，任务被拒绝
+    Overloaded(String),
+}
+
+impl std::fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubmitError::Closed => write!(f, "Worker pool has been closed"),
+            SubmitError::Unavailable => write!(f, "No healthy workers available (workers are being respawned)"),
+            SubmitError::WorkerUnavailable(worker_id) => {
+                write!(f, "Worker {} is not available (it is being respawned)", worker_id)
+            }
+            SubmitError::Overloaded(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// 弹性伸缩时空闲Worker的默认回收时间
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 重建Worker的初始退避时间（连续失败时翻倍）
+const RESPAWN_BACKOFF_BASE: Duration = Duration::from_millis(100);
+/// 重建Worker的最大退避时间
+const RESPAWN_BACKOFF_MAX: Duration = Duration::from_secs(10);
+
+/// 发送给监督线程的事件
+enum SupervisorEvent {
+    /// Worker线程退出（initialized 表示退出前是否初始化成功过，retired 表示空闲回收）
+    Exited {
+        worker_id: usize,
+        initialized: bool,
+        retired: bool,
+    },
+    /// 排队任务多于空闲Worker，请求增加Worker（弹性伸缩）
+    ScaleUp,
+    /// 启动已启用的空闲槽位（亲和 key 落在未启动的槽位上）
+    Start { worker_id: usize },
+    /// Worker池关闭，停止监督
+    Shutdown,
+}
+
+/// Worker专属收件箱（广播和热重载任务）
+///
+/// 保存在共享状态中，Worker重建后新线程继续使用同一个收件箱，未处理的任务不会丢失。
+struct WorkerInbox {
+    tx: mpsc::UnboundedSender<Task>,
+    rx: tokio::sync::Mutex<mpsc::UnboundedReceiver<Task>>,
+    /// 已从收件箱取出的最大广播序号
+    delivered_seq: AtomicU64,
+}
+
+impl WorkerInbox {
+    fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            tx,
+            rx: tokio::sync::Mutex::new(rx),
+            delivered_seq: AtomicU64::new(0),
+        }
+    }
+}
+
+/// 持久化广播的回放日志
+#[derive(Default)]
+struct BroadcastLog {
+    last_seq: u64,
+    entries: Vec<(u64, TaskType)>,
+}
+
+/// Worker线程与监督线程共享的状态
+struct WorkerShared {
+    /// 任务队列（每个Worker一个本地队列，关闭后Worker处理完已排队的任务退出）
+    tasks: TaskQueue,
+    /// 每个Worker槽位是否启用（下标为worker_id；弹性伸缩时未启动或已回收的槽位为 false）
+    ///
+    /// 向收件箱/专属通道投递任务和回收Worker都在持锁期间进行，回收的Worker中不会遗留任务。
+    active: Mutex<Vec<bool>>,
+    inboxes: Vec<WorkerInbox>,
+    broadcast_log: Mutex<BroadcastLog>,
+    /// 每个Worker当前使用的初始化代码（下标为worker_id，重建JsRuntime和重建Worker时使用）
+    init_codes: Mutex<Vec<Option<String>>>,
+    /// 同一时刻只允许一次热重载
+    reload_lock: tokio::sync::Mutex<()>,
+    config: WorkerPoolConfig,
+    /// Worker因生命周期策略重建JsRuntime的次数
+    recycled: AtomicU64,
+    /// 已初始化且仍在运行的Worker数
+    healthy: AtomicUsize,
+    /// 每个Worker是否已初始化且仍在运行（下标为worker_id）
+    worker_healthy: Vec<AtomicBool>,
+    /// 每个Worker槽位是否因意外退出而失效（下标为worker_id，重建后初始化成功前为 true）
+    failed: Vec<AtomicBool>,
+    /// 每个Worker槽位是否承载过亲和 key（下标为worker_id，保存着会话状态，不做空闲回收）
+    pinned: Vec<AtomicBool>,
+    /// Worker因失败被监督线程重建的次数
+    respawned: AtomicU64,
+    /// 弹性伸缩增加/回收Worker的次数
+    scaled_up: AtomicU64,
+    scaled_down: AtomicU64,
+    /// Worker线程句柄（下标为worker_id，重建时替换）
+    handles: Mutex<Vec<Option<thread::JoinHandle<()>>>>,
+    events: std::sync::mpsc::Sender<SupervisorEvent>,
+    /// Worker池已关闭，Worker退出不再重建
+    shutdown: AtomicBool,
+    /// 取消尚未执行的任务（返回错误而不是执行）
+    cancel: AtomicBool,
+    metrics: PoolMetrics,
+}
+
+/// 关闭时被取消的任务返回的错误
+const CANCELLED_ERROR: &str = "JSEngine has been closed; queued task was cancelled";
+
+impl WorkerShared {
+    /// 最少保持的Worker数
+    fn min_workers(&self) -> usize {
+        self.config
+            .min_workers
+            .unwrap_or(self.config.worker_count)
+            .min(self.config.worker_count)
+    }
+
+    /// 是否按负载伸缩Worker数量
+    fn is_elastic(&self) -> bool {
+        self.min_workers() < self.config.worker_count
+    }
+
+    /// 排队任务数超过空闲Worker数（包括正在启动的Worker）且未达到上限时需要增加Worker
+    fn needs_worker(&self, active: &[bool]) -> bool {
+        let active_count = active.iter().filter(|&&active| active).count();
+        if active_count >= self.config.worker_count {
+            return false;
+        }
+        let busy = active
+            .iter()
+            .enumerate()
+            .filter(|&(worker_id, &active)| active && self.metrics.workers[worker_id].busy.load(Ordering::Relaxed))
+            .count();
+        self.tasks.stealable() > active_count - busy
+    }
+
+    /// 启用空闲槽位（调用方按先回放日志再槽位的顺序持有两把锁）
+    ///
+    /// 新Worker启动时回放完整的广播日志，不需要等待收件箱中更早的广播。
+    fn activate(&self, log: &BroadcastLog, active: &mut [bool], worker_id: usize) {
+        self.inboxes[worker_id].delivered_seq.store(log.last_seq, Ordering::SeqCst);
+        active[worker_id] = true;
+    }
+}
+
+/// Worker初始化结果（只在Worker池创建时使用）
+type ReadySender = std::sync::mpsc::Sender<(usize, Result<(), String>)>;
+
+/// Worker线程退出时（包括panic）更新健康计数并通知监督线程
+struct ExitNotifier {
+    worker_id: usize,
+    shared: Arc<WorkerShared>,
+    healthy: bool,
+    /// 空闲回收（监督线程不重建）
+    retired: bool,
+}
+
+impl ExitNotifier {
+    fn mark_healthy(&mut self) {
+        if !self.healthy {
+            self.healthy = true;
+            self.shared.worker_healthy[self.worker_id].store(true, Ordering::SeqCst);
+            self.shared.failed[self.worker_id].store(false, Ordering::SeqCst);
+            self.shared.healthy.fetch_add(1, Ordering::SeqCst);
+            self.shared.tasks.start_accepting(self.worker_id);
+        }
+    }
+
+    /// 空闲超时后尝试回收当前Worker（弹性伸缩），成功时Worker应退出
+    ///
+    /// Worker数不多于 min_workers、承载过亲和 key、或收件箱/本地队列中还有任务时不回收。
+    fn try_retire(&mut self, inbox_rx: &mpsc::UnboundedReceiver<Task>) -> bool {
+        let shared = Arc::clone(&self.shared);
+        let mut active = shared.active.lock().unwrap_or_else(PoisonError::into_inner);
+        let active_count = active.iter().filter(|&&active| active).count();
+        if shared.shutdown.load(Ordering::SeqCst)
+            || active_count <= shared.min_workers()
+            || shared.pinned[self.worker_id].load(Ordering::SeqCst)
+            || !inbox_rx.is_empty()
+            || !shared.tasks.stop_accepting_if_empty(self.worker_id)
+        {
+            return false;
+        }
+        active[self.worker_id] = false;
+        if self.healthy {
+            self.healthy = false;
+            shared.worker_healthy[self.worker_id].store(false, Ordering::SeqCst);
+            shared.healthy.fetch_sub(1, Ordering::SeqCst);
+        }
+        self.retired = true;
+
+        // 已回收的Worker不再占用堆内存
+        let metrics = &shared.metrics.workers[self.worker_id];
+        metrics.heap_used.store(0, Ordering::Relaxed);
+        metrics.heap_total.store(0, Ordering::Relaxed);
+        shared.scaled_down.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+}
+
+impl Drop for ExitNotifier {
+    fn drop(&mut self) {
+        // panic 时任务没有正常结束
+        self.shared.metrics.workers[self.worker_id].busy.store(false, Ordering::Relaxed);
+        // 本地队列中剩余的任务由其他Worker窃取
+        self.shared.tasks.worker_exited(self.worker_id);
+        if self.healthy {
+            self.shared.worker_healthy[self.worker_id].store(false, Ordering::SeqCst);
+            self.shared.healthy.fetch_sub(1, Ordering::SeqCst);
+        }
+        if !self.retired {
+            self.shared.failed[self.worker_id].store(true, Ordering::SeqCst);
+        }
+        let _ = self.shared.events.send(SupervisorEvent::Exited {
+            worker_id: self.worker_id,
+            initialized: self.healthy,
+            retired: self.retired,
+        });
+    }
 }
 
 /// Worker池配置
@@ -59,11 +468,31 @@ pub struct WorkerPoolConfig {
     pub enable_logging: bool,
     /// 随机数种子
     pub random_seed: Option<u32>,
+    /// 每个Worker使用不同的种子（random_seed + worker_id），避免所有Worker输出相同的随机序列
+    pub per_worker_seed: bool,
     /// 启用Node.js兼容
     pub enable_node_compat: bool,
     /// 快速返回模式：函数return后立即返回，不等待定时器
     /// 对于有setInterval/setTimeout的JS代码很有用
     pub fast_return: bool,
+    /// 确定性模式：固定时间/随机数/定时器顺序，所有Worker输出一致
+    pub deterministic: bool,
+    /// 任务队列最大长度（None表示不限制）
+    pub max_queue_size: Option<usize>,
+    /// 队列已满时的提交策略
+    pub submit_policy: SubmitPolicy,
+    /// 每个JsRuntime最多处理的任务数，达到后重建（None表示不限制）
+    pub max_tasks_per_worker: Option<usize>,
+    /// JsRuntime堆内存上限（MB），任务完成后超过则重建（None表示不限制）
+    pub max_worker_heap_mb: Option<usize>,
+    /// 最少保持的Worker数（None表示固定为 worker_count）
+    ///
+    /// 小于 worker_count 时启用弹性伸缩：worker_count 为Worker数上限。
+    pub min_workers: Option<usize>,
+    /// 弹性伸缩时，空闲超过该时间的Worker退出
+    pub idle_timeout: Duration,
+    /// 加载初始化代码后安装的 Hook（每次创建JsRuntime都重新安装）
+    pub hooks: Vec<HookSpec>,
     /// Node.js兼容选项
     #[cfg(feature = "node_compat")]
     pub node_compat_options: Option<NodeCompatOptions>,
@@ -79,8 +508,17 @@ impl Default for WorkerPoolConfig {
             enable_extensions: true,
             enable_logging: false,
             random_seed: None,
+            per_worker_seed: false,
             enable_node_compat: false,
             fast_return: false,  // 默认关闭，保持原有行为
+            deterministic: false,
+            max_queue_size: None,
+            submit_policy: SubmitPolicy::Block,
+            max_tasks_per_worker: None,
+            max_worker_heap_mb: None,
+            min_workers: None,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            hooks: Vec::new(),
             #[cfg(feature = "node_compat")]
             node_compat_options: None,
         }
@@ -89,69 +527,652 @@ impl Default for WorkerPoolConfig {
 
 /// Worker池
 pub struct WorkerPool {
-    task_tx: mpsc::UnboundedSender<Task>,
     worker_count: usize,
-    _handles: Vec<thread::JoinHandle<()>>,
+    max_queue_size: Option<usize>,
+    submit_policy: SubmitPolicy,
+    /// 因队列已满被拒绝的任务数
+    rejected: AtomicU64,
+    shared: Arc<WorkerShared>,
+    supervisor: Mutex<Option<thread::JoinHandle<()>>>,
 }
 
 impl WorkerPool {
     /// 创建Worker池
+    ///
+    /// 等待所有Worker初始化完成（加载init_code），任一Worker初始化失败则返回错误。
     pub fn new(config: WorkerPoolConfig) -> Result<Self, String> {
         // 确保V8已初始化
         ensure_v8_initialized();
 
-        let (task_tx, task_rx) = mpsc::unbounded_channel::<Task>();
-        let task_rx = Arc::new(Mutex::new(task_rx));
-
-        let mut handles = Vec::new();
-
-        for worker_id in 0..config.worker_count {
-            let rx = Arc::clone(&task_rx);
-            let cfg = config.clone();
+        let min_workers = config.min_workers.unwrap_or(config.worker_count).min(config.worker_count);
+
+        let (events_tx, events_rx) = std::sync::mpsc::channel();
+        let shared = Arc::new(WorkerShared {
+            tasks: TaskQueue::new(config.worker_count, config.max_queue_size),
+            active: Mutex::new((0..config.worker_count).map(|worker_id| worker_id < min_workers).collect()),
+            inboxes: (0..config.worker_count).map(|_| WorkerInbox::new()).collect(),
+            broadcast_log: Mutex::new(BroadcastLog::default()),
+            init_codes: Mutex::new(vec![config.init_code.clone(); config.worker_count]),
+            reload_lock: tokio::sync::Mutex::new(()),
+            config: config.clone(),
+            recycled: AtomicU64::new(0),
+            healthy: AtomicUsize::new(0),
+            worker_healthy: (0..config.worker_count).map(|_| AtomicBool::new(false)).collect(),
+            failed: (0..config.worker_count).map(|_| AtomicBool::new(false)).collect(),
+            pinned: (0..config.worker_count).map(|_| AtomicBool::new(false)).collect(),
+            respawned: AtomicU64::new(0),
+            scaled_up: AtomicU64::new(0),
+            scaled_down: AtomicU64::new(0),
+            handles: Mutex::new((0..config.worker_count).map(|_| None).collect()),
+            events: events_tx,
+            shutdown: AtomicBool::new(false),
+            cancel: AtomicBool::new(false),
+            metrics: PoolMetrics::new(config.worker_count),
+        });
+
+        let supervisor = {
+            let shared = Arc::clone(&shared);
+            thread::Builder::new()
+                .name("jscore_supervisor".to_string())
+                .spawn(move || supervisor_main(shared, events_rx))
+                .map_err(|e| format!("Failed to spawn supervisor: {}", e))?
+        };
 
-            let handle = thread::Builder::new()
-                .name(format!("jscore_worker_{}", worker_id))
-                .spawn(move || {
-                    worker_main(worker_id, rx, cfg);
-                })
-                .map_err(|e| format!("Failed to spawn worker {}: {}", worker_id, e))?;
+        let stop = |shared: &WorkerShared| {
+            shared.shutdown.store(true, Ordering::SeqCst);
+            let _ = shared.events.send(SupervisorEvent::Shutdown);
+        };
 
-            handles.push(handle);
+        // 弹性伸缩时先启动 min_workers 个Worker，其余按需启动
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+        for worker_id in 0..min_workers {
+            if let Err(e) = spawn_worker(worker_id, &shared, Some(ready_tx.clone())) {
+                stop(&shared);
+                return Err(format!("Failed to spawn worker {}: {}", worker_id, e));
+            }
+        }
+        drop(ready_tx);
+
+        // 等待所有Worker初始化完成
+        for _ in 0..min_workers {
+            let failure = match ready_rx.recv() {
+                Ok((_, Ok(()))) => continue,
+                Ok((worker_id, Err(e))) => format!("Worker {} failed to initialize: {}", worker_id, e),
+                Err(_) => "Worker thread exited during initialization".to_string(),
+            };
+            stop(&shared);
+            return Err(failure);
         }
 
         Ok(WorkerPool {
-            task_tx,
             worker_count: config.worker_count,
-            _handles: handles,
+            max_queue_size: config.max_queue_size,
+            submit_policy: config.submit_policy,
+            rejected: AtomicU64::new(0),
+            shared,
+            supervisor: Mutex::new(Some(supervisor)),
         })
     }
 
-    /// 提交任务到Worker池
-    pub fn submit(&self, task: Task) -> Result<(), String> {
-        self.task_tx
-            .send(task)
-            .map_err(|_| "Worker pool has been closed".to_string())
+    /// 按提交策略提交任务到Worker池
+    ///
+    /// Block / Timeout 策略在队列已满时异步等待，调用方应在释放GIL后 await。
+    /// 优先级高的任务先被Worker取出，低优先级的任务按较小的份额继续处理（见 task_queue）。
+    /// 指定Worker或亲和 key 的任务同样占用队列名额、参与优先级调度，只是不会被其他Worker窃取。
+    pub async fn submit(&self, task: Task, priority: Priority, target: Target) -> Result<(), SubmitError> {
+        if self.is_closed() {
+            return Err(SubmitError::Closed);
+        }
+
+        // 所有Worker都已退出时立即失败，避免任务在队列中无限等待
+        if self.healthy_workers() == 0 {
+            return Err(SubmitError::Unavailable);
+        }
+
+        let enqueue_error = |e| match (e, self.submit_policy) {
+            (EnqueueError::Full, _) => self.overloaded("task queue is full"),
+            (EnqueueError::Timeout, SubmitPolicy::Timeout(timeout)) => {
+                self.shared.metrics.submit_timeouts.fetch_add(1, Ordering::Relaxed);
+                self.overloaded(&format!("task queue is still full after {:?}", timeout))
+            }
+            _ => SubmitError::Closed,
+        };
+        let permit = self.shared.tasks.acquire(self.submit_policy).await.map_err(enqueue_error)?;
+        match target {
+            Target::Any => self.shared.tasks.enqueue(task, priority, permit, None).map_err(enqueue_error)?,
+            Target::Worker(worker_id) => self.submit_to(worker_id, task, priority, permit)?,
+            Target::Key(key) => self.submit_by_key(&key, task, priority, permit)?,
+        }
+
+        // 弹性伸缩：排队任务多于空闲Worker时由监督线程增加Worker
+        if self.shared.is_elastic() {
+            let active = self.shared.active.lock().unwrap_or_else(PoisonError::into_inner);
+            if self.shared.needs_worker(&active) {
+                let _ = self.shared.events.send(SupervisorEvent::ScaleUp);
+            }
+        }
+        Ok(())
+    }
+
+    /// 是否已关闭
+    pub fn is_closed(&self) -> bool {
+        self.shared.shutdown.load(Ordering::SeqCst)
+    }
+
+    /// 关闭Worker池
+    ///
+    /// 停止接收新任务，Worker退出后不再重建。wait 为 true 时Worker先处理完已排队的任务，
+    /// 最多等待 timeout（None 表示一直等待），超时后取消剩余任务；
+    /// wait 为 false 时立即取消已排队的任务（正在执行的任务会完成）并返回。
+    /// 被取消的任务返回错误。所有Worker线程已退出（isolate 已释放）时返回 true。
+    pub fn close(&self, wait: bool, timeout: Option<Duration>) -> bool {
+        self.shared.shutdown.store(true, Ordering::SeqCst);
+        let _ = self.shared.events.send(SupervisorEvent::Shutdown);
+        if !wait {
+            self.shared.cancel.store(true, Ordering::SeqCst);
+        }
+
+        // 关闭任务队列：已排队的任务处理完后 recv() 返回 None，Worker退出；
+        // 正在等待队列名额的提交方返回 Closed
+        self.shared.tasks.close();
+
+        let finished = self.join_workers(if wait { timeout } else { Some(Duration::ZERO) });
+        if !finished {
+            // 超时：取消剩余任务，Worker完成当前任务后退出
+            self.shared.cancel.store(true, Ordering::SeqCst);
+        }
+        finished
+    }
+
+    /// 等待所有Worker线程退出并回收，超时返回 false
+    fn join_workers(&self, timeout: Option<Duration>) -> bool {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            {
+                let mut handles = self.shared.handles.lock().unwrap_or_else(PoisonError::into_inner);
+                if handles.iter().flatten().all(|handle| handle.is_finished()) {
+                    for handle in handles.iter_mut().filter_map(Option::take) {
+                        let _ = handle.join();
+                    }
+                    break;
+                }
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        if let Some(supervisor) = self.supervisor.lock().unwrap_or_else(PoisonError::into_inner).take() {
+            let _ = supervisor.join();
+        }
+
+        // Worker已全部退出，队列和收件箱中剩余的任务不会再被处理
+        for task in self.shared.tasks.drain() {
+            let _ = task.tx.send(Err(CANCELLED_ERROR.to_string().into()));
+        }
+        for inbox in &self.shared.inboxes {
+            if let Ok(mut rx) = inbox.rx.try_lock() {
+                while let Ok(task) = rx.try_recv() {
+                    let _ = task.tx.send(Err(CANCELLED_ERROR.to_string().into()));
+                }
+            }
+        }
+        true
+    }
+
+    fn overloaded(&self, reason: &str) -> SubmitError {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+        SubmitError::Overloaded(format!(
+            "Worker pool overloaded: {} ({} tasks queued)",
+            reason,
+            self.queue_size()
+        ))
+    }
+
+    /// 将任务放入指定Worker的专属通道（只由该Worker执行）
+    ///
+    /// Worker已退出（正在重建）或未启用时立即失败。
+    fn submit_to(
+        &self,
+        worker_id: usize,
+        task: Task,
+        priority: Priority,
+        permit: Option<OwnedSemaphorePermit>,
+    ) -> Result<(), SubmitError> {
+        // 持锁入队，避免Worker在入队前被回收
+        let active = self.shared.active.lock().unwrap_or_else(PoisonError::into_inner);
+        if !active.get(worker_id).copied().unwrap_or(false) || !self.is_worker_healthy(worker_id) {
+            return Err(SubmitError::WorkerUnavailable(worker_id));
+        }
+        self.shared
+            .tasks
+            .enqueue(task, priority, permit, Some(worker_id))
+            .map_err(|_| SubmitError::Closed)
+    }
+
+    /// 按亲和 key 将任务放入对应Worker的专属通道
+    ///
+    /// 目标槽位未启用（弹性伸缩尚未启动或已回收）时按需启动，任务在其初始化完成后执行。
+    /// 承载过亲和 key 的Worker保存着会话状态，不做空闲回收。
+    fn submit_by_key(
+        &self,
+        key: &str,
+        task: Task,
+        priority: Priority,
+        permit: Option<OwnedSemaphorePermit>,
+    ) -> Result<(), SubmitError> {
+        // 与扩容相同的加锁顺序：先回放日志再槽位；持锁入队，避免Worker在入队前被回收
+        let log = self.shared.broadcast_log.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut active = self.shared.active.lock().unwrap_or_else(PoisonError::into_inner);
+        let worker_id = self.worker_for_key(key).ok_or(SubmitError::Unavailable)?;
+        if !active[worker_id] {
+            self.shared.activate(&log, &mut active, worker_id);
+            let _ = self.shared.events.send(SupervisorEvent::Start { worker_id });
+        }
+        self.shared.pinned[worker_id].store(true, Ordering::SeqCst);
+        self.shared
+            .tasks
+            .enqueue(task, priority, permit, Some(worker_id))
+            .map_err(|_| SubmitError::Closed)
+    }
+
+    /// 亲和 key 对应的Worker槽位
+    ///
+    /// 使用最高随机权重（rendezvous）哈希：在全部 max_workers 个槽位中选择 hash(key, worker_id) 最大者，
+    /// 相同 key 始终落在同一个槽位上，弹性伸缩增减Worker不会改变 key 的归属。
+    /// 只跳过失效（意外退出、正在重建）的槽位：它负责的 key 临时迁移到排名其次的槽位，
+    /// 重建完成后回到原槽位。
+    fn worker_for_key(&self, key: &str) -> Option<usize> {
+        (0..self.max_workers())
+            .filter(|&worker_id| !self.shared.failed[worker_id].load(Ordering::SeqCst))
+            .max_by_key(|&worker_id| {
+                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                key.hash(&mut hasher);
+                worker_id.hash(&mut hasher);
+                hasher.finish()
+            })
+    }
+
+    /// 指定Worker是否已初始化且仍在运行
+    pub fn is_worker_healthy(&self, worker_id: usize) -> bool {
+        self.shared
+            .worker_healthy
+            .get(worker_id)
+            .is_some_and(|healthy| healthy.load(Ordering::SeqCst))
     }
 
-    /// 获取Worker数量
+    /// 向每个Worker投递任务，返回 (worker_id, 结果接收端) 列表
+    ///
+    /// persist 为 true 时记录到回放日志，Worker重建JsRuntime后自动重新执行。
+    /// 广播不受队列长度限制；Worker正在重建时，任务在其重建完成后执行。
+    /// 弹性伸缩时只投递给当前启用的Worker，之后启动的Worker通过回放日志获得持久化的广播。
+    pub fn broadcast(
+        &self,
+        task_type: TaskType,
+        persist: bool,
+    ) -> Result<Vec<(usize, oneshot::Receiver<TaskResult>)>, SubmitError> {
+        if self.is_closed() {
+            return Err(SubmitError::Closed);
+        }
+        // 持锁期间记录日志并投递，保证各Worker收件箱中的序号有序
+        let mut log = self.shared.broadcast_log.lock().unwrap_or_else(PoisonError::into_inner);
+        let seq = persist.then(|| {
+            log.last_seq += 1;
+            log.entries.push((log.last_seq, task_type.clone()));
+            log.last_seq
+        });
+
+        let active = self.shared.active.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut receivers = Vec::with_capacity(self.shared.inboxes.len());
+        for (worker_id, inbox) in self.shared.inboxes.iter().enumerate() {
+            if !active[worker_id] {
+                continue;
+            }
+            let (tx, rx) = oneshot::channel();
+            let task = Task {
+                task_type: TaskType::Broadcast {
+                    seq,
+                    task: Box::new(task_type.clone()),
+                },
+                seed: None,
+                cancel: None,
+                events: None,
+                tx,
+            };
+            inbox.tx.send(task).map_err(|_| SubmitError::Closed)?;
+            receivers.push((worker_id, rx));
+        }
+        Ok(receivers)
+    }
+
+    /// 热重载：将所有Worker切换到新的初始化代码
+    ///
+    /// drain 为 true 时逐个Worker切换（当前任务完成后重建JsRuntime），其余Worker继续处理任务；
+    /// 为 false 时所有Worker同时切换。任一Worker加载新代码失败时，
+    /// 已切换的Worker回滚到旧代码并返回错误，Worker池继续使用旧代码服务。
+    /// 正在重建和未启动的Worker不参与切换，启动时直接使用新代码。
+    pub async fn reload(&self, init_code: Option<String>, drain: bool) -> Result<(), String> {
+        let _guard = self.shared.reload_lock.lock().await;
+        if self.is_closed() {
+            return Err(SubmitError::Closed.to_string());
+        }
+        let old_codes = self.shared.init_codes.lock().unwrap_or_else(PoisonError::into_inner).clone();
+
+        let mut reloaded = Vec::new();
+        let mut failure = None;
+        if drain {
+            for worker_id in 0..self.max_workers() {
+                match self.reload_worker(worker_id, init_code.clone()).await {
+                    Ok(()) => reloaded.push(worker_id),
+                    Err(e) => {
+                        failure = Some((worker_id, e));
+                        break;
+                    }
+                }
+            }
+        } else {
+            let results = futures::future::join_all(
+                (0..self.max_workers()).map(|worker_id| self.reload_worker(worker_id, init_code.clone())),
+            )
+            .await;
+            for (worker_id, result) in results.into_iter().enumerate() {
+                match result {
+                    Ok(()) => reloaded.push(worker_id),
+                    Err(e) => {
+                        failure.get_or_insert((worker_id, e));
+                    }
+                }
+            }
+        }
+
+        let Some((failed_worker, error)) = failure else {
+            return Ok(());
+        };
+
+        // 回滚已切换的Worker
+        for worker_id in reloaded {
+            if let Err(e) = self.reload_worker(worker_id, old_codes[worker_id].clone()).await {
+                eprintln!("[Worker {}] Failed to roll back init code: {}", worker_id, e);
+            }
+        }
+        Err(format!("Worker {} failed to load new init code: {}", failed_worker, error))
+    }
+
+    /// 将单个Worker切换到指定初始化代码，等待其完成
+    async fn reload_worker(&self, worker_id: usize, init_code: Option<String>) -> Result<(), String> {
+        let (tx, rx) = oneshot::channel();
+        {
+            let active = self.shared.active.lock().unwrap_or_else(PoisonError::into_inner);
+            if !active[worker_id] || !self.is_worker_healthy(worker_id) {
+                // Worker未启动或正在等待重建，启动时直接使用新代码
+                self.shared.init_codes.lock().unwrap_or_else(PoisonError::into_inner)[worker_id] = init_code;
+                return Ok(());
+            }
+
+            let task = Task {
+                task_type: TaskType::Reload { init_code },
+                seed: None,
+                cancel: None,
+                events: None,
+                tx,
+            };
+            self.shared.inboxes[worker_id]
+                .tx
+                .send(task)
+                .map_err(|_| SubmitError::Closed.to_string())?;
+        }
+        rx.await
+            .map_err(|_| "Worker exited during reload".to_string())?
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// 运行指标快照（队列长度、各Worker任务数、错误数、堆内存、延迟分布）
+    pub fn stats(&self) -> PoolStats {
+        let counters = PoolCounters {
+            workers: self.worker_count(),
+            healthy_workers: self.healthy_workers(),
+            queue_size: self.queue_size(),
+            max_queue_size: self.max_queue_size(),
+            rejected: self.rejected_tasks(),
+            recycled: self.recycled_workers(),
+            respawned: self.respawned_workers(),
+        };
+        let mut stats = self
+            .shared
+            .metrics
+            .snapshot(counters, |worker_id| self.is_worker_healthy(worker_id));
+        // 只列出启用的Worker（已回收的Worker仍计入累计的任务数和延迟）
+        let active = self.shared.active.lock().unwrap_or_else(PoisonError::into_inner).clone();
+        stats.workers.retain(|worker| active[worker.worker_id]);
+        stats
+    }
+
+    /// 当前排队等待Worker处理的任务数
+    pub fn queue_size(&self) -> usize {
+        self.shared.tasks.queued()
+    }
+
+    /// 各优先级排队等待的任务数（high, normal, low）
+    pub fn queue_sizes(&self) -> [usize; 3] {
+        self.shared.tasks.queued_by_priority()
+    }
+
+    /// 任务队列最大长度（None表示不限制）
+    pub fn max_queue_size(&self) -> Option<usize> {
+        self.max_queue_size
+    }
+
+    /// 因队列已满被拒绝的任务数
+    pub fn rejected_tasks(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    /// Worker因生命周期策略重建JsRuntime的次数
+    pub fn recycled_workers(&self) -> u64 {
+        self.shared.recycled.load(Ordering::Relaxed)
+    }
+
+    /// 已初始化且仍在运行的Worker数
+    pub fn healthy_workers(&self) -> usize {
+        self.shared.healthy.load(Ordering::SeqCst)
+    }
+
+    /// Worker因初始化失败/panic被重建的次数
+    pub fn respawned_workers(&self) -> u64 {
+        self.shared.respawned.load(Ordering::Relaxed)
+    }
+
+    /// 当前启用的Worker数（弹性伸缩时在 min_workers 和 max_workers 之间变化）
     pub fn worker_count(&self) -> usize {
+        self.shared
+            .active
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|&&active| active)
+            .count()
+    }
+
+    /// Worker数上限（worker_id 的取值范围）
+    pub fn max_workers(&self) -> usize {
         self.worker_count
     }
+
+    /// 最少保持的Worker数（固定大小的Worker池等于 max_workers）
+    pub fn min_workers(&self) -> usize {
+        self.shared.min_workers()
+    }
+
+    /// 弹性伸缩增加的Worker数
+    pub fn scaled_up_workers(&self) -> u64 {
+        self.shared.scaled_up.load(Ordering::Relaxed)
+    }
+
+    /// 弹性伸缩回收的空闲Worker数
+    pub fn scaled_down_workers(&self) -> u64 {
+        self.shared.scaled_down.load(Ordering::Relaxed)
+    }
 }
 
 impl Drop for WorkerPool {
     fn drop(&mut self) {
-        // 关闭Channel，通知所有Worker退出
-        // 当task_tx被drop时，所有Worker的recv()会返回None
+        // 没有显式 close() 时不阻塞等待：取消排队中的任务，Worker完成当前任务后退出
+        if !self.is_closed() {
+            self.close(false, None);
+        }
+    }
+}
+
+/// 启动Worker线程并登记句柄
+fn spawn_worker(worker_id: usize, shared: &Arc<WorkerShared>, ready: Option<ReadySender>) -> std::io::Result<()> {
+    // 持锁期间登记句柄，避免与监督线程的重建交错
+    let mut handles = shared.handles.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(old) = handles[worker_id].take() {
+        // 旧线程已退出，join 立即返回（panic 已由 ExitNotifier 报告）
+        let _ = old.join();
+    }
+
+    let worker_shared = Arc::clone(shared);
+    let handle = thread::Builder::new()
+        .name(format!("jscore_worker_{}", worker_id))
+        .spawn(move || {
+            let mut notifier = ExitNotifier {
+                worker_id,
+                shared: Arc::clone(&worker_shared),
+                healthy: false,
+                retired: false,
+            };
+            worker_main(worker_id, &worker_shared, &mut notifier, ready);
+        })?;
+    handles[worker_id] = Some(handle);
+    Ok(())
+}
+
+/// 监督线程主函数：Worker退出后按退避时间重建
+fn supervisor_main(shared: Arc<WorkerShared>, events: std::sync::mpsc::Receiver<SupervisorEvent>) {
+    let mut failures = vec![0u32; shared.config.worker_count];
+    let mut scheduled: Vec<(Instant, usize)> = Vec::new();
+
+    loop {
+        let event = match scheduled.iter().map(|(at, _)| *at).min() {
+            Some(next) => match events.recv_timeout(next.saturating_duration_since(Instant::now())) {
+                Ok(event) => Some(event),
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => None,
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return,
+            },
+            None => match events.recv() {
+                Ok(event) => Some(event),
+                Err(_) => return,
+            },
+        };
+
+        match event {
+            Some(SupervisorEvent::Shutdown) => return,
+            Some(SupervisorEvent::ScaleUp) => {
+                if !shared.shutdown.load(Ordering::SeqCst) {
+                    scale_up(&shared);
+                }
+            }
+            Some(SupervisorEvent::Start { worker_id }) => {
+                if !shared.shutdown.load(Ordering::SeqCst) {
+                    start_slot(&shared, worker_id);
+                }
+            }
+            Some(SupervisorEvent::Exited {
+                worker_id,
+                initialized,
+                retired,
+            }) => {
+                if shared.shutdown.load(Ordering::SeqCst) {
+                    continue;
+                }
+                if retired {
+                    if shared.config.enable_logging {
+                        eprintln!("[Worker {}] Retired after being idle", worker_id);
+                    }
+                    failures[worker_id] = 0;
+                    continue;
+                }
+                // 初始化成功过的Worker（运行中panic）从头开始退避，连续初始化失败则指数退避
+                failures[worker_id] = if initialized { 0 } else { failures[worker_id] + 1 };
+                let delay = (RESPAWN_BACKOFF_BASE * 2u32.pow(failures[worker_id].min(7))).min(RESPAWN_BACKOFF_MAX);
+                eprintln!("[Worker {}] Exited unexpectedly, respawning in {:?}", worker_id, delay);
+                scheduled.push((Instant::now() + delay, worker_id));
+            }
+            None => {}
+        }
+
+        // 重建到期的Worker
+        let now = Instant::now();
+        let (due, later): (Vec<_>, Vec<_>) = scheduled.drain(..).partition(|(at, _)| *at <= now);
+        scheduled = later;
+        for (_, worker_id) in due {
+            if shared.shutdown.load(Ordering::SeqCst) {
+                return;
+            }
+            match spawn_worker(worker_id, &shared, None) {
+                Ok(()) => {
+                    shared.respawned.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e) => {
+                    eprintln!("[Worker {}] Failed to respawn: {}", worker_id, e);
+                    failures[worker_id] += 1;
+                    scheduled.push((now + RESPAWN_BACKOFF_MAX, worker_id));
+                }
+            }
+        }
+    }
+}
+
+/// 增加一个Worker（弹性伸缩）：启用第一个空闲槽位，使用该槽位的初始化代码启动
+fn scale_up(shared: &Arc<WorkerShared>) {
+    let worker_id = {
+        // 与广播相同的加锁顺序：先回放日志再槽位
+        let log = shared.broadcast_log.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut active = shared.active.lock().unwrap_or_else(PoisonError::into_inner);
+        if !shared.needs_worker(&active) {
+            return;
+        }
+        let Some(worker_id) = active.iter().position(|&active| !active) else {
+            return;
+        };
+        shared.activate(&log, &mut active, worker_id);
+        worker_id
+    };
+    start_slot(shared, worker_id);
+}
+
+/// 启动已启用槽位的Worker（弹性伸缩增加Worker、亲和 key 按需启动）
+fn start_slot(shared: &Arc<WorkerShared>, worker_id: usize) {
+    match spawn_worker(worker_id, shared, None) {
+        Ok(()) => {
+            shared.scaled_up.fetch_add(1, Ordering::Relaxed);
+            if shared.config.enable_logging {
+                eprintln!("[Worker {}] Started to absorb queued tasks", worker_id);
+            }
+        }
+        Err(e) => {
+            eprintln!("[Worker {}] Failed to start: {}", worker_id, e);
+            let mut active = shared.active.lock().unwrap_or_else(PoisonError::into_inner);
+            active[worker_id] = false;
+            // 持锁取出已放入专属通道的任务（亲和 key），槽位停用后不会再被处理
+            for task in shared.tasks.drain_worker(worker_id) {
+                let _ = task.tx.send(Err(format!("Worker {} failed to start: {}", worker_id, e).into()));
+            }
+        }
     }
 }
 
 /// Worker线程主函数
 fn worker_main(
     worker_id: usize,
-    task_rx: Arc<Mutex<mpsc::UnboundedReceiver<Task>>>,
-    config: WorkerPoolConfig,
+    shared: &WorkerShared,
+    notifier: &mut ExitNotifier,
+    mut ready: Option<ReadySender>,
 ) {
+    let config = &shared.config;
     if config.enable_logging {
         eprintln!("[Worker {}] Starting...", worker_id);
     }
@@ -164,76 +1185,329 @@ fn worker_main(
         Ok(rt) => rt,
         Err(e) => {
             eprintln!("[Worker {}] Failed to create tokio runtime: {}", worker_id, e);
+            if let Some(ready) = ready.take() {
+                let _ = ready.send((worker_id, Err(format!("Failed to create tokio runtime: {}", e))));
+            }
             return;
         }
     };
 
     rt.block_on(async move {
-        // 创建并初始化JsRuntime（只创建一次！）
-        let (mut js_runtime, result_storage) = match create_and_init_runtime(worker_id, &config).await {
-            Ok((runtime, storage)) => (runtime, storage),
-            Err(e) => {
-                eprintln!("[Worker {}] Failed to initialize runtime: {}", worker_id, e);
-                return;
+        let mut task_count = 0usize;
+        let inbox = &shared.inboxes[worker_id];
+        // 同一时刻只有一个线程服务该Worker，整个线程生命周期内持有收件箱
+        let mut inbox_rx = inbox.rx.lock().await;
+
+        let elastic = shared.is_elastic();
+
+        // 待处理的热重载请求（新的初始化代码和结果通知）
+        let mut pending_reload: Option<(Option<String>, oneshot::Sender<TaskResult>)> = None;
+
+        // 每轮创建一个JsRuntime，达到生命周期上限或热重载时退役并重新创建（队列中的任务不受影响）
+        'runtime: loop {
+            let current_code = shared.init_codes.lock().unwrap_or_else(PoisonError::into_inner)[worker_id].clone();
+            let started = match pending_reload.take() {
+                Some((new_code, reply)) => match start_runtime(worker_id, shared, new_code.as_deref()).await {
+                    Ok(started) => {
+                        shared.init_codes.lock().unwrap_or_else(PoisonError::into_inner)[worker_id] = new_code;
+                        let _ = reply.send(Ok(JsonValue::Null));
+                        Ok(started)
+                    }
+                    Err(e) => {
+                        // 新代码加载失败，继续使用旧代码
+                        eprintln!("[Worker {}] Reload failed, keeping previous init code: {}", worker_id, e);
+                        let _ = reply.send(Err(e.into()));
+                        start_runtime(worker_id, shared, current_code.as_deref()).await
+                    }
+                },
+                None => start_runtime(worker_id, shared, current_code.as_deref()).await,
+            };
+            let StartedRuntime {
+                mut js_runtime,
+                result_storage,
+                mut applied_seq,
+                mut replayed,
+            } = match started {
+                Ok(started) => started,
+                Err(e) => {
+                    eprintln!("[Worker {}] Failed to initialize runtime: {}", worker_id, e);
+                    if let Some(ready) = ready.take() {
+                        let _ = ready.send((worker_id, Err(e)));
+                    }
+                    return;
+                }
+            };
+
+            notifier.mark_healthy();
+            if let Some(ready) = ready.take() {
+                let _ = ready.send((worker_id, Ok(())));
             }
-        };
 
-        if config.enable_logging {
-            eprintln!("[Worker {}] Ready to process tasks", worker_id);
-        }
+            if config.enable_logging {
+                eprintln!("[Worker {}] Ready to process tasks", worker_id);
+            }
 
-        // 任务处理循环
-        let mut task_count = 0usize;
-        loop {
-            // 从队列获取任务
-            let task = {
-                let mut rx_guard = task_rx.lock().unwrap();
-                rx_guard.recv().await
-            };
+            // 任务处理循环
+            let mut runtime_tasks = 0usize;
+            loop {
+                // 优先处理收件箱中的任务（广播、热重载），其次从任务队列获取任务
+                let task = tokio::select! {
+                    biased;
+                    Some(task) = inbox_rx.recv() => Some(task),
+                    task = shared.tasks.recv(worker_id) => task,
+                    // 弹性伸缩：空闲超时且Worker数多于 min_workers 时退出
+                    _ = tokio::time::sleep(config.idle_timeout), if elastic => {
+                        if notifier.try_retire(&inbox_rx) {
+                            if config.enable_logging {
+                                eprintln!("[Worker {}] Idle for {:?}, exiting", worker_id, config.idle_timeout);
+                            }
+                            break 'runtime;
+                        }
+                        continue;
+                    }
+                };
+
+                match task {
+                    Some(mut task) => {
+                        if shared.cancel.load(Ordering::SeqCst) {
+                            let _ = task.tx.send(Err(CANCELLED_ERROR.to_string().into()));
+                            continue;
+                        }
+
+                        if let TaskType::Broadcast { seq, task: inner } = task.task_type {
+                            if let Some(seq) = seq {
+                                inbox.delivered_seq.store(seq, Ordering::SeqCst);
+                                if seq <= applied_seq {
+                                    // 已在回放中执行过
+                                    let result = replayed.remove(&seq).unwrap_or(Ok(JsonValue::Null));
+                                    let _ = task.tx.send(result);
+                                    continue;
+                                }
+                                applied_seq = seq;
+                            }
+                            task.task_type = *inner;
+                        }
 
-            match task {
-                Some(task) => {
-                    task_count += 1;
+                        if let TaskType::Reload { init_code } = task.task_type {
+                            if config.enable_logging {
+                                eprintln!("[Worker {}] Reloading init code", worker_id);
+                            }
+                            // 先销毁旧的 isolate 再创建新的
+                            drop(js_runtime);
+                            pending_reload = Some((init_code, task.tx));
+                            continue 'runtime;
+                        }
 
-                    // 执行任务
-                    let result = execute_task(&mut js_runtime, &result_storage, worker_id, task.task_type, &config).await;
+                        // 已取消的任务直接跳过
+                        if let Some(token) = &task.cancel {
+                            if !token.start(js_runtime.v8_isolate().thread_safe_handle()) {
+                                let _ = task.tx.send(Err(TASK_CANCELLED_ERROR.to_string().into()));
+                                continue;
+                            }
+                        }
 
-                    // 发送结果（忽略接收方已关闭的错误）
-                    let _ = task.tx.send(result);
+                        task_count += 1;
+                        runtime_tasks += 1;
 
-                    // 定期GC
-                    if task_count % 100 == 0 {
-                        js_runtime.v8_isolate().low_memory_notification();
+                        // 丢弃上一个任务遗留的事件（如已结束调用中的定时器发送的）
+                        crate::ext::hook::take_events(&mut js_runtime.op_state().borrow_mut());
 
-                        if config.enable_logging {
-                            eprintln!("[Worker {}] Processed {} tasks (GC triggered)", worker_id, task_count);
+                        // 确定性模式：每个任务都从相同的虚拟时间和随机数状态开始
+                        if config.deterministic {
+                            reset_deterministic_state(&mut js_runtime, config, worker_id);
+                        }
+
+                        // 单次调用种子：执行前切换，执行后恢复Worker原有的随机数状态
+                        let saved_rng = task.seed.map(|seed| {
+                            let op_state = js_runtime.op_state();
+                            let saved = crate::ext::random::begin_call_seed(&mut op_state.borrow_mut(), seed);
+                            saved
+                        });
+
+                        // 执行任务
+                        let metrics = &shared.metrics.workers[worker_id];
+                        metrics.task_started();
+                        let started_at = Instant::now();
+                        let execution = execute_task(&mut js_runtime, &result_storage, worker_id, task.task_type, config);
+                        let result = match &task.cancel {
+                            // 取消时放弃等待中的异步任务（同步执行的JS由 IsolateHandle 终止）
+                            Some(token) => tokio::select! {
+                                result = execution => result,
+                                _ = token.cancelled() => Err(TASK_CANCELLED_ERROR.to_string().into()),
+                            },
+                            None => execution.await,
+                        };
+                        let cancelled = task.cancel.as_ref().is_some_and(|token| token.finish());
+                        let result = if cancelled { Err(TASK_CANCELLED_ERROR.to_string().into()) } else { result };
+                        let heap = js_runtime.v8_isolate().get_heap_statistics();
+                        metrics.task_finished(
+                            started_at.elapsed(),
+                            // $terminate() 拦截是正常的调用结果，不计为错误
+                            matches!(result, Err(TaskError::Failed(_))),
+                            heap.used_heap_size(),
+                            heap.total_heap_size(),
+                        );
+
+                        if let Some(saved) = saved_rng {
+                            let op_state = js_runtime.op_state();
+                            crate::ext::random::end_call_seed(&mut op_state.borrow_mut(), saved);
+                        }
+
+                        // 事件先于结果写入，接收方拿到结果时事件已完整
+                        let events = crate::ext::hook::take_events(&mut js_runtime.op_state().borrow_mut());
+                        if let Some(sink) = &task.events {
+                            sink.lock().unwrap_or_else(PoisonError::into_inner).extend(events);
+                        }
+
+                        // 发送结果（忽略接收方已关闭的错误）
+                        let _ = task.tx.send(result);
+
+                        // 被取消的任务可能留下不完整的状态和待执行的回调，重建JsRuntime
+                        if cancelled {
+                            if config.enable_logging {
+                                eprintln!("[Worker {}] Task cancelled during execution, restarting runtime", worker_id);
+                            }
+                            drop(js_runtime);
+                            continue 'runtime;
+                        }
+
+                        // 生命周期策略：当前任务完成后退役
+                        if let Some(reason) = retire_reason(&mut js_runtime, runtime_tasks, config) {
+                            if config.enable_logging {
+                                eprintln!("[Worker {}] Retiring runtime ({}), restarting", worker_id, reason);
+                            }
+                            // 先销毁旧的 isolate 再创建新的
+                            drop(js_runtime);
+                            shared.recycled.fetch_add(1, Ordering::Relaxed);
+                            continue 'runtime;
+                        }
+
+                        // 定期GC
+                        if task_count % 100 == 0 {
+                            js_runtime.v8_isolate().low_memory_notification();
+
+                            if config.enable_logging {
+                                eprintln!("[Worker {}] Processed {} tasks (GC triggered)", worker_id, task_count);
+                            }
                         }
                     }
-                }
-                None => {
-                    // Channel关闭，退出
-                    if config.enable_logging {
-                        eprintln!("[Worker {}] Shutting down (processed {} tasks)", worker_id, task_count);
+                    None => {
+                        // Channel关闭，退出
+                        if config.enable_logging {
+                            eprintln!("[Worker {}] Shutting down (processed {} tasks)", worker_id, task_count);
+                        }
+                        break 'runtime;
                     }
-                    break;
                 }
             }
         }
     });
 }
 
+/// 已初始化并完成广播回放的JsRuntime
+struct StartedRuntime {
+    js_runtime: JsRuntime,
+    result_storage: Rc<ResultStorage>,
+    /// 已回放的最大广播序号
+    applied_seq: u64,
+    /// 回放中执行、但收件箱中尚未取出的广播结果
+    replayed: HashMap<u64, TaskResult>,
+}
+
+/// 创建JsRuntime并回放持久化的广播，使其与其他Worker状态一致
+async fn start_runtime(
+    worker_id: usize,
+    shared: &WorkerShared,
+    init_code: Option<&str>,
+) -> Result<StartedRuntime, String> {
+    let config = &shared.config;
+    let (mut js_runtime, result_storage) = create_and_init_runtime(worker_id, config, init_code).await?;
+
+    // 收件箱中尚未取出的同一广播直接使用回放结果，不重复执行
+    let mut applied_seq = 0u64;
+    let mut replayed = HashMap::new();
+    let entries = shared
+        .broadcast_log
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entries
+        .clone();
+    let delivered_seq = shared.inboxes[worker_id].delivered_seq.load(Ordering::SeqCst);
+    for (seq, task_type) in entries {
+        if config.deterministic {
+            reset_deterministic_state(&mut js_runtime, config, worker_id);
+        }
+        let result = execute_task(&mut js_runtime, &result_storage, worker_id, task_type, config).await;
+        if let Err(e) = &result {
+            eprintln!("[Worker {}] Broadcast #{} replay failed: {}", worker_id, seq, e);
+        }
+        if seq > delivered_seq {
+            replayed.insert(seq, result);
+        }
+        applied_seq = seq;
+    }
+
+    Ok(StartedRuntime {
+        js_runtime,
+        result_storage,
+        applied_seq,
+        replayed,
+    })
+}
+
+/// 检查当前JsRuntime是否达到生命周期上限，返回退役原因
+fn retire_reason(runtime: &mut JsRuntime, runtime_tasks: usize, config: &WorkerPoolConfig) -> Option<String> {
+    if let Some(max_tasks) = config.max_tasks_per_worker {
+        if runtime_tasks >= max_tasks {
+            return Some(format!("{} tasks processed", runtime_tasks));
+        }
+    }
+    if let Some(max_heap_mb) = config.max_worker_heap_mb {
+        let used_mb = runtime.v8_isolate().get_heap_statistics().used_heap_size() / (1024 * 1024);
+        if used_mb >= max_heap_mb {
+            return Some(format!("heap {} MB >= {} MB", used_mb, max_heap_mb));
+        }
+    }
+    None
+}
+
+/// Worker的随机数种子
+fn worker_seed(config: &WorkerPoolConfig, worker_id: usize) -> Option<u64> {
+    // 确定性模式必须固定随机数，未指定种子时使用默认种子
+    let random_seed = config.random_seed.map(|s| s as u64).or_else(|| {
+        config.deterministic.then_some(crate::ext::deterministic::DEFAULT_SEED)
+    });
+    if config.per_worker_seed {
+        random_seed.map(|s| s.wrapping_add(worker_id as u64))
+    } else {
+        random_seed
+    }
+}
+
+/// 确定性模式：虚拟时钟回到零点，随机数（含 deno_crypto）回到Worker种子的起点
+fn reset_deterministic_state(runtime: &mut JsRuntime, config: &WorkerPoolConfig, worker_id: usize) {
+    let op_state = runtime.op_state();
+    let mut op_state = op_state.borrow_mut();
+    crate::ext::deterministic::reset_clock(&mut op_state);
+    crate::ext::random::reseed(&mut op_state, worker_seed(config, worker_id));
+}
+
 /// 创建并初始化Runtime
 async fn create_and_init_runtime(
     worker_id: usize,
     config: &WorkerPoolConfig,
+    init_code: Option<&str>,
 ) -> Result<(JsRuntime, Rc<ResultStorage>), String> {
     // 创建ResultStorage
     let storage = Rc::new(ResultStorage::new());
 
+    let random_seed = worker_seed(config, worker_id);
+
     // 构建ExtensionOptions
     let mut ext_options = ExtensionOptions {
         enable_logging: config.enable_logging,
-        random_seed: config.random_seed.map(|s| s as u64),
+        random_seed,
+        deterministic: config.deterministic,
         enable_extensions: config.enable_extensions,
         storage: storage.clone(),  // Clone the Rc so we can return it later
         #[cfg(feature = "deno_web_api")]
@@ -298,7 +1572,9 @@ async fn create_and_init_runtime(
         let op_state = runtime.op_state();
         let mut op_state_mut = op_state.borrow_mut();
         op_state_mut.put(isolate_handle);  // 供 op_terminate_execution 使用
-        op_state_mut.put(WorkerId(worker_id));  // 供 op_save_hook_data 使用
+        op_state_mut.put(WorkerId(worker_id));  // 标识当前 Worker
+        op_state_mut.put(crate::ext::hook::EmittedEvents::default());  // 收集 $emit() 事件
+        op_state_mut.put(crate::ext::hook::HookCallbacks::default());  // Hook 的 Python 回调
 
         // 设置快速返回模式
         op_state_mut.put(crate::ext::core::FastReturnMode::new(config.fast_return));
@@ -312,6 +1588,12 @@ async fn create_and_init_runtime(
 
     // 加载扩展的 JavaScript 初始化代码（$terminate 等函数）
     if config.enable_extensions {
+        // Load native-function registry (hooks show [native code])
+        let native_init = crate::ext::protection::get_native_js();
+        runtime
+            .execute_script("<init_native>", native_init.to_string())
+            .map_err(|e| format!("Failed to load native registry: {}", e))?;
+
         // Load core extension functions ($return, $exit, etc.)
         let core_init = crate::ext::core::get_init_js();
         runtime
@@ -324,13 +1606,33 @@ async fn create_and_init_runtime(
             .execute_script("<init_hook>", hook_init.to_string())
             .map_err(|e| format!("Failed to load hook extension: {}", e))?;
 
+        // Load random extension (override Math.random() with seeded RNG)
+        let random_init = crate::ext::random::get_init_js();
+        runtime
+            .execute_script("<init_random>", random_init.to_string())
+            .map_err(|e| format!("Failed to load random extension: {}", e))?;
+
+        // Load deterministic mode (virtual clock for Date/performance.now/timers)
+        if config.deterministic {
+            let deterministic_init = crate::ext::deterministic::get_init_js();
+            runtime
+                .execute_script("<init_deterministic>", deterministic_init.to_string())
+                .map_err(|e| format!("Failed to load deterministic extension: {}", e))?;
+        }
+
+        // Load event loop registry (tracks timers for wait_timers)
+        let event_loop_init = crate::ext::event_loop::get_init_js();
+        runtime
+            .execute_script("<init_event_loop>", event_loop_init.to_string())
+            .map_err(|e| format!("Failed to load event loop extension: {}", e))?;
+
         if config.enable_logging {
             eprintln!("[Worker {}] Loaded extension JavaScript", worker_id);
         }
     }
 
     // 如果有初始化代码，加载它（只加载一次！）
-    if let Some(init_code) = &config.init_code {
+    if let Some(init_code) = init_code {
         if config.enable_logging {
             eprintln!(
                 "[Worker {}] Loading init code ({} bytes)...",
@@ -341,7 +1643,7 @@ async fn create_and_init_runtime(
 
         // 执行初始化代码
         runtime
-            .execute_script("<pool_init>", init_code.clone())
+            .execute_script("<pool_init>", init_code.to_string())
             .map_err(|e| format!("Init code error: {}", e))?;
 
         // 初始化阶段：只处理微任务，不等待宏任务（定时器等）
@@ -365,6 +1667,14 @@ async fn create_and_init_runtime(
         }
     }
 
+    // 安装 Hook（目标函数由初始化代码定义）
+    for hook in &config.hooks {
+        let script = hook.install_script(&mut runtime.op_state().borrow_mut());
+        runtime
+            .execute_script("<install_hook>", script)
+            .map_err(|e| format!("Failed to install hook {}: {}", hook.path, e))?;
+    }
+
     Ok((runtime, storage))
 }
 
@@ -375,7 +1685,7 @@ async fn execute_task(
     worker_id: usize,
     task_type: TaskType,
     config: &WorkerPoolConfig,
-) -> Result<JsonValue, String> {
+) -> TaskResult {
     match task_type {
         TaskType::Execute { code } => {
             // 清空之前的结果
@@ -427,26 +1737,14 @@ async fn execute_task(
                         }
 
                         // 检查是否有 hook data
-                        if let Some(hook_data) = get_hook_data_for_worker(worker_id) {
-                            // 立即清空，避免被下一个任务覆盖
-                            clear_hook_data_for_worker(worker_id);
-
+                        if let Some(error) = take_hook_error(result_storage, worker_id) {
                             if config.enable_logging {
                                 eprintln!("[Worker {}] Hook data detected, returning with data", worker_id);
                             }
-
-                            // 直接返回 hook data，不需要 Python 再次调用
-                            let hook_json: JsonValue = serde_json::from_str(&hook_data)
-                                .map_err(|e| format!("Failed to parse hook data: {}", e))?;
-
-                            return Ok(serde_json::json!({
-                                "__hook__": true,
-                                "worker_id": worker_id,
-                                "data": hook_json
-                            }));
+                            return Err(error);
                         }
                     }
-                    return Err(error_msg);
+                    return Err(error_msg.into());
                 }
                 Ok(result_handle) => {
                     // Forget the result handle - we'll get the result from storage
@@ -468,19 +1766,12 @@ async fn execute_task(
                     runtime.v8_isolate().cancel_terminate_execution();
 
                     // 检查是否有 hook data（$terminate 场景）
-                    if let Some(hook_data) = get_hook_data_for_worker(worker_id) {
-                        clear_hook_data_for_worker(worker_id);
-                        let hook_json: JsonValue = serde_json::from_str(&hook_data)
-                            .map_err(|e| format!("Failed to parse hook data: {}", e))?;
-                        return Ok(serde_json::json!({
-                            "__hook__": true,
-                            "worker_id": worker_id,
-                            "data": hook_json
-                        }));
+                    if let Some(error) = take_hook_error(result_storage, worker_id) {
+                        return Err(error);
                     }
                     // 不是 hook，是正常的结果返回终止，继续获取结果
                 } else {
-                    return Err(error_msg);
+                    return Err(error_msg.into());
                 }
             }
 
@@ -491,119 +1782,144 @@ async fn execute_task(
 
             // 解析 JSON 字符串为 JsonValue
             serde_json::from_str(&json_str)
-                .map_err(|e| format!("Failed to parse result JSON: {}", e))
+                .map_err(|e| format!("Failed to parse result JSON: {}", e).into())
         }
 
         TaskType::Call { func_name, args } => {
-            // 清空之前的结果
-            result_storage.clear();
-
-            // 构造函数调用代码
-            let args_json = serde_json::to_string(&args)
-                .map_err(|e| format!("Failed to serialize args: {}", e))?;
-
-            // 包装函数调用以使用 op_store_result
-            let wrapped_code = format!(
-                r#"
-                (async function() {{
-                    const __result = await Promise.resolve({}(...{}));
-
-                    if (__result === undefined) {{
-                        __getDeno().core.ops.op_store_result("null");
-                        return null;
-                    }}
+            execute_call(runtime, result_storage, worker_id, &func_name, &args, config).await
+        }
 
-                    try {{
-                        const json = JSON.stringify(__result);
-                        __getDeno().core.ops.op_store_result(json);
-                        return __result;
-                    }} catch(e) {{
-                        const str = JSON.stringify(String(__result));
-                        __getDeno().core.ops.op_store_result(str);
-                        return __result;
-                    }}
-                }})()
-                "#,
-                func_name, args_json
-            );
+        // 广播和热重载任务在Worker循环中处理，不会到达这里
+        TaskType::Broadcast { .. } | TaskType::Reload { .. } => {
+            Err("Broadcast/reload task must be delivered through worker inbox".to_string().into())
+        }
 
-            if config.enable_logging {
-                eprintln!("[Worker] Calling: {}(...{})", func_name, args_json);
+        TaskType::CallBatch { func_name, args_list } => {
+            let mut results = Vec::with_capacity(args_list.len());
+            for args in &args_list {
+                let item = match execute_call(runtime, result_storage, worker_id, &func_name, args, config).await {
+                    Ok(value) => serde_json::json!({ "value": value }),
+                    Err(TaskError::Failed(error)) => serde_json::json!({ "error": error }),
+                    Err(TaskError::Hook { worker_id, data }) => serde_json::json!({ "hook": data, "worker_id": worker_id }),
+                };
+                results.push(item);
             }
+            Ok(JsonValue::Array(results))
+        }
+    }
+}
 
-            // 执行调用
-            let execute_result = runtime.execute_script("<pool_call>", wrapped_code);
+/// 调用已定义的函数
+async fn execute_call(
+    runtime: &mut JsRuntime,
+    result_storage: &Rc<ResultStorage>,
+    worker_id: usize,
+    func_name: &str,
+    args: &[JsonValue],
+    config: &WorkerPoolConfig,
+) -> TaskResult {
+    // 清空之前的结果
+    result_storage.clear();
+
+    // 构造函数调用代码
+    let args_json = serde_json::to_string(args)
+        .map_err(|e| format!("Failed to serialize args: {}", e))?;
+
+    // 包装函数调用以使用 op_store_result
+    let wrapped_code = format!(
+        r#"
+        (async function() {{
+            const __result = await Promise.resolve({}(...{}));
+
+            if (__result === undefined) {{
+                __getDeno().core.ops.op_store_result("null");
+                return null;
+            }}
+
+            try {{
+                const json = JSON.stringify(__result);
+                __getDeno().core.ops.op_store_result(json);
+                return __result;
+            }} catch(e) {{
+                const str = JSON.stringify(String(__result));
+                __getDeno().core.ops.op_store_result(str);
+                return __result;
+            }}
+        }})()
+        "#,
+        func_name, args_json
+    );
 
-            match execute_result {
-                Err(e) => {
-                    // 检查是否是 terminate_execution 错误（hook场景或结果返回）
-                    let error_msg = format_js_error(*e);
-                    if error_msg.contains("execution terminated") {
-                        // 恢复 isolate 状态，允许 Worker 继续处理后续任务
-                        runtime.v8_isolate().cancel_terminate_execution();
+    if config.enable_logging {
+        eprintln!("[Worker] Calling: {}(...{})", func_name, args_json);
+    }
 
-                        // 检查是否有 hook data
-                        if let Some(hook_data) = get_hook_data_for_worker(worker_id) {
-                            clear_hook_data_for_worker(worker_id);
-                            let hook_json: JsonValue = serde_json::from_str(&hook_data)
-                                .map_err(|e| format!("Failed to parse hook data: {}", e))?;
-                            return Ok(serde_json::json!({
-                                "__hook__": true,
-                                "worker_id": worker_id,
-                                "data": hook_json
-                            }));
-                        }
+    // 执行调用
+    let execute_result = runtime.execute_script("<pool_call>", wrapped_code);
 
-                        // 检查是否有结果存储（op_store_result 触发的终止）
-                        if result_storage.has_result() {
-                            let json_str = result_storage.take().unwrap();
-                            return serde_json::from_str(&json_str)
-                                .map_err(|e| format!("Failed to parse result JSON: {}", e));
-                        }
-                    }
-                    return Err(error_msg);
+    match execute_result {
+        Err(e) => {
+            // 检查是否是 terminate_execution 错误（hook场景或结果返回）
+            let error_msg = format_js_error(*e);
+            if error_msg.contains("execution terminated") {
+                // 恢复 isolate 状态，允许 Worker 继续处理后续任务
+                runtime.v8_isolate().cancel_terminate_execution();
+
+                // 检查是否有 hook data
+                if let Some(error) = take_hook_error(result_storage, worker_id) {
+                    return Err(error);
                 }
-                Ok(result_handle) => {
-                    std::mem::forget(result_handle);
+
+                // 检查是否有结果存储（op_store_result 触发的终止）
+                if result_storage.has_result() {
+                    let json_str = result_storage.take().unwrap();
+                    return serde_json::from_str(&json_str)
+                        .map_err(|e| format!("Failed to parse result JSON: {}", e).into());
                 }
             }
+            return Err(error_msg.into());
+        }
+        Ok(result_handle) => {
+            std::mem::forget(result_handle);
+        }
+    }
 
-            // 运行事件循环 - op_store_result 会在结果存储后自动终止执行
-            let event_loop_result = runtime
-                .run_event_loop(PollEventLoopOptions::default())
-                .await;
+    // 运行事件循环 - op_store_result 会在结果存储后自动终止执行
+    let event_loop_result = runtime
+        .run_event_loop(PollEventLoopOptions::default())
+        .await;
 
-            if let Err(e) = event_loop_result {
-                let error_msg = format!("Event loop error: {}", e);
-                if error_msg.contains("execution terminated") {
-                    runtime.v8_isolate().cancel_terminate_execution();
+    if let Err(e) = event_loop_result {
+        let error_msg = format!("Event loop error: {}", e);
+        if error_msg.contains("execution terminated") {
+            runtime.v8_isolate().cancel_terminate_execution();
 
-                    // 检查是否有 hook data
-                    if let Some(hook_data) = get_hook_data_for_worker(worker_id) {
-                        clear_hook_data_for_worker(worker_id);
-                        let hook_json: JsonValue = serde_json::from_str(&hook_data)
-                            .map_err(|e| format!("Failed to parse hook data: {}", e))?;
-                        return Ok(serde_json::json!({
-                            "__hook__": true,
-                            "worker_id": worker_id,
-                            "data": hook_json
-                        }));
-                    }
-                    // 正常的结果返回终止，继续获取结果
-                } else {
-                    return Err(error_msg);
-                }
+            // 检查是否有 hook data
+            if let Some(error) = take_hook_error(result_storage, worker_id) {
+                return Err(error);
             }
-
-            // 从 storage 获取结果
-            let json_str = result_storage
-                .take()
-                .ok_or_else(|| "No result stored after event loop".to_string())?;
-
-            serde_json::from_str(&json_str)
-                .map_err(|e| format!("Failed to parse result JSON: {}", e))
+            // 正常的结果返回终止，继续获取结果
+        } else {
+            return Err(error_msg.into());
         }
     }
+
+    // 从 storage 获取结果
+    let json_str = result_storage
+        .take()
+        .ok_or_else(|| "No result stored after event loop".to_string())?;
+
+    serde_json::from_str(&json_str)
+        .map_err(|e| format!("Failed to parse result JSON: {}", e).into())
+}
+
+/// 取出本次调用 $terminate() 保存的 Hook 数据
+///
+/// 同时保留一份到全局存储，供兼容接口 `get_hook_data(worker_id)` 读取
+fn take_hook_error(result_storage: &ResultStorage, worker_id: usize) -> Option<TaskError> {
+    let data = result_storage.take_hook_data()?;
+    save_hook_data_for_worker(worker_id, data.clone());
+    Some(TaskError::Hook { worker_id, data })
 }
 
 /// 格式化JavaScript错误
@@ -287,6 +287,30 @@ def test_real_world_scenario():
     assert len(result['missing_browser_apis']) == 0, f"缺失浏览器API: {result['missing_browser_apis']}"
 
 
+def test_single_native_registry():
+    """测试受保护函数只通过 init_native.js 注册表显示 [native code]"""
+    ctx = never_jscore.Context(deterministic=True)
+
+    result = ctx.evaluate("""
+        const checked = {
+            fetch, setTimeout, Object_keys: Object.keys,
+            open: XMLHttpRequest.prototype.open, Date, Date_now: Date.now,
+        };
+        ({
+            own_to_string: Object.keys(checked).filter((key) => checked[key].hasOwnProperty('toString')),
+            non_native: Object.keys(checked).filter(
+                (key) => !Function.prototype.toString.call(checked[key]).includes('[native code]')
+            ),
+            pending_list: typeof __NEVER_JSCORE_PENDING_NATIVE__,
+        })
+    """)
+
+    assert result['own_to_string'] == [], f"存在自有 toString: {result['own_to_string']}"
+    assert result['non_native'] == [], f"发现非原生函数: {result['non_native']}"
+    assert result['pending_list'] == 'undefined'
+    print("✓ 单一 native 注册表（无自有 toString 属性）")
+
+
 if __name__ == "__main__":
     try:
         test_xhr_protection()
@@ -300,6 +324,8 @@ if __name__ == "__main__":
         test_function_protection_bypass()
         print()
         test_real_world_scenario()
+        print()
+        test_single_native_registry()
 
         print()
         print("=" * 60)
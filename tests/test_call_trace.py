@@ -0,0 +1,255 @@
+"""
+测试函数调用跟踪：ctx.trace()
+
+记录参数、返回值、调用者和时间，组成调用树，可导出为 Chrome trace-event JSON
+"""
+
+import json
+import os
+import sys
+import tempfile
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+JS_CODE = """
+var CryptoJS = {
+    AES: {
+        encrypt: function encrypt(text, key) {
+            return text.split('').reverse().join('') + ':' + key;
+        },
+        decrypt: function decrypt(text) {
+            return text.split(':')[0].split('').reverse().join('');
+        }
+    }
+};
+function getTimestamp() { return 1700000000000; }
+function login(user, password) {
+    const ts = getTimestamp();
+    return CryptoJS.AES.encrypt(user + '|' + password + '|' + ts, 'k3y');
+}
+function checkUser(user) {
+    if (!user) throw new Error('empty user');
+    return true;
+}
+async function fetchToken(id) { return 'token-' + id; }
+function stopHere() { $terminate({ stopped: true }); }
+"""
+
+
+def test_call_tree():
+    """测试调用树：参数、返回值、调用者"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    trace = ctx.trace(["login", "getTimestamp", "CryptoJS.AES.encrypt"])
+
+    result = ctx.call("login", ["user", "pass"])
+    calls = trace.calls()
+    assert len(calls) == 1
+    root = calls[0]
+    assert root["name"] == "login"
+    assert root["args"] == ["user", "pass"]
+    assert root["return"] == result
+    assert root["caller"] is None
+    assert [c["name"] for c in root["children"]] == ["getTimestamp", "CryptoJS.AES.encrypt"]
+    assert root["children"][1]["caller"] == "login"
+    assert root["children"][1]["args"] == ["user|pass|1700000000000", "k3y"]
+    assert root["start"] <= root["children"][0]["start"] <= root["end"]
+    assert root["duration"] >= 0
+    assert len(trace) == 3
+
+    # 扁平列表
+    events = trace.events()
+    assert [(e["id"], e["parent"], e["depth"]) for e in events] == [(0, None, 0), (1, 0, 1), (2, 0, 1)]
+    print("[OK] 调用树")
+
+
+def test_object_and_script_targets():
+    """测试对象路径和 "script:名称" 目标"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE, name="app.js")
+    trace = ctx.trace(["script:app.js", "CryptoJS.AES"])
+
+    ctx.call("login", ["a", "b"])
+    ctx.call("CryptoJS.AES.decrypt", ["x:k"])
+    names = [e["name"] for e in trace.events()]
+    assert names == ["login", "getTimestamp", "CryptoJS.AES.encrypt", "CryptoJS.AES.decrypt"]
+
+    # 被跟踪的函数仍显示为原生函数
+    assert "[native code]" in ctx.evaluate("login.toString()")
+    assert ctx.evaluate("login.name") == "login"
+    assert ctx.evaluate("CryptoJS.AES.encrypt.length") == 2
+    print("[OK] 对象路径和 script: 目标")
+
+
+def test_errors_and_async():
+    """测试抛出异常和 Promise 返回值"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    trace = ctx.trace(["checkUser", "fetchToken"])
+
+    try:
+        ctx.call("checkUser", [""])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "empty user" in str(e)
+    assert ctx.call("fetchToken", [7]) == "token-7"
+
+    failed, token = trace.calls()
+    assert failed["error"] == "empty user"
+    assert failed["return"] is None
+    assert token["return"] == "token-7"
+    assert token["settled"] >= token["end"]
+    print("[OK] 异常和 Promise")
+
+
+def test_capture_options_and_limit():
+    """测试 capture_args / capture_return 和 max_events"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    trace = ctx.trace(["getTimestamp"], max_events=3, capture_args=False, capture_return=False)
+
+    for _ in range(5):
+        ctx.call("getTimestamp", [])
+    calls = trace.calls()
+    assert len(calls) == 3
+    assert calls[0]["args"] is None and calls[0]["return"] is None
+    assert trace.dropped == 2
+    print("[OK] capture 选项和 max_events")
+
+
+def test_stop_and_terminate():
+    """测试 stop() / with 语句，以及被 $terminate() 中断后的调用树"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+
+    with ctx.trace(["getTimestamp", "stopHere"]) as trace:
+        ctx.call("getTimestamp", [])
+        assert trace.active
+        try:
+            ctx.call("stopHere", [])
+            assert False, "应该抛出 HookTerminated"
+        except never_jscore.HookTerminated:
+            pass
+        # 被中断的调用没有结束时间，之后的调用不会成为它的子调用
+        ctx.call("getTimestamp", [])
+    assert not trace.active
+
+    ctx.call("getTimestamp", [])
+    calls = trace.calls()
+    assert [c["name"] for c in calls] == ["getTimestamp", "stopHere", "getTimestamp"]
+    assert calls[1]["end"] is None
+    assert "calls=3" in repr(trace)
+    print("[OK] stop() 和 $terminate()")
+
+
+def test_stop_restores_originals():
+    """测试 stop() 还原被跟踪的函数，重新跟踪不会重复包装"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE + "\nconst lexicalHelper = function () { return 1; };", name="app.js")
+    ctx.eval("var originalLogin = login;")
+
+    with ctx.trace(["script:app.js", "CryptoJS.AES"]) as trace:
+        ctx.call("login", ["a", "b"])
+        assert ctx.evaluate("login === originalLogin") is False
+    assert ctx.evaluate("login === originalLogin") is True
+    assert "getTimestamp()" in ctx.evaluate("login.toString()")
+    trace.stop()  # 重复调用无效果
+
+    # 重新跟踪：每次调用只记录一次
+    retrace = ctx.trace(["login"])
+    ctx.call("login", ["a", "b"])
+    assert [c["name"] for c in retrace.calls()] == ["login"]
+    assert len(trace) == 3
+
+    # 两个跟踪嵌套时按任意顺序停止都能还原
+    inner = ctx.trace(["login"])
+    retrace.stop()
+    ctx.call("login", ["a", "b"])
+    assert len(retrace) == 1 and len(inner) == 1
+    inner.stop()
+    assert ctx.evaluate("login === originalLogin") is True
+
+    # 顶层 const / let / class 不在 "script:" 目标中
+    assert ctx.evaluate("lexicalHelper.toString()").startswith("function ()")
+    print("[OK] stop() 还原被跟踪的函数")
+
+
+def test_chrome_trace():
+    """测试导出 Chrome trace-event JSON"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    trace = ctx.trace(["login", "CryptoJS.AES.encrypt"])
+    ctx.call("login", ["u", "p"])
+
+    data = json.loads(trace.to_chrome_trace())
+    events = data["traceEvents"]
+    assert [e["name"] for e in events] == ["login", "CryptoJS.AES.encrypt"]
+    assert all(e["ph"] == "X" for e in events)
+    outer, inner = events
+    assert outer["ts"] <= inner["ts"] and inner["ts"] + inner["dur"] <= outer["ts"] + outer["dur"]
+    assert inner["args"]["args"][1] == "k3y"
+
+    path = os.path.join(tempfile.mkdtemp(), "trace.json")
+    trace.save(path)
+    with open(path, encoding="utf-8") as f:
+        assert json.load(f) == data
+    print("[OK] Chrome trace-event JSON")
+
+
+def test_invalid_targets():
+    """测试无效的目标"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+
+    for target in ("missing.fn", "script:nope", "CryptoJS.AES.encrypt.length"):
+        try:
+            ctx.trace([target])
+            assert False, f"应该抛出异常: {target}"
+        except Exception as e:
+            assert "Cannot trace" in str(e)
+
+    try:
+        never_jscore.Context(enable_extensions=False).trace(["Math.max"])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "enable_extensions" in str(e)
+    print("[OK] 无效目标")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext.trace()"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile(JS_CODE, name="app.js")
+    trace = ctx.trace(["script:app.js"])
+
+    ctx.call("login", ["a", "b"])
+    assert [c["name"] for c in trace.calls()[0]["children"]] == ["getTimestamp"]
+    trace.stop()
+    assert "getTimestamp()" in ctx.evaluate("login.toString()")
+    ctx.close()
+    print("[OK] ThreadedContext.trace()")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试函数调用跟踪")
+    print("=" * 60)
+
+    test_call_tree()
+    test_object_and_script_targets()
+    test_errors_and_async()
+    test_capture_options_and_limit()
+    test_stop_and_terminate()
+    test_stop_restores_originals()
+    test_chrome_trace()
+    test_invalid_targets()
+    test_threaded_context()
+
+    print("\n" + "=" * 60)
+    print("所有调用跟踪测试通过!")
+    print("=" * 60)
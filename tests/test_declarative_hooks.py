@@ -0,0 +1,247 @@
+"""
+测试声明式 Hook：ctx.hook() / JSEngine(hooks=[...])
+
+log / terminate / replace 三种行为，参数和返回值通过 "hook" 事件上报，
+包装后的函数看起来仍是原生函数
+"""
+
+import sys
+import threading
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+JS_CODE = """
+var CryptoJS = {
+    AES: {
+        encrypt: function encrypt(text, key) {
+            return text.split('').reverse().join('') + ':' + key;
+        }
+    }
+};
+function getTimestamp() { return Date.now(); }
+function login(user, password) {
+    const ts = getTimestamp();
+    return CryptoJS.AES.encrypt(user + '|' + password + '|' + ts, 'k3y');
+}
+async function fetchToken(id) { return 'token-' + id; }
+function sourceOf(fn) { return globalThis[fn].toString(); }
+function describeEncrypt() {
+    const fn = CryptoJS.AES.encrypt;
+    return [fn.name, fn.length, fn.toString(), Function.prototype.toString.call(fn), fn.hasOwnProperty('toString')];
+}
+"""
+
+
+def hook_events(ctx):
+    return [e["data"] for e in ctx.events("hook")]
+
+
+def test_log():
+    """测试 action="log"：调用原函数并上报参数和返回值"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    ctx.hook("CryptoJS.AES.encrypt")
+
+    result = ctx.call("login", ["user", "pass"])
+    events = hook_events(ctx)
+    assert len(events) == 1
+    assert events[0]["path"] == "CryptoJS.AES.encrypt"
+    assert events[0]["args"][1] == "k3y"
+    assert events[0]["return"] == result
+    print("[OK] action='log'")
+
+
+def test_capture_options():
+    """测试 capture_args / capture_return"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    ctx.hook("CryptoJS.AES.encrypt", capture_args=False, capture_return=False)
+
+    ctx.call("login", ["u", "p"])
+    assert hook_events(ctx) == [{"path": "CryptoJS.AES.encrypt"}]
+    print("[OK] capture_args / capture_return")
+
+
+def test_async_return():
+    """测试返回 Promise 时上报 resolve 的值"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    ctx.hook("fetchToken")
+
+    assert ctx.call("fetchToken", [7]) == "token-7"
+    assert hook_events(ctx) == [{"path": "fetchToken", "args": [7], "return": "token-7"}]
+    print("[OK] Promise 返回值")
+
+
+def test_terminate():
+    """测试 action="terminate"：拿到参数后抛出 HookTerminated"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    ctx.hook("CryptoJS.AES.encrypt", action="terminate")
+
+    try:
+        ctx.call("login", ["user", "pass"])
+        assert False, "应该抛出 HookTerminated"
+    except never_jscore.HookTerminated as e:
+        assert e.data["path"] == "CryptoJS.AES.encrypt"
+        assert e.data["args"][0].startswith("user|pass|")
+    print("[OK] action='terminate'")
+
+
+def test_replace():
+    """测试 action="replace"：JS 源码和 Python 函数"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+
+    # Python 函数以 JS 参数调用，返回值传回 JS
+    ctx.hook("getTimestamp", action="replace", replacement=lambda: 1700000000000)
+    result = ctx.call("login", ["a", "b"])
+    assert result.startswith("0000000007171|b|a")
+
+    # JS 源码以原来的 this 和参数调用
+    ctx.hook("CryptoJS.AES.encrypt", action="replace",
+             replacement="function (text, key) { return key + '/' + text; }")
+    assert ctx.call("login", ["a", "b"]) == "k3y/a|b|1700000000000"
+
+    events = hook_events(ctx)
+    assert events[-1]["path"] == "CryptoJS.AES.encrypt"
+    assert events[-1]["return"] == "k3y/a|b|1700000000000"
+
+    # Python 异常在 JS 中抛出
+    def fail(*args):
+        raise ValueError("no timestamp")
+
+    ctx.hook("getTimestamp", action="replace", replacement=fail)
+    try:
+        ctx.call("login", ["a", "b"])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "no timestamp" in str(e)
+    print("[OK] action='replace'")
+
+
+def test_looks_native():
+    """测试包装后的函数看起来仍是原生函数"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+    ctx.hook("CryptoJS.AES.encrypt")
+
+    assert ctx.evaluate("CryptoJS.AES.encrypt.name") == "encrypt"
+    assert ctx.evaluate("CryptoJS.AES.encrypt.length") == 2
+    assert "[native code]" in ctx.evaluate("CryptoJS.AES.encrypt.toString()")
+    assert "[native code]" in ctx.evaluate("Function.prototype.toString.call(CryptoJS.AES.encrypt)")
+    assert ctx.evaluate("CryptoJS.AES.encrypt.hasOwnProperty('toString')") is False
+    assert ctx.evaluate("Object.getOwnPropertyNames(globalThis).includes('__neverjscore_hook__')") is False
+    print("[OK] 包装函数显示为 [native code]")
+
+
+def test_invalid():
+    """测试无效的参数和目标"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+
+    for kwargs in (
+        {"action": "spy"},
+        {"action": "replace"},
+        {"action": "log", "replacement": "function () {}"},
+    ):
+        try:
+            ctx.hook("getTimestamp", **kwargs)
+            assert False, f"应该抛出 ValueError: {kwargs}"
+        except ValueError:
+            pass
+
+    for path in ("missing.fn", "CryptoJS.AES"):
+        try:
+            ctx.hook(path)
+            assert False, f"应该抛出异常: {path}"
+        except Exception as e:
+            assert "Cannot hook" in str(e)
+
+    try:
+        never_jscore.Context(enable_extensions=False).hook("Math.max")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "enable_extensions" in str(e)
+    print("[OK] 无效参数")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext.hook()（Python 回调在 Context 线程上调用）"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile(JS_CODE)
+    ctx.hook("getTimestamp", action="replace", replacement=lambda: 42)
+
+    assert ctx.call("login", ["a", "b"]).startswith("24|b|a")
+    assert [e["data"]["return"] for e in ctx.events("hook")] == [42]
+    ctx.close()
+    print("[OK] ThreadedContext.hook()")
+
+
+def test_engine_hooks():
+    """测试 JSEngine(hooks=[...])：每个Worker安装，事件随调用返回"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2, hooks=[
+        {"path": "CryptoJS.AES.encrypt"},
+        {"path": "getTimestamp", "action": "replace", "replacement": lambda: 1000},
+    ])
+
+    def check(user):
+        result, events = engine.call("login", [user, "pw"], with_events=True)
+        paths = [e["data"]["path"] for e in events if e["channel"] == "hook"]
+        assert paths == ["getTimestamp", "CryptoJS.AES.encrypt"]
+        assert events[-1]["data"]["args"][0] == f"{user}|pw|1000"
+        assert events[-1]["data"]["return"] == result
+
+    threads = [threading.Thread(target=check, args=(f"user{i}",)) for i in range(8)]
+    for t in threads:
+        t.start()
+    for t in threads:
+        t.join()
+
+    # 每个Worker都安装了 Hook，包装函数同样显示为 [native code]
+    for worker_id in range(2):
+        assert "[native code]" in engine.call_on(worker_id, "sourceOf", ["getTimestamp"])
+        native = engine.call_on(worker_id, "describeEncrypt", [])
+        assert native[:2] == ["encrypt", 2]
+        assert "[native code]" in native[2] and "[native code]" in native[3]
+        assert native[4] is False
+    engine.close()
+
+    for hooks in ([{"action": "log"}], [{"path": "getTimestamp", "unknown": 1}]):
+        try:
+            never_jscore.JSEngine(JS_CODE, workers=1, hooks=hooks)
+            assert False, f"应该抛出 ValueError: {hooks}"
+        except ValueError:
+            pass
+
+    try:
+        never_jscore.JSEngine(JS_CODE, workers=1, hooks=[{"path": "missing.fn"}])
+        assert False, "应该抛出 RuntimeError"
+    except RuntimeError as e:
+        assert "missing.fn" in str(e)
+    print("[OK] JSEngine(hooks=[...])")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试声明式 Hook")
+    print("=" * 60)
+
+    test_log()
+    test_capture_options()
+    test_async_return()
+    test_terminate()
+    test_replace()
+    test_looks_native()
+    test_invalid()
+    test_threaded_context()
+    test_engine_hooks()
+
+    print("\n" + "=" * 60)
+    print("所有声明式 Hook 测试通过!")
+    print("=" * 60)
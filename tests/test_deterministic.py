@@ -0,0 +1,177 @@
+"""
+测试确定性执行模式 (deterministic=True)
+
+random_seed 只固定随机数，deterministic 在此基础上进一步固定
+Date / performance.now / crypto.randomUUID / generateKey / 定时器顺序，
+让相同脚本 + 相同输入在多次运行、多个 Worker 之间得到逐字节相同的输出。
+"""
+
+import os
+import subprocess
+import sys
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+
+SNAPSHOT_JS = """
+    JSON.stringify({
+        now: Date.now(),
+        date: new Date().toISOString(),
+        perf: performance.now(),
+        random: Math.random(),
+        uuid: crypto.randomUUID(),
+    })
+"""
+
+
+def test_time_sources_pinned():
+    """测试 Date / performance.now / randomUUID 在两个 Context 之间完全一致"""
+    ctx1 = never_jscore.Context(deterministic=True)
+    out1 = ctx1.evaluate(SNAPSHOT_JS)
+    del ctx1
+
+    ctx2 = never_jscore.Context(deterministic=True)
+    out2 = ctx2.evaluate(SNAPSHOT_JS)
+    del ctx2
+
+    assert out1 == out2, "确定性模式下两次运行输出应逐字节相同"
+    print(f"[OK] 输出: {out1}")
+
+
+def test_clock_is_monotonic():
+    """测试虚拟时钟单调前进，忙等循环可以结束"""
+    ctx = never_jscore.Context(deterministic=True)
+    elapsed = ctx.evaluate("""
+        const start = Date.now();
+        while (Date.now() - start < 50) {}
+        Date.now() - start
+    """)
+    assert elapsed >= 50
+    print(f"[OK] 忙等循环结束，虚拟经过 {elapsed}ms")
+
+
+def test_timer_ordering():
+    """测试定时器严格按 (到期时间, 创建顺序) 执行，且不真实等待"""
+    ctx = never_jscore.Context(deterministic=True)
+    order = ctx.evaluate("""
+        new Promise(resolve => {
+            const order = [];
+            setTimeout(() => order.push('b:20'), 20);
+            setTimeout(() => order.push('a:10'), 10);
+            setTimeout(() => order.push('c:10'), 10);
+            setTimeout(() => { order.push('end'); resolve(order); }, 60000);
+        })
+    """)
+    assert order == ['a:10', 'c:10', 'b:20', 'end'], order
+    print(f"[OK] 定时器顺序: {order}")
+
+
+def test_generate_key_reproducible():
+    """测试 crypto.subtle.generateKey (AES) 生成的密钥可重现"""
+    code = """
+        (async () => {
+            const key = await crypto.subtle.generateKey(
+                { name: 'AES-GCM', length: 256 }, true, ['encrypt', 'decrypt']);
+            const raw = await crypto.subtle.exportKey('raw', key);
+            return Array.from(new Uint8Array(raw));
+        })()
+    """
+    ctx1 = never_jscore.Context(deterministic=True)
+    key1 = ctx1.evaluate(code)
+    del ctx1
+
+    ctx2 = never_jscore.Context(deterministic=True)
+    key2 = ctx2.evaluate(code)
+    del ctx2
+
+    assert key1 == key2, "确定性模式下生成的 AES 密钥应相同"
+    print(f"[OK] AES 密钥: {key1[:8]}...")
+
+
+def test_engine_workers_identical():
+    """测试 JSEngine 多个 Worker 的输出一致"""
+    engine = never_jscore.JSEngine("""
+        function snapshot() {
+            return [Date.now(), Math.random(), crypto.randomUUID()];
+        }
+    """, workers=2, deterministic=True)
+
+    # 每个 Worker 从相同状态开始，首次调用结果一致
+    results = [engine.call_on(worker_id, "snapshot", []) for worker_id in range(2)]
+    assert results[0] == results[1], results
+    engine.close()
+    print(f"[OK] Worker 输出: {results[0]}")
+
+
+def test_engine_state_reset_per_task():
+    """测试 JSEngine 每个任务都从相同状态开始，与 Worker 之前执行过的任务无关"""
+    engine = never_jscore.JSEngine("""
+        function snapshot() {
+            return [Date.now(), performance.now(), Math.random(), crypto.randomUUID()];
+        }
+    """, workers=2, deterministic=True)
+
+    # Worker 0 先执行若干任务，推进时钟和随机数
+    for _ in range(5):
+        engine.call_on(0, "snapshot", [])
+    results = [engine.call_on(worker_id, "snapshot", []) for worker_id in range(2)]
+    assert results[0] == results[1], results
+
+    # 普通调用（由任意 Worker 执行）结果也都相同
+    outputs = [engine.call("snapshot", []) for _ in range(6)]
+    assert all(output == results[0] for output in outputs), outputs
+    engine.close()
+    print("[OK] 每个任务从相同的时钟和随机数状态开始")
+
+
+def run_python(code, env=None):
+    """在新进程中执行代码（V8 哈希种子在进程中第一次创建实例时确定）"""
+    return subprocess.run(
+        [sys.executable, "-W", "always", "-c", code],
+        capture_output=True, text=True, env={**os.environ, **(env or {})},
+    )
+
+
+def test_hash_seed_pinned():
+    """测试第一个实例使用 deterministic=True 时固定哈希种子，否则发出 RuntimeWarning"""
+    first = run_python("import never_jscore; never_jscore.Context(deterministic=True); print('ok')")
+    assert first.returncode == 0 and "RuntimeWarning" not in first.stderr, first.stderr
+
+    late = run_python("import never_jscore; never_jscore.Context(); never_jscore.Context(deterministic=True)")
+    assert late.returncode == 0 and "RuntimeWarning" in late.stderr, late.stderr
+
+    pinned = run_python(
+        "import never_jscore; never_jscore.Context(); never_jscore.Context(deterministic=True)",
+        env={"NEVER_JSCORE_HASH_SEED": "42"},
+    )
+    assert "RuntimeWarning" not in pinned.stderr, pinned.stderr
+
+    invalid = run_python(
+        "import never_jscore; never_jscore.Context()",
+        env={"NEVER_JSCORE_HASH_SEED": "abc"},
+    )
+    assert invalid.returncode != 0 and "ValueError" in invalid.stderr, invalid.stderr
+    print("[OK] 哈希种子固定 / RuntimeWarning / 无效环境变量")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试确定性执行模式")
+    print("=" * 60)
+
+    test_time_sources_pinned()
+    test_clock_is_monotonic()
+    test_timer_ordering()
+    test_generate_key_reproducible()
+    test_engine_workers_identical()
+    test_engine_state_reset_per_task()
+    test_hash_seed_pinned()
+
+    print("\n" + "=" * 60)
+    print("[PASS] 所有确定性模式测试通过！")
+    print("=" * 60)
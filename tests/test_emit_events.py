@@ -0,0 +1,256 @@
+"""
+测试 $emit() 事件：JS 发送中间值而不终止执行
+
+Context / ThreadedContext 通过 events() 和 on_event 回调获取事件，
+JSEngine 通过 call(with_events=True) 和 JSFuture.events() 获取本次调用的事件
+"""
+
+import sys
+import threading
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+JS_CODE = """
+function rounds(n) {
+    let key = 1;
+    for (let i = 0; i < n; i++) {
+        key = (key * 31 + i) % 997;
+        $emit('round_key', { round: i, key: key });
+    }
+    $emit('done', n);
+    return key;
+}
+function emitThenThrow() {
+    $emit('before_error', 'x');
+    throw new Error('boom');
+}
+function emitThenTerminate() {
+    $emit('before_terminate', 1);
+    $terminate({ stopped: true });
+}
+"""
+
+
+def test_context_events():
+    """测试 Context.events() 按发送顺序记录事件，执行不被中断"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+
+    result = ctx.call("rounds", [3])
+    events = ctx.events()
+    assert [e["channel"] for e in events] == ["round_key"] * 3 + ["done"]
+    assert [e["data"]["round"] for e in ctx.events("round_key")] == [0, 1, 2]
+    assert ctx.events("round_key")[-1]["data"]["key"] == result
+    assert ctx.events("done") == [{"channel": "done", "data": 3}]
+
+    # 事件跨调用累积，clear_events() 清空
+    ctx.evaluate("$emit('misc', [1, 'a', null]); 1")
+    assert ctx.events("misc")[0]["data"] == [1, "a", None]
+    ctx.clear_events()
+    assert ctx.events() == []
+    print("[OK] Context.events() 记录事件")
+
+
+def test_context_on_event():
+    """测试 on_event 回调按顺序调用"""
+    received = []
+    ctx = never_jscore.Context(on_event=received.append)
+    ctx.compile(JS_CODE)
+
+    ctx.call("rounds", [2])
+    assert [e["channel"] for e in received] == ["round_key", "round_key", "done"]
+
+    # 函数和 undefined 为 None，无法 JSON 序列化的数据（BigInt、循环引用）转为字符串
+    received.clear()
+    ctx.evaluate("$emit('fn', function f() {}); $emit('undef'); $emit('big', 10n); 1")
+    assert received[0]["data"] is None
+    assert received[1] == {"channel": "undef", "data": None}
+    assert received[2]["data"] == "10"
+
+    # 事件只交给回调，不再记录
+    assert ctx.events() == []
+    print("[OK] on_event 回调")
+
+
+def test_on_event_raises():
+    """测试 on_event 抛出异常时，本次调用的未处理错误仍然被记录和上报"""
+    class Stop(Exception):
+        pass
+
+    def on_event(event):
+        raise Stop()
+
+    reported = []
+    ctx = never_jscore.Context(on_event=on_event, on_error=reported.append)
+    try:
+        ctx.evaluate("$emit('x', 1); Promise.reject(new Error('lost')); 1", wait_timers="all")
+        assert False, "应该抛出 on_event 的异常"
+    except Stop:
+        pass
+    assert [e["message"] for e in ctx.errors] == ["lost"]
+    assert [e["message"] for e in reported] == ["lost"]
+    # 之后的调用不会再看到上一次的错误
+    assert ctx.evaluate("2") == 2
+    assert len(ctx.errors) == 1
+    print("[OK] on_event 异常不影响错误上报")
+
+
+def test_context_events_on_failure():
+    """测试调用失败或被 $terminate() 终止时，之前的事件仍然保留"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+
+    try:
+        ctx.call("emitThenThrow", [])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "boom" in str(e)
+    assert ctx.events("before_error")[0]["data"] == "x"
+
+    try:
+        ctx.call("emitThenTerminate", [])
+        assert False, "应该抛出 HookTerminated"
+    except never_jscore.HookTerminated as e:
+        assert e.data == {"stopped": True}
+    assert ctx.events("before_terminate")[0]["data"] == 1
+    print("[OK] 失败/终止的调用保留事件")
+
+
+def test_async_events():
+    """测试异步代码和定时器中的事件"""
+    ctx = never_jscore.Context()
+    result = ctx.evaluate("""
+        (async () => {
+            $emit('step', 1);
+            await new Promise(r => setTimeout(r, 10));
+            $emit('step', 2);
+            return 'ok';
+        })()
+    """)
+    assert result == "ok"
+    assert [e["data"] for e in ctx.events("step")] == [1, 2]
+    print("[OK] 异步代码中的事件")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext 的事件和回调"""
+    received = []
+    ctx = never_jscore.ThreadedContext(on_event=received.append)
+    ctx.compile(JS_CODE)
+
+    ctx.call("rounds", [4])
+    assert len(received) == 5
+    assert ctx.events() == []
+
+    errors = []
+
+    def worker():
+        try:
+            for _ in range(5):
+                ctx.call("rounds", [1])
+        except Exception as e:
+            errors.append(e)
+
+    threads = [threading.Thread(target=worker) for _ in range(4)]
+    for t in threads:
+        t.start()
+    for t in threads:
+        t.join()
+    assert not errors
+    assert [e["channel"] for e in received].count("done") == 1 + 20
+
+    # 没有回调时记录到 events()
+    ctx.close()
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile(JS_CODE)
+    ctx.call("rounds", [2])
+    assert len(ctx.events("round_key")) == 2
+    ctx.clear_events()
+    assert ctx.events() == []
+    ctx.close()
+    print("[OK] ThreadedContext 事件")
+
+
+def test_engine_with_events():
+    """测试 JSEngine.call(with_events=True) 只返回本次调用的事件"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2)
+
+    # 默认不返回事件
+    assert isinstance(engine.call("rounds", [2]), int)
+
+    result, events = engine.call("rounds", [3], with_events=True)
+    assert [e["channel"] for e in events] == ["round_key"] * 3 + ["done"]
+    assert events[2]["data"]["key"] == result
+
+    result, events = engine.execute("$emit('exec', 7); 1 + 1", with_events=True)
+    assert result == 2
+    assert events == [{"channel": "exec", "data": 7}]
+
+    # 失败时事件设置在异常的 events 属性上
+    try:
+        engine.call("emitThenThrow", [], with_events=True)
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert e.events == [{"channel": "before_error", "data": "x"}]
+
+    try:
+        engine.call("emitThenTerminate", [], with_events=True)
+        assert False, "应该抛出 HookTerminated"
+    except never_jscore.HookTerminated as e:
+        assert e.events[0]["channel"] == "before_terminate"
+
+    # 并发调用的事件互不混合
+    def check(n):
+        _, events = engine.call("rounds", [n], with_events=True)
+        assert len(events) == n + 1
+        assert events[-1]["data"] == n
+
+    threads = [threading.Thread(target=check, args=(n,)) for n in range(1, 9)]
+    for t in threads:
+        t.start()
+    for t in threads:
+        t.join()
+
+    engine.close()
+    print("[OK] JSEngine.call(with_events=True)")
+
+
+def test_future_events():
+    """测试 JSFuture.events()"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1)
+
+    future = engine.submit("rounds", [2])
+    events = future.events(timeout=5)
+    assert [e["channel"] for e in events] == ["round_key", "round_key", "done"]
+    assert future.result() == events[1]["data"]["key"]
+
+    future = engine.submit("emitThenThrow", [])
+    assert future.events(timeout=5)[0]["channel"] == "before_error"
+    assert future.exception() is not None
+
+    engine.close()
+    print("[OK] JSFuture.events()")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 $emit() 事件")
+    print("=" * 60)
+
+    test_context_events()
+    test_context_on_event()
+    test_on_event_raises()
+    test_context_events_on_failure()
+    test_async_events()
+    test_threaded_context()
+    test_engine_with_events()
+    test_future_events()
+
+    print("\n" + "=" * 60)
+    print("所有 $emit() 事件测试通过!")
+    print("=" * 60)
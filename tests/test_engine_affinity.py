@@ -0,0 +1,222 @@
+"""
+测试 JSEngine 亲和路由（affinity_key / call_on）
+
+相同 affinity_key 的调用始终交给同一个 Worker，有状态的脚本（会话计数器、缓存的 token）
+可以直接使用 JSEngine；call_on 在指定 Worker 上执行
+"""
+
+import os
+import sys
+import tempfile
+import threading
+import time
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+JS_CODE = """
+const workerTag = Math.random().toString(36).slice(2);
+const sessions = {};
+
+function touch(session) {
+    sessions[session] = (sessions[session] || 0) + 1;
+    return sessions[session];
+}
+
+function tag() {
+    return workerTag;
+}
+"""
+
+
+def test_affinity_is_sticky():
+    """测试相同 key 的调用落在同一个 Worker 上"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=4)
+    keys = [f"session-{i}" for i in range(8)]
+    results = {key: [] for key in keys}
+
+    def run(key):
+        for _ in range(20):
+            results[key].append(engine.call("touch", [key], affinity_key=key))
+
+    threads = [threading.Thread(target=run, args=(key,)) for key in keys]
+    for t in threads:
+        t.start()
+    for t in threads:
+        t.join()
+
+    # 每个会话的计数器都在同一个 Worker 上连续递增
+    for key in keys:
+        assert results[key] == list(range(1, 21)), (key, results[key])
+
+    # execute 同样支持亲和路由
+    tags = {engine.execute("workerTag", affinity_key="session-0") for _ in range(10)}
+    assert len(tags) == 1
+    print("[OK] 相同 affinity_key 始终交给同一个 Worker")
+
+
+def test_affinity_spreads_keys():
+    """测试不同 key 分散到多个 Worker"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=4)
+    tags = {engine.call("tag", [], affinity_key=f"user-{i}") for i in range(64)}
+    assert len(tags) > 1, "不同 key 应该分散到多个 Worker"
+    print(f"[OK] 64 个 key 分散到 {len(tags)} 个 Worker")
+
+
+def test_call_on():
+    """测试在指定 Worker 上调用"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=3)
+    tags = engine.broadcast("workerTag")
+
+    for worker_id in range(3):
+        for _ in range(5):
+            assert engine.call_on(worker_id, "tag", []) == tags[worker_id]
+
+    assert engine.call_on(1, "touch", ["s"]) == 1
+    assert engine.call_on(1, "touch", ["s"]) == 2
+    assert engine.call_on(2, "touch", ["s"]) == 1
+
+    try:
+        engine.call_on(3, "tag", [])
+        assert False, "worker_id 超出范围应该抛出 ValueError"
+    except ValueError:
+        pass
+    print("[OK] call_on 在指定 Worker 上执行")
+
+
+def test_pinned_tasks_use_queue():
+    """测试亲和 key / call_on 的任务占用队列名额、按优先级排队且不会被其他 Worker 窃取"""
+    code = JS_CODE + """
+    const order = [];
+    function busy(ms) {
+        const end = Date.now() + ms;
+        while (Date.now() < end) {}
+        return ms;
+    }
+    function record(name) { order.push(name); return name; }
+    function getOrder() { return order; }
+    """
+    engine = never_jscore.JSEngine(code, workers=2, max_queue_size=4, submit_policy="reject")
+    blocker = engine.submit("busy", [400], affinity_key="k")
+    deadline = time.time() + 5
+    while not blocker.running() and time.time() < deadline:
+        time.sleep(0.01)
+    assert blocker.running()
+
+    low = [engine.submit("record", ["low"], affinity_key="k", priority="low") for _ in range(2)]
+    high = [engine.submit("record", ["high"], affinity_key="k", priority="high") for _ in range(2)]
+    stats = engine.stats()
+    assert stats["queue_size_by_priority"] == {"high": 2, "normal": 0, "low": 2}
+
+    # 队列已满时与普通任务一样遵循 submit_policy
+    for submit in [
+        lambda: engine.submit("record", ["x"], affinity_key="k"),
+        lambda: engine.call_on(0, "record", ["x"]),
+    ]:
+        try:
+            submit()
+            assert False, "队列已满应该抛出 PoolOverloadedError"
+        except never_jscore.PoolOverloadedError:
+            pass
+
+    blocker.result(timeout=5)
+    for future in low + high:
+        future.result(timeout=5)
+    # 另一个 Worker 一直空闲也没有取走这些任务，高优先级先执行
+    order = engine.call("getOrder", [], affinity_key="k")
+    assert sorted(order) == ["high", "high", "low", "low"], order
+    assert order[0] == "high", order
+    print(f"[OK] 亲和任务遵循队列名额和优先级: {order}")
+
+
+def test_affinity_with_elastic_pool():
+    """测试弹性伸缩增减 Worker 时 key 的归属不变"""
+    code = JS_CODE + """
+    function busy(ms) {
+        const end = Date.now() + ms;
+        while (Date.now() < end) {}
+        return ms;
+    }
+    """
+    engine = never_jscore.JSEngine(code, min_workers=1, max_workers=4, idle_timeout=0.3)
+    keys = [f"session-{i}" for i in range(16)]
+
+    # 落在未启动槽位上的 key 按需启动对应的 Worker
+    tags = {key: engine.call("tag", [], affinity_key=key) for key in keys}
+    for key in keys:
+        assert engine.call("touch", [key], affinity_key=key) == 1
+    assert engine.workers > 1, "key 应该分布到未启动的槽位上"
+
+    # 排队触发扩容，空闲后回收：key 的归属和会话状态不受影响
+    futures = [engine.submit("busy", [100]) for _ in range(12)]
+    for f in futures:
+        f.result(timeout=10)
+    time.sleep(1.0)
+    for key in keys:
+        assert engine.call("tag", [], affinity_key=key) == tags[key], key
+        assert engine.call("touch", [key], affinity_key=key) == 2, key
+    print(f"[OK] 弹性伸缩时 key 的归属不变（{engine.workers} 个 Worker）")
+
+
+def test_rebalance_when_worker_dies():
+    """测试 Worker 退出时 key 迁移到其他 Worker"""
+    flag = os.path.join(tempfile.mkdtemp(), "fail_init")
+
+    # 标志文件存在时初始化失败；每个任务后重建 JsRuntime 以触发重新初始化
+    code = f"""
+        const fs = require('fs');
+        if (fs.existsSync({flag!r})) {{
+            throw new Error('init disabled');
+        }}
+        function ping() {{ return 'pong'; }}
+    """
+    engine = never_jscore.JSEngine(
+        code, workers=2, enable_node_compat=True, max_tasks_per_worker=1
+    )
+
+    open(flag, "w").close()
+    assert engine.call_on(0, "ping", []) == 'pong'  # 任务完成后重建失败，Worker 0 退出
+
+    deadline = time.time() + 5
+    while engine.healthy_workers != 1 and time.time() < deadline:
+        time.sleep(0.05)
+    assert engine.healthy_workers == 1
+
+    try:
+        engine.call_on(0, "ping", [])
+        assert False, "Worker 0 已退出，应该抛出 RuntimeError"
+    except RuntimeError as e:
+        assert "Worker 0" in str(e)
+
+    # 原本属于 Worker 0 的 key 迁移到 Worker 1，调用不受影响
+    os.remove(flag)
+    for i in range(20):
+        assert engine.call("ping", [], affinity_key=f"key-{i}") == 'pong'
+
+    deadline = time.time() + 15
+    while engine.healthy_workers != 2 and time.time() < deadline:
+        time.sleep(0.1)
+    assert engine.healthy_workers == 2
+    assert engine.call_on(0, "ping", []) == 'pong'
+    print("[OK] Worker 退出时 key 迁移到其他 Worker")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 JSEngine 亲和路由")
+    print("=" * 60)
+
+    test_affinity_is_sticky()
+    test_affinity_spreads_keys()
+    test_call_on()
+    test_pinned_tasks_use_queue()
+    test_affinity_with_elastic_pool()
+    test_rebalance_when_worker_dies()
+
+    print("\n" + "=" * 60)
+    print("所有亲和路由测试通过!")
+    print("=" * 60)
@@ -0,0 +1,164 @@
+"""
+测试 JSEngine 批量调用 API（call_many / map）
+
+批量调用分块提交给 Worker，每块只需一次调度，结果按输入顺序返回
+"""
+
+import sys
+import time
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+JS_CODE = """
+function add(a, b) { return a + b; }
+function double(x) { return x * 2; }
+function parse(text) { return JSON.parse(text).value; }
+async function slowEcho(x, delay) {
+    await new Promise(r => setTimeout(r, delay));
+    return x;
+}
+"""
+
+
+def test_call_many_ordered():
+    """测试 call_many 按输入顺序返回结果"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=4)
+    args_list = [[i, i] for i in range(1000)]
+    results = engine.call_many("add", args_list)
+    assert results == [i * 2 for i in range(1000)]
+
+    # 元组也可以作为参数列表
+    assert engine.call_many("add", [(1, 2), (3, 4)]) == [3, 7]
+    assert engine.call_many("add", []) == []
+    print("[OK] call_many 按输入顺序返回 1000 个结果")
+
+
+def test_map():
+    """测试 map 每个元素作为唯一参数"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2)
+    assert engine.map("double", range(100)) == [i * 2 for i in range(100)]
+    assert engine.map("double", [1, 2, 3], chunk_size=1) == [2, 4, 6]
+    print("[OK] map 每个元素作为唯一参数")
+
+
+def test_unordered():
+    """测试 ordered=False 按完成顺序返回"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2)
+    # 第一块最慢，按完成顺序时排在最后
+    results = engine.call_many(
+        "slowEcho", [["slow", 200], ["fast", 0]], ordered=False, chunk_size=1
+    )
+    assert sorted(results) == ["fast", "slow"]
+    assert results == ["fast", "slow"]
+    print("[OK] ordered=False 按完成顺序返回")
+
+
+def test_return_exceptions():
+    """测试单项失败以异常对象返回，不影响其他项"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2)
+    inputs = ['{"value": 1}', 'not json', '{"value": 3}']
+
+    results = engine.map("parse", inputs, return_exceptions=True)
+    assert results[0] == 1
+    assert isinstance(results[1], Exception)
+    assert "JSON" in str(results[1]) or "Unexpected" in str(results[1])
+    assert results[2] == 3
+
+    # 默认：任一失败则抛出异常，并指出失败项的下标
+    try:
+        engine.map("parse", inputs)
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "item 1" in str(e)
+    print("[OK] return_exceptions 返回单项错误对象")
+
+
+def test_rejected_chunks():
+    """测试队列已满时部分分块被拒绝"""
+    code = """
+    let started = 0;
+    function step(x, ms) {
+        started++;
+        const end = Date.now() + ms;
+        while (Date.now() < end) {}
+        return x;
+    }
+    function startedCount() { return started; }
+    """
+    engine = never_jscore.JSEngine(code, workers=1, max_queue_size=2, submit_policy="reject")
+    args_list = [[i, 200] for i in range(6)]
+
+    # return_exceptions：被拒绝的调用逐项返回 PoolOverloadedError，已提交的分块照常返回结果
+    results = engine.call_many("step", args_list, chunk_size=1, return_exceptions=True)
+    rejected = [i for i, r in enumerate(results) if isinstance(r, never_jscore.PoolOverloadedError)]
+    assert rejected and rejected == list(range(rejected[0], 6)), results
+    assert results[:rejected[0]] == list(range(rejected[0])), results
+
+    # 默认：抛出 PoolOverloadedError，已提交的分块被取消（最多第一个分块已经开始执行）
+    engine = never_jscore.JSEngine(code, workers=1, max_queue_size=2, submit_policy="reject")
+    try:
+        engine.call_many("step", args_list, chunk_size=1)
+        assert False, "应该抛出 PoolOverloadedError"
+    except never_jscore.PoolOverloadedError:
+        pass
+    assert engine.call("startedCount", []) <= 1, "已提交的分块应该被取消"
+    print(f"[OK] 队列已满时 {len(rejected)} 个调用被拒绝，已提交的分块照常返回或被取消")
+
+
+def test_invalid_args():
+    """测试参数校验"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1)
+    try:
+        engine.call_many("add", ["ab"])
+        assert False, "字符串不是参数列表，应该抛出 TypeError"
+    except TypeError:
+        pass
+
+    try:
+        engine.call_many("add", [[1, 2]], chunk_size=0)
+        assert False, "chunk_size=0 应该抛出 ValueError"
+    except ValueError:
+        pass
+    print("[OK] 参数校验")
+
+
+def test_batch_faster_than_loop():
+    """测试批量调用比逐个 call 快"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=4)
+    args_list = [[i, 1] for i in range(5000)]
+
+    start = time.perf_counter()
+    for args in args_list:
+        engine.call("add", args)
+    loop_time = time.perf_counter() - start
+
+    start = time.perf_counter()
+    engine.call_many("add", args_list)
+    batch_time = time.perf_counter() - start
+
+    print(f"  逐个 call: {loop_time:.3f}s, call_many: {batch_time:.3f}s")
+    assert batch_time < loop_time
+    print("[OK] call_many 比逐个 call 快")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 JSEngine 批量调用")
+    print("=" * 60)
+
+    test_call_many_ordered()
+    test_map()
+    test_unordered()
+    test_return_exceptions()
+    test_rejected_chunks()
+    test_invalid_args()
+    test_batch_faster_than_loop()
+
+    print("\n" + "=" * 60)
+    print("所有批量调用测试通过!")
+    print("=" * 60)
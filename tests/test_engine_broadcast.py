@@ -0,0 +1,125 @@
+"""
+测试 JSEngine 广播（broadcast / broadcast_call）
+
+广播的代码在每个 Worker 上恰好执行一次，返回每个 Worker 的结果；
+persist=True 时 Worker 重建后自动回放，所有 Worker 状态保持一致
+"""
+
+import sys
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+JS_CODE = """
+const workerTag = Math.random().toString(36).slice(2);
+let apiKey = 'old-key';
+let applied = 0;
+
+function setKey(key) {
+    apiKey = key;
+    applied++;
+    return workerTag;
+}
+
+function getKey() {
+    return apiKey;
+}
+
+function failOn(tag) {
+    if (workerTag === tag) throw new Error('rejected by ' + tag);
+    return workerTag;
+}
+"""
+
+
+def test_broadcast_every_worker_once():
+    """测试每个 Worker 恰好执行一次"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=4)
+
+    tags = engine.broadcast("applied++; workerTag")
+    assert len(tags) == 4
+    assert len(set(tags)) == 4, "每个 Worker 应该各执行一次"
+
+    counts = engine.broadcast("applied")
+    assert counts == [1, 1, 1, 1]
+    print("[OK] broadcast 在每个 Worker 上恰好执行一次")
+
+
+def test_broadcast_call_updates_all_workers():
+    """测试 broadcast_call 更新所有 Worker 的状态"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=3)
+
+    tags = engine.broadcast_call("setKey", ["new-key"])
+    assert len(set(tags)) == 3
+
+    # 之后的普通调用无论落在哪个 Worker 上都能看到新状态
+    assert engine.call_many("getKey", [[]] * 60) == ["new-key"] * 60
+    print("[OK] broadcast_call 更新所有 Worker 的状态")
+
+
+def test_persist_replay_after_recycle():
+    """测试 Worker 重建后回放持久化的广播"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2, max_tasks_per_worker=3)
+
+    engine.broadcast_call("setKey", ["rotated-key"])
+    results = [engine.call("getKey", []) for _ in range(20)]
+    assert engine.recycled_workers >= 2
+    assert results == ["rotated-key"] * 20, results
+
+    # 回放按广播顺序进行，后面的广播覆盖前面的状态
+    engine.broadcast_call("setKey", ["final-key"])
+    results = [engine.call("getKey", []) for _ in range(20)]
+    assert results == ["final-key"] * 20, results
+    print(f"[OK] 重建后回放广播（重建 {engine.recycled_workers} 次）")
+
+
+def test_no_persist():
+    """测试 persist=False 的广播在重建后不回放"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1, max_tasks_per_worker=2)
+
+    engine.broadcast_call("setKey", ["temp-key"], persist=False)
+    assert engine.call("getKey", []) == "temp-key"
+
+    # 超过任务数后 Worker 重建，临时状态丢失
+    results = [engine.call("getKey", []) for _ in range(4)]
+    assert "old-key" in results, results
+    print("[OK] persist=False 不回放")
+
+
+def test_return_exceptions():
+    """测试单个 Worker 失败的处理"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=3)
+    tags = engine.broadcast("workerTag")
+
+    results = engine.broadcast_call("failOn", [tags[1]], persist=False, return_exceptions=True)
+    assert results[0] == tags[0]
+    assert isinstance(results[1], Exception)
+    assert "rejected by" in str(results[1])
+    assert results[2] == tags[2]
+
+    try:
+        engine.broadcast_call("failOn", [tags[2]], persist=False)
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "worker 2" in str(e)
+    print("[OK] return_exceptions 返回单个 Worker 的错误")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 JSEngine 广播")
+    print("=" * 60)
+
+    test_broadcast_every_worker_once()
+    test_broadcast_call_updates_all_workers()
+    test_persist_replay_after_recycle()
+    test_no_persist()
+    test_return_exceptions()
+
+    print("\n" + "=" * 60)
+    print("所有广播测试通过!")
+    print("=" * 60)
@@ -0,0 +1,145 @@
+"""
+测试 JSEngine 关闭（close / 上下文管理器）
+
+close() 停止接收新任务，处理完（wait=True）或取消（wait=False）已排队的任务，
+Worker 线程退出并释放 isolate；with 语句退出时自动关闭
+"""
+
+import sys
+import threading
+import time
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+JS_CODE = """
+function busy(ms) {
+    const end = Date.now() + ms;
+    while (Date.now() < end) {}
+    return ms;
+}
+"""
+
+
+def submit_background(engine, count, ms):
+    """后台提交任务，返回 (线程列表, 结果列表)"""
+    results = [None] * count
+
+    def run(i):
+        try:
+            results[i] = engine.call("busy", [ms])
+        except Exception as e:
+            results[i] = e
+
+    threads = [threading.Thread(target=run, args=(i,)) for i in range(count)]
+    for t in threads:
+        t.start()
+        time.sleep(0.02)
+    return threads, results
+
+
+def test_close_drains_queue():
+    """测试 wait=True 处理完已排队的任务"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1)
+    threads, results = submit_background(engine, 4, 100)
+
+    assert engine.close() is True
+    for t in threads:
+        t.join()
+    assert results == [100] * 4, results
+    assert engine.closed
+    assert "closed" in repr(engine)
+    print("[OK] close() 处理完已排队的任务后退出")
+
+
+def test_close_cancels_queue():
+    """测试 wait=False 取消已排队的任务"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1)
+    threads, results = submit_background(engine, 4, 200)
+
+    start = time.perf_counter()
+    engine.close(wait=False)
+    assert time.perf_counter() - start < 0.1, "wait=False 不应该等待"
+    for t in threads:
+        t.join()
+
+    # 正在执行的任务完成，排队中的任务被取消
+    assert results[0] == 200
+    for r in results[1:]:
+        assert isinstance(r, Exception) and "cancelled" in str(r), r
+    print("[OK] close(wait=False) 取消已排队的任务")
+
+
+def test_close_timeout():
+    """测试等待超时后取消剩余任务"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1)
+    threads, results = submit_background(engine, 3, 300)
+
+    start = time.perf_counter()
+    assert engine.close(timeout=0.1) is False
+    assert time.perf_counter() - start < 0.3
+    for t in threads:
+        t.join()
+    assert results[0] == 300
+    assert any(isinstance(r, Exception) for r in results)
+    print("[OK] close(timeout=...) 超时后取消剩余任务")
+
+
+def test_calls_after_close():
+    """测试关闭后调用抛出异常"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2)
+    engine.close()
+    assert engine.close() is True  # 重复调用是安全的
+
+    for call in [
+        lambda: engine.call("busy", [1]),
+        lambda: engine.execute("1 + 1"),
+        lambda: engine.call_many("busy", [[1]]),
+        lambda: engine.broadcast("1"),
+        lambda: engine.call_on(0, "busy", [1]),
+        lambda: engine.reload(JS_CODE),
+    ]:
+        try:
+            call()
+            assert False, "关闭后调用应该抛出 RuntimeError"
+        except RuntimeError as e:
+            assert "closed" in str(e)
+    print("[OK] 关闭后调用抛出 RuntimeError")
+
+
+def test_context_manager():
+    """测试 with 语句退出时关闭"""
+    with never_jscore.JSEngine(JS_CODE, workers=2) as engine:
+        assert engine.call("busy", [1]) == 1
+        assert not engine.closed
+    assert engine.closed
+    print("[OK] with 语句退出时关闭引擎")
+
+
+def test_many_engines():
+    """测试反复创建和关闭引擎不会积累线程"""
+    for _ in range(20):
+        with never_jscore.JSEngine(JS_CODE, workers=4) as engine:
+            engine.call("busy", [0])
+    print("[OK] 反复创建和关闭 20 个引擎")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 JSEngine 关闭")
+    print("=" * 60)
+
+    test_close_drains_queue()
+    test_close_cancels_queue()
+    test_close_timeout()
+    test_calls_after_close()
+    test_context_manager()
+    test_many_engines()
+
+    print("\n" + "=" * 60)
+    print("所有关闭测试通过!")
+    print("=" * 60)
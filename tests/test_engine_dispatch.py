@@ -0,0 +1,95 @@
+"""
+测试 JSEngine 任务分发（每个 Worker 一个本地队列 + 任务窃取）
+
+任务优先交给空闲 Worker；忙碌 Worker 队列中的任务会被空闲 Worker 窃取，
+不会排在长任务后面等待
+"""
+
+import sys
+import threading
+import time
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+JS_CODE = """
+function add(a, b) { return a + b; }
+function busy(ms) {
+    const end = Date.now() + ms;
+    while (Date.now() < end) {}
+    return ms;
+}
+"""
+
+
+def test_high_call_rate():
+    """测试多线程高频调用结果正确、任务分散到所有 Worker"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=4)
+    errors = []
+
+    def client(seed):
+        try:
+            for i in range(500):
+                assert engine.call("add", [seed, i]) == seed + i
+        except Exception as e:
+            errors.append(e)
+
+    threads = [threading.Thread(target=client, args=(n,)) for n in range(16)]
+    start = time.perf_counter()
+    for t in threads:
+        t.start()
+    for t in threads:
+        t.join()
+    elapsed = time.perf_counter() - start
+    assert not errors, errors[:3]
+
+    stats = engine.stats()
+    assert stats["completed"] == 16 * 500
+    assert stats["queue_size"] == 0
+    assert all(w["tasks"] > 0 for w in stats["per_worker"]), stats["per_worker"]
+    print(f"[OK] 16 线程 x 500 次调用: {16 * 500 / elapsed:.0f} calls/s")
+
+
+def test_steal_from_busy_worker():
+    """测试空闲 Worker 窃取忙碌 Worker 队列中的任务"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2)
+    long_task = engine.submit("busy", [1000])
+    time.sleep(0.05)
+
+    start = time.perf_counter()
+    short = [engine.submit("busy", [10]) for _ in range(20)]
+    for f in short:
+        f.result(timeout=5)
+    elapsed = time.perf_counter() - start
+
+    assert not long_task.done()
+    assert elapsed < 0.8, f"短任务不应该等待长任务: {elapsed:.2f}s"
+    assert long_task.result() == 1000
+    print(f"[OK] 短任务在 {elapsed:.2f}s 内由空闲 Worker 完成")
+
+
+def test_close_drains_all_queues():
+    """测试关闭时处理完所有 Worker 队列中的任务"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=3)
+    futures = [engine.submit("busy", [20]) for _ in range(30)]
+    assert engine.close() is True
+    assert [f.result() for f in futures] == [20] * 30
+    print("[OK] close() 处理完所有队列中的任务")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 JSEngine 任务分发")
+    print("=" * 60)
+
+    test_high_call_rate()
+    test_steal_from_busy_worker()
+    test_close_drains_all_queues()
+
+    print("\n" + "=" * 60)
+    print("所有任务分发测试通过!")
+    print("=" * 60)
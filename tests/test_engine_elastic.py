@@ -0,0 +1,127 @@
+"""
+测试 JSEngine 弹性伸缩（min_workers / max_workers / idle_timeout）
+
+启动 min_workers 个 Worker，排队任务多于空闲 Worker 时按需增加到 max_workers 个，
+空闲超过 idle_timeout 的 Worker 退出；新 Worker 使用相同的初始化代码并回放持久化的广播
+"""
+
+import sys
+import time
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+JS_CODE = """
+globalThis.KEY = 'initial';
+function busy(ms) {
+    const end = Date.now() + ms;
+    while (Date.now() < end) {}
+    return ms;
+}
+function getKey() { return globalThis.KEY; }
+"""
+
+
+def wait_until(predicate, timeout=5):
+    deadline = time.time() + timeout
+    while time.time() < deadline:
+        if predicate():
+            return True
+        time.sleep(0.02)
+    return predicate()
+
+
+def test_fixed_pool_unchanged():
+    """测试未启用弹性伸缩时 Worker 数固定"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2)
+    assert engine.workers == engine.min_workers == engine.max_workers == 2
+    stats = engine.stats()
+    assert stats["workers"] == 2 and stats["scaled_up_workers"] == 0
+    assert "max_workers" not in repr(engine)
+    print("[OK] 固定大小的 Worker 池")
+
+
+def test_scale_up_and_down():
+    """测试排队时增加 Worker、空闲后回收"""
+    engine = never_jscore.JSEngine(JS_CODE, min_workers=1, max_workers=4, idle_timeout=0.5)
+    assert engine.workers == 1
+    assert engine.min_workers == 1 and engine.max_workers == 4
+    assert "max_workers=4" in repr(engine)
+
+    start = time.perf_counter()
+    futures = [engine.submit("busy", [300]) for _ in range(8)]
+    assert wait_until(lambda: engine.workers > 1), "排队时应该增加 Worker"
+    assert [f.result(timeout=10) for f in futures] == [300] * 8
+    elapsed = time.perf_counter() - start
+    assert engine.workers <= 4
+    # 单个 Worker 需要 2.4 秒
+    assert elapsed < 2.0, f"扩容后应该并行执行: {elapsed:.2f}s"
+
+    stats = engine.stats()
+    assert stats["scaled_up_workers"] >= 1
+    assert len(stats["per_worker"]) == stats["workers"]
+    print(f"[OK] 扩容到 {stats['workers']} 个 Worker，8 个任务耗时 {elapsed:.2f}s")
+
+    assert wait_until(lambda: engine.workers == 1, timeout=5), "空闲后应该回收到 min_workers"
+    stats = engine.stats()
+    assert stats["scaled_down_workers"] >= 1
+    assert stats["completed"] == 8  # 回收的 Worker 仍计入累计任务数
+    assert engine.call("busy", [1]) == 1
+    print("[OK] 空闲 Worker 回收到 min_workers")
+
+
+def test_new_workers_share_state():
+    """测试新 Worker 使用相同的初始化代码并回放持久化的广播"""
+    engine = never_jscore.JSEngine(JS_CODE, min_workers=1, max_workers=3, idle_timeout=5)
+    assert engine.broadcast("globalThis.KEY = 'rotated'") == [None]
+
+    futures = [engine.submit("busy", [200]) for _ in range(6)]
+    for f in futures:
+        f.result(timeout=10)
+    assert wait_until(lambda: engine.healthy_workers == engine.workers)
+    assert engine.workers > 1
+
+    active = [w["worker_id"] for w in engine.stats()["per_worker"]]
+    assert [engine.call_on(i, "getKey", []) for i in active] == ["rotated"] * len(active)
+    # 之后的广播投递给所有当前的 Worker
+    assert len(engine.broadcast("1")) == engine.workers
+    print(f"[OK] 新 Worker 与已有 Worker 状态一致（{len(active)} 个 Worker）")
+
+
+def test_invalid_options():
+    """测试无效参数"""
+    for kwargs in [
+        {"min_workers": 0, "max_workers": 2},
+        {"min_workers": 3, "max_workers": 2},
+        {"workers": 2, "max_workers": 4},
+        {"max_workers": 2, "idle_timeout": 0},
+    ]:
+        try:
+            never_jscore.JSEngine(JS_CODE, **kwargs)
+            assert False, f"应该拒绝无效参数: {kwargs}"
+        except ValueError:
+            pass
+
+    # workers 作为上限
+    engine = never_jscore.JSEngine(JS_CODE, workers=3, min_workers=1)
+    assert engine.workers == 1 and engine.max_workers == 3
+    print("[OK] 参数检查")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 JSEngine 弹性伸缩")
+    print("=" * 60)
+
+    test_fixed_pool_unchanged()
+    test_scale_up_and_down()
+    test_new_workers_share_state()
+    test_invalid_options()
+
+    print("\n" + "=" * 60)
+    print("所有弹性伸缩测试通过!")
+    print("=" * 60)
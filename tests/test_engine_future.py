@@ -0,0 +1,185 @@
+"""
+测试 JSEngine 可取消任务（submit / JSFuture）
+
+submit() 立即返回 JSFuture：result(timeout) 等待结果，cancel() 移除排队中的任务或
+终止执行中的任务；await JSFuture 时 asyncio 取消会传递到任务
+"""
+
+import asyncio
+import concurrent.futures
+import sys
+import threading
+import time
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+JS_CODE = """
+let calls = 0;
+function busy(ms) {
+    calls++;
+    const end = Date.now() + ms;
+    while (Date.now() < end) {}
+    return ms;
+}
+async function sleep(ms) {
+    calls++;
+    await new Promise(r => setTimeout(r, ms));
+    return ms;
+}
+function spin() {
+    calls++;
+    while (true) {}
+}
+function getCalls() { return calls; }
+function fail() { throw new Error('boom'); }
+"""
+
+
+def test_result():
+    """测试 result / done / exception"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2)
+    future = engine.submit("busy", [50])
+    assert not future.done()
+    assert future.result(timeout=5) == 50
+    assert future.done() and not future.cancelled()
+    assert future.exception() is None
+    assert "finished" in repr(future)
+
+    future = engine.submit("fail", [])
+    assert "boom" in str(future.exception(timeout=5))
+    try:
+        future.result()
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "boom" in str(e)
+    print("[OK] result / exception")
+
+
+def test_result_timeout():
+    """测试 result 超时不会取消任务"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1)
+    future = engine.submit("busy", [300])
+    try:
+        future.result(timeout=0.05)
+        assert False, "应该抛出 TimeoutError"
+    except TimeoutError:
+        pass
+    assert future.result() == 300
+    print("[OK] result(timeout) 超时")
+
+
+def test_cancel_queued():
+    """测试取消排队中的任务"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1)
+    first = engine.submit("busy", [200])
+    queued = [engine.submit("busy", [10]) for _ in range(5)]
+
+    assert all(f.cancel() for f in queued)
+    assert all(f.cancelled() and f.done() for f in queued)
+    try:
+        queued[0].result()
+        assert False, "应该抛出 CancelledError"
+    except concurrent.futures.CancelledError:
+        pass
+
+    assert first.result() == 200
+    assert first.cancel() is False  # 已完成的任务不能取消
+    # 被取消的任务没有执行
+    assert engine.call("getCalls", []) == 1
+    print("[OK] 取消排队中的任务")
+
+
+def test_cancel_running():
+    """测试终止执行中的任务"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1)
+
+    # 同步死循环：通过 terminate_execution 终止
+    future = engine.submit("spin", [])
+    deadline = time.time() + 5
+    while not future.running() and time.time() < deadline:
+        time.sleep(0.01)
+    assert future.running()
+    assert future.cancel()
+
+    # 异步等待：直接放弃
+    future = engine.submit("sleep", [10000])
+    time.sleep(0.1)
+    start = time.perf_counter()
+    assert future.cancel()
+    assert engine.call("busy", [1]) == 1
+    assert time.perf_counter() - start < 2, "Worker 应该很快恢复"
+
+    # Worker 重建了 JsRuntime，全局状态重置（只有上面的 busy 调用）
+    assert engine.call("getCalls", []) == 1
+    print("[OK] 终止执行中的任务")
+
+
+def test_done_callback():
+    """测试完成回调"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1)
+    event = threading.Event()
+    seen = []
+
+    def callback(future):
+        seen.append(future.result())
+        event.set()
+
+    future = engine.submit("busy", [20])
+    future.add_done_callback(callback)
+    assert event.wait(5)
+    assert seen == [20]
+
+    # 已完成时立即调用
+    future.add_done_callback(lambda f: seen.append("again"))
+    assert seen == [20, "again"]
+    print("[OK] add_done_callback")
+
+
+def test_asyncio():
+    """测试 await JSFuture 与 asyncio 取消"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2)
+
+    async def main():
+        assert await engine.submit("sleep", [20]) == 20
+
+        results = await asyncio.gather(*(engine.submit("busy", [i]) for i in range(10)))
+        assert results == list(range(10))
+
+        future = engine.submit("sleep", [10000])
+        try:
+            await asyncio.wait_for(future, timeout=0.1)
+            assert False, "应该超时"
+        except asyncio.TimeoutError:
+            pass
+        assert future.cancelled()
+
+        try:
+            await engine.submit("fail", [])
+            assert False, "应该抛出异常"
+        except Exception as e:
+            assert "boom" in str(e)
+
+    asyncio.run(main())
+    print("[OK] asyncio 集成")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 JSEngine 可取消任务")
+    print("=" * 60)
+
+    test_result()
+    test_result_timeout()
+    test_cancel_queued()
+    test_cancel_running()
+    test_done_callback()
+    test_asyncio()
+
+    print("\n" + "=" * 60)
+    print("所有可取消任务测试通过!")
+    print("=" * 60)
@@ -0,0 +1,105 @@
+"""
+测试 JSEngine Worker 生命周期策略（max_tasks_per_worker / max_worker_heap_mb）
+
+Worker 达到上限后在当前任务完成后重建 JsRuntime，重新加载初始化代码，
+排队中的任务不会丢失
+"""
+
+import sys
+import threading
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+JS_CODE = """
+// 模拟混淆脚本中不断增长的全局缓存
+const cache = [];
+let runtimeCalls = 0;
+
+function work(x) {
+    runtimeCalls++;
+    return { x, runtimeCalls };
+}
+
+function leak(mb) {
+    cache.push(new Array(mb * 1024 * 128).fill(1.5));
+    return cache.length;
+}
+"""
+
+
+def test_max_tasks_per_worker():
+    """测试处理指定数量任务后重建 Worker"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1, max_tasks_per_worker=3)
+
+    counts = [engine.call("work", [i])["runtimeCalls"] for i in range(7)]
+    # 每 3 个任务换一个新的 JsRuntime，全局状态重置
+    assert counts == [1, 2, 3, 1, 2, 3, 1], counts
+    assert engine.recycled_workers == 2
+    print("[OK] max_tasks_per_worker 按任务数重建 Worker")
+
+
+def test_max_worker_heap_mb():
+    """测试堆内存超限后重建 Worker"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1, max_worker_heap_mb=64)
+
+    sizes = [engine.call("leak", [16]) for _ in range(8)]
+    assert engine.recycled_workers >= 1, sizes
+    # 重建后缓存从头开始
+    assert min(sizes[1:]) == 1, sizes
+    print(f"[OK] max_worker_heap_mb 按堆内存重建 Worker（重建 {engine.recycled_workers} 次）")
+
+
+def test_no_tasks_dropped():
+    """测试重建期间排队的任务不会丢失"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2, max_tasks_per_worker=5)
+    results = [None] * 200
+
+    def submit(i):
+        results[i] = engine.call("work", [i])["x"]
+
+    threads = [threading.Thread(target=submit, args=(i,)) for i in range(200)]
+    for t in threads:
+        t.start()
+    for t in threads:
+        t.join()
+
+    assert results == list(range(200))
+    assert engine.recycled_workers >= 30
+    assert engine.call_many("work", [[i] for i in range(50)])[49]["x"] == 49
+    print(f"[OK] 200 个并发任务全部完成（重建 {engine.recycled_workers} 次）")
+
+
+def test_defaults_and_validation():
+    """测试默认不重建及参数校验"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1)
+    counts = [engine.call("work", [i])["runtimeCalls"] for i in range(10)]
+    assert counts == list(range(1, 11))
+    assert engine.recycled_workers == 0
+
+    for kwargs in [{"max_tasks_per_worker": 0}, {"max_worker_heap_mb": 0}]:
+        try:
+            never_jscore.JSEngine(JS_CODE, workers=1, **kwargs)
+            assert False, f"应该拒绝无效参数: {kwargs}"
+        except ValueError:
+            pass
+    print("[OK] 默认不重建，参数校验正常")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 JSEngine Worker 生命周期策略")
+    print("=" * 60)
+
+    test_max_tasks_per_worker()
+    test_max_worker_heap_mb()
+    test_no_tasks_dropped()
+    test_defaults_and_validation()
+
+    print("\n" + "=" * 60)
+    print("所有生命周期策略测试通过!")
+    print("=" * 60)
@@ -0,0 +1,168 @@
+"""
+测试 JSEngine 任务优先级（priority="high" / "normal" / "low"）
+
+排队时高优先级任务先执行；三个优先级都有任务时按 4:2:1 加权轮转，
+低优先级任务不会被饿死
+"""
+
+import sys
+import time
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+JS_CODE = """
+const order = [];
+function busy(ms) {
+    const end = Date.now() + ms;
+    while (Date.now() < end) {}
+    return ms;
+}
+function record(tag) {
+    order.push(tag);
+    return tag;
+}
+function getOrder() { return order.splice(0); }
+"""
+
+
+def block_worker(engine, ms=300):
+    """让一个空闲的 Worker 忙碌（只有一个 Worker 时，之后提交的任务都会排队）"""
+    future = engine.submit("busy", [ms])
+    deadline = time.time() + 5
+    while not future.running() and time.time() < deadline:
+        time.sleep(0.01)
+    assert future.running()
+    return future
+
+
+def test_high_priority_first():
+    """测试高优先级任务先于已排队的低优先级任务执行"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1)
+    blocker = block_worker(engine)
+
+    low = [engine.submit("record", ["low"], priority="low") for _ in range(8)]
+    high = [engine.submit("record", ["high"], priority="high") for _ in range(8)]
+
+    stats = engine.stats()
+    assert stats["queue_size"] == 16
+    assert stats["queue_size_by_priority"] == {"high": 8, "normal": 0, "low": 8}
+
+    blocker.result()
+    for future in low + high:
+        future.result(timeout=5)
+
+    order = engine.call("getOrder", [])
+    assert len(order) == 16
+    # 后提交的高优先级任务大多排在前面，低优先级任务也在高优先级任务全部完成前得到执行
+    assert order[:8].count("high") >= 6, order
+    last_high = len(order) - 1 - order[::-1].index("high")
+    assert order.index("low") < last_high, order
+    print(f"[OK] 高优先级任务优先执行: {order}")
+
+
+def test_weighted_fairness():
+    """测试三个优先级都有任务时按 4:2:1 出队"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1)
+    blocker = block_worker(engine)
+
+    futures = []
+    for priority in ["low", "normal", "high"]:
+        futures += [engine.submit("record", [priority], priority=priority) for _ in range(21)]
+
+    blocker.result()
+    for future in futures:
+        future.result(timeout=5)
+
+    order = engine.call("getOrder", [])
+    first = order[:21]
+    counts = {p: first.count(p) for p in ["high", "normal", "low"]}
+    assert counts == {"high": 12, "normal": 6, "low": 3}, counts
+    print(f"[OK] 加权轮转 high:normal:low = {counts['high']}:{counts['normal']}:{counts['low']}")
+
+
+def test_priority_on_all_methods():
+    """测试 call / execute / call_many / map 的 priority 参数"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2)
+    assert engine.call("record", ["x"], priority="high") == "x"
+    assert engine.execute("1 + 1", priority="low") == 2
+    assert engine.call_many("busy", [[1], [2]], priority="low") == [1, 2]
+    assert engine.map("busy", [3, 4], priority="high") == [3, 4]
+    assert engine.call("busy", [1], affinity_key="k", priority="low") == 1
+
+    try:
+        engine.call("busy", [1], priority="urgent")
+        assert False, "应该抛出 ValueError"
+    except ValueError as e:
+        assert "priority" in str(e)
+    print("[OK] 各调用方法的 priority 参数")
+
+
+def test_bounded_queue_shared_by_priorities():
+    """测试 max_queue_size 限制所有优先级的任务总数"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1, max_queue_size=2, submit_policy="reject")
+    blocker = block_worker(engine)
+
+    queued = [
+        engine.submit("busy", [1], priority="low"),
+        engine.submit("busy", [1], priority="high"),
+    ]
+    try:
+        engine.submit("busy", [1], priority="high")
+        assert False, "队列已满应该拒绝"
+    except never_jscore.PoolOverloadedError:
+        pass
+
+    blocker.result()
+    assert [f.result(timeout=5) for f in queued] == [1, 1]
+    # 出队后名额归还
+    assert engine.call("busy", [1], priority="low") == 1
+    assert engine.stats()["queue_size"] == 0
+    print("[OK] max_queue_size 限制所有优先级的任务总数")
+
+
+def test_priority_across_workers():
+    """测试多个Worker时，其他队列中的高优先级任务先于本地的低优先级任务执行"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2)
+    # 一个Worker长时间忙碌，另一个Worker先空闲下来，需要窃取对方队列中的任务
+    slow = block_worker(engine, 1500)
+    fast = block_worker(engine, 300)
+
+    low = [engine.submit("record", ["low"], priority="low") for _ in range(8)]
+    high = [engine.submit("record", ["high"], priority="high") for _ in range(8)]
+    # 任务分散在两个Worker的本地队列中
+    assert engine.stats()["queue_size_by_priority"] == {"high": 8, "normal": 0, "low": 8}
+
+    fast.result()
+    for future in low + high:
+        future.result(timeout=5)
+    assert not slow.done()
+
+    order = max((engine.call_on(worker_id, "getOrder", []) for worker_id in range(2)), key=len)
+    assert len(order) == 16, order
+    # 按整个池的 4:2:1 加权轮转：前 8 个任务中至多 2 个低优先级任务
+    # （只在各自队列内排序时，窃取前会先执行完本地的低优先级任务）
+    assert order[:8].count("high") >= 6, order
+    slow.result()
+    engine.close()
+    print(f"[OK] 多个Worker之间按优先级窃取任务: {order}")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 JSEngine 任务优先级")
+    print("=" * 60)
+
+    test_high_priority_first()
+    test_weighted_fairness()
+    test_priority_on_all_methods()
+    test_bounded_queue_shared_by_priorities()
+    test_priority_across_workers()
+
+    print("\n" + "=" * 60)
+    print("所有任务优先级测试通过!")
+    print("=" * 60)
@@ -0,0 +1,145 @@
+"""
+测试 JSEngine 有界任务队列与背压（max_queue_size / submit_policy）
+
+队列已满时：block 等待空位，reject 立即抛出 PoolOverloadedError，
+timeout 等待指定时间后抛出 PoolOverloadedError
+"""
+
+import sys
+import threading
+import time
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+JS_CODE = """
+function busy(ms) {
+    const end = Date.now() + ms;
+    while (Date.now() < end) {}
+    return ms;
+}
+"""
+
+
+def fill_queue(engine, count, ms):
+    """占满 Worker 和队列，返回后台线程列表"""
+    threads = [
+        threading.Thread(target=engine.call, args=("busy", [ms]))
+        for _ in range(count)
+    ]
+    for t in threads:
+        t.start()
+        time.sleep(0.05)
+    return threads
+
+
+def test_reject():
+    """测试 reject 策略：队列已满立即拒绝"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1, max_queue_size=2, submit_policy="reject")
+    assert engine.max_queue_size == 2
+
+    # 1 个任务占用 Worker，2 个任务排队
+    threads = fill_queue(engine, 3, 500)
+    assert engine.queue_size == 2
+
+    start = time.perf_counter()
+    try:
+        engine.call("busy", [1])
+        assert False, "队列已满应该抛出 PoolOverloadedError"
+    except never_jscore.PoolOverloadedError as e:
+        assert "overloaded" in str(e)
+    assert time.perf_counter() - start < 0.1, "reject 不应该等待"
+    assert engine.rejected_tasks == 1
+
+    for t in threads:
+        t.join()
+    assert engine.queue_size == 0
+    assert engine.call("busy", [1]) == 1
+    print("[OK] reject 策略立即抛出 PoolOverloadedError")
+
+
+def test_timeout():
+    """测试 timeout 策略：等待超时后拒绝"""
+    engine = never_jscore.JSEngine(
+        JS_CODE, workers=1, max_queue_size=1, submit_policy="timeout", submit_timeout=0.2
+    )
+    threads = fill_queue(engine, 2, 1000)
+
+    start = time.perf_counter()
+    try:
+        engine.call("busy", [1])
+        assert False, "应该在超时后抛出 PoolOverloadedError"
+    except never_jscore.PoolOverloadedError:
+        pass
+    elapsed = time.perf_counter() - start
+    assert 0.15 < elapsed < 0.8, f"等待时间异常: {elapsed:.3f}s"
+
+    for t in threads:
+        t.join()
+    print(f"[OK] timeout 策略等待 {elapsed:.2f}s 后拒绝")
+
+
+def test_block():
+    """测试 block 策略：等待队列有空位后继续执行"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1, max_queue_size=1)
+    threads = fill_queue(engine, 2, 200)
+
+    # 队列已满，这次调用会等待而不是失败
+    assert engine.call("busy", [1]) == 1
+    assert engine.rejected_tasks == 0
+
+    for t in threads:
+        t.join()
+    print("[OK] block 策略等待空位")
+
+
+def test_batch_respects_policy():
+    """测试批量调用同样受队列限制"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1, max_queue_size=1, submit_policy="reject")
+    try:
+        engine.call_many("busy", [[100]] * 10, chunk_size=1)
+        assert False, "分块超过队列容量应该抛出 PoolOverloadedError"
+    except never_jscore.PoolOverloadedError:
+        pass
+    print("[OK] call_many 受队列限制")
+
+
+def test_invalid_options():
+    """测试参数校验"""
+    for kwargs in [
+        {"submit_policy": "drop"},
+        {"submit_policy": "timeout"},
+        {"submit_policy": "reject", "submit_timeout": 1.0},
+        {"max_queue_size": 0},
+    ]:
+        try:
+            never_jscore.JSEngine(JS_CODE, workers=1, **kwargs)
+            assert False, f"应该拒绝无效参数: {kwargs}"
+        except ValueError:
+            pass
+
+    # 默认不限制队列长度
+    engine = never_jscore.JSEngine(JS_CODE, workers=1)
+    assert engine.max_queue_size is None
+    assert engine.queue_size == 0
+    print("[OK] 参数校验")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 JSEngine 有界任务队列")
+    print("=" * 60)
+
+    test_reject()
+    test_timeout()
+    test_block()
+    test_batch_respects_policy()
+    test_invalid_options()
+
+    print("\n" + "=" * 60)
+    print("所有任务队列测试通过!")
+    print("=" * 60)
@@ -0,0 +1,129 @@
+"""
+测试 JSEngine 热重载（reload）
+
+目标网站更新混淆脚本时，reload 逐个 Worker 切换到新代码，
+切换期间其余 Worker 继续服务，新代码加载失败时回滚并保持引擎可用
+"""
+
+import sys
+import threading
+import time
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+
+def make_code(version):
+    return f"""
+        let apiKey = 'default';
+        function version() {{ return {version}; }}
+        function sign(x) {{ return 'v{version}:' + apiKey + ':' + x; }}
+        function setKey(key) {{ apiKey = key; }}
+    """
+
+
+def test_reload_switches_code():
+    """测试 reload 后所有 Worker 使用新代码"""
+    engine = never_jscore.JSEngine(make_code(1), workers=3)
+    assert set(engine.broadcast_call("version", [])) == {1}
+
+    engine.reload(make_code(2))
+    assert engine.broadcast_call("version", []) == [2, 2, 2]
+    assert engine.call("sign", ["a"]) == "v2:default:a"
+
+    engine.reload(make_code(3), drain=False)
+    assert engine.broadcast_call("version", []) == [3, 3, 3]
+    print("[OK] reload 切换到新代码")
+
+
+def test_reload_under_load():
+    """测试重载期间的请求不会丢失"""
+    engine = never_jscore.JSEngine(make_code(1), workers=2)
+    stop = threading.Event()
+    results = []
+    errors = []
+
+    def worker():
+        while not stop.is_set():
+            try:
+                results.append(engine.call("version", []))
+            except Exception as e:
+                errors.append(e)
+
+    threads = [threading.Thread(target=worker) for _ in range(4)]
+    for t in threads:
+        t.start()
+
+    engine.reload(make_code(2))
+    after = len(results)
+    while len(results) < after + 50:
+        time.sleep(0.01)
+    stop.set()
+    for t in threads:
+        t.join()
+
+    assert not errors, errors
+    assert set(results) <= {1, 2}
+    # 重载完成后只会返回新版本
+    assert set(results[after + 4:]) == {2}
+    print(f"[OK] 重载期间完成 {len(results)} 个请求，没有失败")
+
+
+def test_reload_failure_rolls_back():
+    """测试新代码加载失败时回滚"""
+    engine = never_jscore.JSEngine(make_code(1), workers=2)
+
+    try:
+        engine.reload("function broken( {")
+        assert False, "加载失败应该抛出 RuntimeError"
+    except RuntimeError as e:
+        assert "failed to load new init code" in str(e)
+
+    assert engine.healthy_workers == 2
+    assert engine.broadcast_call("version", []) == [1, 1]
+
+    try:
+        engine.reload("throw new Error('bad script')", drain=False)
+        assert False, "加载失败应该抛出 RuntimeError"
+    except RuntimeError as e:
+        assert "bad script" in str(e)
+    assert engine.broadcast_call("version", []) == [1, 1]
+    print("[OK] 加载失败时回滚到旧代码，引擎继续可用")
+
+
+def test_reload_replays_broadcasts():
+    """测试重载后重新执行持久化的广播"""
+    engine = never_jscore.JSEngine(make_code(1), workers=2)
+    engine.broadcast_call("setKey", ["secret"])
+
+    engine.reload(make_code(2))
+    assert engine.call_many("sign", [["x"]] * 10) == ["v2:secret:x"] * 10
+    print("[OK] 重载后回放持久化的广播")
+
+
+def test_reload_survives_recycle():
+    """测试生命周期重建使用新代码"""
+    engine = never_jscore.JSEngine(make_code(1), workers=1, max_tasks_per_worker=2)
+    engine.reload(make_code(2))
+    assert [engine.call("version", []) for _ in range(6)] == [2] * 6
+    print("[OK] 生命周期重建使用新代码")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 JSEngine 热重载")
+    print("=" * 60)
+
+    test_reload_switches_code()
+    test_reload_under_load()
+    test_reload_failure_rolls_back()
+    test_reload_replays_broadcasts()
+    test_reload_survives_recycle()
+
+    print("\n" + "=" * 60)
+    print("所有热重载测试通过!")
+    print("=" * 60)
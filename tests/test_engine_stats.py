@@ -0,0 +1,151 @@
+"""
+测试 JSEngine 运行指标（stats / prometheus_metrics）
+
+stats() 返回队列长度、正在执行的任务数、各 Worker 任务数、错误数、堆内存和延迟分位数，
+prometheus_metrics() 导出 Prometheus 文本格式
+"""
+
+import sys
+import threading
+import time
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+JS_CODE = """
+function busy(ms) {
+    const end = Date.now() + ms;
+    while (Date.now() < end) {}
+    return ms;
+}
+function fail() { throw new Error('boom'); }
+"""
+
+
+def test_initial_stats():
+    """测试新建引擎的指标"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2)
+    stats = engine.stats()
+
+    assert stats["workers"] == 2
+    assert stats["healthy_workers"] == 2
+    assert stats["queue_size"] == 0
+    assert stats["max_queue_size"] is None
+    assert stats["in_flight"] == 0
+    assert stats["completed"] == 0
+    assert stats["latency_ms"]["count"] == 0
+    assert stats["latency_ms"]["p50"] is None
+    assert [w["worker_id"] for w in stats["per_worker"]] == [0, 1]
+    print("[OK] 新建引擎的指标")
+
+
+def test_counts_and_latency():
+    """测试任务数、错误数和延迟分位数"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2)
+    engine.call_many("busy", [[1]] * 20, chunk_size=1)
+    engine.call("busy", [50])
+    for _ in range(3):
+        try:
+            engine.call("fail", [])
+        except Exception:
+            pass
+
+    stats = engine.stats()
+    assert stats["completed"] == 24, stats["completed"]
+    assert stats["errors"] == 3
+    assert sum(w["tasks"] for w in stats["per_worker"]) == 24
+    assert stats["heap_used_bytes"] > 0
+    assert all(w["heap_used_bytes"] > 0 for w in stats["per_worker"] if w["tasks"])
+
+    latency = stats["latency_ms"]
+    assert latency["count"] == 24
+    assert 0 < latency["p50"] <= latency["p95"] <= latency["p99"] <= latency["max"]
+    assert latency["max"] >= 50
+    assert latency["p50"] < 25
+    print(f"[OK] 延迟 p50={latency['p50']:.2f}ms p99={latency['p99']:.2f}ms")
+
+
+def test_in_flight_and_queue():
+    """测试正在执行和排队的任务数"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=1)
+    threads = [threading.Thread(target=engine.call, args=("busy", [300])) for _ in range(3)]
+    for t in threads:
+        t.start()
+    time.sleep(0.15)
+
+    stats = engine.stats()
+    assert stats["in_flight"] == 1
+    assert stats["queue_size"] == 2
+    assert stats["per_worker"][0]["busy"] is True
+
+    for t in threads:
+        t.join()
+    assert engine.stats()["in_flight"] == 0
+    print("[OK] in_flight / queue_size")
+
+
+def test_submit_timeouts():
+    """测试提交超时计数"""
+    engine = never_jscore.JSEngine(
+        JS_CODE, workers=1, max_queue_size=1, submit_policy="timeout", submit_timeout=0.05
+    )
+    threads = [threading.Thread(target=engine.call, args=("busy", [300])) for _ in range(2)]
+    for t in threads:
+        t.start()
+        time.sleep(0.05)
+    try:
+        engine.call("busy", [1])
+    except never_jscore.PoolOverloadedError:
+        pass
+    for t in threads:
+        t.join()
+
+    stats = engine.stats()
+    assert stats["submit_timeouts"] == 1
+    assert stats["rejected"] == 1
+    print("[OK] submit_timeouts")
+
+
+def test_prometheus():
+    """测试 Prometheus 文本格式"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2)
+    engine.call_many("busy", [[1]] * 10, chunk_size=1)
+
+    text = engine.prometheus_metrics(labels={"pool": "sign"})
+    assert "# TYPE never_jscore_tasks_total counter" in text
+    assert 'never_jscore_tasks_total{pool="sign"} 10' in text
+    assert 'never_jscore_worker_tasks_total{pool="sign",worker="0"}' in text
+    assert "# TYPE never_jscore_task_duration_seconds histogram" in text
+    assert 'never_jscore_task_duration_seconds_bucket{pool="sign",le="+Inf"} 10' in text
+    assert 'never_jscore_task_duration_seconds_count{pool="sign"} 10' in text
+
+    text = engine.prometheus_metrics(namespace="js")
+    assert "js_workers 2" in text
+
+    for kwargs in [{"namespace": "bad-name"}, {"labels": {"worker": "x"}}, {"labels": {"1x": "y"}}]:
+        try:
+            engine.prometheus_metrics(**kwargs)
+            assert False, f"应该拒绝无效参数: {kwargs}"
+        except ValueError:
+            pass
+    print("[OK] Prometheus 文本格式")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 JSEngine 运行指标")
+    print("=" * 60)
+
+    test_initial_stats()
+    test_counts_and_latency()
+    test_in_flight_and_queue()
+    test_submit_timeouts()
+    test_prometheus()
+
+    print("\n" + "=" * 60)
+    print("所有运行指标测试通过!")
+    print("=" * 60)
@@ -0,0 +1,105 @@
+"""
+测试 JSEngine Worker 监督与自动重建
+
+- 初始化代码出错时 JSEngine() 直接抛出异常，而不是留下没有 Worker 的引擎
+- Worker 退出后由监督线程按退避时间自动重建，healthy_workers 反映可用 Worker 数
+- 没有可用 Worker 时调用立即失败，而不是无限等待
+"""
+
+import os
+import sys
+import tempfile
+import time
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+
+def test_init_failure_raises():
+    """测试初始化失败时构造函数抛出异常"""
+    try:
+        never_jscore.JSEngine("throw new Error('bad init code')", workers=2)
+        assert False, "初始化失败应该抛出异常"
+    except RuntimeError as e:
+        assert "failed to initialize" in str(e)
+        assert "bad init code" in str(e)
+
+    try:
+        never_jscore.JSEngine("function broken( {", workers=1)
+        assert False, "语法错误应该抛出异常"
+    except RuntimeError:
+        pass
+    print("[OK] 初始化失败时 JSEngine() 抛出 RuntimeError")
+
+
+def test_healthy_workers():
+    """测试正常情况下所有 Worker 都健康"""
+    engine = never_jscore.JSEngine("function ping() { return 'pong'; }", workers=3)
+    assert engine.healthy_workers == 3
+    assert engine.respawned_workers == 0
+    assert engine.call("ping", []) == 'pong'
+    assert "healthy=3" in repr(engine)
+    print("[OK] healthy_workers 等于 Worker 数")
+
+
+def test_respawn_after_failure():
+    """测试 Worker 退出后自动重建"""
+    flag = os.path.join(tempfile.mkdtemp(), "fail_init")
+
+    # 标志文件存在时初始化失败；每个任务后重建 JsRuntime 以触发重新初始化
+    code = f"""
+        const fs = require('fs');
+        if (fs.existsSync({flag!r})) {{
+            throw new Error('init disabled');
+        }}
+        function ping() {{ return 'pong'; }}
+    """
+    engine = never_jscore.JSEngine(
+        code, workers=1, enable_node_compat=True, max_tasks_per_worker=1
+    )
+    assert engine.healthy_workers == 1
+
+    open(flag, "w").close()
+    assert engine.call("ping", []) == 'pong'  # 任务完成后重建失败，Worker 退出
+
+    deadline = time.time() + 5
+    while engine.healthy_workers != 0 and time.time() < deadline:
+        time.sleep(0.05)
+    assert engine.healthy_workers == 0
+
+    # 没有可用 Worker：立即失败，不会卡住
+    start = time.perf_counter()
+    try:
+        engine.call("ping", [])
+        assert False, "没有可用 Worker 时应该抛出异常"
+    except RuntimeError as e:
+        assert "No healthy workers" in str(e)
+    assert time.perf_counter() - start < 1
+
+    # 恢复初始化条件后，监督线程重建 Worker
+    os.remove(flag)
+    deadline = time.time() + 15
+    while engine.healthy_workers != 1 and time.time() < deadline:
+        time.sleep(0.1)
+    assert engine.healthy_workers == 1
+    assert engine.respawned_workers >= 1
+    assert engine.call("ping", []) == 'pong'
+    print(f"[OK] Worker 退出后自动重建（重建 {engine.respawned_workers} 次）")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 JSEngine Worker 监督与自动重建")
+    print("=" * 60)
+
+    test_init_failure_raises()
+    test_healthy_workers()
+    test_respawn_after_failure()
+
+    print("\n" + "=" * 60)
+    print("所有监督测试通过!")
+    print("=" * 60)
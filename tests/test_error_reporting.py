@@ -0,0 +1,133 @@
+"""
+测试未处理错误上报
+
+展示 on_error 回调 / ctx.errors 收集未处理的 Promise rejection 与
+定时器回调异常，以及 strict_errors 严格模式
+"""
+
+import sys
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+
+def test_unhandled_rejection_collected():
+    """测试未处理的 Promise rejection 被记录，调用本身不受影响"""
+    ctx = never_jscore.Context()
+    result = ctx.evaluate("""
+        Promise.reject(new TypeError('lost rejection'));
+        42
+    """, wait_timers="all")
+    assert result == 42
+
+    errors = ctx.errors
+    assert len(errors) == 1, errors
+    error = errors[0]
+    assert error["type"] == "unhandled_rejection"
+    assert error["source"] == "promise"
+    assert error["name"] == "TypeError"
+    assert error["message"] == "lost rejection"
+    assert "lost rejection" in error["stack"]
+    print(f"[OK] 未处理的 rejection 已记录: {error['name']}: {error['message']}")
+
+
+def test_timer_exception_callback():
+    """测试定时器回调抛出的异常触发 on_error"""
+    received = []
+    ctx = never_jscore.Context(on_error=received.append)
+    ctx.evaluate("""
+        setTimeout(() => { throw new Error('boom in timer'); }, 0);
+        setTimeout(() => { globalThis.after = true; }, 5);
+        1
+    """, wait_timers="all")
+
+    assert len(received) == 1, received
+    assert received[0]["type"] == "uncaught_exception"
+    assert received[0]["source"] == "timer"
+    assert received[0]["message"] == "boom in timer"
+    # 其余定时器照常执行
+    assert ctx.evaluate("globalThis.after") is True
+    assert ctx.errors == received
+    print("[OK] 定时器异常已通过 on_error 上报，事件循环继续运行")
+
+
+def test_non_error_reason():
+    """测试非 Error 类型的 rejection 原因"""
+    ctx = never_jscore.Context()
+    ctx.evaluate("Promise.reject({ code: 403 }); 1", wait_timers="all")
+    error = ctx.errors[0]
+    assert error["name"] == "object"
+    assert error["message"] == '{"code":403}'
+    assert error["stack"] is None
+    print("[OK] 非 Error 原因已序列化")
+
+
+def test_handled_rejection_not_reported():
+    """测试已处理的 rejection 和调用自身的错误不会被上报"""
+    ctx = never_jscore.Context()
+    ctx.evaluate("""
+        Promise.reject(new Error('handled')).catch(() => {});
+        1
+    """, wait_timers="all")
+    assert ctx.errors == []
+
+    # 调用自身的 rejection 仍作为调用异常抛出
+    try:
+        ctx.evaluate("Promise.reject(new Error('own error'))")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "own error" in str(e)
+    assert ctx.errors == []
+    print("[OK] 已处理的 rejection / 调用自身的错误不上报")
+
+
+def test_clear_errors():
+    """测试 clear_errors()"""
+    ctx = never_jscore.Context()
+    ctx.evaluate("Promise.reject(new Error('a')); 1", wait_timers="all")
+    assert len(ctx.errors) == 1
+    ctx.clear_errors()
+    assert ctx.errors == []
+    print("[OK] clear_errors 清空已收集错误")
+
+
+def test_strict_errors():
+    """测试严格模式：未处理错误使当前调用失败"""
+    received = []
+    ctx = never_jscore.Context(strict_errors=True, on_error=received.append)
+    try:
+        ctx.evaluate("""
+            setTimeout(() => { throw new Error('strict boom'); }, 0);
+            1
+        """, wait_timers="all")
+        assert False, "严格模式应该抛出异常"
+    except Exception as e:
+        assert "strict boom" in str(e)
+
+    assert len(received) == 1
+    assert received[0]["message"] == "strict boom"
+
+    # Context 仍可继续使用
+    assert ctx.evaluate("1 + 1") == 2
+    print("[OK] 严格模式下未处理错误使调用失败")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试未处理错误上报")
+    print("=" * 60)
+
+    test_unhandled_rejection_collected()
+    test_timer_exception_callback()
+    test_non_error_reason()
+    test_handled_rejection_not_reported()
+    test_clear_errors()
+    test_strict_errors()
+
+    print("\n" + "=" * 60)
+    print("所有错误上报测试通过!")
+    print("=" * 60)
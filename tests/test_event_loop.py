@@ -0,0 +1,145 @@
+"""
+测试事件循环检查与显式驱动
+
+展示 pending_tasks() / run_until_idle() / run_microtasks()
+以及每次调用的 wait_timers 策略
+"""
+
+import sys
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+
+def test_until_result_cancels_call_timers():
+    """测试默认策略：返回结果并取消本次调用创建的定时器"""
+    ctx = never_jscore.Context()
+    result = ctx.evaluate("""
+        globalThis.fired = false;
+        setTimeout(() => { globalThis.fired = true; }, 10);
+        42
+    """)
+    assert result == 42
+    assert ctx.pending_tasks()["timers"] == []
+    assert ctx.run_until_idle(timeout_ms=500)
+    assert ctx.evaluate("globalThis.fired") is False
+    print("[OK] until_result: 定时器已取消")
+
+
+def test_compile_timer_default_policy():
+    """测试默认策略下 compile() 创建的定时器：异步调用保留，同步调用（auto_await=False）全部取消"""
+    ctx = never_jscore.Context()
+    ctx.compile("globalThis.ticks = 0; setInterval(() => { globalThis.ticks++; }, 1);")
+
+    # 异步模式只取消本次调用创建的定时器
+    ctx.evaluate("setTimeout(() => {}, 10); 1")
+    assert [t["type"] for t in ctx.pending_tasks()["timers"]] == ["interval"]
+
+    # 同步模式与原有行为一致：包括 compile() 创建的定时器在内全部取消
+    assert ctx.evaluate("1", auto_await=False) == 1
+    assert ctx.pending_tasks()["timers"] == []
+    assert ctx.run_until_idle(timeout_ms=500)
+    print("[OK] until_result: 同步调用取消 compile() 创建的定时器")
+
+
+def test_none_keeps_timers():
+    """测试 wait_timers='none'：定时器保留，可用 run_until_idle() 继续执行"""
+    ctx = never_jscore.Context()
+    ctx.evaluate("""
+        globalThis.fired = false;
+        setTimeout(() => { globalThis.fired = true; }, 10);
+        1
+    """, wait_timers="none")
+
+    timers = ctx.pending_tasks()["timers"]
+    assert len(timers) == 1 and timers[0]["type"] == "timeout", timers
+
+    assert ctx.run_until_idle(timeout_ms=1000) is True
+    assert ctx.evaluate("globalThis.fired") is True
+    assert ctx.pending_tasks()["timers"] == []
+    print(f"[OK] none: 定时器保留并在 run_until_idle 中执行 {timers}")
+
+
+def test_all_waits_for_timers():
+    """测试 wait_timers='all'：等待所有定时器完成后返回"""
+    ctx = never_jscore.Context()
+    ctx.compile("globalThis.log = [];")
+    ctx.evaluate("""
+        setTimeout(() => log.push('a'), 20);
+        setTimeout(() => log.push('b'), 40);
+        'started'
+    """, wait_timers="all")
+    assert ctx.evaluate("log") == ['a', 'b']
+    print("[OK] all: 所有定时器已执行")
+
+
+def test_interval_timeout():
+    """测试存在 setInterval 时 run_until_idle 超时返回 False"""
+    ctx = never_jscore.Context()
+    ctx.evaluate("globalThis.timer = setInterval(() => {}, 5); 1", wait_timers="none")
+    intervals = [t for t in ctx.pending_tasks()["timers"] if t["type"] == "interval"]
+    assert len(intervals) == 1
+
+    assert ctx.run_until_idle(timeout_ms=50) is False
+    ctx.evaluate("clearInterval(globalThis.timer)", wait_timers="none")
+    assert ctx.pending_tasks()["timers"] == []
+    print("[OK] setInterval: run_until_idle 超时返回 False")
+
+
+def test_run_microtasks():
+    """测试 run_microtasks() 只执行 Promise 回调"""
+    ctx = never_jscore.Context()
+    ctx.compile("globalThis.state = [];")
+    ctx.evaluate("""
+        setTimeout(() => state.push('timer'), 0);
+        1
+    """, wait_timers="none")
+    ctx.run_microtasks()
+    # 微任务检查点不会触发定时器
+    assert len(ctx.pending_tasks()["timers"]) == 1
+    print("[OK] run_microtasks 不触发定时器")
+
+
+def test_pending_promises():
+    """测试 Promise 统计"""
+    ctx = never_jscore.Context()
+    ctx.evaluate("1")
+    # 首次调用 pending_tasks() 开始统计，之前创建的 Promise 无法计入
+    assert ctx.pending_tasks()["promises"] is None
+    assert ctx.pending_tasks()["promises"] == 0
+    ctx.evaluate("globalThis.never = new Promise(() => {}); 1", wait_timers="none")
+    assert ctx.pending_tasks()["promises"] >= 1
+    print(f"[OK] 待处理 Promise: {ctx.pending_tasks()['promises']}")
+
+
+def test_invalid_policy():
+    """测试非法策略"""
+    ctx = never_jscore.Context()
+    try:
+        ctx.evaluate("1", wait_timers="sometimes")
+        assert False, "应该抛出 ValueError"
+    except ValueError as e:
+        print(f"[OK] 非法策略被拒绝: {e}")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试事件循环检查与驱动")
+    print("=" * 60)
+
+    test_until_result_cancels_call_timers()
+    test_compile_timer_default_policy()
+    test_none_keeps_timers()
+    test_all_waits_for_timers()
+    test_interval_timeout()
+    test_run_microtasks()
+    test_pending_promises()
+    test_invalid_policy()
+
+    print("\n" + "=" * 60)
+    print("[PASS] 所有事件循环测试通过！")
+    print("=" * 60)
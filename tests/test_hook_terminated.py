@@ -0,0 +1,157 @@
+"""
+测试 HookTerminated：$terminate() 的数据随本次调用返回
+
+Context / ThreadedContext / JSEngine 的调用被 $terminate(data) 终止时抛出 HookTerminated，
+e.data 为解析后的数据；多个 Context、多个 JSEngine 的 Hook 数据互不覆盖
+"""
+
+import json
+import sys
+import threading
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+JS_CODE = """
+function hooked(tag) {
+    try {
+        $terminate({ tag: tag, nested: [1, 2, 3] });
+    } catch (e) {
+        return "caught";
+    }
+    return "not terminated";
+}
+function normal(x) { return x * 2; }
+"""
+
+
+def test_context_raises():
+    """测试 Context.call / evaluate 抛出 HookTerminated"""
+    ctx = never_jscore.Context()
+    ctx.compile(JS_CODE)
+
+    try:
+        ctx.call("hooked", ["a"])
+        assert False, "应该抛出 HookTerminated"
+    except never_jscore.HookTerminated as e:
+        assert e.data == {"tag": "a", "nested": [1, 2, 3]}
+        assert e.worker_id is None
+
+    try:
+        ctx.evaluate("$terminate('plain string')")
+        assert False, "应该抛出 HookTerminated"
+    except never_jscore.HookTerminated as e:
+        assert e.data == "plain string"
+
+    # 终止后 Context 可以继续使用
+    assert ctx.call("normal", [21]) == 42
+    print("[OK] Context 抛出 HookTerminated")
+
+
+def test_contexts_isolated():
+    """测试多个 Context 的 Hook 数据互不覆盖"""
+    ctx1 = never_jscore.Context()
+    ctx2 = never_jscore.Context()
+    for ctx in (ctx1, ctx2):
+        ctx.compile(JS_CODE)
+
+    for ctx, tag in ((ctx1, "ctx1"), (ctx2, "ctx2")):
+        try:
+            ctx.call("hooked", [tag])
+        except never_jscore.HookTerminated:
+            pass
+
+    # 兼容接口：每个 Context 只返回自己的数据
+    assert json.loads(ctx1.get_hook_data())["tag"] == "ctx1"
+    assert json.loads(ctx2.get_hook_data())["tag"] == "ctx2"
+
+    ctx1.clear_hook_data()
+    assert ctx1.get_hook_data() is None
+    assert ctx2.get_hook_data() is not None
+    print("[OK] 多个 Context 的 Hook 数据隔离")
+
+
+def test_threaded_context():
+    """测试 ThreadedContext 抛出 HookTerminated"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile(JS_CODE)
+    try:
+        ctx.call("hooked", ["threaded"])
+        assert False, "应该抛出 HookTerminated"
+    except never_jscore.HookTerminated as e:
+        assert e.data["tag"] == "threaded"
+    assert ctx.call("normal", [1]) == 2
+    print("[OK] ThreadedContext 抛出 HookTerminated")
+
+
+def test_engines_isolated():
+    """测试两个 JSEngine 并发拦截时数据属于各自的调用"""
+    engines = [never_jscore.JSEngine(JS_CODE, workers=2) for _ in range(2)]
+    errors = []
+
+    def client(engine_index, n):
+        try:
+            for i in range(50):
+                tag = f"{engine_index}-{n}-{i}"
+                try:
+                    engines[engine_index].call("hooked", [tag])
+                    errors.append(f"{tag} not terminated")
+                except never_jscore.HookTerminated as e:
+                    if e.data["tag"] != tag:
+                        errors.append(f"{tag} got {e.data['tag']}")
+                    if e.worker_id not in (0, 1):
+                        errors.append(f"{tag} bad worker_id {e.worker_id}")
+        except Exception as e:
+            errors.append(repr(e))
+
+    threads = [threading.Thread(target=client, args=(i % 2, i)) for i in range(8)]
+    for t in threads:
+        t.start()
+    for t in threads:
+        t.join()
+    assert not errors, errors[:5]
+
+    # 拦截不计为错误
+    assert engines[0].stats()["errors"] == 0
+    print("[OK] 多个 JSEngine 并发拦截互不覆盖")
+
+
+def test_engine_batch_and_future():
+    """测试 call_many / submit 中的 HookTerminated"""
+    engine = never_jscore.JSEngine(JS_CODE, workers=2)
+
+    results = engine.call_many("hooked", [["x"], ["y"]], return_exceptions=True)
+    assert all(isinstance(r, never_jscore.HookTerminated) for r in results)
+    assert [r.data["tag"] for r in results] == ["x", "y"]
+
+    try:
+        engine.call_many("hooked", [["z"]])
+        assert False, "应该抛出 HookTerminated"
+    except never_jscore.HookTerminated as e:
+        assert e.data["tag"] == "z"
+
+    future = engine.submit("hooked", ["future"])
+    error = future.exception(timeout=5)
+    assert isinstance(error, never_jscore.HookTerminated)
+    assert error.data["tag"] == "future"
+    print("[OK] call_many / submit 返回 HookTerminated")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 HookTerminated")
+    print("=" * 60)
+
+    test_context_raises()
+    test_contexts_isolated()
+    test_threaded_context()
+    test_engines_isolated()
+    test_engine_batch_and_future()
+
+    print("\n" + "=" * 60)
+    print("所有 HookTerminated 测试通过!")
+    print("=" * 60)
@@ -0,0 +1,172 @@
+"""
+测试 Ctrl+C 中断正在执行的 JavaScript
+
+JS 在释放 GIL 后执行，Ctrl+C（SIGINT）会终止 isolate 并交给 Python 的 SIGINT
+处理函数（默认抛出 KeyboardInterrupt），Context 之后仍可继续使用
+"""
+
+import os
+import signal
+import sys
+import threading
+import time
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+
+def send_sigint_later(delay=0.2):
+    """模拟用户在 delay 秒后按下 Ctrl+C"""
+    def fire():
+        if sys.platform == 'win32':
+            signal.raise_signal(signal.SIGINT)
+        else:
+            os.kill(os.getpid(), signal.SIGINT)
+    timer = threading.Timer(delay, fire)
+    timer.start()
+    return timer
+
+
+def test_interrupt_busy_loop():
+    """测试中断死循环"""
+    ctx = never_jscore.Context()
+    ctx.compile("function spin() { while (true) {} }")
+
+    send_sigint_later()
+    start = time.time()
+    try:
+        ctx.call("spin", [])
+        assert False, "应该抛出 KeyboardInterrupt"
+    except KeyboardInterrupt:
+        pass
+    elapsed = time.time() - start
+    assert elapsed < 5, f"中断耗时过长: {elapsed:.2f}s"
+
+    # Context 仍可使用
+    assert ctx.call("Math.max", [1, 2]) == 2
+    assert ctx.evaluate("1 + 1") == 2
+    print(f"[OK] 死循环在 {elapsed:.2f}s 内被中断，Context 可继续使用")
+
+
+def test_interrupt_waiting_timer():
+    """测试中断等待中的定时器（JS 空闲，事件循环在等待）"""
+    ctx = never_jscore.Context()
+    send_sigint_later()
+    start = time.time()
+    try:
+        ctx.evaluate("new Promise(resolve => setTimeout(resolve, 60000))")
+        assert False, "应该抛出 KeyboardInterrupt"
+    except KeyboardInterrupt:
+        pass
+    assert time.time() - start < 5
+
+    assert ctx.evaluate("'alive'") == 'alive'
+    print("[OK] 等待定时器时被中断")
+
+
+def test_custom_sigint_handler():
+    """测试 Python 中自定义的 SIGINT 处理函数仍然生效"""
+    class Stop(Exception):
+        pass
+
+    def handler(signum, frame):
+        raise Stop()
+
+    previous = signal.signal(signal.SIGINT, handler)
+    try:
+        ctx = never_jscore.Context()
+        send_sigint_later()
+        try:
+            ctx.evaluate("while (true) {}")
+            assert False, "应该抛出 Stop"
+        except Stop:
+            pass
+        assert ctx.evaluate("2 * 21") == 42
+    finally:
+        signal.signal(signal.SIGINT, previous)
+    print("[OK] 自定义 SIGINT 处理函数生效")
+
+
+def test_handler_without_exception():
+    """测试 SIGINT 处理函数没有抛出异常时调用以 RuntimeError 结束，被中断调用的状态不会混入下一次调用"""
+    received = []
+
+    def handler(signum, frame):
+        received.append(signum)
+
+    previous = signal.signal(signal.SIGINT, handler)
+    try:
+        ctx = never_jscore.Context()
+        send_sigint_later()
+        try:
+            ctx.evaluate("$emit('step', 1); while (true) {}")
+            assert False, "应该抛出 RuntimeError"
+        except RuntimeError as e:
+            assert "interrupted" in str(e)
+        assert received == [signal.SIGINT]
+
+        assert ctx.evaluate("$emit('next', 2); 2 * 21") == 42
+        assert [e["channel"] for e in ctx.events()] == ["next"]
+        assert ctx.errors == []
+    finally:
+        signal.signal(signal.SIGINT, previous)
+    print("[OK] 处理函数没有抛出异常时抛出 RuntimeError，中断的调用不留下事件")
+
+
+def test_interrupt_blocking_syscall_after_calls():
+    """测试 Context 调用之后，Ctrl+C 仍能打断纯 Python 的阻塞系统调用（不带 SA_RESTART）"""
+    if sys.platform == 'win32':
+        print("[SKIP] Windows 没有 SA_RESTART")
+        return
+    import socket
+
+    ctx = never_jscore.Context()
+    assert ctx.evaluate("1 + 1") == 2
+
+    a, b = socket.socketpair()
+    # 保险：万一没有被打断，5 秒后发送数据结束 recv()
+    fallback = threading.Timer(5, lambda: b.send(b"x"))
+    fallback.start()
+    send_sigint_later()
+    start = time.time()
+    try:
+        a.recv(1)
+        assert False, "recv() 应该被 Ctrl+C 打断"
+    except KeyboardInterrupt:
+        pass
+    finally:
+        fallback.cancel()
+        a.close()
+        b.close()
+    assert time.time() - start < 4
+    assert ctx.evaluate("'alive'") == 'alive'
+    print("[OK] Context 调用后 Ctrl+C 仍能打断阻塞的 recv()")
+
+
+def test_no_interrupt_normal_calls():
+    """测试未按 Ctrl+C 时调用不受影响"""
+    ctx = never_jscore.Context()
+    for i in range(100):
+        assert ctx.evaluate(f"{i} * 2") == i * 2
+    print("[OK] 正常调用不受影响")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 Ctrl+C 中断")
+    print("=" * 60)
+
+    test_interrupt_busy_loop()
+    test_interrupt_waiting_timer()
+    test_custom_sigint_handler()
+    test_handler_without_exception()
+    test_interrupt_blocking_syscall_after_calls()
+    test_no_interrupt_normal_calls()
+
+    print("\n" + "=" * 60)
+    print("所有中断测试通过!")
+    print("=" * 60)
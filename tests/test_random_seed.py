@@ -298,6 +298,84 @@ def test_real_world_sign_generation():
     print(f"[OK] 完全可重现，方便调试！")
 
 
+def test_runtime_reseed():
+    """测试 set_random_seed() 运行时重设种子"""
+    ctx = never_jscore.Context()
+    ctx.set_random_seed(2024)
+    first = ctx.evaluate("[Math.random(), crypto.randomUUID(), Array.from(crypto.getRandomValues(new Uint8Array(4)))]")
+
+    ctx.set_random_seed(2024)
+    second = ctx.evaluate("[Math.random(), crypto.randomUUID(), Array.from(crypto.getRandomValues(new Uint8Array(4)))]")
+
+    assert first == second, "重设相同种子后应该产生相同的随机序列"
+
+    # 与构造时传入种子的结果一致
+    ctx_seeded = never_jscore.Context(random_seed=2024)
+    assert ctx_seeded.evaluate("Math.random()") == first[0]
+
+    print(f"\n[OK] 重设种子后序列一致: {first[1]}")
+
+
+def test_rng_state_snapshot_restore():
+    """测试 get_rng_state() / set_rng_state() 保存并回放随机序列"""
+    ctx = never_jscore.Context(random_seed=7)
+    ctx.evaluate("Math.random(); crypto.randomUUID()")
+
+    state = ctx.get_rng_state()
+    assert state["seed"] == 7
+
+    a = ctx.evaluate("[Math.random(), crypto.randomUUID()]")
+    ctx.set_rng_state(state)
+    b = ctx.evaluate("[Math.random(), crypto.randomUUID()]")
+
+    assert a == b, "恢复状态后应该从相同位置继续"
+    print(f"[OK] 状态 {state} 回放成功")
+
+
+def test_engine_per_worker_and_per_call_seed():
+    """测试 JSEngine 的 per_worker_seed 和单次调用 seed"""
+    code = "function rand() { return [Math.random(), crypto.randomUUID()]; }"
+
+    # per_worker_seed: 各Worker首个随机数不同
+    engine = never_jscore.JSEngine(code, workers=2, random_seed=1, per_worker_seed=True)
+    worker0 = never_jscore.Context(random_seed=1).evaluate("Math.random()")
+    worker1 = never_jscore.Context(random_seed=2).evaluate("Math.random()")
+    assert worker0 != worker1
+    first = engine.call("rand", [])
+    assert first[0] in (worker0, worker1)
+
+    # 单次调用种子：无论分配到哪个Worker结果都相同
+    results = [engine.call("rand", [], seed=99) for _ in range(6)]
+    assert all(r == results[0] for r in results), results
+
+    print(f"[OK] 单次调用种子结果: {results[0]}")
+
+
+def test_per_call_seed_keeps_worker_stream():
+    """测试单次调用种子结束后，Worker 的随机数（包括原生密钥生成）继续原来的序列"""
+    code = """
+        function rand() { return Math.random(); }
+        async function aesKey() {
+            const key = await crypto.subtle.generateKey({ name: 'AES-GCM', length: 128 }, true, ['encrypt']);
+            return Array.from(new Uint8Array(await crypto.subtle.exportKey('raw', key))).join(',');
+        }
+    """
+    engine = never_jscore.JSEngine(code, workers=1, random_seed=5)
+    keys = []
+    for _ in range(3):
+        keys.append(engine.call("aesKey", []))
+        engine.call("rand", [], seed=99)
+    # 不会在每次单次调用种子之后从 Worker 种子的起点重新开始
+    assert len(set(keys)) == 3, keys
+    engine.close()
+
+    # 同一个 Worker 种子从头运行，序列与上面一致
+    engine = never_jscore.JSEngine(code, workers=1, random_seed=5)
+    assert [engine.call("aesKey", []) for _ in range(3)] == keys
+    engine.close()
+    print("[OK] 单次调用种子不重置 Worker 随机数序列")
+
+
 if __name__ == "__main__":
     print("=" * 60)
     print("测试确定性随机数功能")
@@ -311,6 +389,10 @@ if __name__ == "__main__":
     test_reproducible_encryption()
     test_sequence_consistency()
     test_mixed_random_apis()
+    test_runtime_reseed()
+    test_rng_state_snapshot_restore()
+    test_engine_per_worker_and_per_call_seed()
+    test_per_call_seed_keeps_worker_stream()
     # test_real_world_sign_generation()
 
     print("\n" + "=" * 60)
@@ -0,0 +1,198 @@
+"""
+测试轻量级 realm（ctx.create_realm()）
+
+realm 与父 Context 共享 Isolate 和事件循环，但拥有独立的全局对象，
+类似 iframe / ShadowRealm
+"""
+
+import sys
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+
+def test_isolated_globals():
+    """测试 realm 的全局变量与内置对象互相隔离"""
+    ctx = never_jscore.Context()
+    ctx.eval("var owner = 'main'")
+    realm = ctx.create_realm()
+
+    realm.eval("var owner = 'realm'; Array.prototype.evil = 1;")
+    assert realm.eval("owner") == 'realm'
+    assert ctx.evaluate("owner") == 'main'
+
+    # 内置对象是 realm 自己的，原型污染不会影响父 Context
+    assert realm.eval("[].evil") == 1
+    assert ctx.evaluate("[].evil") is None
+    assert realm.eval("Array === globalThis.Array") is True
+    print("[OK] 全局变量与内置对象互相隔离")
+
+
+def test_web_apis_installed():
+    """测试 realm 中可以使用 Web API"""
+    ctx = never_jscore.Context()
+    realm = ctx.create_realm()
+
+    assert realm.eval("btoa('hi')") == 'aGk='
+    assert realm.eval("new URL('https://a.com/p?x=1').searchParams.get('x')") == '1'
+    assert realm.eval("new TextEncoder().encode('abc').length") == 3
+    assert realm.eval("typeof crypto.randomUUID()") == 'string'
+    print("[OK] Web API 在 realm 中可用")
+
+
+def test_call_and_functions():
+    """测试在 realm 中定义并调用函数"""
+    ctx = never_jscore.Context()
+    realm = ctx.create_realm()
+
+    realm.eval("function add(a, b) { return a + b; }")
+    assert realm.call("add", [1, 2]) == 3
+    assert realm.eval("typeof add") == 'function'
+
+    # 父 Context 看不到 realm 中定义的函数
+    assert ctx.evaluate("typeof add") == 'undefined'
+    print("[OK] realm.call() 调用 realm 中定义的函数")
+
+
+def test_async_shared_event_loop():
+    """测试 realm 中的 Promise 和定时器由共享的事件循环驱动"""
+    ctx = never_jscore.Context()
+    realm = ctx.create_realm()
+
+    assert realm.eval("Promise.resolve(42)") == 42
+    result = realm.eval("new Promise(r => setTimeout(() => r('timer done'), 10))")
+    assert result == 'timer done'
+
+    realm.eval("async function fetchLater(x) { await null; return x * 2; }")
+    assert realm.call("fetchLater", [21]) == 42
+    print("[OK] Promise / setTimeout 在 realm 中正常工作")
+
+
+def test_errors():
+    """测试 realm 中的异常转换为 Python 异常"""
+    ctx = never_jscore.Context()
+    realm = ctx.create_realm()
+
+    try:
+        realm.eval("throw new Error('boom')")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert 'boom' in str(e)
+
+    try:
+        realm.eval("Promise.reject(new Error('async boom'))")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert 'async boom' in str(e)
+
+    # 出错后 realm 和父 Context 仍然可用
+    assert realm.eval("1 + 1") == 2
+    assert ctx.evaluate("1 + 1") == 2
+    print("[OK] realm 异常转换为 Python 异常")
+
+
+def test_multiple_realms_and_close():
+    """测试多个 realm 互相独立，close() 后拒绝调用"""
+    ctx = never_jscore.Context()
+    a = ctx.create_realm()
+    b = ctx.create_realm()
+    assert a.id != b.id
+
+    a.eval("var name = 'a'")
+    b.eval("var name = 'b'")
+    assert a.eval("name") == 'a'
+    assert b.eval("name") == 'b'
+
+    a.close()
+    try:
+        a.eval("1")
+        assert False, "close() 后调用应该抛出异常"
+    except Exception as e:
+        assert 'closed' in str(e)
+    assert b.eval("name") == 'b'
+    print("[OK] 多个 realm 互相独立，close() 后拒绝调用")
+
+
+def test_date_isolated():
+    """测试 realm 中修改 Date.prototype 不影响父 Context（包括确定性模式）"""
+    for deterministic in (False, True):
+        ctx = never_jscore.Context(deterministic=deterministic)
+        realm = ctx.create_realm()
+
+        realm.eval("Date.prototype.getTime = () => 42; Date.now = () => 0;")
+        assert realm.eval("new Date(5).getTime()") == 42
+        assert ctx.evaluate("new Date(5).getTime()") == 5
+        assert ctx.evaluate("Date.now()") > 0
+        assert ctx.evaluate("Date.prototype.constructor === Date") is True
+
+    # 确定性模式下 realm 的 Date 使用同一个虚拟时钟
+    ctx = never_jscore.Context(deterministic=True)
+    realm = ctx.create_realm()
+    assert realm.eval("Date === globalThis.Date && Date.prototype.constructor === Date") is True
+    assert realm.eval("new Date().getTime() === Date.now()") is True
+    assert abs(realm.eval("Date.now()") - ctx.evaluate("Date.now()")) < 1000
+    assert realm.eval("typeof Date()") == 'string'
+    print("[OK] realm 中修改 Date 不影响父 Context")
+
+
+def test_expose_parent():
+    """测试 parent / top 默认指向 realm 自身，expose_parent=True 时指向父 Context"""
+    ctx = never_jscore.Context()
+    ctx.eval("globalThis.window = globalThis; var owner = 'main';")
+
+    realm = ctx.create_realm()
+    assert realm.eval("window === globalThis && self === globalThis") is True
+    assert realm.eval("parent === globalThis && top === globalThis") is True
+    assert realm.eval("typeof parent.owner") == 'undefined'
+
+    exposed = ctx.create_realm(expose_parent=True)
+    assert exposed.eval("parent !== globalThis && top === parent") is True
+    assert exposed.eval("parent.owner") == 'main'
+    print("[OK] expose_parent 控制 parent / top")
+
+
+def test_internals_hidden():
+    """测试运行时内部对象不进入 realm，realm 内置函数同样显示为 native"""
+    ctx = never_jscore.Context(deterministic=True)
+    realm = ctx.create_realm()
+
+    for name in ('Deno', '__getDeno', '__NEVER_JSCORE_NATIVE__', '__neverjscore_hook__'):
+        assert realm.eval(f"typeof globalThis['{name}']") == 'undefined', name
+    assert realm.eval("Function.prototype.toString !== Object.getPrototypeOf(fetch).toString") is True
+
+    for expr in ("Function.prototype.toString.call(fetch)", "Math.random.toString()",
+                 "Date.toString()", "Date.now.toString()", "Object.keys.toString()",
+                 "Function.prototype.toString.toString()"):
+        assert '[native code]' in realm.eval(expr), expr
+    assert '[native code]' not in realm.eval("(function f() { return 1; }).toString()")
+
+    # 父 Context 中隐藏的全局名称在 realm 的反射 API 中同样被过滤
+    realm.eval("globalThis.process = {}; globalThis.Buffer = function Buffer() {};")
+    assert realm.eval("Object.keys(globalThis).includes('process')") is False
+    assert realm.eval("Reflect.ownKeys(globalThis).includes('Buffer')") is False
+    assert realm.eval("Object.keys({ process: 1 })") == ['process']
+    print("[OK] realm 中隐藏运行时内部对象")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试轻量级 realm")
+    print("=" * 60)
+
+    test_isolated_globals()
+    test_web_apis_installed()
+    test_call_and_functions()
+    test_async_shared_event_loop()
+    test_errors()
+    test_multiple_realms_and_close()
+    test_date_isolated()
+    test_expose_parent()
+    test_internals_hidden()
+
+    print("\n" + "=" * 60)
+    print("所有 realm 测试通过!")
+    print("=" * 60)
@@ -0,0 +1,131 @@
+"""
+测试线程安全的 ThreadedContext
+
+Context 绑定创建它的线程；ThreadedContext 在专用线程中持有 Context，
+任意 Python 线程都可以调用，调用按提交顺序串行执行
+"""
+
+import sys
+import threading
+from concurrent.futures import ThreadPoolExecutor
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+
+def test_basic_api():
+    """测试与 Context 相同的基本 API"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile("function add(a, b) { return a + b; }")
+    assert ctx.call("add", [1, 2]) == 3
+    assert ctx.evaluate("[1, 2, 3].map(x => x * 2)") == [2, 4, 6]
+    assert ctx.eval("globalThis.answer = 42", return_value=True) == 42
+    assert ctx.evaluate("answer") == 42
+    assert ctx.evaluate("Promise.resolve('async ok')") == 'async ok'
+    ctx.close()
+    print("[OK] 基本 API 正常")
+
+
+def test_shared_across_threads():
+    """测试多个线程共享同一个上下文"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile("""
+        let counter = 0;
+        function inc() { return ++counter; }
+    """)
+
+    with ThreadPoolExecutor(max_workers=8) as pool:
+        results = list(pool.map(lambda _: ctx.call("inc", []), range(200)))
+
+    # 每次调用都拿到唯一的值：调用被串行执行，没有丢失或重复
+    assert sorted(results) == list(range(1, 201))
+    assert ctx.evaluate("counter") == 200
+    ctx.close()
+    print("[OK] 8 个线程共享上下文，200 次调用串行执行")
+
+
+def test_order_preserved():
+    """测试同一线程提交的调用按顺序执行"""
+    ctx = never_jscore.ThreadedContext()
+    ctx.compile("const log = []; function push(x) { log.push(x); return log.length; }")
+    for i in range(50):
+        ctx.call("push", [i])
+    assert ctx.evaluate("log") == list(range(50))
+    ctx.close()
+    print("[OK] 调用按提交顺序执行")
+
+
+def test_created_in_other_thread():
+    """测试在一个线程创建、在另一个线程使用"""
+    holder = {}
+
+    def create():
+        holder["ctx"] = never_jscore.ThreadedContext()
+        holder["ctx"].compile("function who() { return 'threaded'; }")
+
+    t = threading.Thread(target=create)
+    t.start()
+    t.join()
+
+    ctx = holder["ctx"]
+    assert ctx.call("who", []) == 'threaded'
+    ctx.close()
+    print("[OK] 跨线程创建与使用")
+
+
+def test_errors():
+    """测试 JS 异常与未处理错误收集"""
+    received = []
+    ctx = never_jscore.ThreadedContext(on_error=received.append)
+    try:
+        ctx.evaluate("throw new Error('threaded failure')")
+        assert False, "应该抛出异常"
+    except Exception as e:
+        assert "threaded failure" in str(e)
+
+    ctx.evaluate("Promise.reject(new Error('lost')); 1", wait_timers="all")
+    assert [e["message"] for e in ctx.errors] == ["lost"]
+    assert received == ctx.errors
+
+    # 异常后仍可继续使用
+    assert ctx.evaluate("1 + 1") == 2
+    ctx.close()
+    print("[OK] 异常与未处理错误正确返回")
+
+
+def test_close():
+    """测试 close() 与上下文管理器"""
+    with never_jscore.ThreadedContext() as ctx:
+        assert ctx.evaluate("'inside'") == 'inside'
+        assert not ctx.closed
+    assert ctx.closed
+
+    try:
+        ctx.evaluate("1")
+        assert False, "关闭后调用应该抛出异常"
+    except RuntimeError as e:
+        assert "closed" in str(e)
+
+    ctx.close()  # 重复关闭不报错
+    print("[OK] close() 后拒绝新调用")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 ThreadedContext")
+    print("=" * 60)
+
+    test_basic_api()
+    test_shared_across_threads()
+    test_order_preserved()
+    test_created_in_other_thread()
+    test_errors()
+    test_close()
+
+    print("\n" + "=" * 60)
+    print("所有 ThreadedContext 测试通过!")
+    print("=" * 60)
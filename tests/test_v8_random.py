@@ -0,0 +1,97 @@
+"""
+测试 V8 Math.random (xorshift128+) 精确模拟
+
+参考值由 `node --random-seed=<seed>` 生成，
+用于验证与真实 V8 (Chrome/Node) 输出逐位一致。
+"""
+
+import sys
+
+# Set UTF-8 encoding for Windows console
+if sys.platform == 'win32':
+    import io
+    sys.stdout = io.TextIOWrapper(sys.stdout.buffer, encoding='utf-8')
+
+import never_jscore
+
+
+# node --random-seed=12345 -e "console.log([Math.random(), Math.random(), Math.random()])"
+NODE_SEED_12345 = [0.9044192244068718, 0.5645421251397142, 0.062021669432033466]
+
+# node --random-seed=2024，前 12 个输出
+NODE_SEED_2024 = [
+    0.4949631934402221, 0.7981969456359801, 0.6329380352602356, 0.1282793649157068,
+    0.9132284424700219, 0.11292741725463395, 0.592701957842473, 0.5636203415246457,
+    0.4366167076880456, 0.4989942909180074, 0.027756105822887234, 0.9123277148089695,
+]
+
+
+def test_v8_seed_matches_node():
+    """测试 set_v8_random_seed 与 node --random-seed 输出一致"""
+    ctx = never_jscore.Context()
+    ctx.set_v8_random_seed(12345)
+    values = ctx.evaluate("[Math.random(), Math.random(), Math.random()]")
+    assert values == NODE_SEED_12345, values
+    print(f"[OK] V8 种子序列: {values}")
+
+
+def test_recover_from_outputs():
+    """测试从观察到的输出恢复状态并预测后续值"""
+    ctx = never_jscore.Context()
+    state = ctx.recover_v8_random_state(NODE_SEED_2024[:5])
+    predicted = ctx.evaluate("Array.from({length: 7}, () => Math.random())")
+    assert predicted == NODE_SEED_2024[5:], predicted
+    print(f"[OK] 恢复状态 {state}，预测值与 Node 一致")
+
+
+def test_recover_with_cache_offset():
+    """测试观察值不在缓存块开头时使用 cache_offset"""
+    ctx = never_jscore.Context()
+    ctx.recover_v8_random_state(NODE_SEED_2024[3:8], cache_offset=3)
+    predicted = ctx.evaluate("[Math.random(), Math.random(), Math.random(), Math.random()]")
+    assert predicted == NODE_SEED_2024[8:], predicted
+    print("[OK] cache_offset 恢复成功")
+
+
+def test_state_roundtrip():
+    """测试 get_v8_random_state / set_v8_random_state 往返"""
+    ctx = never_jscore.Context()
+    ctx.set_v8_random_seed(2024)
+    ctx.evaluate("Math.random(); Math.random()")
+    state = ctx.get_v8_random_state()
+    a = ctx.evaluate("[Math.random(), Math.random()]")
+
+    ctx.set_v8_random_state(*state)
+    b = ctx.evaluate("[Math.random(), Math.random()]")
+    assert a == b == NODE_SEED_2024[2:4]
+
+    # set_random_seed 退出 V8 模式
+    ctx.set_random_seed(1)
+    assert ctx.get_v8_random_state() is None
+    print(f"[OK] 状态往返: {state}")
+
+
+def test_recover_rejects_invalid():
+    """测试非 V8 输出会被拒绝"""
+    ctx = never_jscore.Context()
+    try:
+        ctx.recover_v8_random_state([0.1, 0.2, 0.3, 0.4, 0.5])
+        assert False, "应该抛出异常"
+    except Exception as e:
+        print(f"[OK] 拒绝无效输出: {e}")
+
+
+if __name__ == "__main__":
+    print("=" * 60)
+    print("测试 V8 Math.random 模拟")
+    print("=" * 60)
+
+    test_v8_seed_matches_node()
+    test_recover_from_outputs()
+    test_recover_with_cache_offset()
+    test_state_roundtrip()
+    test_recover_rejects_invalid()
+
+    print("\n" + "=" * 60)
+    print("[PASS] 所有 V8 Math.random 测试通过！")
+    print("=" * 60)